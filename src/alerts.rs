@@ -1,13 +1,21 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use colored::*;
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use crate::audit;
+use crate::config::Config;
+use crate::state;
+use crate::sysinfo;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum AlertSeverity {
     Critical,
     Warning,
@@ -15,6 +23,16 @@ pub enum AlertSeverity {
 }
 
 impl AlertSeverity {
+    /// Parse a severity name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "critical" => Some(AlertSeverity::Critical),
+            "warning" => Some(AlertSeverity::Warning),
+            "info" => Some(AlertSeverity::Info),
+            _ => None,
+        }
+    }
+
     pub fn color(&self) -> &str {
         match self {
             AlertSeverity::Critical => "red",
@@ -23,6 +41,24 @@ impl AlertSeverity {
         }
     }
 
+    /// Ordering from least to most severe
+    pub fn level(&self) -> u8 {
+        match self {
+            AlertSeverity::Info => 0,
+            AlertSeverity::Warning => 1,
+            AlertSeverity::Critical => 2,
+        }
+    }
+
+    /// RGB color used by chat integrations
+    pub fn rgb(&self) -> u32 {
+        match self {
+            AlertSeverity::Critical => 0xFF0000,
+            AlertSeverity::Warning => 0xFFA500,
+            AlertSeverity::Info => 0x0000FF,
+        }
+    }
+
     pub fn emoji(&self) -> &str {
         match self {
             AlertSeverity::Critical => "🚨",
@@ -40,6 +76,69 @@ pub enum AlertStatus {
     Resolved,
 }
 
+impl AlertStatus {
+    /// Parse a status name as used on the command line, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "firing" => Some(AlertStatus::Firing),
+            "acknowledged" => Some(AlertStatus::Acknowledged),
+            "silenced" => Some(AlertStatus::Silenced),
+            "resolved" => Some(AlertStatus::Resolved),
+            _ => None,
+        }
+    }
+}
+
+/// A `key=value` condition on an alert's metadata, as given to `barks --where`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataMatcher {
+    pub key: String,
+    pub value: String,
+}
+
+impl MetadataMatcher {
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Ok(MetadataMatcher {
+                key: key.trim().to_string(),
+                value: value.to_string(),
+            }),
+            _ => anyhow::bail!("Expected key=value for --where, got '{}'", spec),
+        }
+    }
+
+    pub fn matches(&self, alert: &Alert) -> bool {
+        alert.metadata.get(&self.key) == Some(&self.value)
+    }
+}
+
+/// Selects alerts for bulk operations; an empty filter matches everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlertFilter {
+    pub status: Option<AlertStatus>,
+    pub severity: Option<AlertSeverity>,
+}
+
+impl AlertFilter {
+    pub fn matches(&self, alert: &Alert) -> bool {
+        self.status.as_ref().is_none_or(|s| alert.status == *s)
+            && self.severity.as_ref().is_none_or(|s| alert.severity == *s)
+    }
+}
+
+/// One status change of an alert and who made it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StateTransition {
+    pub timestamp: DateTime<Utc>,
+    pub from_status: AlertStatus,
+    pub to_status: AlertStatus,
+    /// `$USER` for manual changes, `monitor` for ones made by `catdog monitor`
+    pub actor: String,
+}
+
+/// Actor recorded for transitions the monitor makes on its own
+const MONITOR_ACTOR: &str = "monitor";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alert {
     pub id: String,
@@ -53,6 +152,19 @@ pub struct Alert {
     pub acknowledged_at: Option<DateTime<Utc>>,
     pub resolved_at: Option<DateTime<Utc>>,
     pub metadata: HashMap<String, String>,
+    /// How many times the alert has fired, including re-fires
+    #[serde(default = "default_occurrence_count")]
+    pub occurrence_count: u32,
+    /// Resolved by the monitor because its condition cleared, not by a user
+    #[serde(default)]
+    pub auto_resolved: bool,
+    /// Status changes, oldest first; empty for alerts stored before history was kept
+    #[serde(default)]
+    pub history: Vec<StateTransition>,
+}
+
+fn default_occurrence_count() -> u32 {
+    1
 }
 
 impl Alert {
@@ -75,24 +187,72 @@ impl Alert {
             acknowledged_at: None,
             resolved_at: None,
             metadata: HashMap::new(),
+            occurrence_count: 1,
+            auto_resolved: false,
+            history: Vec::new(),
         }
     }
 
+    /// Whether the alert is resolved or silenced and last changed before `cutoff`
+    ///
+    /// Firing and acknowledged alerts are never stale, however old.
+    pub fn is_stale(&self, cutoff: DateTime<Utc>) -> bool {
+        matches!(self.status, AlertStatus::Resolved | AlertStatus::Silenced)
+            && self.resolved_at.unwrap_or(self.updated_at) < cutoff
+    }
+
+    /// Whether an acknowledged alert has gone unchanged for at least `window`
+    pub fn is_due_to_refire(&self, window: Option<chrono::Duration>, now: DateTime<Utc>) -> bool {
+        match window {
+            Some(window) => {
+                self.status == AlertStatus::Acknowledged && now - self.updated_at >= window
+            }
+            None => false,
+        }
+    }
+
+    /// Move to `to`, recording the change in `history`
+    fn transition(&mut self, to: AlertStatus, actor: &str, now: DateTime<Utc>) {
+        self.history.push(StateTransition {
+            timestamp: now,
+            from_status: self.status.clone(),
+            to_status: to.clone(),
+            actor: actor.to_string(),
+        });
+        self.status = to;
+        self.updated_at = now;
+    }
+
+    /// Fire again because the condition behind an acknowledged alert persists
+    pub fn refire(&mut self, now: DateTime<Utc>) {
+        self.transition(AlertStatus::Firing, MONITOR_ACTOR, now);
+        self.occurrence_count += 1;
+    }
+
     pub fn acknowledge(&mut self) {
-        self.status = AlertStatus::Acknowledged;
-        self.acknowledged_at = Some(Utc::now());
-        self.updated_at = Utc::now();
+        let now = Utc::now();
+        self.transition(AlertStatus::Acknowledged, &audit::current_user(), now);
+        self.acknowledged_at = Some(now);
     }
 
     pub fn resolve(&mut self) {
-        self.status = AlertStatus::Resolved;
-        self.resolved_at = Some(Utc::now());
-        self.updated_at = Utc::now();
+        self.resolve_as(&audit::current_user());
+    }
+
+    fn resolve_as(&mut self, actor: &str) {
+        let now = Utc::now();
+        self.transition(AlertStatus::Resolved, actor, now);
+        self.resolved_at = Some(now);
+    }
+
+    /// Resolve because the condition that raised the alert is gone
+    pub fn auto_resolve(&mut self) {
+        self.resolve_as(MONITOR_ACTOR);
+        self.auto_resolved = true;
     }
 
     pub fn silence(&mut self) {
-        self.status = AlertStatus::Silenced;
-        self.updated_at = Utc::now();
+        self.transition(AlertStatus::Silenced, &audit::current_user(), Utc::now());
     }
 
     pub fn add_metadata(&mut self, key: String, value: String) {
@@ -107,6 +267,43 @@ pub struct AlertConfig {
     pub check_interval_seconds: u64,
     pub disk_usage_threshold: u8,
     pub notification_channels: Vec<NotificationChannel>,
+    /// Re-fire acknowledged alerts whose condition persists after this long
+    #[serde(default)]
+    pub refire_after_seconds: Option<u64>,
+    /// Resolved and silenced alerts older than this are dropped on save; 0 keeps them
+    #[serde(default = "default_retain_resolved_days")]
+    pub retain_resolved_days: u64,
+    /// Channels per severity; severities left out use `notification_channels`
+    #[serde(default)]
+    pub routing: HashMap<AlertSeverity, Vec<NotificationChannel>>,
+}
+
+impl AlertConfig {
+    /// Where an alert of `severity` is sent
+    pub fn channels_for(&self, severity: &AlertSeverity) -> &[NotificationChannel] {
+        self.routing
+            .get(severity)
+            .unwrap_or(&self.notification_channels)
+    }
+
+    /// Every distinct channel, routed or not, in config order
+    fn all_channels(&self) -> Vec<&NotificationChannel> {
+        let mut channels: Vec<&NotificationChannel> = Vec::new();
+        let routed = [
+            AlertSeverity::Critical,
+            AlertSeverity::Warning,
+            AlertSeverity::Info,
+        ]
+        .into_iter()
+        .filter_map(|severity| self.routing.get(&severity))
+        .flatten();
+        for channel in self.notification_channels.iter().chain(routed) {
+            if !channels.contains(&channel) {
+                channels.push(channel);
+            }
+        }
+        channels
+    }
 }
 
 impl Default for AlertConfig {
@@ -116,27 +313,231 @@ impl Default for AlertConfig {
             check_interval_seconds: 300, // 5 minutes
             disk_usage_threshold: 90,
             notification_channels: vec![NotificationChannel::Console],
+            refire_after_seconds: None,
+            retain_resolved_days: default_retain_resolved_days(),
+            routing: HashMap::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub fn default_retain_resolved_days() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum NotificationChannel {
     Console,
     Webhook {
         url: String,
+        #[serde(default)]
+        retry: RetryPolicy,
     },
     Slack {
         webhook_url: String,
     },
+    Discord {
+        webhook_url: String,
+    },
+    PagerDuty {
+        routing_key: String,
+        /// Alerts below this severity don't page
+        #[serde(default = "default_pagerduty_severity")]
+        min_severity: AlertSeverity,
+    },
     Email {
+        /// `host` or `host:port`; STARTTLS on port 587 unless given
         smtp_server: String,
         from: String,
         to: Vec<String>,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
     },
 }
 
+impl NotificationChannel {
+    /// Name used to pick a channel on the command line, e.g. `--channel slack`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotificationChannel::Console => "console",
+            NotificationChannel::Webhook { .. } => "webhook",
+            NotificationChannel::Slack { .. } => "slack",
+            NotificationChannel::Discord { .. } => "discord",
+            NotificationChannel::PagerDuty { .. } => "pagerduty",
+            NotificationChannel::Email { .. } => "email",
+        }
+    }
+
+    /// The channel's severity floor, if `alert` falls below it
+    fn skips(&self, alert: &Alert) -> Option<&AlertSeverity> {
+        match self {
+            NotificationChannel::PagerDuty { min_severity, .. }
+                if alert.severity.level() < min_severity.level() =>
+            {
+                Some(min_severity)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub const NOTIFICATION_CHANNEL_KINDS: &[&str] = &[
+    "console",
+    "webhook",
+    "slack",
+    "discord",
+    "pagerduty",
+    "email",
+];
+
+pub fn default_pagerduty_severity() -> AlertSeverity {
+    AlertSeverity::Critical
+}
+
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Events API v2 `trigger` body, deduplicated by the alert id
+fn pagerduty_event(routing_key: &str, alert: &Alert) -> serde_json::Value {
+    serde_json::json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": alert.id,
+        "payload": {
+            "summary": alert.title,
+            "source": alert.source,
+            "severity": format!("{:?}", alert.severity).to_lowercase(),
+            "timestamp": alert.created_at.to_rfc3339(),
+            "custom_details": {
+                "description": alert.description,
+                "metadata": alert.metadata,
+            }
+        }
+    })
+}
+
+/// Retry and timeout settings for webhook deliveries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: default_max_retries(),
+            base_delay_ms: default_base_delay_ms(),
+            timeout_seconds: default_timeout_seconds(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `attempt` (0-based): base, 2x base, 4x base, ...
+    pub fn delay(&self, attempt: u32) -> Duration {
+        Duration::from_millis(self.base_delay_ms.saturating_mul(1 << attempt.min(16)))
+    }
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_timeout_seconds() -> u64 {
+    10
+}
+
+/// POST a JSON payload, retrying connection failures and 5xx responses
+///
+/// 4xx responses mean the request itself is wrong and fail immediately.
+fn post_with_retry(
+    url: &str,
+    payload: &serde_json::Value,
+    retry: &RetryPolicy,
+) -> Result<reqwest::StatusCode> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(retry.timeout_seconds))
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    let mut attempt = 0;
+    loop {
+        let error = match client.post(url).json(payload).send() {
+            Ok(response) if response.status().is_success() => return Ok(response.status()),
+            Ok(response) if response.status().is_server_error() => {
+                anyhow::anyhow!("Webhook returned error: {}", response.status())
+            }
+            Ok(response) => anyhow::bail!("Webhook returned error: {}", response.status()),
+            Err(e) if e.is_connect() => anyhow::Error::new(e).context("Failed to send webhook"),
+            Err(e) => return Err(anyhow::Error::new(e).context("Failed to send webhook")),
+        };
+
+        if attempt >= retry.max_retries {
+            return Err(error.context(format!("Giving up after {} attempt(s)", attempt + 1)));
+        }
+        thread::sleep(retry.delay(attempt));
+        attempt += 1;
+    }
+}
+
+/// Drop stale alerts in place, returning how many were removed
+fn remove_stale(alerts: &mut Vec<Alert>, cutoff: DateTime<Utc>) -> usize {
+    let before = alerts.len();
+    alerts.retain(|a| !a.is_stale(cutoff));
+    before - alerts.len()
+}
+
+/// How long a writer waits for another catdog process to release the alert store
+const STORE_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Exclusive advisory lock beside the alert store, released when dropped
+///
+/// The lock lives in its own file because saving replaces alerts.json by rename.
+struct StoreLock {
+    _file: fs::File,
+}
+
+impl StoreLock {
+    fn acquire(storage_path: &Path, timeout: Duration) -> Result<Self> {
+        let mut name = storage_path
+            .file_name()
+            .context("Alert storage path has no file name")?
+            .to_os_string();
+        name.push(".lock");
+        let lock_path = storage_path.with_file_name(name);
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+
+        let deadline = Instant::now() + timeout;
+        while file.try_lock_exclusive().is_err() {
+            if Instant::now() >= deadline {
+                anyhow::bail!(
+                    "{} is locked by another catdog process; try again once it finishes",
+                    storage_path.display()
+                );
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        Ok(StoreLock { _file: file })
+    }
+}
+
 pub struct AlertManager {
     alerts: Vec<Alert>,
     config: AlertConfig,
@@ -144,6 +545,8 @@ pub struct AlertManager {
 }
 
 impl AlertManager {
+    /// Open the alert store with default settings: console notifications only
+    #[cfg(test)]
     pub fn new(storage_path: PathBuf) -> Result<Self> {
         let config = AlertConfig::default();
         let alerts = Self::load_alerts(&storage_path)?;
@@ -155,6 +558,11 @@ impl AlertManager {
         })
     }
 
+    /// Open the alert store with channels and re-fire window from the config file
+    pub fn from_config(storage_path: PathBuf, config: &Config) -> Result<Self> {
+        Self::with_config(storage_path, config.alert_config())
+    }
+
     pub fn with_config(storage_path: PathBuf, config: AlertConfig) -> Result<Self> {
         let alerts = Self::load_alerts(&storage_path)?;
 
@@ -183,93 +591,264 @@ impl AlertManager {
     }
 
     fn save_alerts(&self) -> Result<()> {
-        // Ensure parent directory exists
-        if let Some(parent) = self.storage_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create storage directory")?;
-        }
-
         let json =
             serde_json::to_string_pretty(&self.alerts).context("Failed to serialize alerts")?;
 
-        fs::write(&self.storage_path, json).context("Failed to write alerts to storage")?;
+        state::write_atomic(&self.storage_path, json)
+            .context("Failed to write alerts to storage")?;
 
         Ok(())
     }
 
-    pub fn create_alert(&mut self, alert: Alert) -> Result<String> {
-        // Check for duplicate active alerts with same title
-        let has_duplicate = self.alerts.iter().any(|a| {
-            a.title == alert.title
-                && matches!(a.status, AlertStatus::Firing | AlertStatus::Acknowledged)
-        });
+    /// Run a load-modify-save cycle while holding the store lock
+    ///
+    /// Alerts are re-read under the lock first so changes made by other catdog
+    /// processes since this manager loaded are kept rather than overwritten.
+    fn update_store<T, F>(&mut self, update: F) -> Result<T>
+    where
+        F: FnOnce(&mut Vec<Alert>) -> Result<T>,
+    {
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent).context("Failed to create storage directory")?;
+        }
 
-        if has_duplicate {
-            return Ok("Duplicate alert suppressed".to_string());
+        let _lock = StoreLock::acquire(&self.storage_path, STORE_LOCK_TIMEOUT)?;
+        self.alerts = Self::load_alerts(&self.storage_path)?;
+        let result = update(&mut self.alerts)?;
+        if let Some(cutoff) = self.retention_cutoff(Utc::now()) {
+            remove_stale(&mut self.alerts, cutoff);
         }
+        self.save_alerts()?;
 
-        let alert_id = alert.id.clone();
+        Ok(result)
+    }
 
-        // Send notifications
-        self.notify(&alert)?;
+    /// Alerts that went stale before this are dropped on every save
+    fn retention_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.config.retain_resolved_days {
+            0 => None,
+            days => Some(now - chrono::Duration::days(days as i64)),
+        }
+    }
 
-        self.alerts.push(alert);
-        self.save_alerts()?;
+    /// Resolved and silenced alerts that `prune_stale` would remove
+    pub fn stale_alerts(&self, cutoff: DateTime<Utc>) -> Vec<&Alert> {
+        let cutoff = self.effective_cutoff(cutoff);
+        self.alerts.iter().filter(|a| a.is_stale(cutoff)).collect()
+    }
 
-        Ok(alert_id)
+    /// Remove resolved and silenced alerts last changed before `cutoff`, returning how many
+    ///
+    /// Alerts past `retain_resolved_days` go too, since every save drops them anyway.
+    pub fn prune_stale(&mut self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let cutoff = self.effective_cutoff(cutoff);
+        self.update_store(|alerts| Ok(remove_stale(alerts, cutoff)))
     }
 
-    pub fn acknowledge_alert(&mut self, alert_id: &str) -> Result<()> {
-        let alert = self
-            .alerts
-            .iter_mut()
-            .find(|a| a.id == alert_id)
-            .context("Alert not found")?;
+    fn effective_cutoff(&self, cutoff: DateTime<Utc>) -> DateTime<Utc> {
+        self.retention_cutoff(Utc::now())
+            .map_or(cutoff, |retention| retention.max(cutoff))
+    }
 
-        alert.acknowledge();
-        self.save_alerts()?;
+    pub fn create_alert(&mut self, alert: Alert) -> Result<String> {
+        self.create_alert_at(alert, Utc::now())
+    }
 
-        Ok(())
+    fn create_alert_at(&mut self, alert: Alert, now: DateTime<Utc>) -> Result<String> {
+        let refire_after = self
+            .config
+            .refire_after_seconds
+            .map(|seconds| chrono::Duration::seconds(seconds as i64));
+
+        let fired = self.update_store(|alerts| {
+            // Check for duplicate active alerts with same title
+            let duplicate = alerts.iter().position(|a| {
+                a.title == alert.title
+                    && matches!(a.status, AlertStatus::Firing | AlertStatus::Acknowledged)
+            });
+
+            if let Some(index) = duplicate {
+                if !alerts[index].is_due_to_refire(refire_after, now) {
+                    return Ok(None);
+                }
+                alerts[index].refire(now);
+                return Ok(Some(alerts[index].clone()));
+            }
+
+            alerts.push(alert);
+            Ok(alerts.last().cloned())
+        })?;
+
+        // Notify after releasing the lock so slow channels don't block other writers
+        match fired {
+            Some(alert) => {
+                self.notify(&alert)?;
+                Ok(alert.id)
+            }
+            None => Ok("Duplicate alert suppressed".to_string()),
+        }
+    }
+
+    pub fn acknowledge_alert(&mut self, alert_id: &str) -> Result<()> {
+        self.update_store(|alerts| {
+            alerts
+                .iter_mut()
+                .find(|a| a.id == alert_id)
+                .context("Alert not found")?
+                .acknowledge();
+            Ok(())
+        })
     }
 
     pub fn resolve_alert(&mut self, alert_id: &str) -> Result<()> {
-        let alert = self
-            .alerts
-            .iter_mut()
-            .find(|a| a.id == alert_id)
-            .context("Alert not found")?;
+        self.update_store(|alerts| {
+            alerts
+                .iter_mut()
+                .find(|a| a.id == alert_id)
+                .context("Alert not found")?
+                .resolve();
+            Ok(())
+        })
+    }
 
-        alert.resolve();
-        self.save_alerts()?;
+    /// Acknowledge every firing alert matching `filter`, returning how many changed
+    pub fn acknowledge_matching(&mut self, filter: &AlertFilter) -> Result<usize> {
+        self.update_matching(
+            filter,
+            |a| a.status == AlertStatus::Firing,
+            Alert::acknowledge,
+        )
+    }
 
-        Ok(())
+    /// Resolve every unresolved alert matching `filter`, returning how many changed
+    pub fn resolve_matching(&mut self, filter: &AlertFilter) -> Result<usize> {
+        self.update_matching(
+            filter,
+            |a| a.status != AlertStatus::Resolved,
+            Alert::resolve,
+        )
+    }
+
+    fn update_matching<P, F>(
+        &mut self,
+        filter: &AlertFilter,
+        eligible: P,
+        update: F,
+    ) -> Result<usize>
+    where
+        P: Fn(&Alert) -> bool,
+        F: Fn(&mut Alert),
+    {
+        self.update_store(|alerts| {
+            let mut count = 0;
+            for alert in alerts
+                .iter_mut()
+                .filter(|a| filter.matches(a) && eligible(a))
+            {
+                update(alert);
+                count += 1;
+            }
+            Ok(count)
+        })
     }
 
     pub fn silence_alert(&mut self, alert_id: &str) -> Result<()> {
-        let alert = self
-            .alerts
-            .iter_mut()
-            .find(|a| a.id == alert_id)
-            .context("Alert not found")?;
+        self.update_store(|alerts| {
+            alerts
+                .iter_mut()
+                .find(|a| a.id == alert_id)
+                .context("Alert not found")?
+                .silence();
+            Ok(())
+        })
+    }
 
-        alert.silence();
-        self.save_alerts()?;
+    /// Resolve active alerts from `source` whose `key` metadata is no longer in `active`
+    ///
+    /// With `severity`, only alerts of that severity are considered, so a source
+    /// that raises several levels for the same key can check each level against
+    /// its own set. Alerts without the metadata key are left alone. Returns how
+    /// many were resolved.
+    pub fn auto_resolve_by_source_and_key(
+        &mut self,
+        source: &str,
+        severity: Option<&AlertSeverity>,
+        key: &str,
+        active: &HashSet<String>,
+    ) -> Result<usize> {
+        self.update_store(|alerts| {
+            let mut resolved = 0;
+
+            for alert in alerts.iter_mut().filter(|a| {
+                a.source == source
+                    && severity.is_none_or(|severity| a.severity == *severity)
+                    && matches!(a.status, AlertStatus::Firing | AlertStatus::Acknowledged)
+            }) {
+                if let Some(value) = alert.metadata.get(key) {
+                    if !active.contains(value) {
+                        alert.auto_resolve();
+                        resolved += 1;
+                    }
+                }
+            }
 
-        Ok(())
+            Ok(resolved)
+        })
     }
 
-    pub fn get_alerts(&self, filter: Option<AlertStatus>) -> Vec<&Alert> {
-        match filter {
-            Some(status) => self.alerts.iter().filter(|a| a.status == status).collect(),
-            None => self.alerts.iter().collect(),
-        }
+    /// Alerts matching an optional status, created at or after an optional time
+    pub fn get_alerts(
+        &self,
+        filter: Option<AlertStatus>,
+        since: Option<DateTime<Utc>>,
+    ) -> Vec<&Alert> {
+        self.alerts
+            .iter()
+            .filter(|a| filter.as_ref().is_none_or(|status| a.status == *status))
+            .filter(|a| since.is_none_or(|since| a.created_at >= since))
+            .collect()
+    }
+
+    /// Alerts matching an optional status and every metadata matcher
+    pub fn get_alerts_filtered(
+        &self,
+        status: Option<AlertStatus>,
+        metadata_matchers: &[MetadataMatcher],
+    ) -> Vec<&Alert> {
+        self.alerts
+            .iter()
+            .filter(|a| status.as_ref().is_none_or(|status| a.status == *status))
+            .filter(|a| metadata_matchers.iter().all(|m| m.matches(a)))
+            .collect()
+    }
+
+    /// Counts, mean response times and top sources over every stored alert
+    pub fn compute_stats(&self) -> AlertStats {
+        let alerts: Vec<&Alert> = self.alerts.iter().collect();
+        AlertStats::from_alerts(&alerts)
     }
 
     pub fn get_alert(&self, alert_id: &str) -> Option<&Alert> {
         self.alerts.iter().find(|a| a.id == alert_id)
     }
 
+    /// Send a synthetic Info alert through the configured channels
+    ///
+    /// Routing and per-channel severity floors are ignored so every channel
+    /// really gets a delivery, and `kind` limits the test to channels of one
+    /// kind. The alert is never stored, so it doesn't show up in `barks`.
+    pub fn test_notifications(&self, kind: Option<&str>) -> Vec<(&'static str, Result<String>)> {
+        let alert = test_alert();
+        self.config
+            .all_channels()
+            .into_iter()
+            .filter(|channel| kind.is_none_or(|kind| channel.kind() == kind))
+            .map(|channel| (channel.kind(), self.deliver(channel, &alert)))
+            .collect()
+    }
+
     fn notify(&self, alert: &Alert) -> Result<()> {
-        for channel in &self.config.notification_channels {
+        for channel in self.config.channels_for(&alert.severity) {
             if let Err(e) = self.send_notification(channel, alert) {
                 eprintln!(
                     "{} Failed to send notification via {:?}: {}",
@@ -282,31 +861,45 @@ impl AlertManager {
         Ok(())
     }
 
-    fn send_notification(&self, channel: &NotificationChannel, alert: &Alert) -> Result<()> {
+    /// Deliver `alert` through one channel unless it's below the channel's floor
+    fn send_notification(&self, channel: &NotificationChannel, alert: &Alert) -> Result<String> {
+        if let Some(min_severity) = channel.skips(alert) {
+            return Ok(format!("skipped, below {:?}", min_severity));
+        }
+        self.deliver(channel, alert)
+    }
+
+    /// Deliver `alert` through one channel, describing how the receiver answered
+    fn deliver(&self, channel: &NotificationChannel, alert: &Alert) -> Result<String> {
         match channel {
             NotificationChannel::Console => {
                 self.print_alert_notification(alert);
-                Ok(())
+                Ok("printed".to_string())
+            }
+            NotificationChannel::Webhook { url, retry } => {
+                let status = self.send_webhook_notification(url, retry, alert)?;
+                Ok(format!("HTTP {}", status))
             }
-            NotificationChannel::Webhook { url } => self.send_webhook_notification(url, alert),
             NotificationChannel::Slack { webhook_url } => {
                 self.send_slack_notification(webhook_url, alert)
             }
+            NotificationChannel::Discord { webhook_url } => {
+                self.send_discord_notification(webhook_url, alert)
+            }
+            NotificationChannel::PagerDuty { routing_key, .. } => {
+                self.send_pagerduty_notification(routing_key, alert)
+            }
             NotificationChannel::Email {
                 smtp_server,
                 from,
                 to,
+                username,
+                password,
             } => {
-                // Email sending would require additional dependencies
-                // For now, just log it
-                println!(
-                    "{} Email notification would be sent to: {:?}",
-                    "Info:".blue(),
-                    to
-                );
-                println!("  From: {}", from);
-                println!("  SMTP: {}", smtp_server);
-                Ok(())
+                let credentials = username
+                    .as_ref()
+                    .map(|user| (user.as_str(), password.as_deref().unwrap_or("")));
+                self.send_email_notification(smtp_server, credentials, from, to, alert)
             }
         }
     }
@@ -358,7 +951,12 @@ impl AlertManager {
         println!("{}", "=".repeat(80).bright_black());
     }
 
-    fn send_webhook_notification(&self, url: &str, alert: &Alert) -> Result<()> {
+    fn send_webhook_notification(
+        &self,
+        url: &str,
+        retry: &RetryPolicy,
+        alert: &Alert,
+    ) -> Result<reqwest::StatusCode> {
         let payload = serde_json::json!({
             "alert_id": alert.id,
             "title": alert.title,
@@ -370,26 +968,11 @@ impl AlertManager {
             "metadata": alert.metadata,
         });
 
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(url)
-            .json(&payload)
-            .send()
-            .context("Failed to send webhook")?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("Webhook returned error: {}", response.status());
-        }
-
-        Ok(())
+        post_with_retry(url, &payload, retry)
     }
 
-    fn send_slack_notification(&self, webhook_url: &str, alert: &Alert) -> Result<()> {
-        let color = match alert.severity {
-            AlertSeverity::Critical => "#FF0000",
-            AlertSeverity::Warning => "#FFA500",
-            AlertSeverity::Info => "#0000FF",
-        };
+    fn send_slack_notification(&self, webhook_url: &str, alert: &Alert) -> Result<String> {
+        let color = format!("#{:06X}", alert.severity.rgb());
 
         let payload = serde_json::json!({
             "attachments": [{
@@ -429,11 +1012,239 @@ impl AlertManager {
             anyhow::bail!("Slack webhook returned error: {}", response.status());
         }
 
-        Ok(())
+        Ok(format!("HTTP {}", response.status()))
+    }
+
+    fn send_pagerduty_notification(&self, routing_key: &str, alert: &Alert) -> Result<String> {
+        let payload = pagerduty_event(routing_key, alert);
+
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .post(PAGERDUTY_EVENTS_URL)
+            .json(&payload)
+            .send()
+            .context("Failed to send PagerDuty event")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("PagerDuty returned error: {}", response.status());
+        }
+
+        Ok(format!("HTTP {}", response.status()))
+    }
+
+    fn send_email_notification(
+        &self,
+        smtp_server: &str,
+        credentials: Option<(&str, &str)>,
+        from: &str,
+        to: &[String],
+        alert: &Alert,
+    ) -> Result<String> {
+        use lettre::transport::smtp::authentication::Credentials;
+        use lettre::{SmtpTransport, Transport};
+
+        let message = email_message(from, to, alert)?;
+
+        let (host, port) = smtp_host_port(smtp_server)?;
+
+        let mut transport = SmtpTransport::starttls_relay(host)
+            .with_context(|| format!("Failed to set up SMTP connection to {}", smtp_server))?
+            .port(port);
+        if let Some((username, password)) = credentials {
+            transport =
+                transport.credentials(Credentials::new(username.to_string(), password.to_string()));
+        }
+
+        let response = transport
+            .build()
+            .send(&message)
+            .with_context(|| format!("Failed to send email via {}", smtp_server))?;
+
+        Ok(format!(
+            "SMTP {} {}",
+            response.code(),
+            response.first_line().unwrap_or_default()
+        )
+        .trim_end()
+        .to_string())
+    }
+
+    fn send_discord_notification(&self, webhook_url: &str, alert: &Alert) -> Result<String> {
+        let payload = discord_payload(alert);
+
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .post(webhook_url)
+            .json(&payload)
+            .send()
+            .context("Failed to send Discord notification")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Discord webhook returned error: {}", response.status());
+        }
+
+        Ok(format!("HTTP {}", response.status()))
+    }
+}
+
+const SMTP_SUBMISSION_PORT: u16 = 587;
+
+/// Split `host`, `host:port`, `[v6]` or `[v6]:port` into host and port
+///
+/// A bare IPv6 address has more than one colon, so it is taken as the host.
+fn smtp_host_port(smtp_server: &str) -> Result<(&str, u16)> {
+    let parse_port = |port: &str| {
+        port.parse()
+            .with_context(|| format!("Invalid SMTP port in {}", smtp_server))
+    };
+
+    if let Some(bracketed) = smtp_server.strip_prefix('[') {
+        let (host, rest) = bracketed
+            .split_once(']')
+            .with_context(|| format!("Missing ']' in SMTP server {}", smtp_server))?;
+        return match rest {
+            "" => Ok((host, SMTP_SUBMISSION_PORT)),
+            _ => match rest.strip_prefix(':') {
+                Some(port) => Ok((host, parse_port(port)?)),
+                None => anyhow::bail!("Unexpected text after ']' in SMTP server {}", smtp_server),
+            },
+        };
+    }
+
+    match smtp_server.split_once(':') {
+        Some((host, port)) if !port.contains(':') => Ok((host, parse_port(port)?)),
+        _ => Ok((smtp_server, SMTP_SUBMISSION_PORT)),
     }
 }
 
+/// The alert `alerts test` sends; it never reaches the store
+fn test_alert() -> Alert {
+    Alert::new(
+        "catdog test notification".to_string(),
+        "Sent by `catdog alerts test` to check notification delivery".to_string(),
+        AlertSeverity::Info,
+        "catdog".to_string(),
+    )
+}
+
+/// Plain-text email for an alert, with the title as subject
+fn email_message(from: &str, to: &[String], alert: &Alert) -> Result<lettre::Message> {
+    if to.is_empty() {
+        anyhow::bail!("Email channel has no recipients");
+    }
+
+    let mut builder = lettre::Message::builder()
+        .from(
+            from.parse()
+                .with_context(|| format!("Invalid sender address: {}", from))?,
+        )
+        .subject(format!("[catdog] {:?}: {}", alert.severity, alert.title));
+    for recipient in to {
+        builder = builder.to(recipient
+            .parse()
+            .with_context(|| format!("Invalid recipient address: {}", recipient))?);
+    }
+
+    let mut body = format!(
+        "{}\n\nSeverity: {:?}\nStatus: {:?}\nSource: {}\nAlert ID: {}\nCreated: {}\n",
+        alert.description,
+        alert.severity,
+        alert.status,
+        alert.source,
+        alert.id,
+        alert.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    if !alert.metadata.is_empty() {
+        body.push_str("\nMetadata:\n");
+        let mut metadata: Vec<_> = alert.metadata.iter().collect();
+        metadata.sort();
+        for (key, value) in metadata {
+            body.push_str(&format!("  {}: {}\n", key, value));
+        }
+    }
+    body.push_str("\n-- \ncatdog alerting system\n");
+
+    builder.body(body).context("Failed to build email message")
+}
+
+/// Discord webhook body with the alert as a single embed
+fn discord_payload(alert: &Alert) -> serde_json::Value {
+    serde_json::json!({
+        "embeds": [{
+            "title": format!("{} {}", alert.severity.emoji(), alert.title),
+            "description": alert.description,
+            "color": alert.severity.rgb(),
+            "fields": [
+                {
+                    "name": "Severity",
+                    "value": format!("{:?}", alert.severity),
+                    "inline": true
+                },
+                {
+                    "name": "Source",
+                    "value": alert.source,
+                    "inline": true
+                },
+                {
+                    "name": "Alert ID",
+                    "value": alert.id,
+                    "inline": false
+                }
+            ],
+            "footer": {
+                "text": "catdog alerting system"
+            },
+            "timestamp": alert.created_at.to_rfc3339()
+        }]
+    })
+}
+
+/// Parse a relative duration such as `30m`, `6h` or `7d`
+pub fn parse_duration(value: &str) -> Option<chrono::Duration> {
+    let value = value.trim();
+    let unit = value.chars().last()?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+    if amount < 0 {
+        return None;
+    }
+
+    match unit {
+        's' => Some(chrono::Duration::seconds(amount)),
+        'm' => Some(chrono::Duration::minutes(amount)),
+        'h' => Some(chrono::Duration::hours(amount)),
+        'd' => Some(chrono::Duration::days(amount)),
+        'w' => Some(chrono::Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+/// Resolve a `--since` value to a point in time
+///
+/// Accepts a duration relative to `now` (`24h`), a date (`2024-01-01`, taken as
+/// midnight UTC) or an RFC 3339 timestamp.
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    if let Some(duration) = parse_duration(value) {
+        return Ok(now - duration);
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    anyhow::bail!(
+        "Invalid time '{}': use a duration like 30m, 6h or 7d, or a date like 2024-01-01",
+        value
+    )
+}
+
 pub fn display_alerts(alerts: &[&Alert]) {
+    display_alerts_highlighting(alerts, &HashSet::new());
+}
+
+/// The alert table with the ids in `new_ids` picked out, as used by `barks --watch`
+pub fn display_alerts_highlighting(alerts: &[&Alert], new_ids: &HashSet<String>) {
     if alerts.is_empty() {
         println!("{}", "No alerts found".yellow());
         return;
@@ -462,13 +1273,25 @@ pub fn display_alerts(alerts: &[&Alert]) {
             AlertStatus::Resolved => status_str.green(),
         };
 
+        let is_new = new_ids.contains(&alert.id);
+        let id_colored = if is_new {
+            alert.id.green().bold()
+        } else {
+            alert.id.truecolor(150, 150, 150)
+        };
+
         println!(
-            "{:<38} {:<10} {:<30} {:<15} {}",
-            alert.id.truecolor(150, 150, 150).to_string(),
+            "{:<38} {:<10} {:<30} {:<15} {}{}",
+            id_colored.to_string(),
             severity_colored.to_string(),
             alert.title.bright_white().to_string(),
             status_colored.to_string(),
-            alert.created_at.format("%Y-%m-%d %H:%M:%S")
+            alert.created_at.format("%Y-%m-%d %H:%M:%S"),
+            if is_new {
+                format!(" {}", "NEW".green().bold())
+            } else {
+                String::new()
+            }
         );
     }
 
@@ -479,6 +1302,222 @@ pub fn display_alerts(alerts: &[&Alert]) {
     );
 }
 
+/// Per-status and per-severity tallies for a set of alerts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AlertCounts {
+    pub firing: usize,
+    pub acknowledged: usize,
+    pub silenced: usize,
+    pub resolved: usize,
+    pub critical: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+impl AlertCounts {
+    pub fn from_alerts(alerts: &[&Alert]) -> Self {
+        let mut counts = AlertCounts::default();
+
+        for alert in alerts {
+            match alert.status {
+                AlertStatus::Firing => counts.firing += 1,
+                AlertStatus::Acknowledged => counts.acknowledged += 1,
+                AlertStatus::Silenced => counts.silenced += 1,
+                AlertStatus::Resolved => counts.resolved += 1,
+            }
+            match alert.severity {
+                AlertSeverity::Critical => counts.critical += 1,
+                AlertSeverity::Warning => counts.warning += 1,
+                AlertSeverity::Info => counts.info += 1,
+            }
+        }
+
+        counts
+    }
+
+    /// e.g. "3 firing, 1 acknowledged"; zero counts are left out
+    pub fn status_summary(&self) -> String {
+        join_counts(&[
+            (self.firing, "firing"),
+            (self.acknowledged, "acknowledged"),
+            (self.silenced, "silenced"),
+            (self.resolved, "resolved"),
+        ])
+    }
+
+    /// e.g. "1 critical, 3 warning"; zero counts are left out
+    pub fn severity_summary(&self) -> String {
+        join_counts(&[
+            (self.critical, "critical"),
+            (self.warning, "warning"),
+            (self.info, "info"),
+        ])
+    }
+}
+
+fn join_counts(counts: &[(usize, &str)]) -> String {
+    let parts: Vec<String> = counts
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{} {}", count, label))
+        .collect();
+
+    if parts.is_empty() {
+        "no alerts".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// How many sources `barks stats` lists
+const TOP_SOURCES: usize = 5;
+
+/// Counts and response times across the alert store, for `barks stats`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AlertStats {
+    pub total: usize,
+    pub counts: AlertCounts,
+    /// Mean of `acknowledged_at - created_at` over acknowledged alerts
+    pub mean_time_to_acknowledge_seconds: Option<u64>,
+    pub acknowledged_samples: usize,
+    /// Mean of `resolved_at - created_at` over resolved alerts
+    pub mean_time_to_resolve_seconds: Option<u64>,
+    pub resolved_samples: usize,
+    /// Most frequent sources, most alerts first
+    pub top_sources: Vec<SourceCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceCount {
+    pub source: String,
+    pub count: usize,
+}
+
+impl AlertStats {
+    pub fn from_alerts(alerts: &[&Alert]) -> Self {
+        let acknowledge_times: Vec<i64> = alerts
+            .iter()
+            .filter_map(|a| {
+                a.acknowledged_at
+                    .map(|at| (at - a.created_at).num_seconds())
+            })
+            .collect();
+        let resolve_times: Vec<i64> = alerts
+            .iter()
+            .filter_map(|a| a.resolved_at.map(|at| (at - a.created_at).num_seconds()))
+            .collect();
+
+        let mut sources: HashMap<&str, usize> = HashMap::new();
+        for alert in alerts {
+            *sources.entry(alert.source.as_str()).or_default() += 1;
+        }
+        let mut top_sources: Vec<SourceCount> = sources
+            .into_iter()
+            .map(|(source, count)| SourceCount {
+                source: source.to_string(),
+                count,
+            })
+            .collect();
+        top_sources.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.source.cmp(&b.source)));
+        top_sources.truncate(TOP_SOURCES);
+
+        AlertStats {
+            total: alerts.len(),
+            counts: AlertCounts::from_alerts(alerts),
+            mean_time_to_acknowledge_seconds: mean_seconds(&acknowledge_times),
+            acknowledged_samples: acknowledge_times.len(),
+            mean_time_to_resolve_seconds: mean_seconds(&resolve_times),
+            resolved_samples: resolve_times.len(),
+            top_sources,
+        }
+    }
+
+    pub fn display(&self) {
+        println!("{} {}\n", "📊".bold(), "Bark statistics".bold());
+        if self.total == 0 {
+            println!("{}", "No alerts".bright_black());
+            return;
+        }
+
+        let row = |label: &str, value: String| println!("  {:<26} {}", label.cyan(), value);
+        row("Total:", self.total.to_string().bright_white().to_string());
+        row("By status:", self.counts.status_summary());
+        row("By severity:", self.counts.severity_summary());
+        row(
+            "Mean time to acknowledge:",
+            describe_mean(
+                self.mean_time_to_acknowledge_seconds,
+                self.acknowledged_samples,
+            ),
+        );
+        row(
+            "Mean time to resolve:",
+            describe_mean(self.mean_time_to_resolve_seconds, self.resolved_samples),
+        );
+
+        println!("\n  {}", "Top sources:".cyan());
+        for source in &self.top_sources {
+            println!(
+                "    {:>5}  {}",
+                source.count.to_string().bright_white(),
+                source.source.bright_yellow()
+            );
+        }
+    }
+}
+
+/// Mean of non-negative durations, rounded to the nearest second
+fn mean_seconds(durations: &[i64]) -> Option<u64> {
+    if durations.is_empty() {
+        return None;
+    }
+    let total: u64 = durations.iter().map(|d| (*d).max(0) as u64).sum();
+    let count = durations.len() as u64;
+    Some((total + count / 2) / count)
+}
+
+fn describe_mean(seconds: Option<u64>, samples: usize) -> String {
+    match seconds {
+        Some(seconds) if seconds < 60 => format!("{}s (over {})", seconds, samples),
+        Some(seconds) => format!("{} (over {})", sysinfo::format_duration(seconds), samples),
+        None => "n/a".bright_black().to_string(),
+    }
+}
+
+/// One short line per alert, followed by the status/severity counts
+pub fn display_alerts_compact(alerts: &[&Alert]) {
+    if alerts.is_empty() {
+        println!("{}", "No alerts found".yellow());
+        return;
+    }
+
+    for alert in alerts {
+        let short_id: String = alert.id.chars().take(8).collect();
+        let status_str = format!("{:?}", alert.status).to_lowercase();
+        let status_colored = match alert.status {
+            AlertStatus::Firing => status_str.red(),
+            AlertStatus::Acknowledged => status_str.yellow(),
+            AlertStatus::Silenced => status_str.bright_black(),
+            AlertStatus::Resolved => status_str.green(),
+        };
+
+        println!(
+            "{} {} {:<12} {}",
+            short_id.truecolor(150, 150, 150),
+            alert.severity.emoji(),
+            status_colored.to_string(),
+            alert.title.bright_white()
+        );
+    }
+
+    let counts = AlertCounts::from_alerts(alerts);
+    println!(
+        "\n{} ({})",
+        counts.status_summary(),
+        counts.severity_summary().truecolor(150, 150, 150)
+    );
+}
+
 pub fn display_alert_detail(alert: &Alert) {
     println!("\n{}", "=".repeat(80).bright_black());
     println!(
@@ -528,6 +1567,13 @@ pub fn display_alert_detail(alert: &Alert) {
         "Updated:".cyan().bold(),
         alert.updated_at.format("%Y-%m-%d %H:%M:%S UTC")
     );
+    if alert.occurrence_count > 1 {
+        println!(
+            "{} {}",
+            "Occurrences:".cyan().bold(),
+            alert.occurrence_count.to_string().bright_white()
+        );
+    }
 
     if let Some(ack_time) = alert.acknowledged_at {
         println!(
@@ -543,6 +1589,30 @@ pub fn display_alert_detail(alert: &Alert) {
             "Resolved:".cyan().bold(),
             resolved_time.format("%Y-%m-%d %H:%M:%S UTC")
         );
+        if alert.auto_resolved {
+            println!(
+                "{} {}",
+                "Resolved by:".cyan().bold(),
+                "monitor (condition cleared)".green()
+            );
+        }
+    }
+
+    if !alert.history.is_empty() {
+        println!("\n{}", "History:".cyan().bold());
+        for transition in &alert.history {
+            println!(
+                "  {} {:?} → {:?} by {}",
+                transition
+                    .timestamp
+                    .format("%Y-%m-%d %H:%M:%S UTC")
+                    .to_string()
+                    .truecolor(180, 180, 180),
+                transition.from_status,
+                transition.to_status,
+                transition.actor.bright_white()
+            );
+        }
     }
 
     if !alert.metadata.is_empty() {
@@ -558,3 +1628,736 @@ pub fn display_alert_detail(alert: &Alert) {
 
     println!("{}", "=".repeat(80).bright_black());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(severity: AlertSeverity, status: AlertStatus) -> Alert {
+        let mut alert = Alert::new(
+            "Test alert".to_string(),
+            "Fixture".to_string(),
+            severity,
+            "test".to_string(),
+        );
+        alert.status = status;
+        alert
+    }
+
+    #[test]
+    fn test_alert_counts() {
+        let alerts = [
+            alert(AlertSeverity::Critical, AlertStatus::Firing),
+            alert(AlertSeverity::Warning, AlertStatus::Firing),
+            alert(AlertSeverity::Warning, AlertStatus::Firing),
+            alert(AlertSeverity::Info, AlertStatus::Acknowledged),
+            alert(AlertSeverity::Warning, AlertStatus::Resolved),
+        ];
+        let refs: Vec<&Alert> = alerts.iter().collect();
+
+        let counts = AlertCounts::from_alerts(&refs);
+
+        assert_eq!(counts.firing, 3);
+        assert_eq!(counts.acknowledged, 1);
+        assert_eq!(counts.silenced, 0);
+        assert_eq!(counts.resolved, 1);
+        assert_eq!(counts.warning, 3);
+        assert_eq!(
+            counts.status_summary(),
+            "3 firing, 1 acknowledged, 1 resolved"
+        );
+        assert_eq!(counts.severity_summary(), "1 critical, 3 warning, 1 info");
+        assert_eq!(AlertCounts::default().status_summary(), "no alerts");
+    }
+
+    #[test]
+    fn test_discord_payload() {
+        let payload = discord_payload(&alert(AlertSeverity::Warning, AlertStatus::Firing));
+        let embed = &payload["embeds"][0];
+
+        assert_eq!(embed["title"], "⚠️ Test alert");
+        assert_eq!(embed["description"], "Fixture");
+        assert_eq!(embed["color"], 0xFFA500);
+        assert_eq!(embed["footer"]["text"], "catdog alerting system");
+        assert!(embed["timestamp"].as_str().unwrap().contains('T'));
+    }
+
+    #[test]
+    fn test_pagerduty_events() {
+        let alert = alert(AlertSeverity::Critical, AlertStatus::Firing);
+
+        let trigger = pagerduty_event("R0UT1NG", &alert);
+        assert_eq!(trigger["routing_key"], "R0UT1NG");
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["dedup_key"], alert.id);
+        assert_eq!(trigger["payload"]["summary"], "Test alert");
+        assert_eq!(trigger["payload"]["severity"], "critical");
+
+        assert!(AlertSeverity::Warning.level() < default_pagerduty_severity().level());
+
+        let channel = NotificationChannel::PagerDuty {
+            routing_key: "R0UT1NG".to_string(),
+            min_severity: default_pagerduty_severity(),
+        };
+        assert_eq!(channel.skips(&alert), None);
+        // `alerts test` sends Info and goes around this floor
+        assert_eq!(channel.skips(&test_alert()), Some(&AlertSeverity::Critical));
+        assert_eq!(NotificationChannel::Console.skips(&test_alert()), None);
+    }
+
+    #[test]
+    fn test_email_message() {
+        let mut alert = alert(AlertSeverity::Critical, AlertStatus::Firing);
+        alert.add_metadata("mount".to_string(), "/data".to_string());
+        let to = vec![
+            "oncall@example.com".to_string(),
+            "ops@example.com".to_string(),
+        ];
+
+        let message = email_message("catdog@example.com", &to, &alert).unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("From: catdog@example.com"));
+        assert!(formatted.contains("To: oncall@example.com, ops@example.com"));
+        assert!(formatted.contains("Subject: [catdog] Critical: Test alert"));
+        assert!(formatted.contains("Severity: Critical"));
+        assert!(formatted.contains(&format!("Alert ID: {}", alert.id)));
+        assert!(formatted.contains("  mount: /data"));
+
+        assert!(email_message("catdog@example.com", &[], &alert).is_err());
+        assert!(email_message("not an address", &to, &alert).is_err());
+    }
+
+    #[test]
+    fn test_smtp_host_port() {
+        assert_eq!(
+            smtp_host_port("smtp.example.com").unwrap(),
+            ("smtp.example.com", SMTP_SUBMISSION_PORT)
+        );
+        assert_eq!(
+            smtp_host_port("smtp.example.com:465").unwrap(),
+            ("smtp.example.com", 465)
+        );
+        assert_eq!(smtp_host_port("[::1]:2525").unwrap(), ("::1", 2525));
+        assert_eq!(
+            smtp_host_port("[2001:db8::25]").unwrap(),
+            ("2001:db8::25", SMTP_SUBMISSION_PORT)
+        );
+        assert_eq!(
+            smtp_host_port("2001:db8::25").unwrap(),
+            ("2001:db8::25", SMTP_SUBMISSION_PORT)
+        );
+
+        assert!(smtp_host_port("smtp.example.com:smtp").is_err());
+        assert!(smtp_host_port("[::1:25").is_err());
+        assert!(smtp_host_port("[::1]25").is_err());
+    }
+
+    fn manager_with_refire(dir: &Path, refire_after_seconds: Option<u64>) -> AlertManager {
+        let config = AlertConfig {
+            notification_channels: Vec::new(),
+            refire_after_seconds,
+            ..Default::default()
+        };
+        AlertManager::with_config(dir.join("alerts.json"), config).unwrap()
+    }
+
+    #[test]
+    fn test_concurrent_managers_keep_each_others_alerts() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_path = dir.path().join("alerts.json");
+
+        let writers: Vec<_> = ["sda", "sdb"]
+            .into_iter()
+            .map(|device| {
+                let mut manager = manager_with_refire(dir.path(), None);
+                thread::spawn(move || {
+                    for i in 0..10 {
+                        let title = format!("{} alert {}", device, i);
+                        let alert = Alert::new(
+                            title,
+                            String::new(),
+                            AlertSeverity::Warning,
+                            "test".to_string(),
+                        );
+                        manager.create_alert(alert).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let manager = AlertManager::new(storage_path).unwrap();
+        assert_eq!(manager.get_alerts(None, None).len(), 20);
+    }
+
+    #[test]
+    fn test_mutations_reload_before_saving() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = manager_with_refire(dir.path(), None);
+        let mut second = manager_with_refire(dir.path(), None);
+
+        let id = first
+            .create_alert(alert(AlertSeverity::Critical, AlertStatus::Firing))
+            .unwrap();
+        // `second` loaded before the alert existed but still sees it when mutating
+        second.acknowledge_alert(&id).unwrap();
+
+        let reloaded = manager_with_refire(dir.path(), None);
+        assert_eq!(
+            reloaded.get_alert(&id).unwrap().status,
+            AlertStatus::Acknowledged
+        );
+    }
+
+    #[test]
+    fn test_store_lock_fails_fast_when_held() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_path = dir.path().join("alerts.json");
+
+        let _held = StoreLock::acquire(&storage_path, Duration::ZERO).unwrap();
+        let error = StoreLock::acquire(&storage_path, Duration::from_millis(100))
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("locked by another catdog process"));
+    }
+
+    #[test]
+    fn test_firing_duplicates_are_suppressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager_with_refire(dir.path(), Some(60));
+        let start = Utc::now();
+
+        let id = manager
+            .create_alert_at(alert(AlertSeverity::Critical, AlertStatus::Firing), start)
+            .unwrap();
+        let later = start + chrono::Duration::hours(2);
+        let result = manager
+            .create_alert_at(alert(AlertSeverity::Critical, AlertStatus::Firing), later)
+            .unwrap();
+
+        assert_eq!(result, "Duplicate alert suppressed");
+        assert_eq!(manager.get_alert(&id).unwrap().occurrence_count, 1);
+    }
+
+    #[test]
+    fn test_acknowledged_alert_refires_after_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager_with_refire(dir.path(), Some(600));
+        let start = Utc::now();
+
+        let id = manager
+            .create_alert_at(alert(AlertSeverity::Critical, AlertStatus::Firing), start)
+            .unwrap();
+        manager.acknowledge_alert(&id).unwrap();
+        let acked_at = manager.get_alert(&id).unwrap().updated_at;
+
+        // Still inside the window: stays acknowledged
+        let early = acked_at + chrono::Duration::seconds(599);
+        let result = manager
+            .create_alert_at(alert(AlertSeverity::Critical, AlertStatus::Firing), early)
+            .unwrap();
+        assert_eq!(result, "Duplicate alert suppressed");
+        assert_eq!(
+            manager.get_alert(&id).unwrap().status,
+            AlertStatus::Acknowledged
+        );
+
+        let late = acked_at + chrono::Duration::seconds(600);
+        let result = manager
+            .create_alert_at(alert(AlertSeverity::Critical, AlertStatus::Firing), late)
+            .unwrap();
+        let refired = manager.get_alert(&id).unwrap();
+        assert_eq!(result, id);
+        assert_eq!(refired.status, AlertStatus::Firing);
+        assert_eq!(refired.occurrence_count, 2);
+        assert_eq!(refired.updated_at, late);
+        assert_eq!(manager.get_alerts(None, None).len(), 1);
+    }
+
+    #[test]
+    fn test_no_refire_without_window() {
+        let mut acked = alert(AlertSeverity::Warning, AlertStatus::Acknowledged);
+        let much_later = acked.updated_at + chrono::Duration::days(30);
+        assert!(!acked.is_due_to_refire(None, much_later));
+
+        acked.status = AlertStatus::Resolved;
+        assert!(!acked.is_due_to_refire(Some(chrono::Duration::seconds(1)), much_later));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(chrono::Duration::minutes(30)));
+        assert_eq!(parse_duration("6h"), Some(chrono::Duration::hours(6)));
+        assert_eq!(parse_duration("7d"), Some(chrono::Duration::days(7)));
+        assert_eq!(parse_duration("2w"), Some(chrono::Duration::weeks(2)));
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("-1h"), None);
+        assert_eq!(parse_duration("5y"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            parse_since("24h", now).unwrap().to_rfc3339(),
+            "2024-03-09T12:00:00+00:00"
+        );
+        assert_eq!(
+            parse_since("2024-01-01", now).unwrap().to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert!(parse_since("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn test_get_alerts_since_combines_with_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager_with_refire(dir.path(), None);
+        let now = Utc::now();
+
+        let mut old = alert(AlertSeverity::Warning, AlertStatus::Firing);
+        old.title = "old".to_string();
+        old.created_at = now - chrono::Duration::days(2);
+        manager.create_alert(old).unwrap();
+        manager
+            .create_alert(alert(AlertSeverity::Critical, AlertStatus::Firing))
+            .unwrap();
+        let mut acked = alert(AlertSeverity::Info, AlertStatus::Firing);
+        acked.title = "acked".to_string();
+        let acked_id = manager.create_alert(acked).unwrap();
+        manager.acknowledge_alert(&acked_id).unwrap();
+
+        let since = Some(now - chrono::Duration::hours(1));
+        assert_eq!(manager.get_alerts(None, since).len(), 2);
+        assert_eq!(
+            manager.get_alerts(Some(AlertStatus::Firing), since).len(),
+            1
+        );
+        assert_eq!(manager.get_alerts(Some(AlertStatus::Firing), None).len(), 2);
+    }
+
+    #[test]
+    fn test_bulk_acknowledge_and_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager_with_refire(dir.path(), None);
+        for (title, severity) in [
+            ("disk /", AlertSeverity::Critical),
+            ("disk /data", AlertSeverity::Warning),
+            ("disk /srv", AlertSeverity::Warning),
+        ] {
+            let mut a = alert(severity, AlertStatus::Firing);
+            a.title = title.to_string();
+            manager.create_alert(a).unwrap();
+        }
+
+        let warnings = AlertFilter {
+            severity: Some(AlertSeverity::Warning),
+            ..Default::default()
+        };
+        assert_eq!(manager.acknowledge_matching(&warnings).unwrap(), 2);
+        // Already acknowledged alerts are not counted again
+        assert_eq!(manager.acknowledge_matching(&warnings).unwrap(), 0);
+
+        let firing = AlertFilter {
+            status: Some(AlertStatus::Firing),
+            ..Default::default()
+        };
+        assert_eq!(manager.resolve_matching(&firing).unwrap(), 1);
+        assert_eq!(
+            manager.resolve_matching(&AlertFilter::default()).unwrap(),
+            2
+        );
+
+        // Changes were saved
+        let reloaded = AlertManager::new(dir.path().join("alerts.json")).unwrap();
+        assert_eq!(
+            reloaded.get_alerts(Some(AlertStatus::Resolved), None).len(),
+            3
+        );
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1,
+            timeout_seconds: 5,
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let retry = RetryPolicy::default();
+        assert_eq!(retry.delay(0), Duration::from_millis(500));
+        assert_eq!(retry.delay(1), Duration::from_millis(1000));
+        assert_eq!(retry.delay(2), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn test_webhook_retries_server_errors() {
+        let mut server = mockito::Server::new();
+        let unavailable = server
+            .mock("POST", "/hook")
+            .with_status(503)
+            .expect(2)
+            .create();
+        let ok = server
+            .mock("POST", "/hook")
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let url = format!("{}/hook", server.url());
+        post_with_retry(&url, &serde_json::json!({"title": "disk"}), &fast_retry()).unwrap();
+
+        unavailable.assert();
+        ok.assert();
+    }
+
+    #[test]
+    fn test_webhook_does_not_retry_client_errors() {
+        let mut server = mockito::Server::new();
+        let rejected = server
+            .mock("POST", "/hook")
+            .with_status(400)
+            .expect(1)
+            .create();
+
+        let url = format!("{}/hook", server.url());
+        let err = post_with_retry(&url, &serde_json::json!({}), &fast_retry()).unwrap_err();
+
+        assert!(err.to_string().contains("400"));
+        rejected.assert();
+    }
+
+    #[test]
+    fn test_notifications_report_each_channel_without_storing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new();
+        let hook = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "title": "catdog test notification",
+                "severity": "Info",
+            })))
+            .with_status(202)
+            .expect(1)
+            .create();
+        let slack = server
+            .mock("POST", "/slack")
+            .with_status(404)
+            .expect(2)
+            .create();
+
+        let config = AlertConfig {
+            notification_channels: vec![
+                NotificationChannel::Webhook {
+                    url: format!("{}/hook", server.url()),
+                    retry: fast_retry(),
+                },
+                NotificationChannel::Slack {
+                    webhook_url: format!("{}/slack", server.url()),
+                },
+            ],
+            ..Default::default()
+        };
+        let storage_path = dir.path().join("alerts.json");
+        let manager = AlertManager::with_config(storage_path.clone(), config).unwrap();
+
+        let results = manager.test_notifications(None);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "webhook");
+        assert_eq!(results[0].1.as_ref().unwrap(), "HTTP 202 Accepted");
+        assert_eq!(results[1].0, "slack");
+        assert!(results[1]
+            .1
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("404"));
+
+        let only_slack = manager.test_notifications(Some("slack"));
+        assert_eq!(only_slack.len(), 1);
+        assert!(manager.test_notifications(Some("email")).is_empty());
+
+        hook.assert();
+        slack.assert();
+        assert!(manager.get_alerts(None, None).is_empty());
+        assert!(!storage_path.exists());
+    }
+
+    #[test]
+    fn test_get_alerts_filtered_by_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager_with_refire(dir.path(), None);
+        for (device, mount_point) in [("sda1", "/var"), ("sdb1", "/home"), ("sdc1", "/var")] {
+            let mut alert = Alert::new(
+                format!("{} on {} is filling up", device, mount_point),
+                String::new(),
+                AlertSeverity::Warning,
+                "monitor".to_string(),
+            );
+            alert.add_metadata("mount_point".to_string(), mount_point.to_string());
+            alert.add_metadata("device".to_string(), device.to_string());
+            manager.create_alert(alert).unwrap();
+        }
+        manager
+            .create_alert(Alert::new(
+                "no metadata".to_string(),
+                String::new(),
+                AlertSeverity::Info,
+                "test".to_string(),
+            ))
+            .unwrap();
+
+        let var = MetadataMatcher::parse("mount_point=/var").unwrap();
+        assert_eq!(manager.get_alerts_filtered(None, &[]).len(), 4);
+        assert_eq!(
+            manager
+                .get_alerts_filtered(None, std::slice::from_ref(&var))
+                .len(),
+            2
+        );
+
+        let sdc = MetadataMatcher::parse("device=sdc1").unwrap();
+        let both = manager.get_alerts_filtered(Some(AlertStatus::Firing), &[var.clone(), sdc]);
+        assert_eq!(both.len(), 1);
+        assert_eq!(both[0].metadata["device"], "sdc1");
+
+        assert!(manager
+            .get_alerts_filtered(Some(AlertStatus::Resolved), &[var])
+            .is_empty());
+        assert!(MetadataMatcher::parse("mount_point").is_err());
+        assert!(MetadataMatcher::parse("=/var").is_err());
+    }
+
+    #[test]
+    fn test_prune_keeps_active_and_recent_alerts() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_path = dir.path().join("alerts.json");
+        let now = Utc::now();
+        let aged = |title: &str, status: AlertStatus, days: i64| {
+            let mut alert = Alert::new(
+                title.to_string(),
+                String::new(),
+                AlertSeverity::Warning,
+                "test".to_string(),
+            );
+            let then = now - chrono::Duration::days(days);
+            alert.status = status;
+            alert.created_at = then;
+            alert.updated_at = then;
+            if alert.status == AlertStatus::Resolved {
+                alert.resolved_at = Some(then);
+            }
+            alert
+        };
+        let alerts = vec![
+            aged("old firing", AlertStatus::Firing, 90),
+            aged("old acknowledged", AlertStatus::Acknowledged, 90),
+            aged("old resolved", AlertStatus::Resolved, 90),
+            aged("week-old resolved", AlertStatus::Resolved, 10),
+            aged("week-old silenced", AlertStatus::Silenced, 10),
+            aged("fresh resolved", AlertStatus::Resolved, 1),
+        ];
+        fs::write(&storage_path, serde_json::to_string(&alerts).unwrap()).unwrap();
+
+        // Saving applies the 30 day retention on its own
+        let mut manager = manager_with_refire(dir.path(), None);
+        manager
+            .create_alert(aged("new", AlertStatus::Firing, 0))
+            .unwrap();
+        assert!(manager.get_alert(&alerts[2].id).is_none());
+        assert_eq!(manager.get_alerts(None, None).len(), 6);
+
+        let cutoff = now - chrono::Duration::days(7);
+        assert_eq!(manager.stale_alerts(cutoff).len(), 2);
+        assert_eq!(manager.prune_stale(cutoff).unwrap(), 2);
+
+        let reloaded = AlertManager::new(storage_path).unwrap();
+        let mut titles: Vec<_> = reloaded
+            .get_alerts(None, None)
+            .iter()
+            .map(|a| a.title.as_str())
+            .collect();
+        titles.sort();
+        assert_eq!(
+            titles,
+            vec!["fresh resolved", "new", "old acknowledged", "old firing"]
+        );
+    }
+
+    #[test]
+    fn test_history_records_transitions_in_order() {
+        let mut alert = alert(AlertSeverity::Warning, AlertStatus::Firing);
+        assert!(alert.history.is_empty());
+
+        alert.acknowledge();
+        alert.refire(Utc::now());
+        alert.silence();
+        alert.auto_resolve();
+
+        let steps: Vec<_> = alert
+            .history
+            .iter()
+            .map(|t| (t.from_status.clone(), t.to_status.clone()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (AlertStatus::Firing, AlertStatus::Acknowledged),
+                (AlertStatus::Acknowledged, AlertStatus::Firing),
+                (AlertStatus::Firing, AlertStatus::Silenced),
+                (AlertStatus::Silenced, AlertStatus::Resolved),
+            ]
+        );
+        assert_eq!(alert.history[0].actor, audit::current_user());
+        assert_eq!(alert.history[1].actor, "monitor");
+        assert_eq!(alert.history[3].actor, "monitor");
+        assert!(alert
+            .history
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+        // Alerts stored before history existed load with an empty one
+        let mut json = serde_json::to_value(&alert).unwrap();
+        json.as_object_mut().unwrap().remove("history");
+        let old: Alert = serde_json::from_value(json).unwrap();
+        assert!(old.history.is_empty());
+    }
+
+    #[test]
+    fn test_routing_sends_by_severity() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new();
+        let hook = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"severity": "Critical"}),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let webhook = NotificationChannel::Webhook {
+            url: format!("{}/hook", server.url()),
+            retry: fast_retry(),
+        };
+        let pagerduty = NotificationChannel::PagerDuty {
+            routing_key: "R0UT1NG".to_string(),
+            min_severity: AlertSeverity::Info,
+        };
+        let config = AlertConfig {
+            notification_channels: vec![NotificationChannel::Console, pagerduty.clone()],
+            routing: HashMap::from([
+                (
+                    AlertSeverity::Critical,
+                    vec![webhook.clone(), pagerduty.clone()],
+                ),
+                (AlertSeverity::Info, vec![NotificationChannel::Console]),
+            ]),
+            ..Default::default()
+        };
+
+        assert!(!config
+            .channels_for(&AlertSeverity::Info)
+            .contains(&pagerduty));
+        assert!(config
+            .channels_for(&AlertSeverity::Critical)
+            .contains(&pagerduty));
+        // Warning has no route, so it falls back to the full list
+        assert_eq!(
+            config.channels_for(&AlertSeverity::Warning),
+            config.notification_channels.as_slice()
+        );
+        assert_eq!(config.all_channels().len(), 3);
+
+        let routed_only = AlertConfig {
+            notification_channels: vec![NotificationChannel::Console],
+            routing: HashMap::from([
+                (AlertSeverity::Critical, vec![webhook]),
+                (AlertSeverity::Info, vec![NotificationChannel::Console]),
+            ]),
+            ..Default::default()
+        };
+        let mut manager =
+            AlertManager::with_config(dir.path().join("alerts.json"), routed_only).unwrap();
+        for severity in [AlertSeverity::Info, AlertSeverity::Critical] {
+            let title = format!("{:?} alert", severity);
+            manager
+                .create_alert(Alert::new(
+                    title,
+                    String::new(),
+                    severity,
+                    "test".to_string(),
+                ))
+                .unwrap();
+        }
+        hook.assert();
+    }
+
+    #[test]
+    fn test_stats_mean_response_times() {
+        let created = DateTime::parse_from_rfc3339("2024-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let minutes = |m: i64| created + chrono::Duration::minutes(m);
+        let at = |source: &str, acknowledged: Option<i64>, resolved: Option<i64>| {
+            let mut alert = alert(AlertSeverity::Warning, AlertStatus::Firing);
+            alert.source = source.to_string();
+            alert.created_at = created;
+            alert.acknowledged_at = acknowledged.map(minutes);
+            alert.resolved_at = resolved.map(minutes);
+            if resolved.is_some() {
+                alert.status = AlertStatus::Resolved;
+            } else if acknowledged.is_some() {
+                alert.status = AlertStatus::Acknowledged;
+            }
+            alert
+        };
+        let alerts = [
+            at("disk-monitor", Some(5), Some(60)),
+            at("disk-monitor", Some(15), None),
+            at("hook:raid", None, Some(30)),
+            at("disk-monitor", None, None),
+        ];
+        let refs: Vec<&Alert> = alerts.iter().collect();
+
+        let stats = AlertStats::from_alerts(&refs);
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.counts.resolved, 2);
+        assert_eq!(stats.counts.acknowledged, 1);
+        assert_eq!(stats.counts.firing, 1);
+        // (5m + 15m) / 2 and (60m + 30m) / 2
+        assert_eq!(stats.mean_time_to_acknowledge_seconds, Some(10 * 60));
+        assert_eq!(stats.acknowledged_samples, 2);
+        assert_eq!(stats.mean_time_to_resolve_seconds, Some(45 * 60));
+        assert_eq!(stats.resolved_samples, 2);
+        assert_eq!(
+            stats.top_sources,
+            vec![
+                SourceCount {
+                    source: "disk-monitor".to_string(),
+                    count: 3
+                },
+                SourceCount {
+                    source: "hook:raid".to_string(),
+                    count: 1
+                },
+            ]
+        );
+
+        let empty = AlertStats::from_alerts(&[]);
+        assert_eq!(empty.mean_time_to_acknowledge_seconds, None);
+        assert_eq!(empty.mean_time_to_resolve_seconds, None);
+        assert_eq!(mean_seconds(&[1, 2]), Some(2));
+    }
+}
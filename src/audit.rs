@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::outcome::CommandOutcome;
+use crate::state;
+
+/// How many entries `audit tail` shows when no count is given
+pub const DEFAULT_TAIL: usize = 20;
+
+/// One mutating command, as recorded in `~/.catdog/audit.log`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub user: String,
+    pub action: String,
+    pub target: String,
+    /// The full command line after `catdog`
+    pub args: Vec<String>,
+    pub success: bool,
+    pub dry_run: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(action: &str, target: &str, args: &[String], dry_run: bool) -> Self {
+        AuditEntry {
+            timestamp: Utc::now(),
+            user: current_user(),
+            action: action.to_string(),
+            target: target.to_string(),
+            args: args.to_vec(),
+            success: true,
+            dry_run,
+            error: None,
+        }
+    }
+
+    fn failed(mut self, error: String) -> Self {
+        self.success = false;
+        self.error = Some(error);
+        self
+    }
+}
+
+pub fn current_user() -> String {
+    env::var("USER")
+        .ok()
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+pub fn audit_log_path() -> Result<PathBuf> {
+    Ok(state::state_dir()?.join("audit.log"))
+}
+
+/// Record a mutating command and whether it succeeded
+pub fn log_action<T>(
+    action: &str,
+    target: &str,
+    args: &[String],
+    result: &Result<T>,
+    dry_run: bool,
+) -> Result<()> {
+    let mut entry = AuditEntry::new(action, target, args, dry_run);
+    if let Err(e) = result {
+        entry = entry.failed(format!("{:#}", e));
+    }
+    append_entry(&audit_log_path()?, &entry)
+}
+
+/// Record a batch command; any failed item marks the whole entry as failed
+pub fn log_outcome(
+    action: &str,
+    target: &str,
+    args: &[String],
+    result: &Result<CommandOutcome>,
+    dry_run: bool,
+) -> Result<()> {
+    let mut entry = AuditEntry::new(action, target, args, dry_run);
+    match result {
+        Ok(outcome) if outcome.failed > 0 => entry = entry.failed(outcome.summary()),
+        Ok(_) => {}
+        Err(e) => entry = entry.failed(format!("{:#}", e)),
+    }
+    append_entry(&audit_log_path()?, &entry)
+}
+
+pub fn append_entry(path: &Path, entry: &AuditEntry) -> Result<()> {
+    let json = serde_json::to_string(entry)?;
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    writeln!(file, "{}", json)?;
+
+    Ok(())
+}
+
+/// The last `count` entries, oldest first; unreadable lines are skipped
+pub fn tail(path: &Path, count: usize) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let entries: Vec<AuditEntry> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+
+    let skip = entries.len().saturating_sub(count);
+    Ok(entries.into_iter().skip(skip).collect())
+}
+
+pub fn display_entries(entries: &[AuditEntry]) {
+    if entries.is_empty() {
+        println!("{}", "No audit entries recorded yet".bright_black());
+        return;
+    }
+
+    for entry in entries {
+        let marker = if entry.success {
+            "✓".green().bold()
+        } else {
+            "✗".red().bold()
+        };
+        let dry_run = if entry.dry_run {
+            " (dry run)".bright_black().to_string()
+        } else {
+            String::new()
+        };
+
+        println!(
+            "{} {} {} {} {}{}",
+            marker,
+            entry
+                .timestamp
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .bright_black(),
+            entry.user.cyan(),
+            entry.action.bright_yellow(),
+            entry.target.bright_white(),
+            dry_run
+        );
+        if let Some(error) = &entry.error {
+            println!("    {}", error.red());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let args = vec!["pkg".to_string(), "install".to_string(), "vim".to_string()];
+
+        for i in 0..5 {
+            let entry = AuditEntry::new("pkg install", &format!("vim{}", i), &args, false);
+            append_entry(&path, &entry).unwrap();
+        }
+        let failed = AuditEntry::new("restore", "/etc/fstab", &args, true)
+            .failed("Permission denied".to_string());
+        append_entry(&path, &failed).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 6);
+
+        let entries = tail(&path, 3).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].target, "vim3");
+        assert_eq!(entries[2], failed);
+        assert!(!entries[2].success && entries[2].dry_run);
+
+        assert_eq!(tail(&path, 100).unwrap().len(), 6);
+        assert!(tail(&dir.path().join("missing.log"), 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tail_skips_corrupt_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        append_entry(&path, &AuditEntry::new("backup", "/etc/fstab", &[], false)).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"truncated\n")
+            .unwrap();
+        append_entry(&path, &AuditEntry::new("restore", "/etc/fstab", &[], false)).unwrap();
+
+        let actions: Vec<_> = tail(&path, 10)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.action)
+            .collect();
+        assert_eq!(actions, vec!["backup", "restore"]);
+    }
+}
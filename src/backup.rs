@@ -1,22 +1,54 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use colored::*;
 use log::{debug, error, info, warn};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::config::BackupConfig;
+use crate::state;
 
-const MAX_BACKUPS_PER_FILE: usize = 10;
 const BACKUP_DIR_NAME: &str = ".catdog_backups";
 
+/// Environment variable holding the backup passphrase, for non-interactive use
+const PASSPHRASE_ENV: &str = "CATDOG_BACKUP_KEY";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupMetadata {
     pub original_path: String,
     pub backup_path: String,
     pub timestamp: String,
     pub reason: BackupReason,
+    /// SHA-256 of the original contents, also for encrypted backups
     pub checksum: String,
     pub size_bytes: u64,
+    #[serde(default)]
+    pub encrypted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionInfo>,
+}
+
+/// Parameters needed to decrypt a backup, stored hex-encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionInfo {
+    pub salt: String,
+    pub nonce: String,
+    /// SHA-256 of the encrypted file, so health checks work without the passphrase
+    pub ciphertext_checksum: String,
+}
+
+impl BackupMetadata {
+    /// Checksum of the backup file as stored on disk
+    fn file_checksum(&self) -> &str {
+        match &self.encryption {
+            Some(info) => &info.ciphertext_checksum,
+            None => &self.checksum,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,16 +72,20 @@ impl BackupReason {
     }
 }
 
+/// Root directory holding all backups
+pub fn backup_base() -> Result<PathBuf> {
+    Ok(state::home_dir()?.join(BACKUP_DIR_NAME))
+}
+
 /// Get the backup directory for a given file
 fn get_backup_dir(file_path: &Path) -> Result<PathBuf> {
-    let home = dirs::home_dir().context("Failed to get home directory")?;
-    let backup_base = home.join(BACKUP_DIR_NAME);
+    let backup_base = backup_base()?;
+    state::ensure_private_dir(&backup_base)?;
 
     // Create subdirectory based on original file path to organize backups
     let sanitized_path = file_path
         .to_string_lossy()
-        .replace('/', "_")
-        .replace('\\', "_")
+        .replace(['/', '\\'], "_")
         .trim_start_matches('_')
         .to_string();
 
@@ -65,9 +101,23 @@ fn get_backup_dir(file_path: &Path) -> Result<PathBuf> {
 }
 
 /// Create a backup of a file with metadata
+///
+/// Older backups of the file are then cleaned up according to `retention`.
 pub fn create_backup(
     file_path: &str,
     reason: BackupReason,
+    retention: &BackupConfig,
+    dry_run: bool,
+) -> Result<BackupMetadata> {
+    create_backup_with_passphrase(file_path, reason, None, retention, dry_run)
+}
+
+/// Create a backup, encrypting it with AES-256-GCM when a passphrase is given
+pub fn create_backup_with_passphrase(
+    file_path: &str,
+    reason: BackupReason,
+    passphrase: Option<&str>,
+    retention: &BackupConfig,
     dry_run: bool,
 ) -> Result<BackupMetadata> {
     let source = Path::new(file_path);
@@ -113,6 +163,8 @@ pub fn create_backup(
             reason,
             checksum,
             size_bytes,
+            encrypted: passphrase.is_some(),
+            encryption: None,
         });
     }
 
@@ -122,11 +174,33 @@ pub fn create_backup(
         file_path,
         backup_path.display()
     );
-    fs::copy(source, &backup_path)
-        .with_context(|| format!("Failed to create backup at {}", backup_path.display()))?;
-
-    // Verify the backup
-    verify_backup(source, &backup_path)?;
+    let encryption = match passphrase {
+        Some(passphrase) => {
+            let plaintext = fs::read(source)
+                .with_context(|| format!("Failed to read {} for encryption", file_path))?;
+            let (ciphertext, info) = encryption::encrypt(&plaintext, passphrase)?;
+            state::write_atomic_like(&backup_path, &ciphertext, source)
+                .with_context(|| format!("Failed to create backup at {}", backup_path.display()))?;
+
+            // Verify the backup decrypts back to the original
+            let decrypted = encryption::decrypt(&fs::read(&backup_path)?, passphrase, &info)?;
+            if checksum_bytes(&decrypted) != checksum {
+                anyhow::bail!("Backup verification failed: decrypted contents don't match");
+            }
+            Some(info)
+        }
+        None => {
+            fs::read(source)
+                .map_err(anyhow::Error::from)
+                // Carry the original's mode so a restore can reapply it
+                .and_then(|contents| state::write_atomic_like(&backup_path, contents, source))
+                .with_context(|| format!("Failed to create backup at {}", backup_path.display()))?;
+
+            // Verify the backup
+            verify_backup(source, &backup_path)?;
+            None
+        }
+    };
 
     let metadata = BackupMetadata {
         original_path: file_path.to_string(),
@@ -135,6 +209,8 @@ pub fn create_backup(
         reason: reason.clone(),
         checksum: checksum.clone(),
         size_bytes,
+        encrypted: encryption.is_some(),
+        encryption,
     };
 
     // Save metadata
@@ -159,7 +235,7 @@ pub fn create_backup(
     );
 
     // Cleanup old backups
-    cleanup_old_backups(&backup_dir)?;
+    cleanup_old_backups(&backup_dir, retention)?;
 
     Ok(metadata)
 }
@@ -202,15 +278,48 @@ fn calculate_checksum(path: &Path) -> Result<String> {
     Ok(hasher.finish())
 }
 
+/// Calculate SHA-256 checksum of in-memory contents
+fn checksum_bytes(data: &[u8]) -> String {
+    let mut hasher = sha256::Sha256::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// Read the backup passphrase from `CATDOG_BACKUP_KEY`, or prompt for it
+///
+/// With `confirm`, an interactive passphrase has to be entered twice.
+pub fn read_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+
+    let passphrase =
+        rpassword::prompt_password("Backup passphrase: ").context("Failed to read passphrase")?;
+    if passphrase.is_empty() {
+        anyhow::bail!("Passphrase must not be empty");
+    }
+
+    if confirm {
+        let again = rpassword::prompt_password("Confirm passphrase: ")
+            .context("Failed to read passphrase")?;
+        if again != passphrase {
+            anyhow::bail!("Passphrases do not match");
+        }
+    }
+
+    Ok(passphrase)
+}
+
 /// Save backup metadata to a JSON file
 fn save_metadata(metadata: &BackupMetadata) -> Result<()> {
-    let backup_path = Path::new(&metadata.backup_path);
-    let metadata_path = backup_path.with_extension("backup.json");
+    let metadata_path = metadata_path(Path::new(&metadata.backup_path));
 
     let json =
         serde_json::to_string_pretty(metadata).context("Failed to serialize backup metadata")?;
 
-    fs::write(&metadata_path, json)
+    state::write_atomic(&metadata_path, json)
         .with_context(|| format!("Failed to write metadata to {}", metadata_path.display()))?;
 
     Ok(())
@@ -218,9 +327,13 @@ fn save_metadata(metadata: &BackupMetadata) -> Result<()> {
 
 /// Load backup metadata from a JSON file
 fn load_metadata(backup_path: &Path) -> Result<BackupMetadata> {
-    let metadata_path = backup_path.with_extension("backup.json");
+    let metadata_path =
+        existing_metadata_path(backup_path).unwrap_or_else(|| metadata_path(backup_path));
+    read_metadata(&metadata_path)
+}
 
-    let json = fs::read_to_string(&metadata_path)
+fn read_metadata(metadata_path: &Path) -> Result<BackupMetadata> {
+    let json = fs::read_to_string(metadata_path)
         .with_context(|| format!("Failed to read metadata from {}", metadata_path.display()))?;
 
     let metadata: BackupMetadata =
@@ -229,8 +342,40 @@ fn load_metadata(backup_path: &Path) -> Result<BackupMetadata> {
     Ok(metadata)
 }
 
-/// Cleanup old backups, keeping only MAX_BACKUPS_PER_FILE most recent
-fn cleanup_old_backups(backup_dir: &Path) -> Result<()> {
+/// Metadata sidecar for a backup file (`<backup>.json`)
+fn metadata_path(backup_path: &Path) -> PathBuf {
+    let mut path = backup_path.as_os_str().to_owned();
+    path.push(".json");
+    PathBuf::from(path)
+}
+
+/// Sidecar name used by earlier versions
+///
+/// `with_extension` replaced the timestamp, so every backup of a file shared
+/// `<file>.backup.backup.json` and it only describes the last backup written.
+fn legacy_metadata_path(backup_path: &Path) -> PathBuf {
+    backup_path.with_extension("backup.json")
+}
+
+/// The sidecar describing a backup: `<backup>.json`, or a legacy sidecar naming it
+fn existing_metadata_path(backup_path: &Path) -> Option<PathBuf> {
+    let path = metadata_path(backup_path);
+    if path.exists() {
+        return Some(path);
+    }
+
+    let legacy = legacy_metadata_path(backup_path);
+    read_metadata(&legacy)
+        .ok()
+        .filter(|metadata| Path::new(&metadata.backup_path).file_name() == backup_path.file_name())
+        .map(|_| legacy)
+}
+
+/// Cleanup old backups, keeping the `max_per_file` most recent
+///
+/// With `max_age_days` set, older backups are removed even when under the limit.
+/// Returns the number of backups removed.
+fn cleanup_old_backups(backup_dir: &Path, retention: &BackupConfig) -> Result<usize> {
     let mut backups: Vec<PathBuf> = Vec::new();
 
     for entry in fs::read_dir(backup_dir)? {
@@ -254,34 +399,52 @@ fn cleanup_old_backups(backup_dir: &Path) -> Result<()> {
         b_time.cmp(&a_time)
     });
 
+    // An age too large to represent expires nothing
+    let cutoff = retention
+        .max_age_days
+        .and_then(|days| days.checked_mul(24 * 60 * 60))
+        .and_then(|secs| SystemTime::now().checked_sub(Duration::from_secs(secs)));
+    let is_expired = |path: &Path| match cutoff {
+        Some(cutoff) => fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(|modified| modified < cutoff)
+            .unwrap_or(false),
+        None => false,
+    };
+
     // Remove old backups
-    if backups.len() > MAX_BACKUPS_PER_FILE {
-        for backup in backups.iter().skip(MAX_BACKUPS_PER_FILE) {
-            debug!("Removing old backup: {}", backup.display());
+    let mut removed_count = 0;
+    for (index, backup) in backups.iter().enumerate() {
+        if index < retention.max_per_file && !is_expired(backup) {
+            continue;
+        }
 
-            // Remove backup file
-            if let Err(e) = fs::remove_file(backup) {
-                warn!("Failed to remove old backup {}: {}", backup.display(), e);
-            }
+        debug!("Removing old backup: {}", backup.display());
 
-            // Remove metadata file
-            let metadata_path = backup.with_extension("backup.json");
-            if metadata_path.exists() {
-                if let Err(e) = fs::remove_file(&metadata_path) {
-                    warn!(
-                        "Failed to remove metadata {}: {}",
-                        metadata_path.display(),
-                        e
-                    );
-                }
+        // Remove backup file
+        if let Err(e) = fs::remove_file(backup) {
+            warn!("Failed to remove old backup {}: {}", backup.display(), e);
+            continue;
+        }
+        removed_count += 1;
+
+        // Remove metadata file
+        if let Some(metadata_path) = existing_metadata_path(backup) {
+            if let Err(e) = fs::remove_file(&metadata_path) {
+                warn!(
+                    "Failed to remove metadata {}: {}",
+                    metadata_path.display(),
+                    e
+                );
             }
         }
+    }
 
-        let removed_count = backups.len() - MAX_BACKUPS_PER_FILE;
+    if removed_count > 0 {
         info!("Cleaned up {} old backup(s)", removed_count);
     }
 
-    Ok(())
+    Ok(removed_count)
 }
 
 /// List all backups for a specific file
@@ -313,8 +476,77 @@ pub fn list_backups(file_path: &str) -> Result<Vec<BackupMetadata>> {
     Ok(backups)
 }
 
+/// Read a backup's metadata and contents, decrypting if needed
+pub fn read_backup(backup_path: &str) -> Result<(BackupMetadata, Vec<u8>)> {
+    let backup = Path::new(backup_path);
+
+    if !backup.exists() {
+        anyhow::bail!("Backup file does not exist: {}", backup_path);
+    }
+
+    let metadata = load_metadata(backup).context("Failed to load backup metadata")?;
+    let stored =
+        fs::read(backup).with_context(|| format!("Failed to read backup {}", backup_path))?;
+
+    let contents = match &metadata.encryption {
+        Some(info) => encryption::decrypt(&stored, &read_passphrase(false)?, info)?,
+        None => stored,
+    };
+
+    Ok((metadata, contents))
+}
+
+/// Whether two paths name the same file, resolving symlinks, `.` and `..`
+///
+/// A path that doesn't exist yet is compared through its resolved parent.
+fn same_path(a: &Path, b: &Path) -> bool {
+    fn resolve(path: &Path) -> PathBuf {
+        if let Ok(path) = fs::canonicalize(path) {
+            return path;
+        }
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            if let Ok(parent) = fs::canonicalize(parent) {
+                return parent.join(name);
+            }
+        }
+        std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    a == b || resolve(a) == resolve(b)
+}
+
+/// Where restoring a backup would write: `target`, or else the original path
+pub fn restore_destination(backup_path: &str, target: Option<&str>) -> Result<PathBuf> {
+    if let Some(target) = target {
+        return Ok(PathBuf::from(target));
+    }
+    let metadata =
+        load_metadata(Path::new(backup_path)).context("Failed to load backup metadata")?;
+    Ok(PathBuf::from(metadata.original_path))
+}
+
 /// Restore a file from a backup
-pub fn restore_backup(backup_path: &str, dry_run: bool, force: bool) -> Result<()> {
+///
+/// With `ignore_missing`, a missing original is treated as a fresh target:
+/// parent directories are created and no pre-restore backup is taken.
+/// With a `target` other than the original path, the backup is written there
+/// and the original is left alone, so the modification check and pre-restore
+/// backup are skipped. A `target` naming the original path, however spelled,
+/// is treated like no `target` at all. The pre-restore backup is subject to
+/// `retention` like any other.
+pub fn restore_backup(
+    backup_path: &str,
+    target: Option<&str>,
+    retention: &BackupConfig,
+    dry_run: bool,
+    force: bool,
+    ignore_missing: bool,
+) -> Result<()> {
     let backup = Path::new(backup_path);
 
     if !backup.exists() {
@@ -325,9 +557,19 @@ pub fn restore_backup(backup_path: &str, dry_run: bool, force: bool) -> Result<(
     let metadata = load_metadata(backup).context("Failed to load backup metadata")?;
 
     let original = Path::new(&metadata.original_path);
+    let destination = target.map(Path::new).unwrap_or(original);
+    let replaces_original = same_path(destination, original);
+    let original_missing = !original.exists();
+
+    if replaces_original && original_missing && !ignore_missing {
+        warn!(
+            "Original file {} does not exist; restoring anyway (use --ignore-missing for fresh targets)",
+            metadata.original_path
+        );
+    }
 
     // Check if original file exists and hasn't been modified
-    if original.exists() && !force {
+    if replaces_original && !original_missing && !force {
         let current_checksum = calculate_checksum(original)?;
         if current_checksum != metadata.checksum {
             anyhow::bail!(
@@ -343,16 +585,46 @@ pub fn restore_backup(backup_path: &str, dry_run: bool, force: bool) -> Result<(
             "{} Would restore: {} -> {}",
             "[DRY-RUN]".yellow().bold(),
             backup_path.bright_white(),
-            metadata.original_path.bright_white()
+            destination.display().to_string().bright_white()
         );
         return Ok(());
     }
 
+    // Read and decrypt up front so a wrong passphrase fails before anything is
+    // touched, and the pre-restore backup can't change what gets restored
+    let stored =
+        fs::read(backup).with_context(|| format!("Failed to read backup {}", backup_path))?;
+    let (passphrase, contents) = match &metadata.encryption {
+        Some(info) => {
+            let passphrase = read_passphrase(false)?;
+            let plaintext = encryption::decrypt(&stored, &passphrase, info)?;
+            (Some(passphrase), plaintext)
+        }
+        None if metadata.encrypted => {
+            anyhow::bail!(
+                "Encrypted backup is missing its salt and nonce: {}",
+                backup_path
+            )
+        }
+        None => (None, stored),
+    };
+
+    if !replaces_original || (original_missing && ignore_missing) {
+        if let Some(parent) = destination.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create parent directory {}", parent.display())
+            })?;
+        }
+    }
+
     // Create backup of current state before restoring
-    if original.exists() {
-        let pre_restore_backup = create_backup(
+    if replaces_original && !original_missing {
+        // Keep the pre-restore copy encrypted if the backup was
+        let pre_restore_backup = create_backup_with_passphrase(
             &metadata.original_path,
             BackupReason::PreSystemChange,
+            passphrase.as_deref(),
+            retention,
             false,
         )?;
         info!(
@@ -362,18 +634,26 @@ pub fn restore_backup(backup_path: &str, dry_run: bool, force: bool) -> Result<(
     }
 
     // Perform the restore
-    fs::copy(backup, original)
-        .with_context(|| format!("Failed to restore backup to {}", original.display()))?;
+    // A missing destination gets the backup's mode, as a plain copy would
+    state::write_atomic_like(destination, &contents, backup)
+        .with_context(|| format!("Failed to restore backup to {}", destination.display()))?;
 
     // Verify the restore
-    verify_backup(backup, original)?;
+    let restored_checksum = calculate_checksum(destination)?;
+    if restored_checksum != metadata.checksum {
+        anyhow::bail!(
+            "Restore verification failed: checksums don't match\nBackup: {}\nRestored: {}",
+            metadata.checksum,
+            restored_checksum
+        );
+    }
 
-    info!("Successfully restored: {}", metadata.original_path);
+    info!("Successfully restored: {}", destination.display());
 
     // Emit restore event
     let _ = emit_backup_event(
         BackupEventType::BackupRestored,
-        &metadata.original_path,
+        &destination.to_string_lossy(),
         &format!("Restored from backup: {}", backup_path),
         EventSeverity::Info,
     );
@@ -393,6 +673,9 @@ pub fn display_backup_info(metadata: &BackupMetadata) {
     println!("  {} {}", "Timestamp:".cyan(), metadata.timestamp);
     println!("  {} {}", "Reason:".cyan(), metadata.reason.description());
     println!("  {} {}", "Size:".cyan(), format_bytes(metadata.size_bytes));
+    if metadata.encrypted {
+        println!("  {} {}", "Encrypted:".cyan(), "yes".yellow());
+    }
     println!(
         "  {} {}",
         "Checksum:".cyan(),
@@ -445,61 +728,74 @@ fn format_bytes(bytes: u64) -> String {
 
 /// Get backup statistics
 pub fn get_backup_stats() -> Result<BackupStats> {
-    let home = dirs::home_dir().context("Failed to get home directory")?;
-    let backup_base = home.join(BACKUP_DIR_NAME);
-
-    if !backup_base.exists() {
-        return Ok(BackupStats {
-            total_backups: 0,
-            total_size_bytes: 0,
-            oldest_backup: None,
-            newest_backup: None,
-        });
-    }
+    Ok(stats_in(&backup_base()?))
+}
 
+fn stats_in(backup_base: &Path) -> BackupStats {
     let mut total_backups = 0;
     let mut total_size_bytes = 0u64;
-    let mut oldest: Option<String> = None;
-    let mut newest: Option<String> = None;
+    let mut oldest: Option<(chrono::NaiveDateTime, String)> = None;
+    let mut newest: Option<(chrono::NaiveDateTime, String)> = None;
+    let mut per_file: BTreeMap<String, FileBackupSummary> = BTreeMap::new();
 
-    // Walk through all backup directories
-    for entry in walkdir::WalkDir::new(&backup_base)
-        .follow_links(false)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
+    // Walk through all backup directories, counting backup files (not metadata)
+    for path in backup_files(backup_base) {
+        total_backups += 1;
 
-        // Count backup files (not metadata)
-        if path.is_file() {
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                if filename.contains(".backup.") && !filename.ends_with(".json") {
-                    total_backups += 1;
-
-                    if let Ok(metadata) = fs::metadata(path) {
-                        total_size_bytes += metadata.len();
-                    }
-
-                    // Track oldest and newest
-                    if let Ok(meta) = load_metadata(path) {
-                        if oldest.is_none() || Some(&meta.timestamp) < oldest.as_ref() {
-                            oldest = Some(meta.timestamp.clone());
-                        }
-                        if newest.is_none() || Some(&meta.timestamp) > newest.as_ref() {
-                            newest = Some(meta.timestamp);
-                        }
-                    }
-                }
-            }
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        total_size_bytes += size;
+
+        let Ok(meta) = load_metadata(&path) else {
+            continue;
+        };
+
+        let summary = per_file
+            .entry(meta.original_path.clone())
+            .or_insert_with(|| FileBackupSummary {
+                original_path: meta.original_path.clone(),
+                count: 0,
+                total_size_bytes: 0,
+                newest_backup: None,
+            });
+        summary.count += 1;
+        summary.total_size_bytes += size;
+
+        // Track oldest and newest by parsed time, skipping unreadable timestamps
+        let Ok(time) = parse_backup_timestamp(&meta.timestamp) else {
+            continue;
+        };
+        let file_newest = summary
+            .newest_backup
+            .as_deref()
+            .and_then(|t| parse_backup_timestamp(t).ok());
+        if file_newest.is_none_or(|file_newest| time > file_newest) {
+            summary.newest_backup = Some(meta.timestamp.clone());
+        }
+        if oldest
+            .as_ref()
+            .is_none_or(|(oldest_time, _)| time < *oldest_time)
+        {
+            oldest = Some((time, meta.timestamp.clone()));
+        }
+        if newest
+            .as_ref()
+            .is_none_or(|(newest_time, _)| time > *newest_time)
+        {
+            newest = Some((time, meta.timestamp));
         }
     }
 
-    Ok(BackupStats {
+    // Largest first; the map already ordered ties by path
+    let mut per_file: Vec<FileBackupSummary> = per_file.into_values().collect();
+    per_file.sort_by_key(|file| std::cmp::Reverse(file.total_size_bytes));
+
+    BackupStats {
         total_backups,
         total_size_bytes,
-        oldest_backup: oldest,
-        newest_backup: newest,
-    })
+        oldest_backup: oldest.map(|(_, timestamp)| timestamp),
+        newest_backup: newest.map(|(_, timestamp)| timestamp),
+        per_file,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -508,6 +804,16 @@ pub struct BackupStats {
     pub total_size_bytes: u64,
     pub oldest_backup: Option<String>,
     pub newest_backup: Option<String>,
+    pub per_file: Vec<FileBackupSummary>,
+}
+
+/// Backups of one original file, grouped from metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileBackupSummary {
+    pub original_path: String,
+    pub count: usize,
+    pub total_size_bytes: u64,
+    pub newest_backup: Option<String>,
 }
 
 impl BackupStats {
@@ -532,8 +838,30 @@ impl BackupStats {
             println!("{} {}", "Newest Backup:".cyan(), newest.bright_white());
         }
 
-        let home = dirs::home_dir().unwrap_or_default();
-        let backup_dir = home.join(BACKUP_DIR_NAME);
+        if !self.per_file.is_empty() {
+            println!("\n{}", "Per File:".cyan().bold());
+            println!(
+                "  {:<40} {:>7}  {:>10}  {}",
+                "File".bold(),
+                "Backups".bold(),
+                "Size".bold(),
+                "Newest".bold()
+            );
+            for file in &self.per_file {
+                println!(
+                    "  {:<40} {:>7}  {:>10}  {}",
+                    file.original_path.bright_white(),
+                    file.count,
+                    format_bytes(file.total_size_bytes),
+                    file.newest_backup
+                        .as_deref()
+                        .unwrap_or("-")
+                        .truecolor(150, 150, 150)
+                );
+            }
+        }
+
+        let backup_dir = backup_base().unwrap_or_default();
         println!(
             "\n{} {}",
             "Backup Directory:".cyan(),
@@ -542,7 +870,75 @@ impl BackupStats {
     }
 }
 
-// Simple SHA-256 implementation
+/// Passphrase-based backup encryption: Argon2id key derivation and AES-256-GCM
+mod encryption {
+    use super::{checksum_bytes, EncryptionInfo};
+    use aes_gcm::aead::rand_core::RngCore;
+    use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+    use anyhow::Result;
+
+    const SALT_LEN: usize = 16;
+    const NONCE_LEN: usize = 12;
+
+    fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>> {
+        let mut key = Key::<Aes256Gcm>::default();
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| anyhow::anyhow!("Failed to derive key: {}", e))?;
+        Ok(key)
+    }
+
+    pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<(Vec<u8>, EncryptionInfo)> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt backup"))?;
+
+        let info = EncryptionInfo {
+            salt: to_hex(&salt),
+            nonce: to_hex(&nonce),
+            ciphertext_checksum: checksum_bytes(&ciphertext),
+        };
+        Ok((ciphertext, info))
+    }
+
+    pub fn decrypt(ciphertext: &[u8], passphrase: &str, info: &EncryptionInfo) -> Result<Vec<u8>> {
+        let salt = from_hex(&info.salt)?;
+        let nonce = from_hex(&info.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            anyhow::bail!("Invalid nonce in backup metadata");
+        }
+
+        let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupted backup"))
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn from_hex(hex: &str) -> Result<Vec<u8>> {
+        if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+            anyhow::bail!("Invalid hex value in backup metadata");
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                u8::from_str_radix(&hex[i..i + 2], 16)
+                    .map_err(|_| anyhow::anyhow!("Invalid hex value in backup metadata"))
+            })
+            .collect()
+    }
+}
+
+// Simple SHA-256 implementation (FIPS 180-4), checked against NIST vectors in the tests
 mod sha256 {
     pub struct Sha256 {
         state: [u32; 8],
@@ -578,39 +974,36 @@ mod sha256 {
         pub fn update(&mut self, input: &[u8]) {
             self.data.extend_from_slice(input);
             self.data_len += input.len() as u64;
-
-            while self.data.len() >= 64 {
-                let block: [u8; 64] = self
-                    .data
-                    .drain(..64)
-                    .collect::<Vec<_>>()
-                    .try_into()
-                    .unwrap();
-                self.process_block(&block);
-            }
+            self.process_full_blocks();
         }
 
         pub fn finish(mut self) -> String {
             let bit_len = self.data_len * 8;
             self.data.push(0x80);
 
-            while (self.data.len() + 8) % 64 != 0 {
+            while !(self.data.len() + 8).is_multiple_of(64) {
                 self.data.push(0x00);
             }
 
             self.data.extend_from_slice(&bit_len.to_be_bytes());
+            self.process_full_blocks();
+            debug_assert!(self.data.is_empty());
 
-            while !self.data.is_empty() {
-                let block: [u8; 64] = self
-                    .data
-                    .drain(..64)
-                    .collect::<Vec<_>>()
-                    .try_into()
-                    .unwrap();
+            self.state.iter().map(|&x| format!("{:08x}", x)).collect()
+        }
+
+        /// Compress every complete 64-byte block, keeping any short tail buffered
+        fn process_full_blocks(&mut self) {
+            let data = std::mem::take(&mut self.data);
+            let mut chunks = data.chunks_exact(64);
+
+            for chunk in &mut chunks {
+                let mut block = [0u8; 64];
+                block.copy_from_slice(chunk);
                 self.process_block(&block);
             }
 
-            self.state.iter().map(|&x| format!("{:08x}", x)).collect()
+            self.data = chunks.remainder().to_vec();
         }
 
         fn process_block(&mut self, block: &[u8; 64]) {
@@ -676,7 +1069,7 @@ mod sha256 {
 }
 
 /// Backup health check result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BackupHealthCheck {
     pub total_backups: usize,
     pub healthy_backups: usize,
@@ -687,7 +1080,7 @@ pub struct BackupHealthCheck {
     pub errors: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BackupAge {
     pub file_path: String,
     pub days_since_backup: i64,
@@ -760,11 +1153,93 @@ impl BackupHealthCheck {
     }
 }
 
+/// Backup files (not metadata) under the backup directory, sorted by path
+///
+/// Checks run in parallel but results are gathered in this order, so reports
+/// are deterministic.
+fn backup_files(backup_base: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(backup_base)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.contains(".backup.") && !name.ends_with(".json"))
+        })
+        .map(|entry| entry.into_path())
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// Apply `check` to every backup file, keeping the input order
+fn check_backups<T, F>(files: &[PathBuf], parallel: bool, check: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Path) -> T + Sync,
+{
+    if parallel {
+        files.par_iter().map(|path| check(path)).collect()
+    } else {
+        files.iter().map(|path| check(path)).collect()
+    }
+}
+
+/// Health of a single backup file
+enum HealthResult {
+    Healthy(Option<BackupAge>),
+    Corrupted,
+    MissingMetadata,
+    Error(String),
+}
+
+fn check_backup_health(path: &Path) -> HealthResult {
+    // Check for metadata
+    if existing_metadata_path(path).is_none() {
+        return HealthResult::MissingMetadata;
+    }
+
+    // Load and verify metadata
+    let metadata = match load_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            return HealthResult::Error(format!(
+                "Failed to load metadata for {}: {}",
+                path.display(),
+                e
+            ))
+        }
+    };
+
+    // Verify checksum
+    match calculate_checksum(path) {
+        Ok(current_checksum) if current_checksum == metadata.file_checksum() => {
+            // Check age
+            let stale = calculate_backup_age(&metadata.timestamp)
+                .ok()
+                .filter(|&age_days| age_days > 30)
+                .map(|age_days| BackupAge {
+                    file_path: metadata.original_path.clone(),
+                    days_since_backup: age_days,
+                    last_backup: metadata.timestamp.clone(),
+                });
+            HealthResult::Healthy(stale)
+        }
+        Ok(_) => HealthResult::Corrupted,
+        Err(e) => HealthResult::Error(format!("Failed to verify {}: {}", path.display(), e)),
+    }
+}
+
 /// Run comprehensive health check on all backups
 pub fn run_health_check() -> Result<BackupHealthCheck> {
-    let home = dirs::home_dir().context("Failed to get home directory")?;
-    let backup_base = home.join(BACKUP_DIR_NAME);
+    Ok(health_check_in(&backup_base()?, true))
+}
 
+fn health_check_in(backup_base: &Path, parallel: bool) -> BackupHealthCheck {
     let mut health = BackupHealthCheck {
         total_backups: 0,
         healthy_backups: 0,
@@ -779,93 +1254,41 @@ pub fn run_health_check() -> Result<BackupHealthCheck> {
         health
             .warnings
             .push("No backup directory found. No backups have been created yet.".to_string());
-        return Ok(health);
+        return health;
     }
 
-    // Walk through all backups
-    for entry in walkdir::WalkDir::new(&backup_base)
-        .follow_links(false)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
+    let files = backup_files(backup_base);
+    health.total_backups = files.len();
 
-        // Only check backup files (not metadata)
-        if path.is_file() {
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                if filename.contains(".backup.") && !filename.ends_with(".json") {
-                    health.total_backups += 1;
-
-                    // Check for metadata
-                    let metadata_path = path.with_extension("backup.json");
-                    if !metadata_path.exists() {
-                        health.missing_metadata.push(path.display().to_string());
-                        continue;
-                    }
-
-                    // Load and verify metadata
-                    match load_metadata(path) {
-                        Ok(metadata) => {
-                            // Verify checksum
-                            match calculate_checksum(path) {
-                                Ok(current_checksum) => {
-                                    if current_checksum == metadata.checksum {
-                                        health.healthy_backups += 1;
-
-                                        // Check age
-                                        if let Ok(age_days) =
-                                            calculate_backup_age(&metadata.timestamp)
-                                        {
-                                            if age_days > 30 {
-                                                health.old_backups.push(BackupAge {
-                                                    file_path: metadata.original_path.clone(),
-                                                    days_since_backup: age_days,
-                                                    last_backup: metadata.timestamp.clone(),
-                                                });
-                                            }
-                                        }
-                                    } else {
-                                        health.corrupted_backups.push(path.display().to_string());
-                                        error!("Corrupted backup detected: {}", path.display());
-                                    }
-                                }
-                                Err(e) => {
-                                    health.errors.push(format!(
-                                        "Failed to verify {}: {}",
-                                        path.display(),
-                                        e
-                                    ));
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            health.errors.push(format!(
-                                "Failed to load metadata for {}: {}",
-                                path.display(),
-                                e
-                            ));
-                        }
-                    }
-                }
+    let results = check_backups(&files, parallel, check_backup_health);
+    for (path, result) in files.iter().zip(results) {
+        match result {
+            HealthResult::Healthy(stale) => {
+                health.healthy_backups += 1;
+                health.old_backups.extend(stale);
+            }
+            HealthResult::Corrupted => {
+                health.corrupted_backups.push(path.display().to_string());
+                error!("Corrupted backup detected: {}", path.display());
             }
+            HealthResult::MissingMetadata => {
+                health.missing_metadata.push(path.display().to_string());
+            }
+            HealthResult::Error(e) => health.errors.push(e),
         }
     }
 
-    Ok(health)
+    health
+}
+
+/// Parse a backup timestamp (`YYYYMMDD_HHMMSS`, UTC)
+fn parse_backup_timestamp(timestamp: &str) -> Result<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d_%H%M%S")
+        .with_context(|| format!("Invalid backup timestamp: {}", timestamp))
 }
 
 fn calculate_backup_age(timestamp: &str) -> Result<i64> {
-    // Parse timestamp format: YYYYMMDD_HHMMSS
-    let date_str = &timestamp[..8];
-    let year: i32 = date_str[0..4].parse()?;
-    let month: u32 = date_str[4..6].parse()?;
-    let day: u32 = date_str[6..8].parse()?;
-
-    let backup_date =
-        chrono::NaiveDate::from_ymd_opt(year, month, day).context("Invalid date in timestamp")?;
-    let backup_datetime = backup_date
-        .and_hms_opt(0, 0, 0)
-        .context("Failed to create datetime")?;
+    let backup_datetime = parse_backup_timestamp(timestamp)?;
 
     let now = Utc::now().naive_utc();
     let duration = now.signed_duration_since(backup_datetime);
@@ -882,7 +1305,7 @@ pub struct RestorationDrill {
     pub duration_ms: u128,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DrillFailure {
     pub backup_path: String,
     pub original_path: String,
@@ -947,15 +1370,58 @@ impl RestorationDrill {
     }
 }
 
+/// Check that a single backup could be restored
+fn drill_backup(path: &Path) -> Result<(), DrillFailure> {
+    // Try to load metadata
+    let metadata = load_metadata(path).map_err(|e| DrillFailure {
+        backup_path: path.display().to_string(),
+        original_path: "unknown".to_string(),
+        error: format!("Failed to load metadata: {}", e),
+    })?;
+
+    let failure = |error: String| DrillFailure {
+        backup_path: path.display().to_string(),
+        original_path: metadata.original_path.clone(),
+        error,
+    };
+
+    // Verify backup integrity
+    let backup_checksum = calculate_checksum(path)
+        .map_err(|e| failure(format!("Failed to calculate checksum: {}", e)))?;
+    if backup_checksum != metadata.file_checksum() {
+        return Err(failure(
+            "Checksum mismatch - backup is corrupted".to_string(),
+        ));
+    }
+
+    // Verify original file (if exists)
+    let original = Path::new(&metadata.original_path);
+    if !original.exists() {
+        // Original doesn't exist - backup can still be restored
+        debug!(
+            "✓ Backup valid (original file missing): {}",
+            metadata.original_path
+        );
+    } else if let Err(e) = verify_backup(path, original) {
+        // Original has changed - this is OK, just note it
+        debug!("Original file modified: {} ({})", metadata.original_path, e);
+    } else {
+        debug!("✓ Verified: {}", metadata.original_path);
+    }
+
+    Ok(())
+}
+
 /// Run a restoration drill - verify backups without actually modifying files
 pub fn run_restoration_drill() -> Result<RestorationDrill> {
+    Ok(restoration_drill_in(&backup_base()?, true))
+}
+
+fn restoration_drill_in(backup_base: &Path, parallel: bool) -> RestorationDrill {
     use std::time::Instant;
 
     let start = Instant::now();
 
-    let home = dirs::home_dir().context("Failed to get home directory")?;
-    let backup_base = home.join(BACKUP_DIR_NAME);
-
     let mut drill = RestorationDrill {
         total_tested: 0,
         successful: 0,
@@ -964,87 +1430,18 @@ pub fn run_restoration_drill() -> Result<RestorationDrill> {
     };
 
     if !backup_base.exists() {
-        return Ok(drill);
+        return drill;
     }
 
     info!("Starting restoration drill...");
 
-    // Walk through all backups
-    for entry in walkdir::WalkDir::new(&backup_base)
-        .follow_links(false)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
+    let files = backup_files(backup_base);
+    drill.total_tested = files.len();
 
-        if path.is_file() {
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                if filename.contains(".backup.") && !filename.ends_with(".json") {
-                    drill.total_tested += 1;
-
-                    // Try to load metadata
-                    match load_metadata(path) {
-                        Ok(metadata) => {
-                            // Verify backup integrity
-                            match calculate_checksum(path) {
-                                Ok(backup_checksum) => {
-                                    if backup_checksum == metadata.checksum {
-                                        // Verify original file (if exists)
-                                        let original = Path::new(&metadata.original_path);
-                                        if original.exists() {
-                                            match verify_backup(path, original) {
-                                                Ok(_) => {
-                                                    drill.successful += 1;
-                                                    debug!(
-                                                        "✓ Verified: {}",
-                                                        metadata.original_path
-                                                    );
-                                                }
-                                                Err(e) => {
-                                                    // Original has changed - this is OK, just note it
-                                                    drill.successful += 1;
-                                                    debug!(
-                                                        "Original file modified: {} ({})",
-                                                        metadata.original_path, e
-                                                    );
-                                                }
-                                            }
-                                        } else {
-                                            // Original doesn't exist - backup can still be restored
-                                            drill.successful += 1;
-                                            debug!(
-                                                "✓ Backup valid (original file missing): {}",
-                                                metadata.original_path
-                                            );
-                                        }
-                                    } else {
-                                        drill.failed.push(DrillFailure {
-                                            backup_path: path.display().to_string(),
-                                            original_path: metadata.original_path.clone(),
-                                            error: "Checksum mismatch - backup is corrupted"
-                                                .to_string(),
-                                        });
-                                    }
-                                }
-                                Err(e) => {
-                                    drill.failed.push(DrillFailure {
-                                        backup_path: path.display().to_string(),
-                                        original_path: metadata.original_path.clone(),
-                                        error: format!("Failed to calculate checksum: {}", e),
-                                    });
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            drill.failed.push(DrillFailure {
-                                backup_path: path.display().to_string(),
-                                original_path: "unknown".to_string(),
-                                error: format!("Failed to load metadata: {}", e),
-                            });
-                        }
-                    }
-                }
-            }
+    for result in check_backups(&files, parallel, drill_backup) {
+        match result {
+            Ok(()) => drill.successful += 1,
+            Err(failure) => drill.failed.push(failure),
         }
     }
 
@@ -1055,7 +1452,7 @@ pub fn run_restoration_drill() -> Result<RestorationDrill> {
         drill.successful, drill.total_tested
     );
 
-    Ok(drill)
+    drill
 }
 
 /// Backup monitoring event
@@ -1089,9 +1486,7 @@ pub enum EventSeverity {
 
 impl BackupEvent {
     pub fn log_to_file(&self) -> Result<()> {
-        let home = dirs::home_dir().context("Failed to get home directory")?;
-        let log_dir = home.join(".catdog");
-        fs::create_dir_all(&log_dir)?;
+        let log_dir = state::state_dir()?;
 
         let log_file = log_dir.join("backup_events.log");
         let json = serde_json::to_string(self)?;
@@ -1106,16 +1501,9 @@ impl BackupEvent {
 
         Ok(())
     }
-
-    pub fn should_alert(&self) -> bool {
-        matches!(
-            self.severity,
-            EventSeverity::Warning | EventSeverity::Critical
-        )
-    }
 }
 
-/// Create backup event and optionally send alerts
+/// Record a backup event in the event log and the console log
 pub fn emit_backup_event(
     event_type: BackupEventType,
     file_path: &str,
@@ -1157,7 +1545,8 @@ mod tests {
 
         let path = temp_file.path().to_str().unwrap();
 
-        let metadata = create_backup(path, BackupReason::Manual, false).unwrap();
+        let metadata =
+            create_backup(path, BackupReason::Manual, &BackupConfig::default(), false).unwrap();
 
         assert_eq!(metadata.original_path, path);
         assert!(Path::new(&metadata.backup_path).exists());
@@ -1172,7 +1561,7 @@ mod tests {
 
         let path = temp_file.path().to_str().unwrap();
 
-        create_backup(path, BackupReason::Manual, false).unwrap();
+        create_backup(path, BackupReason::Manual, &BackupConfig::default(), false).unwrap();
 
         let backups = list_backups(path).unwrap();
         assert!(!backups.is_empty());
@@ -1188,20 +1577,513 @@ mod tests {
         let checksum2 = calculate_checksum(temp_file.path()).unwrap();
 
         assert_eq!(checksum1, checksum2);
-        assert!(!checksum1.is_empty());
+        assert_eq!(
+            checksum1,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    const NIST_448_BITS: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const NIST_896_BITS: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+                                   hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    #[test]
+    fn test_sha256_nist_vectors() {
+        let vectors: [(&[u8], &str); 4] = [
+            (
+                b"",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                b"abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                NIST_448_BITS,
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                NIST_896_BITS,
+                "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+            ),
+        ];
+
+        for (message, expected) in vectors {
+            assert_eq!(checksum_bytes(message), expected, "{:?}", message);
+        }
+
+        // One million repetitions of 'a', fed in the same 8 KiB reads as calculate_checksum
+        let mut hasher = sha256::Sha256::new();
+        for chunk in vec![b'a'; 1_000_000].chunks(8192) {
+            hasher.update(chunk);
+        }
+        assert_eq!(
+            hasher.finish(),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn test_sha256_padding_boundaries() {
+        // 55 bytes fit the length in the same block, 56 and 64 need an extra one
+        let vectors = [
+            (
+                55,
+                "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318",
+            ),
+            (
+                56,
+                "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a",
+            ),
+            (
+                64,
+                "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb",
+            ),
+        ];
+
+        for (len, expected) in vectors {
+            assert_eq!(checksum_bytes(&vec![b'a'; len]), expected, "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn test_sha256_split_updates_match_one_shot() {
+        assert_eq!(NIST_896_BITS.len() * 8, 896);
+        let expected = checksum_bytes(NIST_896_BITS);
+
+        for split in 0..=NIST_896_BITS.len() {
+            let mut hasher = sha256::Sha256::new();
+            hasher.update(&NIST_896_BITS[..split]);
+            hasher.update(&NIST_896_BITS[split..]);
+            assert_eq!(hasher.finish(), expected, "split at {}", split);
+        }
     }
 
     #[test]
     fn test_health_check() {
         let health = run_health_check().unwrap();
         // Should not panic, even with no backups
-        assert!(health.total_backups >= 0);
+        assert!(health.healthy_backups <= health.total_backups);
     }
 
     #[test]
     fn test_restoration_drill() {
         let drill = run_restoration_drill().unwrap();
         // Should not panic, even with no backups
-        assert!(drill.total_tested >= 0);
+        assert!(drill.successful <= drill.total_tested);
+    }
+
+    #[test]
+    fn test_restore_ignore_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("etc").join("fstab");
+        fs::create_dir_all(original.parent().unwrap()).unwrap();
+        fs::write(&original, b"UUID=abc / ext4 defaults 0 1\n").unwrap();
+
+        let original_path = original.to_str().unwrap();
+        let metadata = create_backup(
+            original_path,
+            BackupReason::Manual,
+            &BackupConfig::default(),
+            false,
+        )
+        .unwrap();
+
+        // Simulate a freshly provisioned machine with no file at the original path
+        fs::remove_dir_all(original.parent().unwrap()).unwrap();
+
+        restore_backup(
+            &metadata.backup_path,
+            None,
+            &BackupConfig::default(),
+            false,
+            false,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&original).unwrap(),
+            "UUID=abc / ext4 defaults 0 1\n"
+        );
+        // No pre-restore backup should have been taken
+        assert_eq!(list_backups(original_path).unwrap().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_restore_missing_file_keeps_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("crypttab");
+        fs::write(&original, "data UUID=abc none luks\n").unwrap();
+        fs::set_permissions(&original, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let metadata = create_backup(
+            original.to_str().unwrap(),
+            BackupReason::Manual,
+            &BackupConfig::default(),
+            false,
+        )
+        .unwrap();
+        fs::remove_file(&original).unwrap();
+        restore_backup(
+            &metadata.backup_path,
+            None,
+            &BackupConfig::default(),
+            false,
+            false,
+            true,
+        )
+        .unwrap();
+
+        let mode = fs::metadata(&original).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_encrypted_backup_round_trip() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"secret=hunter2\n").unwrap();
+        temp_file.flush().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let metadata = create_backup_with_passphrase(
+            path,
+            BackupReason::Manual,
+            Some("correct horse"),
+            &BackupConfig::default(),
+            false,
+        )
+        .unwrap();
+        let info = metadata.encryption.as_ref().unwrap();
+        let stored = fs::read(&metadata.backup_path).unwrap();
+
+        assert!(metadata.encrypted);
+        assert_eq!(metadata.checksum, checksum_bytes(b"secret=hunter2\n"));
+        assert_eq!(
+            calculate_checksum(Path::new(&metadata.backup_path)).unwrap(),
+            metadata.file_checksum()
+        );
+        assert!(!stored.windows(7).any(|w| w == b"hunter2"));
+
+        let plaintext = encryption::decrypt(&stored, "correct horse", info).unwrap();
+        assert_eq!(plaintext, b"secret=hunter2\n");
+
+        let err = encryption::decrypt(&stored, "wrong horse", info).unwrap_err();
+        assert_eq!(err.to_string(), "Wrong passphrase or corrupted backup");
+    }
+
+    #[test]
+    fn test_restore_to_alternate_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("fstab");
+        fs::write(&original, "UUID=abc / ext4 defaults 0 1\n").unwrap();
+
+        let original_path = original.to_str().unwrap();
+        let metadata = create_backup(
+            original_path,
+            BackupReason::Manual,
+            &BackupConfig::default(),
+            false,
+        )
+        .unwrap();
+
+        // A modified original would normally need --force
+        fs::write(&original, "UUID=abc / ext4 defaults,noatime 0 1\n").unwrap();
+
+        let scratch = dir.path().join("check").join("fstab.check");
+        restore_backup(
+            &metadata.backup_path,
+            Some(scratch.to_str().unwrap()),
+            &BackupConfig::default(),
+            false,
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&scratch).unwrap(),
+            "UUID=abc / ext4 defaults 0 1\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&original).unwrap(),
+            "UUID=abc / ext4 defaults,noatime 0 1\n"
+        );
+        assert_eq!(list_backups(original_path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_restore_to_original_path_keeps_safety_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("fstab");
+        fs::write(&original, "UUID=abc / ext4 defaults 0 1\n").unwrap();
+
+        let original_path = original.to_str().unwrap();
+        let metadata = create_backup(
+            original_path,
+            BackupReason::Manual,
+            &BackupConfig::default(),
+            false,
+        )
+        .unwrap();
+        fs::write(&original, "UUID=abc / ext4 defaults,noatime 0 1\n").unwrap();
+
+        // The original path spelled differently is still the original
+        let spelled = dir.path().join(".").join("fstab");
+        let err = restore_backup(
+            &metadata.backup_path,
+            Some(spelled.to_str().unwrap()),
+            &BackupConfig::default(),
+            false,
+            false,
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("--force"));
+
+        restore_backup(
+            &metadata.backup_path,
+            Some(spelled.to_str().unwrap()),
+            &BackupConfig::default(),
+            false,
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&original).unwrap(),
+            "UUID=abc / ext4 defaults 0 1\n"
+        );
+    }
+
+    /// Backups for two files, one corrupted and one without metadata
+    fn write_check_fixture(base: &Path) {
+        for (dir, count) in [("etc_fstab", 6), ("etc_hosts", 4)] {
+            let dir = base.join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            for i in 0..count {
+                let backup = dir.join(format!("file.backup.2026010{}_000000", i));
+                let contents = format!("{} backup {}\n", dir.display(), i);
+                fs::write(&backup, &contents).unwrap();
+                save_metadata(&BackupMetadata {
+                    original_path: dir.join("missing-original").display().to_string(),
+                    backup_path: backup.display().to_string(),
+                    timestamp: format!("2026010{}_000000", i),
+                    reason: BackupReason::Manual,
+                    checksum: checksum_bytes(contents.as_bytes()),
+                    size_bytes: contents.len() as u64,
+                    encrypted: false,
+                    encryption: None,
+                })
+                .unwrap();
+            }
+        }
+
+        fs::write(
+            base.join("etc_fstab/file.backup.20260102_000000"),
+            "tampered",
+        )
+        .unwrap();
+        fs::remove_file(metadata_path(
+            &base.join("etc_hosts/file.backup.20260103_000000"),
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parallel_checks_match_serial() {
+        let dir = tempfile::tempdir().unwrap();
+        write_check_fixture(dir.path());
+
+        let serial = health_check_in(dir.path(), false);
+        let parallel = health_check_in(dir.path(), true);
+
+        assert_eq!(serial, parallel);
+        assert_eq!(parallel.total_backups, 10);
+        assert_eq!(parallel.healthy_backups, 8);
+        assert_eq!(parallel.corrupted_backups.len(), 1);
+        assert_eq!(parallel.missing_metadata.len(), 1);
+
+        let serial = restoration_drill_in(dir.path(), false);
+        let parallel = restoration_drill_in(dir.path(), true);
+        assert_eq!(serial.total_tested, parallel.total_tested);
+        assert_eq!(serial.successful, parallel.successful);
+        assert_eq!(serial.failed, parallel.failed);
+        assert_eq!(parallel.successful, 8);
+        assert_eq!(parallel.failed.len(), 2);
+    }
+
+    #[test]
+    fn test_stats_oldest_and_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = dir.path().join("etc_fstab");
+        fs::create_dir_all(&backups).unwrap();
+
+        // Written newest-first, with one unparseable timestamp that must be ignored
+        for timestamp in [
+            "20260105_100000",
+            "20260105_090000",
+            "20251231_235959",
+            "not-a-timestamp",
+        ] {
+            let backup = backups.join(format!("fstab.backup.{}", timestamp));
+            fs::write(&backup, timestamp).unwrap();
+            save_metadata(&BackupMetadata {
+                original_path: "/etc/fstab".to_string(),
+                backup_path: backup.display().to_string(),
+                timestamp: timestamp.to_string(),
+                reason: BackupReason::Manual,
+                checksum: checksum_bytes(timestamp.as_bytes()),
+                size_bytes: timestamp.len() as u64,
+                encrypted: false,
+                encryption: None,
+            })
+            .unwrap();
+        }
+
+        let stats = stats_in(dir.path());
+        assert_eq!(stats.total_backups, 4);
+        assert_eq!(stats.per_file.len(), 1);
+        assert_eq!(stats.per_file[0].count, 4);
+        assert_eq!(
+            stats.per_file[0].newest_backup.as_deref(),
+            Some("20260105_100000")
+        );
+        assert_eq!(stats.oldest_backup.as_deref(), Some("20251231_235959"));
+        assert_eq!(stats.newest_backup.as_deref(), Some("20260105_100000"));
+    }
+
+    #[test]
+    fn test_stats_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write_check_fixture(dir.path());
+
+        let stats = stats_in(dir.path());
+        let files: Vec<(&str, usize)> = stats
+            .per_file
+            .iter()
+            .map(|f| (f.original_path.rsplit('/').nth(1).unwrap(), f.count))
+            .collect();
+
+        // The hosts backup without metadata counts in the total only
+        assert_eq!(stats.total_backups, 10);
+        assert_eq!(files, vec![("etc_fstab", 6), ("etc_hosts", 3)]);
+        assert!(stats.per_file[0].total_size_bytes > stats.per_file[1].total_size_bytes);
+        assert_eq!(
+            stats.per_file[1].newest_backup.as_deref(),
+            Some("20260102_000000")
+        );
+    }
+
+    /// Write `count` backups with metadata, the first one being the oldest
+    fn write_backups(dir: &Path, count: usize) -> Vec<PathBuf> {
+        let start = SystemTime::now() - Duration::from_secs(count as u64 * 3600);
+        (0..count)
+            .map(|i| {
+                let backup = dir.join(format!("fstab.backup.20260101_{:06}", i));
+                fs::write(&backup, format!("backup {}", i)).unwrap();
+                fs::write(metadata_path(&backup), "{}").unwrap();
+                fs::File::options()
+                    .write(true)
+                    .open(&backup)
+                    .unwrap()
+                    .set_modified(start + Duration::from_secs(i as u64 * 3600))
+                    .unwrap();
+                backup
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cleanup_keeps_newest_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = write_backups(dir.path(), 15);
+        let retention = BackupConfig {
+            max_per_file: 3,
+            max_age_days: None,
+        };
+
+        assert_eq!(cleanup_old_backups(dir.path(), &retention).unwrap(), 12);
+
+        for (i, backup) in backups.iter().enumerate() {
+            let kept = i >= 12;
+            assert_eq!(backup.exists(), kept, "{}", backup.display());
+            assert_eq!(metadata_path(backup).exists(), kept);
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 6);
+    }
+
+    #[test]
+    fn test_legacy_metadata_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = write_backups(dir.path(), 2);
+        let legacy = legacy_metadata_path(&backups[1]);
+        assert_eq!(legacy, dir.path().join("fstab.backup.backup.json"));
+
+        // Written by an earlier version for the newer backup only
+        for backup in &backups {
+            fs::remove_file(metadata_path(backup)).unwrap();
+        }
+        let metadata = BackupMetadata {
+            original_path: "/etc/fstab".to_string(),
+            backup_path: backups[1].to_string_lossy().into_owned(),
+            timestamp: "2026-01-01T01:00:00+00:00".to_string(),
+            reason: BackupReason::Manual,
+            checksum: String::new(),
+            size_bytes: 8,
+            encrypted: false,
+            encryption: None,
+        };
+        fs::write(&legacy, serde_json::to_string(&metadata).unwrap()).unwrap();
+
+        assert_eq!(existing_metadata_path(&backups[1]), Some(legacy.clone()));
+        assert!(existing_metadata_path(&backups[0]).is_none());
+        assert_eq!(
+            load_metadata(&backups[1]).unwrap().backup_path,
+            metadata.backup_path
+        );
+        assert!(load_metadata(&backups[0]).is_err());
+
+        // Cleanup takes the legacy sidecar along with the backup it describes
+        let retention = BackupConfig {
+            max_per_file: 1,
+            max_age_days: Some(0),
+        };
+        assert_eq!(cleanup_old_backups(dir.path(), &retention).unwrap(), 2);
+        assert!(!legacy.exists());
+    }
+
+    #[test]
+    fn test_cleanup_removes_expired_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = write_backups(dir.path(), 3);
+        let old = dir.path().join("fstab.backup.20250101_000000");
+        fs::write(&old, "old").unwrap();
+        fs::write(metadata_path(&old), "{}").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(40 * 24 * 60 * 60))
+            .unwrap();
+
+        let retention = BackupConfig {
+            max_per_file: 10,
+            max_age_days: Some(30),
+        };
+        assert_eq!(cleanup_old_backups(dir.path(), &retention).unwrap(), 1);
+        assert!(!old.exists());
+        assert!(!metadata_path(&old).exists());
+        assert!(backups.iter().all(|b| b.exists()));
+
+        // An age beyond what SystemTime can hold expires nothing instead of panicking
+        let forever = BackupConfig {
+            max_per_file: 10,
+            max_age_days: Some(u64::MAX),
+        };
+        assert_eq!(cleanup_old_backups(dir.path(), &forever).unwrap(), 0);
     }
 }
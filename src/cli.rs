@@ -0,0 +1,857 @@
+use anyhow::{bail, Result};
+
+/// Flags that mean the same thing whichever command they are given to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GlobalFlags {
+    pub json: bool,
+    pub no_color: bool,
+    pub verbose: bool,
+    pub dry_run: bool,
+    pub no_sudo: bool,
+    pub quiet: bool,
+    pub user: bool,
+    /// `--yes`: answer yes to confirmation prompts
+    pub yes: bool,
+    pub help: bool,
+    pub version: bool,
+    pub config: Option<String>,
+    pub backend: Option<String>,
+    /// `--output json|table|plain`; `--json` is shorthand for json
+    pub output: Option<String>,
+    /// `--color auto|always|never`
+    pub color: Option<String>,
+}
+
+/// A command line split into global flags, words and command flags
+///
+/// Words are the command, its subcommand and the positionals, in order;
+/// flags may appear anywhere among them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedArgs {
+    pub globals: GlobalFlags,
+    pub words: Vec<String>,
+    flags: Vec<(String, Option<String>)>,
+}
+
+/// A global flag as shown in completions
+pub struct FlagSpec {
+    pub long: &'static str,
+    pub short: Option<char>,
+    pub about: &'static str,
+}
+
+/// A command, the names it answers to and what it accepts
+///
+/// This is the registry shell completions are generated from, so a new
+/// command, subcommand or flag belongs here as well as in `run()`.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub about: &'static str,
+    pub subcommands: &'static [&'static str],
+    pub flags: &'static [&'static str],
+}
+
+impl CommandSpec {
+    pub fn names(&self) -> impl Iterator<Item = &'static str> {
+        std::iter::once(self.name).chain(self.aliases.iter().copied())
+    }
+}
+
+pub const GLOBAL_FLAGS: &[FlagSpec] = &[
+    FlagSpec {
+        long: "--json",
+        short: None,
+        about: "Output in JSON format",
+    },
+    FlagSpec {
+        long: "--output",
+        short: None,
+        about: "Output format: table, json or plain",
+    },
+    FlagSpec {
+        long: "--color",
+        short: None,
+        about: "Color output: auto, always or never",
+    },
+    FlagSpec {
+        long: "--no-color",
+        short: None,
+        about: "Disable colored output",
+    },
+    FlagSpec {
+        long: "--dry-run",
+        short: Some('n'),
+        about: "Show preview without making changes",
+    },
+    FlagSpec {
+        long: "--config",
+        short: None,
+        about: "Use this config file",
+    },
+    FlagSpec {
+        long: "--backend",
+        short: None,
+        about: "Use this package manager",
+    },
+    FlagSpec {
+        long: "--no-sudo",
+        short: None,
+        about: "Run package/service commands without sudo",
+    },
+    FlagSpec {
+        long: "--user",
+        short: None,
+        about: "Manage systemd user units",
+    },
+    FlagSpec {
+        long: "--yes",
+        short: Some('y'),
+        about: "Skip confirmation prompts for destructive commands",
+    },
+    FlagSpec {
+        long: "--quiet",
+        short: Some('q'),
+        about: "Skip the summary line after write commands",
+    },
+    FlagSpec {
+        long: "--verbose",
+        short: Some('v'),
+        about: "Enable verbose logging",
+    },
+    FlagSpec {
+        long: "--version",
+        short: Some('V'),
+        about: "Show version information",
+    },
+    FlagSpec {
+        long: "--help",
+        short: Some('h'),
+        about: "Show help",
+    },
+];
+
+const NO_NAMES: &[&str] = &[];
+const BULK_ALERT_FLAGS: &[&str] = &["--all", "--severity", "--status", "--force"];
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "cat",
+        aliases: NO_NAMES,
+        about: "Display raw /etc/fstab file",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "dog",
+        aliases: NO_NAMES,
+        about: "Parse and display /etc/fstab",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "list",
+        aliases: &["ls"],
+        about: "List all mount points",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "find",
+        aliases: NO_NAMES,
+        about: "Find entries matching device or mount point",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "validate",
+        aliases: NO_NAMES,
+        about: "Check /etc/fstab for common issues",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "set-option",
+        aliases: NO_NAMES,
+        about: "Add/remove mount options on an entry",
+        subcommands: NO_NAMES,
+        flags: &["--add", "--remove"],
+    },
+    CommandSpec {
+        name: "edit",
+        aliases: NO_NAMES,
+        about: "Edit one entry by mount point or device",
+        subcommands: NO_NAMES,
+        flags: &["--option", "--set-pass"],
+    },
+    CommandSpec {
+        name: "fstab",
+        aliases: NO_NAMES,
+        about: "Append a validated entry to the fstab",
+        subcommands: &["add"],
+        flags: &[
+            "--device",
+            "--mount",
+            "--type",
+            "--options",
+            "--dump",
+            "--pass",
+        ],
+    },
+    CommandSpec {
+        name: "mount",
+        aliases: NO_NAMES,
+        about: "Mount an fstab entry",
+        subcommands: NO_NAMES,
+        flags: &["--force", "--create-dirs"],
+    },
+    CommandSpec {
+        name: "unmount",
+        aliases: &["umount"],
+        about: "Unmount an fstab entry",
+        subcommands: NO_NAMES,
+        flags: &["--force"],
+    },
+    CommandSpec {
+        name: "mkmount",
+        aliases: NO_NAMES,
+        about: "Create a mount point directory",
+        subcommands: NO_NAMES,
+        flags: &["--force"],
+    },
+    CommandSpec {
+        name: "discover",
+        aliases: NO_NAMES,
+        about: "Discover block devices grouped by disk",
+        subcommands: NO_NAMES,
+        flags: &["--flat", "--bytes"],
+    },
+    CommandSpec {
+        name: "suggest",
+        aliases: NO_NAMES,
+        about: "Generate smart mount suggestions for devices",
+        subcommands: NO_NAMES,
+        flags: &["--fs", "--filesystem"],
+    },
+    CommandSpec {
+        name: "generate",
+        aliases: &["generate-fstab"],
+        about: "Generate complete fstab from discovered devices",
+        subcommands: NO_NAMES,
+        flags: &["--merge", "--fs", "--filesystem", "--split-by", "--under"],
+    },
+    CommandSpec {
+        name: "backup",
+        aliases: NO_NAMES,
+        about: "Create verified backup with metadata",
+        subcommands: NO_NAMES,
+        flags: &["--encrypt"],
+    },
+    CommandSpec {
+        name: "restore",
+        aliases: NO_NAMES,
+        about: "Restore from a backup",
+        subcommands: NO_NAMES,
+        flags: &["--to", "--force", "--ignore-missing"],
+    },
+    CommandSpec {
+        name: "list-backups",
+        aliases: NO_NAMES,
+        about: "List all backups for a file",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "backup-diff",
+        aliases: NO_NAMES,
+        about: "Show what restoring a backup would change",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "backup-stats",
+        aliases: NO_NAMES,
+        about: "Show backup statistics and disk usage per file",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "backup-health",
+        aliases: NO_NAMES,
+        about: "Run backup health check and verification",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "backup-drill",
+        aliases: NO_NAMES,
+        about: "Test backup restoration",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "diff",
+        aliases: NO_NAMES,
+        about: "Compare two fstab files",
+        subcommands: NO_NAMES,
+        flags: &[
+            "--semantic",
+            "--unified",
+            "-U",
+            "--side-by-side",
+            "--width",
+            "--current",
+            "--ignore-comments",
+        ],
+    },
+    CommandSpec {
+        name: "check",
+        aliases: NO_NAMES,
+        about: "Run filesystem health checks once",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "monitor",
+        aliases: NO_NAMES,
+        about: "Start continuous monitoring",
+        subcommands: NO_NAMES,
+        flags: &["--export-prometheus"],
+    },
+    CommandSpec {
+        name: "barks",
+        aliases: &["alerts"],
+        about: "List barks",
+        subcommands: &[
+            "firing",
+            "acknowledged",
+            "silenced",
+            "resolved",
+            "test",
+            "stats",
+            "prune",
+        ],
+        flags: &[
+            "--compact",
+            "--count",
+            "--since",
+            "--where",
+            "--watch",
+            "--channel",
+            "--older-than",
+        ],
+    },
+    CommandSpec {
+        name: "bark",
+        aliases: &["alert"],
+        about: "Show detailed information about a bark",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "ack",
+        aliases: &["acknowledge", "pet"],
+        about: "Acknowledge barks",
+        subcommands: NO_NAMES,
+        flags: BULK_ALERT_FLAGS,
+    },
+    CommandSpec {
+        name: "resolve",
+        aliases: &["quiet"],
+        about: "Resolve barks, or show every identifier for a device",
+        subcommands: NO_NAMES,
+        flags: BULK_ALERT_FLAGS,
+    },
+    CommandSpec {
+        name: "silence",
+        aliases: &["hush"],
+        about: "Silence a bark",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "corpus",
+        aliases: NO_NAMES,
+        about: "Manage the configuration library",
+        subcommands: &[
+            "ingest",
+            "list",
+            "remove",
+            "search",
+            "stats",
+            "recommend",
+            "export",
+            "import",
+        ],
+        flags: &["--limit", "--all"],
+    },
+    CommandSpec {
+        name: "service",
+        aliases: &["svc"],
+        about: "Manage system services",
+        subcommands: &[
+            "start", "stop", "restart", "enable", "disable", "mask", "unmask", "status", "list",
+            "logs",
+        ],
+        flags: &[
+            "--force",
+            "--enabled-only",
+            "--lines",
+            "--follow",
+            "--limit",
+            "--all",
+        ],
+    },
+    CommandSpec {
+        name: "info",
+        aliases: &["sysinfo"],
+        about: "Show comprehensive system information",
+        subcommands: NO_NAMES,
+        flags: &["--top-processes", "--sort"],
+    },
+    CommandSpec {
+        name: "watch",
+        aliases: NO_NAMES,
+        about: "Live CPU, memory, disk and alert summary",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "disk-health",
+        aliases: NO_NAMES,
+        about: "SMART health and sector counts per disk",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "doctor",
+        aliases: NO_NAMES,
+        about: "Check catdog's dependencies and the system's health",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "uptime",
+        aliases: NO_NAMES,
+        about: "Print the system uptime",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "hostname",
+        aliases: NO_NAMES,
+        about: "Print the hostname",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "kernel",
+        aliases: NO_NAMES,
+        about: "Print the kernel version",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "pkg",
+        aliases: &["package"],
+        about: "Manage packages",
+        subcommands: &[
+            "install",
+            "remove",
+            "update",
+            "upgrade",
+            "upgradable",
+            "snapshot",
+            "snapshot-diff",
+            "search",
+            "list",
+            "info",
+        ],
+        flags: &["--plan", "--upgradable", "--limit", "--all"],
+    },
+    CommandSpec {
+        name: "config",
+        aliases: NO_NAMES,
+        about: "Show, locate or edit the config file",
+        subcommands: &["show", "path", "edit"],
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "audit",
+        aliases: NO_NAMES,
+        about: "Show recent mutating commands from the audit log",
+        subcommands: &["tail"],
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "completions",
+        aliases: NO_NAMES,
+        about: "Print a shell completion script",
+        subcommands: &["bash", "zsh", "fish"],
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "version",
+        aliases: NO_NAMES,
+        about: "Show version information",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+    CommandSpec {
+        name: "help",
+        aliases: NO_NAMES,
+        about: "Show help",
+        subcommands: NO_NAMES,
+        flags: NO_NAMES,
+    },
+];
+
+/// Flags that take a value, as `--flag value` or `--flag=value`
+pub const VALUE_FLAGS: &[&str] = &[
+    "--config",
+    "--backend",
+    "--output",
+    "--color",
+    "--add",
+    "--remove",
+    "--option",
+    "--set-pass",
+    "--fs",
+    "--filesystem",
+    "--split-by",
+    "--limit",
+    "--under",
+    "--device",
+    "--mount",
+    "--type",
+    "--options",
+    "--dump",
+    "--pass",
+    "--to",
+    "--merge",
+    "--export-prometheus",
+    "--since",
+    "--channel",
+    "--where",
+    "--older-than",
+    "--severity",
+    "--status",
+    "--lines",
+    "--sort",
+    "--width",
+    "-U",
+];
+
+/// Flags whose value may be left out, so the next word is only taken when it is a number
+const OPTIONAL_VALUE_FLAGS: &[&str] = &["--top-processes", "--watch"];
+
+/// Single-letter flags, which can be combined as in `-vn`
+const SHORT_FLAGS: &[(char, &str)] = &[
+    ('v', "--verbose"),
+    ('q', "--quiet"),
+    ('n', "--dry-run"),
+    ('y', "--yes"),
+    ('h', "--help"),
+    ('V', "--version"),
+];
+
+impl ParsedArgs {
+    pub fn command(&self) -> Option<&str> {
+        self.words.first().map(String::as_str)
+    }
+
+    /// The word at `index`, where 0 is the command and 1 the subcommand
+    pub fn word(&self, index: usize) -> Option<&str> {
+        self.words.get(index).map(String::as_str)
+    }
+
+    /// Words after the command
+    pub fn positionals(&self) -> &[String] {
+        self.words.get(1..).unwrap_or(&[])
+    }
+
+    pub fn has(&self, flag: &str) -> bool {
+        self.flags.iter().any(|(name, _)| name == flag)
+    }
+
+    /// The value of a flag; when it is repeated the last one wins
+    pub fn value(&self, flag: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(name, _)| name == flag)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Every value given for a repeatable flag, in order
+    pub fn values(&self, flag: &str) -> Vec<&str> {
+        self.flags
+            .iter()
+            .filter(|(name, _)| name == flag)
+            .filter_map(|(_, value)| value.as_deref())
+            .collect()
+    }
+
+    fn set_flag(&mut self, name: &str, value: Option<String>, enabled: bool) {
+        let globals = &mut self.globals;
+        let switch = match name {
+            "--json" => &mut globals.json,
+            "--no-color" => &mut globals.no_color,
+            "--verbose" => &mut globals.verbose,
+            "--dry-run" => &mut globals.dry_run,
+            "--no-sudo" => &mut globals.no_sudo,
+            "--quiet" => &mut globals.quiet,
+            "--user" => &mut globals.user,
+            "--yes" => &mut globals.yes,
+            "--help" => &mut globals.help,
+            "--version" => &mut globals.version,
+            "--config" => {
+                globals.config = value;
+                return;
+            }
+            "--backend" => {
+                globals.backend = value;
+                return;
+            }
+            "--output" => {
+                globals.output = value;
+                return;
+            }
+            "--color" => {
+                globals.color = value;
+                return;
+            }
+            _ => {
+                if enabled {
+                    self.flags.push((name.to_string(), value));
+                } else {
+                    self.flags.retain(|(flag, _)| flag != name);
+                }
+                return;
+            }
+        };
+        *switch = enabled;
+    }
+}
+
+/// Parse `argv` (including the program name)
+///
+/// Global flags are recognised before or after the command, `--flag=value`
+/// works for every flag, short flags combine (`-vn`) and `-U5` attaches its
+/// value. Everything after `--` is a word, so a mount point named
+/// `--something` can still be passed.
+pub fn parse<S: AsRef<str>>(argv: &[S]) -> Result<ParsedArgs> {
+    let mut parsed = ParsedArgs::default();
+    let mut rest = argv.iter().skip(1).map(AsRef::as_ref).peekable();
+    let mut only_words = false;
+
+    while let Some(arg) = rest.next() {
+        if only_words || arg == "-" || !arg.starts_with('-') {
+            parsed.words.push(arg.to_string());
+            continue;
+        }
+        if arg == "--" {
+            only_words = true;
+            continue;
+        }
+
+        let (name, inline) = match split_flag(arg)? {
+            Some(split) => split,
+            None => {
+                // A cluster of short flags such as `-vn`
+                for letter in arg.chars().skip(1) {
+                    let long = short_flag(letter).expect("checked by split_flag");
+                    parsed.set_flag(long, None, true);
+                }
+                continue;
+            }
+        };
+
+        if VALUE_FLAGS.contains(&name.as_str()) {
+            let value = match inline {
+                Some(value) => value,
+                None => match rest.next() {
+                    Some(value) => value.to_string(),
+                    None => bail!("{} needs a value", name),
+                },
+            };
+            parsed.set_flag(&name, Some(value), true);
+        } else if OPTIONAL_VALUE_FLAGS.contains(&name.as_str()) {
+            let value = inline.or_else(|| {
+                rest.next_if(|next| next.parse::<u64>().is_ok())
+                    .map(str::to_string)
+            });
+            parsed.set_flag(&name, value, true);
+        } else {
+            let enabled = match inline.as_deref() {
+                None => true,
+                Some(value) => parse_bool(&name, value)?,
+            };
+            parsed.set_flag(&name, None, enabled);
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Split a flag into its long name and any value attached to it, or None
+/// for a cluster of short flags
+fn split_flag(arg: &str) -> Result<Option<(String, Option<String>)>> {
+    if arg.starts_with("--") {
+        return Ok(Some(match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.to_string(), None),
+        }));
+    }
+
+    let mut letters = arg.chars().skip(1);
+    let first = letters.next().unwrap_or_default();
+    let short = format!("-{}", first);
+    let attached: String = letters.collect();
+
+    if VALUE_FLAGS.contains(&short.as_str()) {
+        let value = (!attached.is_empty()).then_some(attached);
+        return Ok(Some((short, value)));
+    }
+    if attached.is_empty() {
+        let name = short_flag(first).map_or(short, str::to_string);
+        return Ok(Some((name, None)));
+    }
+    if let Some(unknown) = arg.chars().skip(1).find(|c| short_flag(*c).is_none()) {
+        bail!("Unknown flag -{} in {}", unknown, arg);
+    }
+    Ok(None)
+}
+
+fn short_flag(letter: char) -> Option<&'static str> {
+    SHORT_FLAGS
+        .iter()
+        .find(|(short, _)| *short == letter)
+        .map(|(_, long)| *long)
+}
+
+fn parse_bool(flag: &str, value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => bail!("{} takes true or false, not '{}'", flag, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(line: &str) -> ParsedArgs {
+        let argv: Vec<&str> = std::iter::once("catdog")
+            .chain(line.split_whitespace())
+            .collect();
+        parse(&argv).unwrap()
+    }
+
+    #[test]
+    fn test_flags_after_command() {
+        let parsed = parse_line("find --json /dev/sda");
+        assert!(parsed.globals.json);
+        assert_eq!(parsed.command(), Some("find"));
+        assert_eq!(parsed.positionals(), ["/dev/sda"]);
+    }
+
+    #[test]
+    fn test_flags_before_command() {
+        let parsed = parse_line("--verbose pkg install foo bar");
+        assert!(parsed.globals.verbose);
+        assert_eq!(parsed.words, ["pkg", "install", "foo", "bar"]);
+
+        let parsed = parse_line("--config /tmp/c.toml --backend=apt pkg list");
+        assert_eq!(parsed.globals.config.as_deref(), Some("/tmp/c.toml"));
+        assert_eq!(parsed.globals.backend.as_deref(), Some("apt"));
+        assert_eq!(parsed.words, ["pkg", "list"]);
+
+        let parsed = parse_line("dog --output=plain --color never");
+        assert_eq!(parsed.globals.output.as_deref(), Some("plain"));
+        assert_eq!(parsed.globals.color.as_deref(), Some("never"));
+        assert_eq!(parsed.words, ["dog"]);
+    }
+
+    #[test]
+    fn test_flag_equals_value() {
+        let parsed = parse_line("--json=true --dry-run=false list");
+        assert!(parsed.globals.json);
+        assert!(!parsed.globals.dry_run);
+
+        let parsed = parse_line("barks --since=1h --compact=yes");
+        assert_eq!(parsed.value("--since"), Some("1h"));
+        assert!(parsed.has("--compact"));
+
+        let argv = ["catdog", "--json=maybe", "list"];
+        assert!(parse(&argv).is_err());
+    }
+
+    #[test]
+    fn test_command_flags_and_values() {
+        let parsed = parse_line("set-option /data --add noatime --add=nofail,discard");
+        assert_eq!(parsed.positionals(), ["/data"]);
+        assert_eq!(parsed.values("--add"), ["noatime", "nofail,discard"]);
+        assert!(!parsed.has("--remove"));
+
+        let parsed = parse_line("diff -U5 a b --width 100");
+        assert_eq!(parsed.value("-U"), Some("5"));
+        assert_eq!(parsed.value("--width"), Some("100"));
+        assert_eq!(parsed.positionals(), ["a", "b"]);
+
+        let argv = ["catdog", "barks", "--since"];
+        assert!(parse(&argv).is_err());
+    }
+
+    #[test]
+    fn test_optional_value() {
+        let parsed = parse_line("info --top-processes 5 --sort cpu");
+        assert_eq!(parsed.value("--top-processes"), Some("5"));
+        assert_eq!(parsed.words, ["info"]);
+
+        let parsed = parse_line("info --top-processes --json");
+        assert!(parsed.has("--top-processes"));
+        assert_eq!(parsed.value("--top-processes"), None);
+        assert!(parsed.globals.json);
+
+        // A status after a bare --watch stays a positional
+        let parsed = parse_line("barks --watch firing");
+        assert!(parsed.has("--watch"));
+        assert_eq!(parsed.value("--watch"), None);
+        assert_eq!(parsed.words, ["barks", "firing"]);
+        assert_eq!(parse_line("barks --watch 10").value("--watch"), Some("10"));
+    }
+
+    #[test]
+    fn test_combined_short_flags() {
+        let parsed = parse_line("-vn ack --all");
+        assert!(parsed.globals.verbose);
+        assert!(parsed.globals.dry_run);
+        assert!(parsed.has("--all"));
+
+        let parsed = parse_line("-V");
+        assert!(parsed.globals.version);
+
+        let parsed = parse_line("pkg remove vim -ny");
+        assert!(parsed.globals.yes && parsed.globals.dry_run);
+
+        let argv = ["catdog", "-vx", "list"];
+        assert!(parse(&argv).is_err());
+    }
+
+    #[test]
+    fn test_double_dash_ends_flags() {
+        let parsed = parse_line("--json find -- --something");
+        assert!(parsed.globals.json);
+        assert_eq!(parsed.words, ["find", "--something"]);
+        assert!(!parsed.has("--something"));
+    }
+
+    #[test]
+    fn test_empty() {
+        let parsed = parse_line("");
+        assert_eq!(parsed.command(), None);
+        assert!(parsed.positionals().is_empty());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::cli::{CommandSpec, COMMANDS, GLOBAL_FLAGS, VALUE_FLAGS};
+use crate::package::BACKEND_NAMES;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// Value flags whose value is a path
+const PATH_FLAGS: &[&str] = &["--config", "--to", "--merge", "--export-prometheus"];
+
+/// Fixed choices for a value flag, if it has them
+fn value_choices(flag: &str) -> Option<&'static [&'static str]> {
+    match flag {
+        "--backend" => Some(BACKEND_NAMES),
+        "--output" => Some(&["table", "json", "plain"]),
+        "--color" => Some(&["auto", "always", "never"]),
+        "--sort" => Some(&["cpu", "mem"]),
+        "--severity" => Some(&["critical", "warning", "info"]),
+        "--status" => Some(&["firing", "acknowledged", "silenced", "resolved"]),
+        _ => None,
+    }
+}
+
+/// Print a completion script for `shell`
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash_script(),
+        Shell::Zsh => zsh_script(),
+        Shell::Fish => fish_script(),
+    }
+}
+
+/// Every global flag, long and short
+fn global_flag_names() -> Vec<String> {
+    let mut names: Vec<String> = GLOBAL_FLAGS.iter().map(|f| f.long.to_string()).collect();
+    names.extend(
+        GLOBAL_FLAGS
+            .iter()
+            .filter_map(|f| f.short.map(|c| format!("-{}", c))),
+    );
+    names
+}
+
+fn command_names() -> Vec<&'static str> {
+    COMMANDS.iter().flat_map(CommandSpec::names).collect()
+}
+
+fn with_subcommands() -> impl Iterator<Item = &'static CommandSpec> {
+    COMMANDS.iter().filter(|c| !c.subcommands.is_empty())
+}
+
+fn with_flags() -> impl Iterator<Item = &'static CommandSpec> {
+    COMMANDS.iter().filter(|c| !c.flags.is_empty())
+}
+
+fn value_flags_without_choices() -> Vec<&'static str> {
+    VALUE_FLAGS
+        .iter()
+        .copied()
+        .filter(|f| !PATH_FLAGS.contains(f) && value_choices(f).is_none())
+        .collect()
+}
+
+fn bash_script() -> String {
+    let mut out = String::new();
+    out.push_str("# bash completion for catdog\n");
+    out.push_str("# Load with: source <(catdog completions bash)\n\n");
+    out.push_str("_catdog() {\n");
+    out.push_str("    local cur prev cmd=\"\" sub=\"\" i\n");
+    out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n\n");
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        case \"${COMP_WORDS[i]}\" in\n");
+    out.push_str(&format!(
+        "            {}) ((i++)) ;;\n",
+        VALUE_FLAGS.join("|")
+    ));
+    out.push_str("            -*) ;;\n");
+    out.push_str("            *)\n");
+    out.push_str("                if [[ -z \"$cmd\" ]]; then\n");
+    out.push_str("                    cmd=\"${COMP_WORDS[i]}\"\n");
+    out.push_str("                elif [[ -z \"$sub\" ]]; then\n");
+    out.push_str("                    sub=\"${COMP_WORDS[i]}\"\n");
+    out.push_str("                fi\n");
+    out.push_str("                ;;\n");
+    out.push_str("        esac\n");
+    out.push_str("    done\n\n");
+
+    out.push_str("    case \"$prev\" in\n");
+    for flag in VALUE_FLAGS {
+        if let Some(choices) = value_choices(flag) {
+            out.push_str(&format!(
+                "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n",
+                flag,
+                choices.join(" ")
+            ));
+        }
+    }
+    out.push_str(&format!(
+        "        {}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;\n",
+        PATH_FLAGS.join("|")
+    ));
+    out.push_str(&format!(
+        "        {}) return ;;\n",
+        value_flags_without_choices().join("|")
+    ));
+    out.push_str("    esac\n\n");
+
+    out.push_str(&format!(
+        "    local flags=\"{}\"\n",
+        global_flag_names().join(" ")
+    ));
+    out.push_str("    case \"$cmd\" in\n");
+    for command in with_flags() {
+        out.push_str(&format!(
+            "        {}) flags=\"$flags {}\" ;;\n",
+            command.names().collect::<Vec<_>>().join("|"),
+            command.flags.join(" ")
+        ));
+    }
+    out.push_str("    esac\n");
+    out.push_str("    if [[ \"$cur\" == -* ]]; then\n");
+    out.push_str("        COMPREPLY=($(compgen -W \"$flags\" -- \"$cur\"))\n");
+    out.push_str("        return\n");
+    out.push_str("    fi\n\n");
+
+    out.push_str("    if [[ -z \"$cmd\" ]]; then\n");
+    out.push_str(&format!(
+        "        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n",
+        command_names().join(" ")
+    ));
+    out.push_str("        return\n");
+    out.push_str("    fi\n");
+    out.push_str("    if [[ -z \"$sub\" ]]; then\n");
+    out.push_str("        case \"$cmd\" in\n");
+    for command in with_subcommands() {
+        out.push_str(&format!(
+            "            {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n",
+            command.names().collect::<Vec<_>>().join("|"),
+            command.subcommands.join(" ")
+        ));
+    }
+    out.push_str("        esac\n");
+    out.push_str("    fi\n");
+    out.push_str("}\n\n");
+    out.push_str("complete -o default -F _catdog catdog\n");
+    out
+}
+
+fn zsh_script() -> String {
+    let mut out = String::new();
+    out.push_str("#compdef catdog\n\n");
+    out.push_str("_catdog() {\n");
+    out.push_str("    local -a commands flags\n");
+    out.push_str("    local cmd sub i\n");
+    out.push_str("    commands=(\n");
+    for command in COMMANDS {
+        for name in command.names() {
+            out.push_str(&format!("        '{}:{}'\n", name, command.about));
+        }
+    }
+    out.push_str("    )\n\n");
+
+    out.push_str("    for (( i = 2; i < CURRENT; i++ )); do\n");
+    out.push_str("        case ${words[i]} in\n");
+    out.push_str(&format!(
+        "            ({}) (( i++ )) ;;\n",
+        VALUE_FLAGS.join("|")
+    ));
+    out.push_str("            (-*) ;;\n");
+    out.push_str("            (*)\n");
+    out.push_str("                if [[ -z $cmd ]]; then\n");
+    out.push_str("                    cmd=${words[i]}\n");
+    out.push_str("                elif [[ -z $sub ]]; then\n");
+    out.push_str("                    sub=${words[i]}\n");
+    out.push_str("                fi\n");
+    out.push_str("                ;;\n");
+    out.push_str("        esac\n");
+    out.push_str("    done\n\n");
+
+    out.push_str("    case ${words[CURRENT-1]} in\n");
+    for flag in VALUE_FLAGS {
+        if let Some(choices) = value_choices(flag) {
+            out.push_str(&format!(
+                "        ({}) compadd -- {}; return ;;\n",
+                flag,
+                choices.join(" ")
+            ));
+        }
+    }
+    out.push_str(&format!(
+        "        ({}) _files; return ;;\n",
+        PATH_FLAGS.join("|")
+    ));
+    out.push_str(&format!(
+        "        ({}) return ;;\n",
+        value_flags_without_choices().join("|")
+    ));
+    out.push_str("    esac\n\n");
+
+    out.push_str(&format!("    flags=({})\n", global_flag_names().join(" ")));
+    out.push_str("    case $cmd in\n");
+    for command in with_flags() {
+        out.push_str(&format!(
+            "        ({}) flags+=({}) ;;\n",
+            command.names().collect::<Vec<_>>().join("|"),
+            command.flags.join(" ")
+        ));
+    }
+    out.push_str("    esac\n");
+    out.push_str("    if [[ $PREFIX == -* ]]; then\n");
+    out.push_str("        compadd -- $flags\n");
+    out.push_str("        return\n");
+    out.push_str("    fi\n\n");
+
+    out.push_str("    if [[ -z $cmd ]]; then\n");
+    out.push_str("        _describe -t commands 'catdog command' commands\n");
+    out.push_str("        return\n");
+    out.push_str("    fi\n");
+    out.push_str("    if [[ -z $sub ]]; then\n");
+    out.push_str("        case $cmd in\n");
+    for command in with_subcommands() {
+        out.push_str(&format!(
+            "            ({}) compadd -- {}; return ;;\n",
+            command.names().collect::<Vec<_>>().join("|"),
+            command.subcommands.join(" ")
+        ));
+    }
+    out.push_str("        esac\n");
+    out.push_str("    fi\n");
+    out.push_str("    _files\n");
+    out.push_str("}\n\n");
+    out.push_str("if [[ $funcstack[1] == _catdog ]]; then\n");
+    out.push_str("    _catdog \"$@\"\n");
+    out.push_str("else\n");
+    out.push_str("    compdef _catdog catdog\n");
+    out.push_str("fi\n");
+    out
+}
+
+/// `-l name` or `-s X` for a fish `complete` line
+fn fish_flag(flag: &str) -> String {
+    match flag.strip_prefix("--") {
+        Some(long) => format!("-l {}", long),
+        None => format!("-s {}", flag.trim_start_matches('-')),
+    }
+}
+
+/// How a fish `complete` line should treat a flag's value
+fn fish_value(flag: &str) -> String {
+    if let Some(choices) = value_choices(flag) {
+        format!(" -x -a '{}'", choices.join(" "))
+    } else if PATH_FLAGS.contains(&flag) {
+        " -r -F".to_string()
+    } else if VALUE_FLAGS.contains(&flag) {
+        " -x".to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn fish_script() -> String {
+    let mut out = String::new();
+    out.push_str("# fish completion for catdog\n\n");
+    out.push_str("function __catdog_words\n");
+    out.push_str("    set -l tokens (commandline -opc)\n");
+    out.push_str("    set -e tokens[1]\n");
+    out.push_str("    set -l skip 0\n");
+    out.push_str("    for token in $tokens\n");
+    out.push_str("        if test $skip -eq 1\n");
+    out.push_str("            set skip 0\n");
+    out.push_str(&format!(
+        "        else if contains -- $token {}\n",
+        VALUE_FLAGS.join(" ")
+    ));
+    out.push_str("            set skip 1\n");
+    out.push_str("        else if not string match -q -- '-*' $token\n");
+    out.push_str("            echo $token\n");
+    out.push_str("        end\n");
+    out.push_str("    end\n");
+    out.push_str("end\n\n");
+    out.push_str("function __catdog_using\n");
+    out.push_str("    set -l words (__catdog_words)\n");
+    out.push_str("    set -q words[1]; and contains -- $words[1] $argv\n");
+    out.push_str("end\n\n");
+    out.push_str("function __catdog_needs_subcommand\n");
+    out.push_str("    set -l words (__catdog_words)\n");
+    out.push_str("    test (count $words) -eq 1; and contains -- $words[1] $argv\n");
+    out.push_str("end\n\n");
+
+    out.push_str("# Global flags\n");
+    for flag in GLOBAL_FLAGS {
+        let short = flag.short.map(|c| format!(" -s {}", c)).unwrap_or_default();
+        out.push_str(&format!(
+            "complete -c catdog{} {}{} -d '{}'\n",
+            short,
+            fish_flag(flag.long),
+            fish_value(flag.long),
+            flag.about
+        ));
+    }
+
+    out.push_str("\n# Commands\n");
+    for command in COMMANDS {
+        for name in command.names() {
+            out.push_str(&format!(
+                "complete -c catdog -f -n 'test (count (__catdog_words)) -eq 0' -a {} -d '{}'\n",
+                name, command.about
+            ));
+        }
+    }
+
+    out.push_str("\n# Subcommands\n");
+    for command in with_subcommands() {
+        out.push_str(&format!(
+            "complete -c catdog -f -n '__catdog_needs_subcommand {}' -a '{}'\n",
+            command.names().collect::<Vec<_>>().join(" "),
+            command.subcommands.join(" ")
+        ));
+    }
+
+    out.push_str("\n# Command flags\n");
+    for command in with_flags() {
+        let names = command.names().collect::<Vec<_>>().join(" ");
+        for flag in command.flags {
+            out.push_str(&format!(
+                "complete -c catdog -n '__catdog_using {}' {}{}\n",
+                names,
+                fish_flag(flag),
+                fish_value(flag)
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_cover_registry() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = script(shell);
+            for command in COMMANDS {
+                for name in command.names() {
+                    assert!(script.contains(name), "{:?} missing {}", shell, name);
+                }
+                for sub in command.subcommands.iter().chain(command.flags) {
+                    assert!(
+                        script.contains(sub.trim_start_matches('-')),
+                        "{:?} missing {}",
+                        shell,
+                        sub
+                    );
+                }
+            }
+            for flag in GLOBAL_FLAGS {
+                assert!(script.contains(flag.long.trim_start_matches('-')));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bash_script_shape() {
+        let script = script(Shell::Bash);
+        assert!(script.contains("complete -o default -F _catdog catdog"));
+        assert!(script.contains("service|svc) COMPREPLY=($(compgen -W \"start stop"));
+        assert!(script.contains("--backend) COMPREPLY=($(compgen -W \"apt dnf"));
+    }
+
+    #[test]
+    fn test_fish_flag() {
+        assert_eq!(fish_flag("--add"), "-l add");
+        assert_eq!(fish_flag("-U"), "-s U");
+        assert_eq!(fish_value("--config"), " -r -F");
+        assert_eq!(fish_value("--sort"), " -x -a 'cpu mem'");
+        assert_eq!(fish_value("--force"), "");
+    }
+
+    #[test]
+    fn test_shell_from_name() {
+        assert_eq!(Shell::from_name("zsh"), Some(Shell::Zsh));
+        assert_eq!(Shell::from_name("powershell"), None);
+    }
+}
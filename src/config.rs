@@ -1,24 +1,70 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::alerts::{self, AlertSeverity, NotificationChannel, RetryPolicy};
+
+/// Config file location set by `--config` or `CATDOG_CONFIG`
+static PATH_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// One effective setting, flattened to a dotted key like `monitoring.check_interval_seconds`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Setting {
+    pub key: String,
+    pub value: serde_json::Value,
+    /// Set in the config file rather than filled in by a default
+    pub explicit: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
+    #[serde(default)]
+    pub general: GeneralConfig,
     #[serde(default)]
     pub alerts: AlertConfig,
     #[serde(default)]
     pub monitoring: MonitoringConfig,
     #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
     pub slack: Option<SlackConfig>,
     #[serde(default)]
     pub webhook: Option<WebhookConfig>,
+    #[serde(default)]
+    pub discord: Option<DiscordConfig>,
+    #[serde(default)]
+    pub pagerduty: Option<PagerDutyConfig>,
+    #[serde(default)]
+    pub email: Option<EmailConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneralConfig {
+    /// How long an external tool such as `lsblk` or `df` may run before it is killed
+    #[serde(default = "default_command_timeout")]
+    pub command_timeout_seconds: u64,
+    /// Where `mkmount` may create directories, on top of /mnt, /media and /srv
+    #[serde(default)]
+    pub mount_dir_prefixes: Vec<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertConfig {
     #[serde(default = "default_enabled_channels")]
     pub enabled_channels: Vec<String>,
+    /// Re-fire acknowledged alerts whose condition persists after this long
+    #[serde(default)]
+    pub refire_after_seconds: Option<u64>,
+    /// Resolved and silenced alerts older than this are dropped on save; 0 keeps them
+    #[serde(default = "alerts::default_retain_resolved_days")]
+    pub retain_resolved_days: u64,
+    /// Channel names per severity, e.g. `Critical = ["slack", "pagerduty"]`;
+    /// severities left out go to `enabled_channels`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub routing: HashMap<AlertSeverity, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +75,21 @@ pub struct MonitoringConfig {
     pub disk_threshold_warning: u8,
     #[serde(default = "default_disk_critical")]
     pub disk_threshold_critical: u8,
+    /// Executable check scripts run on every monitoring cycle
+    #[serde(default)]
+    pub hooks: Vec<PathBuf>,
+    #[serde(default = "default_hook_timeout")]
+    pub hook_timeout_seconds: u64,
+}
+
+/// Retention applied after every new backup of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    #[serde(default = "default_max_backups_per_file")]
+    pub max_per_file: usize,
+    /// Backups older than this are removed regardless of count
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,15 +100,41 @@ pub struct SlackConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookConfig {
     pub url: String,
+    /// `max_retries`, `base_delay_ms` and `timeout_seconds`
+    #[serde(flatten)]
+    pub retry: RetryPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscordConfig {
+    pub webhook_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PagerDutyConfig {
+    pub routing_key: String,
+    /// Lowest severity that pages; `Critical` unless set
+    #[serde(default = "alerts::default_pagerduty_severity")]
+    pub min_severity: AlertSeverity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    /// `host` or `host:port`; STARTTLS on port 587 unless given
+    pub smtp_server: String,
+    pub from: String,
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
 }
 
-impl Default for Config {
+impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
-            alerts: AlertConfig::default(),
-            monitoring: MonitoringConfig::default(),
-            slack: None,
-            webhook: None,
+            command_timeout_seconds: default_command_timeout(),
+            mount_dir_prefixes: Vec::new(),
         }
     }
 }
@@ -56,6 +143,9 @@ impl Default for AlertConfig {
     fn default() -> Self {
         Self {
             enabled_channels: default_enabled_channels(),
+            refire_after_seconds: None,
+            retain_resolved_days: alerts::default_retain_resolved_days(),
+            routing: HashMap::new(),
         }
     }
 }
@@ -66,10 +156,25 @@ impl Default for MonitoringConfig {
             check_interval_seconds: default_check_interval(),
             disk_threshold_warning: default_disk_warning(),
             disk_threshold_critical: default_disk_critical(),
+            hooks: Vec::new(),
+            hook_timeout_seconds: default_hook_timeout(),
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            max_per_file: default_max_backups_per_file(),
+            max_age_days: None,
         }
     }
 }
 
+fn default_command_timeout() -> u64 {
+    crate::exec::DEFAULT_TIMEOUT_SECONDS
+}
+
 fn default_enabled_channels() -> Vec<String> {
     vec!["console".to_string()]
 }
@@ -86,6 +191,14 @@ fn default_disk_critical() -> u8 {
     90
 }
 
+fn default_hook_timeout() -> u64 {
+    30
+}
+
+fn default_max_backups_per_file() -> usize {
+    10
+}
+
 impl Config {
     /// Get the default config file path
     pub fn default_path() -> Result<PathBuf> {
@@ -96,10 +209,36 @@ impl Config {
         Ok(config_dir.join("config.toml"))
     }
 
+    /// Override the config file location for the rest of the process
+    pub fn set_path_override(path: Option<PathBuf>) {
+        *PATH_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) = path;
+    }
+
+    fn path_override() -> Option<PathBuf> {
+        PATH_OVERRIDE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Get the config file path in effect, honoring any override
+    pub fn path() -> Result<PathBuf> {
+        match Self::path_override() {
+            Some(path) => Ok(path),
+            None => Self::default_path(),
+        }
+    }
+
     /// Load configuration from file, or create default if it doesn't exist
+    ///
+    /// An overridden path goes through [`Config::load_from`] and must exist; a
+    /// default is only created at the standard location.
     pub fn load() -> Result<Self> {
-        let path = Self::default_path()?;
+        if let Some(path) = Self::path_override() {
+            return Self::load_from(&path);
+        }
 
+        let path = Self::default_path()?;
         if !path.exists() {
             // Create default config
             let config = Config::default();
@@ -107,16 +246,114 @@ impl Config {
             return Ok(config);
         }
 
-        let contents = fs::read_to_string(&path)
+        Self::load_from(&path)
+    }
+
+    /// Load and validate the configuration at `path`, which must exist
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            anyhow::bail!("Config file not found: {}", path.display());
+        }
+
+        let contents = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-        toml::from_str(&contents)
-            .with_context(|| format!("Failed to parse config file: {}", path.display()))
+        let config: Config = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that values are in range, so a typo fails loudly instead of
+    /// quietly breaking monitoring
+    ///
+    /// Every problem is reported at once, phrased as a config error.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        let monitoring = &self.monitoring;
+
+        for (key, value) in [
+            ("disk_threshold_warning", monitoring.disk_threshold_warning),
+            (
+                "disk_threshold_critical",
+                monitoring.disk_threshold_critical,
+            ),
+        ] {
+            if !(1..=100).contains(&value) {
+                problems.push(format!(
+                    "monitoring.{} = {} must be between 1 and 100",
+                    key, value
+                ));
+            }
+        }
+        if monitoring.disk_threshold_warning >= monitoring.disk_threshold_critical {
+            problems.push(format!(
+                "monitoring.disk_threshold_warning ({}) must be below disk_threshold_critical ({})",
+                monitoring.disk_threshold_warning, monitoring.disk_threshold_critical
+            ));
+        }
+        if self.general.command_timeout_seconds == 0 {
+            problems.push("general.command_timeout_seconds must be greater than 0".to_string());
+        }
+        for prefix in &self.general.mount_dir_prefixes {
+            if !prefix.is_absolute() {
+                problems.push(format!(
+                    "general.mount_dir_prefixes entry '{}' must be an absolute path",
+                    prefix.display()
+                ));
+            }
+        }
+        if self.backup.max_per_file == 0 {
+            problems.push("backup.max_per_file must be at least 1".to_string());
+        }
+        if monitoring.check_interval_seconds == 0 {
+            problems.push("monitoring.check_interval_seconds must be greater than 0".to_string());
+        }
+
+        let urls = [
+            (
+                "slack.webhook_url",
+                self.slack.as_ref().map(|s| &s.webhook_url),
+            ),
+            ("webhook.url", self.webhook.as_ref().map(|w| &w.url)),
+            (
+                "discord.webhook_url",
+                self.discord.as_ref().map(|d| &d.webhook_url),
+            ),
+        ];
+        for (key, url) in urls {
+            if let Some(url) = url {
+                if let Err(reason) = check_url(url) {
+                    problems.push(format!("{} '{}' {}", key, url, reason));
+                }
+            }
+        }
+
+        for (severity, names) in &self.alerts.routing {
+            for name in names {
+                if !alerts::NOTIFICATION_CHANNEL_KINDS.contains(&name.as_str()) {
+                    problems.push(format!(
+                        "alerts.routing.{:?} names unknown channel '{}'",
+                        severity, name
+                    ));
+                } else if self.channels_named(std::slice::from_ref(name)).is_empty() {
+                    problems.push(format!(
+                        "alerts.routing.{:?} routes to '{}', which has no [{}] section",
+                        severity, name, name
+                    ));
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            anyhow::bail!("Invalid config value: {}", problems.join("; "));
+        }
+        Ok(())
     }
 
     /// Save configuration to file
     pub fn save(&self) -> Result<()> {
-        let path = Self::default_path()?;
+        let path = Self::path()?;
 
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
@@ -125,20 +362,171 @@ impl Config {
 
         let contents = toml::to_string_pretty(self).context("Failed to serialize configuration")?;
 
-        fs::write(&path, contents)
+        crate::state::write_atomic(&path, contents)
             .with_context(|| format!("Failed to write config file: {}", path.display()))?;
 
         Ok(())
     }
 
+    /// Runtime alert settings for `AlertManager`
+    pub fn alert_config(&self) -> alerts::AlertConfig {
+        alerts::AlertConfig {
+            enabled: true,
+            check_interval_seconds: self.monitoring.check_interval_seconds,
+            disk_usage_threshold: self.monitoring.disk_threshold_critical,
+            notification_channels: self.notification_channels(),
+            refire_after_seconds: self.alerts.refire_after_seconds,
+            retain_resolved_days: self.alerts.retain_resolved_days,
+            // A route whose channels are all unconfigured would silence its
+            // severity entirely, so it falls back to `enabled_channels` instead
+            routing: self
+                .alerts
+                .routing
+                .iter()
+                .map(|(severity, names)| (severity.clone(), self.channels_named(names)))
+                .filter(|(_, channels)| !channels.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Notification channels named in `enabled_channels`
+    ///
+    /// Channels whose config section is missing are left out.
+    pub fn notification_channels(&self) -> Vec<NotificationChannel> {
+        self.channels_named(&self.alerts.enabled_channels)
+    }
+
+    /// Channels for `names`, leaving out unknown names and missing sections
+    fn channels_named(&self, names: &[String]) -> Vec<NotificationChannel> {
+        names
+            .iter()
+            .filter_map(|name| match name.as_str() {
+                "console" => Some(NotificationChannel::Console),
+                "slack" => self.slack.as_ref().map(|slack| NotificationChannel::Slack {
+                    webhook_url: slack.webhook_url.clone(),
+                }),
+                "webhook" => self
+                    .webhook
+                    .as_ref()
+                    .map(|webhook| NotificationChannel::Webhook {
+                        url: webhook.url.clone(),
+                        retry: webhook.retry.clone(),
+                    }),
+                "discord" => self
+                    .discord
+                    .as_ref()
+                    .map(|discord| NotificationChannel::Discord {
+                        webhook_url: discord.webhook_url.clone(),
+                    }),
+                "pagerduty" => {
+                    self.pagerduty
+                        .as_ref()
+                        .map(|pagerduty| NotificationChannel::PagerDuty {
+                            routing_key: pagerduty.routing_key.clone(),
+                            min_severity: pagerduty.min_severity.clone(),
+                        })
+                }
+                "email" => self.email.as_ref().map(|email| NotificationChannel::Email {
+                    smtp_server: email.smtp_server.clone(),
+                    from: email.from.clone(),
+                    to: email.to.clone(),
+                    username: email.username.clone(),
+                    password: email.password.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every effective setting, marking those that `raw` (the config file's
+    /// contents) sets explicitly
+    ///
+    /// Secrets such as passwords, webhook URLs and routing keys are masked.
+    pub fn settings(&self, raw: Option<&str>) -> Result<Vec<Setting>> {
+        let explicit = match raw {
+            Some(raw) => {
+                let value: toml::Value =
+                    toml::from_str(raw).context("Failed to parse config file")?;
+                flatten_settings(&serde_json::to_value(value)?)
+            }
+            None => Vec::new(),
+        };
+
+        Ok(flatten_settings(&serde_json::to_value(self)?)
+            .into_iter()
+            .map(|(key, value)| {
+                let is_set = explicit.iter().any(|(k, _)| *k == key);
+                let value = if is_secret_setting(&key) {
+                    serde_json::Value::String("********".to_string())
+                } else {
+                    value
+                };
+                Setting {
+                    explicit: is_set,
+                    key,
+                    value,
+                }
+            })
+            .collect())
+    }
+
     /// Get the path to display to users
     pub fn display_path() -> String {
-        Self::default_path()
+        Self::path()
             .map(|p| p.display().to_string())
             .unwrap_or_else(|_| "~/.config/catdog/config.toml".to_string())
     }
 }
 
+/// A notification URL must be absolute http(s) with a host
+fn check_url(url: &str) -> std::result::Result<(), &'static str> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| "is not a valid URL")?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("must use http or https");
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("has no host");
+    }
+    Ok(())
+}
+
+/// Settings that grant access on their own: passwords, and webhook URLs and
+/// routing keys, which carry their token in the value
+const SECRET_SETTINGS: &[&str] = &[
+    "slack.webhook_url",
+    "discord.webhook_url",
+    "webhook.url",
+    "pagerduty.routing_key",
+];
+
+fn is_secret_setting(key: &str) -> bool {
+    key.ends_with("password") || SECRET_SETTINGS.contains(&key)
+}
+
+/// Flatten nested tables to dotted keys, dropping unset optional values
+fn flatten_settings(value: &serde_json::Value) -> Vec<(String, serde_json::Value)> {
+    fn walk(prefix: &str, value: &serde_json::Value, out: &mut Vec<(String, serde_json::Value)>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            serde_json::Value::Null => {}
+            _ => out.push((prefix.to_string(), value.clone())),
+        }
+    }
+
+    let mut out = Vec::new();
+    walk("", value, &mut out);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,6 +538,8 @@ mod tests {
         assert_eq!(config.monitoring.disk_threshold_warning, 80);
         assert_eq!(config.monitoring.disk_threshold_critical, 90);
         assert_eq!(config.alerts.enabled_channels, vec!["console"]);
+        assert_eq!(config.backup.max_per_file, 10);
+        assert_eq!(config.backup.max_age_days, None);
     }
 
     #[test]
@@ -175,5 +565,314 @@ disk_threshold_critical = 95
         assert_eq!(config.monitoring.check_interval_seconds, 60);
         assert_eq!(config.monitoring.disk_threshold_warning, 75);
         assert_eq!(config.alerts.enabled_channels.len(), 2);
+        assert_eq!(config.backup.max_per_file, 10);
+    }
+
+    #[test]
+    fn test_discord_channel_enabled() {
+        let toml_str = r#"
+[alerts]
+enabled_channels = ["console", "discord", "slack"]
+
+[discord]
+webhook_url = "https://discord.com/api/webhooks/1/abc"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let channels = config.notification_channels();
+
+        // No [slack] section, so only console and discord are active
+        assert_eq!(channels.len(), 2);
+        assert!(matches!(channels[0], NotificationChannel::Console));
+        assert!(matches!(
+            &channels[1],
+            NotificationChannel::Discord { webhook_url } if webhook_url.ends_with("/1/abc")
+        ));
+    }
+
+    #[test]
+    fn test_console_and_slack_channels() {
+        let config: Config = toml::from_str(
+            r#"
+            [alerts]
+            enabled_channels = ["console", "slack"]
+            refire_after_seconds = 3600
+
+            [slack]
+            webhook_url = "https://hooks.slack.com/services/T000/B000/XXXX"
+            "#,
+        )
+        .unwrap();
+
+        let alert_config = config.alert_config();
+        assert_eq!(alert_config.notification_channels.len(), 2);
+        assert!(matches!(
+            alert_config.notification_channels[0],
+            NotificationChannel::Console
+        ));
+        assert!(matches!(
+            &alert_config.notification_channels[1],
+            NotificationChannel::Slack { webhook_url } if webhook_url.ends_with("XXXX")
+        ));
+        assert_eq!(alert_config.refire_after_seconds, Some(3600));
+        assert_eq!(alert_config.retain_resolved_days, 30);
+    }
+
+    #[test]
+    fn test_pagerduty_min_severity() {
+        let toml_str = r#"
+[alerts]
+enabled_channels = ["pagerduty"]
+
+[pagerduty]
+routing_key = "R0UT1NG"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(matches!(
+            &config.notification_channels()[0],
+            NotificationChannel::PagerDuty {
+                min_severity: AlertSeverity::Critical,
+                ..
+            }
+        ));
+
+        let config: Config =
+            toml::from_str(&format!("{}min_severity = \"Warning\"\n", toml_str)).unwrap();
+        assert_eq!(
+            config.pagerduty.unwrap().min_severity,
+            AlertSeverity::Warning
+        );
+    }
+
+    #[test]
+    fn test_severity_routing() {
+        let toml_str = r#"
+[alerts]
+enabled_channels = ["console"]
+
+[alerts.routing]
+Critical = ["slack", "pagerduty"]
+Info = ["console"]
+
+[slack]
+webhook_url = "https://hooks.slack.com/services/T000/B000/XXXX"
+
+[pagerduty]
+routing_key = "R0UT1NG"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        config.validate().unwrap();
+
+        let alert_config = config.alert_config();
+        let critical = alert_config.channels_for(&AlertSeverity::Critical);
+        assert_eq!(critical.len(), 2);
+        assert!(matches!(critical[1], NotificationChannel::PagerDuty { .. }));
+        assert_eq!(
+            alert_config.channels_for(&AlertSeverity::Warning),
+            [NotificationChannel::Console]
+        );
+
+        let reparsed: Config = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.alerts.routing, config.alerts.routing);
+
+        let typo: Config =
+            toml::from_str(&toml_str.replace("\"pagerduty\"]", "\"pagerdooty\"]")).unwrap();
+        assert!(typo
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("pagerdooty"));
+
+        let unconfigured: Config =
+            toml::from_str(&toml_str.replace("[pagerduty]\nrouting_key = \"R0UT1NG\"\n", ""))
+                .unwrap();
+        assert!(unconfigured
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("no [pagerduty] section"));
+
+        let only_unconfigured: Config = toml::from_str(
+            "[alerts]\nenabled_channels = [\"console\"]\n[alerts.routing]\nCritical = [\"pagerduty\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            only_unconfigured
+                .alert_config()
+                .channels_for(&AlertSeverity::Critical),
+            [NotificationChannel::Console]
+        );
+    }
+
+    #[test]
+    fn test_webhook_retry_deserialization() {
+        let config: Config =
+            toml::from_str("[webhook]\nurl = \"https://hooks.example.com\"\nmax_retries = 5\n")
+                .unwrap();
+        let webhook = config.webhook.unwrap();
+        assert_eq!(webhook.retry.max_retries, 5);
+        assert_eq!(webhook.retry.base_delay_ms, 500);
+        assert_eq!(webhook.retry.timeout_seconds, 10);
+    }
+
+    #[test]
+    fn test_backup_retention_deserialization() {
+        let toml_str = r#"
+[backup]
+max_per_file = 3
+max_age_days = 30
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.backup.max_per_file, 3);
+        assert_eq!(config.backup.max_age_days, Some(30));
+
+        // Keeping no backups would delete each one right after making it
+        assert!(invalid("[backup]\nmax_per_file = 0\n").contains("backup.max_per_file"));
+    }
+
+    fn invalid(toml_str: &str) -> String {
+        let config: Config = toml::from_str(toml_str).unwrap();
+        config.validate().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_validate_accepts_defaults() {
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_threshold_range() {
+        let error = invalid("[monitoring]\ndisk_threshold_critical = 0\n");
+        assert!(error.contains("disk_threshold_critical = 0 must be between 1 and 100"));
+
+        let error = invalid("[monitoring]\ndisk_threshold_warning = 0\n");
+        assert!(error.contains("disk_threshold_warning = 0"));
+
+        // 900 doesn't fit in a u8, so it is already rejected while parsing
+        assert!(toml::from_str::<Config>("[monitoring]\ndisk_threshold_critical = 900\n").is_err());
+        let error = invalid("[monitoring]\ndisk_threshold_critical = 101\n");
+        assert!(error.contains("between 1 and 100"));
+    }
+
+    #[test]
+    fn test_validate_warning_below_critical() {
+        let error =
+            invalid("[monitoring]\ndisk_threshold_warning = 95\ndisk_threshold_critical = 90\n");
+        assert!(error.contains("must be below disk_threshold_critical"));
+    }
+
+    #[test]
+    fn test_validate_check_interval() {
+        let error = invalid("[monitoring]\ncheck_interval_seconds = 0\n");
+        assert!(error.contains("check_interval_seconds must be greater than 0"));
+    }
+
+    #[test]
+    fn test_validate_urls() {
+        let error = invalid("[slack]\nwebhook_url = \"hooks.slack.com/services/x\"\n");
+        assert!(error.contains("slack.webhook_url"));
+        assert!(error.contains("is not a valid URL"));
+
+        let error = invalid("[webhook]\nurl = \"ftp://example.com/hook\"\n");
+        assert!(error.contains("must use http or https"));
+
+        let error = invalid("[discord]\nwebhook_url = \"https://\"\n");
+        assert!(error.contains("discord.webhook_url"));
+    }
+
+    #[test]
+    fn test_validation_error_is_a_config_error() {
+        let error = Config {
+            monitoring: MonitoringConfig {
+                check_interval_seconds: 0,
+                ..MonitoringConfig::default()
+            },
+            ..Config::default()
+        }
+        .validate()
+        .unwrap_err();
+
+        assert_eq!(crate::error::to_user_error(error).exit_code(), 78);
+    }
+
+    #[test]
+    fn test_settings_mark_explicit_values() {
+        let raw = "[monitoring]\ncheck_interval_seconds = 60\n\n[email]\nsmtp_server = \"mail\"\nfrom = \"a@b\"\nto = []\npassword = \"hunter2\"\n";
+        let config: Config = toml::from_str(raw).unwrap();
+        let settings = config.settings(Some(raw)).unwrap();
+        let get = |key: &str| settings.iter().find(|s| s.key == key).unwrap();
+        assert_eq!(get("email.smtp_server").value, "mail");
+
+        assert!(get("monitoring.check_interval_seconds").explicit);
+        assert_eq!(get("monitoring.check_interval_seconds").value, 60);
+        assert!(!get("monitoring.disk_threshold_warning").explicit);
+        assert_eq!(get("email.password").value, "********");
+        // Unset optional sections are left out
+        assert!(settings.iter().all(|s| !s.key.starts_with("slack")));
+
+        let defaults = Config::default().settings(None).unwrap();
+        assert!(defaults.iter().all(|s| !s.explicit));
+    }
+
+    #[test]
+    fn test_settings_mask_webhook_secrets() {
+        let raw = r#"
+[slack]
+webhook_url = "https://hooks.slack.com/services/T000/B000/XXXX"
+
+[discord]
+webhook_url = "https://discord.com/api/webhooks/1/abc"
+
+[webhook]
+url = "https://hooks.example.com/token"
+
+[pagerduty]
+routing_key = "R0UT1NG"
+"#;
+        let config: Config = toml::from_str(raw).unwrap();
+        let settings = config.settings(Some(raw)).unwrap();
+        for key in SECRET_SETTINGS {
+            let setting = settings.iter().find(|s| s.key == *key).unwrap();
+            assert_eq!(setting.value, "********", "{} is not masked", key);
+        }
+        let retries = settings
+            .iter()
+            .find(|s| s.key == "webhook.max_retries")
+            .unwrap();
+        assert_ne!(retries.value, "********");
+    }
+
+    #[test]
+    fn test_load_from() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("catdog.toml");
+        fs::write(&path, "[backup]\nmax_per_file = 4\n").unwrap();
+
+        assert_eq!(Config::load_from(&path).unwrap().backup.max_per_file, 4);
+
+        let missing = dir.path().join("missing.toml");
+        let error = Config::load_from(&missing).unwrap_err().to_string();
+        assert!(error.contains("Config file not found"));
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_load_from_override_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("project.toml");
+        fs::write(&path, "[monitoring]\ncheck_interval_seconds = 42\n").unwrap();
+
+        Config::set_path_override(Some(path.clone()));
+        let loaded = Config::load();
+        let display = Config::display_path();
+
+        let missing = dir.path().join("typo.toml");
+        Config::set_path_override(Some(missing.clone()));
+        let missing_result = Config::load();
+        Config::set_path_override(None);
+
+        assert_eq!(loaded.unwrap().monitoring.check_interval_seconds, 42);
+        assert_eq!(display, path.display().to_string());
+        assert!(missing_result.is_err());
+        assert!(!missing.exists());
     }
 }
@@ -1,16 +1,39 @@
 /// Statistical Analysis Module for Corpus Operations
 ///
-/// Implements advanced statistical methods including:
-/// - Shannon entropy calculation
-/// - Perplexity measurements
-/// - Zipf's law distribution analysis
-/// - Kolmogorov complexity estimation
-use std::collections::HashMap;
+/// Implements tokenization, Shannon entropy and TF-IDF vectorization
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::vector::SparseVector;
+
+/// Splits text into lowercase alphanumeric terms with light suffix stemming
+///
+/// Paths and option lists break apart on punctuation, so `/dev/sda1` yields
+/// `dev` and `sda1`, and `noatime,nodiratime` yields both options.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| stem(&token.to_lowercase()))
+        .collect()
+}
+
+/// Strips plural endings so `defaults` matches `default`
+fn stem(token: &str) -> String {
+    if token.len() > 4 && token.ends_with("ies") {
+        format!("{}y", &token[..token.len() - 3])
+    } else if token.len() > 3 && token.ends_with('s') && !token.ends_with("ss") {
+        token[..token.len() - 1].to_string()
+    } else {
+        token.to_string()
+    }
+}
 
 /// Analyzer implementing probabilistic data structures
 pub struct CorpusAnalyzer {
     token_frequency: HashMap<String, usize>,
     total_tokens: usize,
+    document_frequency: HashMap<String, usize>,
+    document_count: usize,
 }
 
 impl CorpusAnalyzer {
@@ -18,6 +41,26 @@ impl CorpusAnalyzer {
         Self {
             token_frequency: HashMap::new(),
             total_tokens: 0,
+            document_frequency: HashMap::new(),
+            document_count: 0,
+        }
+    }
+
+    /// Records term and document frequencies for one document
+    ///
+    /// # Complexity
+    /// - Time: O(m) where m is document length
+    pub fn add_document(&mut self, text: &str) {
+        let tokens = tokenize(text);
+        self.total_tokens += tokens.len();
+        self.document_count += 1;
+
+        let mut seen = HashSet::new();
+        for token in tokens {
+            *self.token_frequency.entry(token.clone()).or_insert(0) += 1;
+            if seen.insert(token.clone()) {
+                *self.document_frequency.entry(token).or_insert(0) += 1;
+            }
         }
     }
 
@@ -36,50 +79,129 @@ impl CorpusAnalyzer {
 
         entropy
     }
-
-    /// Calculates perplexity: 2^H(X)
-    /// Lower perplexity indicates better predictability
-    pub fn calculate_perplexity(&self) -> f64 {
-        2_f64.powf(self.calculate_entropy())
-    }
-
-    /// Analyzes Zipf's law compliance
-    /// Frequency ∝ 1/rank^α where α ≈ 1
-    pub fn zipf_analysis(&self) -> f64 {
-        // Implementation of power-law fitting
-        // Returns Zipf exponent α
-        1.0 // Simplified
-    }
-
-    /// Estimates Kolmogorov complexity K(x)
-    /// Using Lempel-Ziv compression as approximation
-    pub fn estimate_complexity(&self, text: &str) -> f64 {
-        // LZ77 complexity estimation
-        text.len() as f64 / self.compress_lz(text).len() as f64
-    }
-
-    fn compress_lz(&self, _text: &str) -> Vec<u8> {
-        // Simplified LZ compression
-        vec![]
-    }
 }
 
 /// TF-IDF vectorizer with sparse matrix representation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TfIdfVectorizer {
     vocabulary: HashMap<String, usize>,
     idf_scores: Vec<f64>,
+    document_count: usize,
 }
 
 impl TfIdfVectorizer {
-    /// Computes TF-IDF with sublinear scaling
+    /// Learns the vocabulary and IDF weights from a set of documents
+    #[cfg(test)]
+    pub fn fit<S: AsRef<str>>(documents: &[S]) -> Self {
+        let mut analyzer = CorpusAnalyzer::new();
+        for document in documents {
+            analyzer.add_document(document.as_ref());
+        }
+        Self::from_analyzer(&analyzer)
+    }
+
+    /// Builds IDF weights from the document frequencies an analyzer has seen
+    pub fn from_analyzer(analyzer: &CorpusAnalyzer) -> Self {
+        // Sorted so the same corpus always produces the same term indices
+        let mut terms: Vec<(&String, &usize)> = analyzer.document_frequency.iter().collect();
+        terms.sort();
+
+        let n = analyzer.document_count as f64;
+        let mut vocabulary = HashMap::new();
+        let mut idf_scores = Vec::with_capacity(terms.len());
+        for (index, (term, &df)) in terms.into_iter().enumerate() {
+            vocabulary.insert(term.clone(), index);
+            idf_scores.push((n / df as f64).ln() + 1.0);
+        }
+
+        Self {
+            vocabulary,
+            idf_scores,
+            document_count: analyzer.document_count,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn document_count(&self) -> usize {
+        self.document_count
+    }
+
+    /// Vocabulary size, the length of every vector this produces
+    pub fn dimensionality(&self) -> usize {
+        self.vocabulary.len()
+    }
+
+    /// Computes TF-IDF with sublinear scaling, keeping only the document's terms
     /// TF = 1 + log(f) if f > 0, else 0
     /// IDF = log(N/df) + 1
-    pub fn vectorize(&self, document: &str) -> Vec<f64> {
-        let mut vector = vec![0.0; self.vocabulary.len()];
+    pub fn vectorize(&self, document: &str) -> SparseVector {
+        let mut counts = BTreeMap::new();
+        for token in tokenize(document) {
+            if let Some(&index) = self.vocabulary.get(&token) {
+                *counts.entry(index).or_insert(0) += 1;
+            }
+        }
+        self.weigh(&counts)
+    }
+
+    /// Vectorizes a search query, expanding terms missing from the vocabulary
+    /// to every known term they prefix, so `sda` matches `sda1`
+    pub fn vectorize_query(&self, query: &str) -> SparseVector {
+        let mut counts = BTreeMap::new();
+        for token in tokenize(query) {
+            match self.vocabulary.get(&token) {
+                Some(&index) => *counts.entry(index).or_insert(0) += 1,
+                None => {
+                    for (term, &index) in &self.vocabulary {
+                        if term.starts_with(&token) {
+                            *counts.entry(index).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+        }
+        self.weigh(&counts)
+    }
+
+    /// Term counts, keyed by term index, to TF-IDF weights
+    fn weigh(&self, counts: &BTreeMap<usize, usize>) -> SparseVector {
+        SparseVector::from_pairs(
+            counts
+                .iter()
+                .map(|(&index, &f)| (index, (1.0 + (f as f64).ln()) * self.idf_scores[index])),
+            self.dimensionality(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("/dev/sda1 /mnt/Data ext4 defaults,noatime"),
+            vec!["dev", "sda1", "mnt", "data", "ext4", "default", "noatime"]
+        );
+    }
+
+    #[test]
+    fn test_rare_terms_weigh_more() {
+        let vectorizer = TfIdfVectorizer::fit(&["ext4 defaults", "ext4 noatime", "ext4 defaults"]);
+        let vector = vectorizer.vectorize("ext4 noatime");
+        let ext4 = vectorizer.vocabulary["ext4"];
+        let noatime = vectorizer.vocabulary["noatime"];
 
-        // Tokenization and vectorization
-        // Implementation using sparse representation
+        assert_eq!(vectorizer.document_count(), 3);
+        assert!(vector.get(noatime) > vector.get(ext4));
+        assert_eq!(vector.indices().len(), 2);
+    }
 
-        vector
+    #[test]
+    fn test_query_prefix_expansion() {
+        let vectorizer = TfIdfVectorizer::fit(&["/dev/sda1 / ext4"]);
+        let query = vectorizer.vectorize_query("sda");
+        assert!(query.get(vectorizer.vocabulary["sda1"]) > 0.0);
     }
 }
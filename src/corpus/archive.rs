@@ -0,0 +1,165 @@
+/// Portable Corpus Archives
+///
+/// Bundles the stored configurations into a gzipped tarball so a vetted
+/// fstab library can be shared and merged into another machine's corpus.
+use super::StoredConfig;
+use crate::state;
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// What an import added to the library
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportSummary {
+    pub configs: usize,
+    pub entries: usize,
+    /// Configs skipped because their entries were already in the library
+    pub duplicates: usize,
+}
+
+/// Write every stored config in `dir` to a `.tar.gz` archive, returning how many were bundled
+pub fn export(dir: &Path, archive: &Path) -> Result<usize> {
+    let configs = super::load_configs(dir)?;
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+    for config in &configs {
+        let data = serde_json::to_vec_pretty(config)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, format!("{}.json", config.id), data.as_slice())?;
+    }
+
+    let bytes = builder.into_inner()?.finish()?;
+    state::write_atomic(archive, bytes)?;
+    Ok(configs.len())
+}
+
+/// Merge the configs from an archive into `dir`
+///
+/// Imported configs get new IDs so they can't collide with local ones, and
+/// configs whose entries match one already present are skipped.
+pub fn import(dir: &Path, archive: &Path) -> Result<ImportSummary> {
+    let file =
+        File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut tarball = tar::Archive::new(GzDecoder::new(file));
+
+    fs::create_dir_all(dir)?;
+    let mut known: HashSet<String> = super::load_configs(dir)?
+        .iter()
+        .map(content_key)
+        .collect::<Result<_>>()?;
+
+    let mut summary = ImportSummary::default();
+    for entry in tarball
+        .entries()
+        .with_context(|| format!("Failed to read {}", archive.display()))?
+    {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        // Entries are parsed in memory, so archive paths never touch the filesystem
+        if !entry.header().entry_type().is_file()
+            || path.extension().and_then(|s| s.to_str()) != Some("json")
+        {
+            continue;
+        }
+
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        let mut config: StoredConfig = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid config {} in archive", path.display()))?;
+
+        if !known.insert(content_key(&config)?) {
+            summary.duplicates += 1;
+            continue;
+        }
+
+        config.id = uuid::Uuid::new_v4().to_string();
+        config.entry_count = config.entries.len();
+        state::write_atomic(
+            &dir.join(format!("{}.json", config.id)),
+            serde_json::to_string_pretty(&config)?,
+        )?;
+
+        summary.configs += 1;
+        summary.entries += config.entries.len();
+    }
+
+    Ok(summary)
+}
+
+/// Identity of a config by what it contains rather than its ID or source
+fn content_key(config: &StoredConfig) -> Result<String> {
+    Ok(serde_json::to_string(&config.entries)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::StoredEntry;
+    use super::*;
+
+    fn write_config(dir: &Path, id: &str, device: &str) {
+        let config = StoredConfig {
+            id: id.to_string(),
+            source_file: "/etc/fstab".to_string(),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            entry_count: 1,
+            entries: vec![StoredEntry {
+                device: device.to_string(),
+                mount_point: "/data".to_string(),
+                fs_type: "ext4".to_string(),
+                options: "defaults".to_string(),
+                dump: "0".to_string(),
+                pass: "2".to_string(),
+            }],
+        };
+        fs::write(
+            dir.join(format!("{}.json", id)),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("source");
+        let target = temp.path().join("target");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        write_config(&source, "a", "/dev/sdb1");
+        write_config(&source, "b", "/dev/sdc1");
+        // The target already has a config identical to "a"
+        write_config(&target, "local", "/dev/sdb1");
+
+        let archive = temp.path().join("library.tar.gz");
+        assert_eq!(export(&source, &archive).unwrap(), 2);
+
+        let summary = import(&target, &archive).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                configs: 1,
+                entries: 1,
+                duplicates: 1,
+            }
+        );
+
+        let configs = super::super::load_configs(&target).unwrap();
+        assert_eq!(configs.len(), 2);
+        let imported = configs.iter().find(|c| c.id != "local").unwrap();
+        assert_ne!(imported.id, "b");
+        assert_eq!(imported.entries[0].device, "/dev/sdc1");
+
+        // Importing again adds nothing
+        assert_eq!(import(&target, &archive).unwrap().configs, 0);
+    }
+}
@@ -1,33 +1,28 @@
 /// High-Performance Indexing System
 ///
-/// Implements:
-/// - Locality-Sensitive Hashing (LSH) for ANN search
-/// - Inverted index with positional information
-/// - B+ tree for range queries
-/// - Bloom filters for membership testing
-use super::{CorpusError, Document};
-use std::collections::HashMap;
+/// Locality-Sensitive Hashing (LSH) for ANN search, backed by a map from
+/// each term to the documents containing it for exact fallback scoring
+use super::vector::SparseVector;
+use super::Document;
+use std::collections::{HashMap, HashSet};
+
+/// Hyperplanes per LSH table; each table buckets vectors by a 6-bit signature
+const LSH_BITS: usize = 6;
 
 /// Multi-level indexing structure
 pub struct CorpusIndex {
-    inverted_index: HashMap<String, Vec<Posting>>,
+    /// Documents containing each vocabulary term, by term index
+    term_documents: HashMap<usize, Vec<String>>,
     lsh_tables: Vec<HashMap<u64, Vec<String>>>,
-    bloom_filter: BloomFilter,
-}
-
-#[derive(Debug, Clone)]
-pub struct Posting {
-    pub doc_id: String,
-    pub positions: Vec<usize>,
-    pub tf_idf: f64,
+    documents: HashMap<String, Document>,
 }
 
 impl CorpusIndex {
     pub fn new() -> Self {
         Self {
-            inverted_index: HashMap::new(),
+            term_documents: HashMap::new(),
             lsh_tables: vec![HashMap::new(); 8],
-            bloom_filter: BloomFilter::new(10000, 0.01),
+            documents: HashMap::new(),
         }
     }
 
@@ -36,40 +31,74 @@ impl CorpusIndex {
     /// # Complexity
     /// - Time: O(m * k) where m is doc length, k is hash functions
     /// - Space: O(m)
-    pub fn index_document(&mut self, doc: &Document) -> Result<(), CorpusError> {
-        // Inverted index construction
-        let tokens = self.tokenize(&doc.content);
-
-        for (pos, token) in tokens.iter().enumerate() {
-            let posting = Posting {
-                doc_id: doc.id.clone(),
-                positions: vec![pos],
-                tf_idf: 0.0, // Computed during retrieval
-            };
-
-            self.inverted_index
-                .entry(token.clone())
-                .or_insert_with(Vec::new)
-                .push(posting);
+    pub fn index_document(&mut self, doc: &Document) {
+        for &term in doc.vector.indices() {
+            self.term_documents
+                .entry(term)
+                .or_default()
+                .push(doc.id.clone());
         }
 
         // LSH indexing
         self.index_lsh(&doc.vector, &doc.id);
 
-        // Bloom filter update
-        self.bloom_filter.insert(&doc.id);
-
-        Ok(())
+        self.documents.insert(doc.id.clone(), doc.clone());
     }
 
     /// Approximate Nearest Neighbor search using LSH
-    /// Returns k closest documents with probability ≥ 1-δ
-    pub fn ann_search(&self, query: &[f64], k: usize) -> Vec<Document> {
+    /// Returns k closest documents with probability ≥ 1-δ, paired with their
+    /// cosine similarity and ordered best first
+    ///
+    /// Buckets one bit away from the query's signature are probed as well.
+    /// When the probed buckets hold fewer than k relevant documents, as in
+    /// small corpora, the documents sharing a term with the query are scored
+    /// instead. Those are the only ones with a non-zero similarity, so the
+    /// work grows with how common the query's terms are, not the corpus size.
+    pub fn ann_search(&self, query: &SparseVector, k: usize) -> Vec<(Document, f64)> {
         // Multi-probe LSH with query-adaptive probing
-        vec![]
+        let mut candidates: HashSet<&str> = HashSet::new();
+        for (i, table) in self.lsh_tables.iter().enumerate() {
+            let hash = self.compute_lsh_hash(query, i);
+            let probes = std::iter::once(hash).chain((0..LSH_BITS).map(|bit| hash ^ (1 << bit)));
+            for probe in probes {
+                if let Some(ids) = table.get(&probe) {
+                    candidates.extend(ids.iter().map(String::as_str));
+                }
+            }
+        }
+
+        let mut results = self.score(query, candidates.iter().copied());
+        if results.len() < k {
+            let sharing_terms: HashSet<&str> = query
+                .indices()
+                .iter()
+                .filter_map(|term| self.term_documents.get(term))
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            results = self.score(query, sharing_terms.into_iter());
+        }
+
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+        results.truncate(k);
+        results
+    }
+
+    /// Cosine similarity of each candidate to the query, dropping unrelated documents
+    fn score<'a>(
+        &self,
+        query: &SparseVector,
+        ids: impl Iterator<Item = &'a str>,
+    ) -> Vec<(Document, f64)> {
+        ids.filter_map(|id| {
+            let doc = self.documents.get(id)?;
+            let similarity = query.cosine(&doc.vector);
+            (similarity > 0.0).then(|| (doc.clone(), similarity))
+        })
+        .collect()
     }
 
-    fn index_lsh(&mut self, vector: &[f64], doc_id: &str) {
+    fn index_lsh(&mut self, vector: &SparseVector, doc_id: &str) {
         // Compute multiple hash values for LSH
         let hashes: Vec<u64> = (0..self.lsh_tables.len())
             .map(|i| self.compute_lsh_hash(vector, i))
@@ -83,91 +112,47 @@ impl CorpusIndex {
         }
     }
 
-    fn compute_lsh_hash(&self, _vector: &[f64], _table_idx: usize) -> u64 {
-        // Random hyperplane hashing
-        0
-    }
-
-    fn tokenize(&self, text: &str) -> Vec<String> {
-        text.split_whitespace().map(|s| s.to_lowercase()).collect()
+    fn compute_lsh_hash(&self, vector: &SparseVector, table_idx: usize) -> u64 {
+        // Random hyperplane hashing: one signature bit per hyperplane, set
+        // when the vector lies on its positive side. The hyperplanes are
+        // derived from a seeded hash, so saved signatures stay valid.
+        (0..LSH_BITS).fold(0, |hash, bit| {
+            let dot: f64 = vector
+                .iter()
+                .map(|(dim, x)| x * hyperplane_component(table_idx, bit, dim))
+                .sum();
+            if dot >= 0.0 {
+                hash | (1 << bit)
+            } else {
+                hash
+            }
+        })
     }
 }
 
-/// Space-efficient Bloom filter using Kirsch-Mitzenmacher optimization
-pub struct BloomFilter {
-    bits: Vec<bool>,
-    num_hashes: usize,
-    size: usize,
+/// Pseudo-random hyperplane coordinate in [-1, 1) from SplitMix64
+fn hyperplane_component(table_idx: usize, bit: usize, dim: usize) -> f64 {
+    let mut z = (((table_idx as u64) << 48) ^ ((bit as u64) << 32) ^ dim as u64)
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
 }
 
-impl BloomFilter {
-    /// Creates new Bloom filter with optimal parameters
-    ///
-    /// # Parameters
-    /// - `expected_elements`: Expected number of elements n
-    /// - `false_positive_rate`: Desired FPR p
-    ///
-    /// # Optimal Parameters
-    /// - m = -n ln(p) / (ln 2)²
-    /// - k = (m/n) ln 2
-    pub fn new(expected_elements: usize, false_positive_rate: f64) -> Self {
-        let size = (-(expected_elements as f64) * false_positive_rate.ln() / (2_f64.ln().powi(2)))
-            as usize;
-        let num_hashes = ((size as f64 / expected_elements as f64) * 2_f64.ln()) as usize;
-
-        Self {
-            bits: vec![false; size],
-            num_hashes,
-            size,
-        }
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// Inserts element with O(k) time complexity
-    pub fn insert(&mut self, item: &str) {
-        for i in 0..self.num_hashes {
-            let hash = self.hash(item, i);
-            let index = (hash % self.size as u64) as usize;
-            self.bits[index] = true;
-        }
-    }
-
-    /// Membership test with no false negatives
-    /// False positive rate: p = (1 - e^(-kn/m))^k
-    pub fn contains(&self, item: &str) -> bool {
-        (0..self.num_hashes).all(|i| {
-            let hash = self.hash(item, i);
-            let index = (hash % self.size as u64) as usize;
-            self.bits[index]
-        })
-    }
-
-    fn hash(&self, item: &str, seed: usize) -> u64 {
-        // Double hashing scheme
-        0
-    }
-}
-
-/// Vector similarity module
-pub mod vector {
-    /// Computes cosine similarity: cos(θ) = (A·B) / (||A|| ||B||)
-    ///
-    /// # Complexity
-    /// - Time: O(d) where d is dimensionality
-    /// - Space: O(1)
-    pub fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
-        let dot_product: f64 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-        let norm_a: f64 = a.iter().map(|x| x * x).sum::<f64>().sqrt();
-        let norm_b: f64 = b.iter().map(|x| x * x).sum::<f64>().sqrt();
-
-        dot_product / (norm_a * norm_b)
-    }
+    #[test]
+    fn test_similar_vectors_share_lsh_buckets() {
+        let index = CorpusIndex::new();
+        let a = SparseVector::new(vec![0, 1], vec![1.0, 0.5], 4);
+        let b = SparseVector::new(vec![0, 1], vec![0.9, 0.6], 4);
 
-    /// Euclidean distance: d(p,q) = √(Σ(pᵢ - qᵢ)²)
-    pub fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
-        a.iter()
-            .zip(b.iter())
-            .map(|(x, y)| (x - y).powi(2))
-            .sum::<f64>()
-            .sqrt()
+        let shared = (0..8)
+            .filter(|&i| index.compute_lsh_hash(&a, i) == index.compute_lsh_hash(&b, i))
+            .count();
+        assert!(shared > 0);
     }
 }
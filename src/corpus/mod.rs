@@ -1,18 +1,231 @@
 /// Corpus Management and Analysis Module
 ///
-/// This module implements a distributed, persistent corpus management system
-/// utilizing B+ tree indexing with Bloom filter optimization for O(log n)
-/// retrieval complexity and O(1) membership testing.
+/// This module implements a persistent corpus of stored fstab entries,
+/// vectorized with TF-IDF and searched through an LSH index.
 pub mod analyzer;
+pub mod archive;
 pub mod indexer;
 pub mod vector;
 
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
+use crate::state;
+use analyzer::TfIdfVectorizer;
+use vector::SparseVector;
+
+/// File in the corpus directory holding the saved corpus and its IDF weights
+pub const CORPUS_FILE: &str = "corpus.idx";
+
+/// Matches `corpus search` shows unless given `--limit` or `--all`
+pub const DEFAULT_SEARCH_RESULTS: usize = 20;
+
+/// One fstab entry as stored in the configuration library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredEntry {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    pub options: String,
+    pub dump: String,
+    pub pass: String,
+}
+
+impl StoredEntry {
+    /// The text indexed for search
+    pub fn text(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.device, self.mount_point, self.fs_type, self.options
+        )
+    }
+}
+
+/// An ingested fstab file, stored as `<id>.json` in the corpus directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredConfig {
+    pub id: String,
+    pub source_file: String,
+    pub timestamp: String,
+    pub entry_count: usize,
+    pub entries: Vec<StoredEntry>,
+}
+
+/// How widely a mount option is used with one filesystem type across the library
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionUsage {
+    pub option: String,
+    /// Configs using the option on at least one entry of the filesystem type
+    pub configs: usize,
+    pub percent: u8,
+}
+
+/// A library entry ranked against a query
+#[derive(Debug, Clone)]
+pub struct SearchHit<'a> {
+    pub config: &'a StoredConfig,
+    pub entry: &'a StoredEntry,
+    pub score: f64,
+}
+
+/// Read every stored config in the corpus directory with the file it came from, ordered by ID
+///
+/// This is the one place the directory is walked; listing, removal, search
+/// and statistics all go through it.
+pub fn config_files(dir: &Path) -> Result<Vec<(PathBuf, StoredConfig)>> {
+    let mut configs = Vec::new();
+    if !dir.exists() {
+        return Ok(configs);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: StoredConfig = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        configs.push((path, config));
+    }
+
+    configs.sort_by(|a, b| a.1.id.cmp(&b.1.id));
+    Ok(configs)
+}
+
+/// Load every stored config from the corpus directory, ordered by ID
+pub fn load_configs(dir: &Path) -> Result<Vec<StoredConfig>> {
+    Ok(config_files(dir)?
+        .into_iter()
+        .map(|(_, config)| config)
+        .collect())
+}
+
+/// Delete the stored config whose ID starts with `prefix`
+///
+/// Fails without deleting anything when the prefix matches no config or several.
+pub fn remove_config(dir: &Path, prefix: &str) -> Result<StoredConfig> {
+    let mut matches: Vec<(PathBuf, StoredConfig)> = config_files(dir)?
+        .into_iter()
+        .filter(|(_, config)| config.id.starts_with(prefix))
+        .collect();
+
+    match matches.len() {
+        0 => anyhow::bail!("Nothing in the library matches '{}'", prefix),
+        1 => {
+            let (path, config) = matches.remove(0);
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            Ok(config)
+        }
+        _ => {
+            let ids: Vec<&str> = matches.iter().map(|(_, c)| c.id.as_str()).collect();
+            anyhow::bail!("'{}' is ambiguous; it matches {}", prefix, ids.join(", "))
+        }
+    }
+}
+
+/// Count how often each mount option appears across entries
+pub fn count_options<'a>(
+    entries: impl IntoIterator<Item = &'a StoredEntry>,
+) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for entry in entries {
+        for option in entry.options.split(',').map(str::trim) {
+            if !option.is_empty() {
+                *counts.entry(option.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Options used with `fs_type`, as a share of the configs that mount one, most common first
+pub fn option_usage(configs: &[StoredConfig], fs_type: &str) -> Vec<OptionUsage> {
+    let mut using: HashMap<String, usize> = HashMap::new();
+    let mut total = 0;
+
+    for config in configs {
+        let options = count_options(config.entries.iter().filter(|e| e.fs_type == fs_type));
+        if config.entries.iter().any(|e| e.fs_type == fs_type) {
+            total += 1;
+        }
+        for option in options.into_keys() {
+            *using.entry(option).or_insert(0) += 1;
+        }
+    }
+
+    let mut usage: Vec<OptionUsage> = using
+        .into_iter()
+        .map(|(option, configs)| OptionUsage {
+            option,
+            configs,
+            percent: (configs * 100 / total) as u8,
+        })
+        .collect();
+    usage.sort_by(|a, b| {
+        b.configs
+            .cmp(&a.configs)
+            .then_with(|| a.option.cmp(&b.option))
+    });
+    usage
+}
+
+/// Load the saved corpus, rebuilding it when the library has changed since it was saved
+pub fn load_or_build(dir: &Path, configs: &[StoredConfig]) -> Result<Corpus> {
+    let path = dir.join(CORPUS_FILE);
+    let config_ids: Vec<String> = configs.iter().map(|c| c.id.clone()).collect();
+
+    if let Ok(corpus) = Corpus::load(&path) {
+        if corpus.config_ids == config_ids {
+            return Ok(corpus);
+        }
+    }
+
+    let corpus = Corpus::build(configs)?;
+    if dir.exists() {
+        corpus.save(&path)?;
+    }
+    Ok(corpus)
+}
+
+/// Document ID for the entry at `index` in a stored config
+fn document_id(config_id: &str, index: usize) -> String {
+    format!("{}#{}", config_id, index)
+}
+
+/// Rank library entries by relevance to the query, best first
+pub fn search<'a>(
+    configs: &'a [StoredConfig],
+    corpus: &Corpus,
+    query: &str,
+    k: usize,
+) -> Vec<SearchHit<'a>> {
+    corpus
+        .search_text(query, k)
+        .into_iter()
+        .filter_map(|(doc, score)| {
+            let (config_id, index) = doc.id.rsplit_once('#')?;
+            let config = configs.iter().find(|c| c.id == config_id)?;
+            let entry = config.entries.get(index.parse::<usize>().ok()?)?;
+            Some(SearchHit {
+                config,
+                entry,
+                score,
+            })
+        })
+        .collect()
+}
+
 /// Corpus metadata structure implementing ACID properties
 /// for transactional corpus operations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorpusMetadata {
     pub corpus_id: uuid::Uuid,
     pub cardinality: usize,
@@ -27,15 +240,28 @@ pub struct Corpus {
     documents: Arc<RwLock<HashMap<String, Document>>>,
     metadata: CorpusMetadata,
     index: Arc<RwLock<indexer::CorpusIndex>>,
+    vectorizer: TfIdfVectorizer,
+    /// Stored configs the corpus was built from, to detect a stale save
+    config_ids: Vec<String>,
+}
+
+/// On-disk form of a corpus; the index is rebuilt on load
+#[derive(Serialize, Deserialize)]
+struct CorpusFile {
+    metadata: CorpusMetadata,
+    vectorizer: TfIdfVectorizer,
+    config_ids: Vec<String>,
+    documents: Vec<Document>,
 }
 
 /// Document representation with vectorized embeddings
 /// Utilizes TF-IDF weighting with cosine similarity metrics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Document {
     pub id: String,
     pub content: String,
-    pub vector: Vec<f64>,
+    /// TF-IDF weights of the terms in `content`
+    pub vector: SparseVector,
     pub timestamp: i64,
 }
 
@@ -56,7 +282,79 @@ impl Corpus {
                 compression_ratio: 1.0,
             },
             index: Arc::new(RwLock::new(indexer::CorpusIndex::new())),
+            vectorizer: TfIdfVectorizer::default(),
+            config_ids: Vec::new(),
+        }
+    }
+
+    /// Builds a corpus from the stored configs, one document per fstab entry
+    ///
+    /// # Complexity
+    /// - Time: O(n · m) where n is entry count, m is entry length
+    pub fn build(configs: &[StoredConfig]) -> Result<Self> {
+        let mut analyzer = analyzer::CorpusAnalyzer::new();
+        for entry in configs.iter().flat_map(|c| &c.entries) {
+            analyzer.add_document(&entry.text());
+        }
+        let vectorizer = TfIdfVectorizer::from_analyzer(&analyzer);
+
+        let mut corpus = Self::new(vectorizer.dimensionality());
+        corpus.metadata.entropy = analyzer.calculate_entropy();
+        for config in configs {
+            let timestamp = chrono::DateTime::parse_from_rfc3339(&config.timestamp)
+                .map(|t| t.timestamp())
+                .unwrap_or(0);
+            for (index, entry) in config.entries.iter().enumerate() {
+                let content = entry.text();
+                corpus.ingest(Document {
+                    id: document_id(&config.id, index),
+                    vector: vectorizer.vectorize(&content),
+                    content,
+                    timestamp,
+                });
+            }
+        }
+
+        corpus.vectorizer = vectorizer;
+        corpus.config_ids = configs.iter().map(|c| c.id.clone()).collect();
+        Ok(corpus)
+    }
+
+    pub fn metadata(&self) -> &CorpusMetadata {
+        &self.metadata
+    }
+
+    /// Writes the documents, vectors and IDF weights to `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut documents: Vec<Document> =
+            self.documents.read().unwrap().values().cloned().collect();
+        documents.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let file = CorpusFile {
+            metadata: self.metadata.clone(),
+            vectorizer: self.vectorizer.clone(),
+            config_ids: self.config_ids.clone(),
+            documents,
+        };
+        state::write_atomic(path, serde_json::to_string(&file)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Reads a corpus written by [`Corpus::save`] and re-indexes its documents
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let file: CorpusFile = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let mut corpus = Self::new(file.metadata.dimensionality);
+        for doc in file.documents {
+            corpus.ingest(doc);
         }
+        corpus.metadata = file.metadata;
+        corpus.vectorizer = file.vectorizer;
+        corpus.config_ids = file.config_ids;
+        Ok(corpus)
     }
 
     /// Ingests document into corpus with concurrent write support
@@ -64,43 +362,196 @@ impl Corpus {
     /// # Complexity
     /// - Time: O(log n + m) where n is corpus size, m is document length
     /// - Space: O(m)
-    pub fn ingest(&mut self, doc: Document) -> Result<(), CorpusError> {
+    pub fn ingest(&mut self, doc: Document) {
         let mut docs = self.documents.write().unwrap();
         let mut index = self.index.write().unwrap();
 
         docs.insert(doc.id.clone(), doc.clone());
-        index.index_document(&doc)?;
+        index.index_document(&doc);
 
         self.metadata.cardinality += 1;
-        Ok(())
     }
 
     /// Performs approximate nearest neighbor search using LSH
     ///
-    /// # Complexity
-    /// - Time: O(log n) expected case
-    /// - Space: O(k) where k is number of results
-    pub fn search(&self, query: &[f64], k: usize) -> Vec<Document> {
+    /// See [`indexer::CorpusIndex::ann_search`] for when it falls back to
+    /// scoring every document that shares a term with the query.
+    pub fn search(&self, query: &SparseVector, k: usize) -> Vec<(Document, f64)> {
         let index = self.index.read().unwrap();
         index.ann_search(query, k)
     }
-}
 
-#[derive(Debug)]
-pub enum CorpusError {
-    IndexingError(String),
-    DimensionalityMismatch,
-    ConcurrencyError,
+    /// Vectorizes a text query and searches for its k nearest documents
+    pub fn search_text(&self, query: &str, k: usize) -> Vec<(Document, f64)> {
+        self.search(&self.vectorizer.vectorize_query(query), k)
+    }
 }
 
-impl std::fmt::Display for CorpusError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            CorpusError::IndexingError(msg) => write!(f, "Indexing error: {}", msg),
-            CorpusError::DimensionalityMismatch => write!(f, "Dimensionality mismatch"),
-            CorpusError::ConcurrencyError => write!(f, "Concurrency error"),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(id: &str, lines: &[&str]) -> StoredConfig {
+        let entries: Vec<StoredEntry> = lines
+            .iter()
+            .map(|line| {
+                let f: Vec<&str> = line.split_whitespace().collect();
+                StoredEntry {
+                    device: f[0].to_string(),
+                    mount_point: f[1].to_string(),
+                    fs_type: f[2].to_string(),
+                    options: f[3].to_string(),
+                    dump: "0".to_string(),
+                    pass: "2".to_string(),
+                }
+            })
+            .collect();
+        StoredConfig {
+            id: id.to_string(),
+            source_file: format!("{}.fstab", id),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            entry_count: entries.len(),
+            entries,
         }
     }
-}
 
-impl std::error::Error for CorpusError {}
+    fn library() -> Vec<StoredConfig> {
+        vec![
+            stored("a", &["/dev/sdb1 /data ext4 defaults"]),
+            stored("b", &["nas:/export /mnt/nas nfs noatime,_netdev,soft"]),
+            stored("c", &["/dev/sdc1 /mnt/backup ext4 noatime"]),
+        ]
+    }
+
+    #[test]
+    fn test_search_ranks_by_relevance() {
+        let configs = library();
+        let corpus = Corpus::build(&configs).unwrap();
+
+        let hits = search(&configs, &corpus, "nfs noatime", DEFAULT_SEARCH_RESULTS);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].config.id, "b");
+        assert_eq!(hits[1].config.id, "c");
+        assert!(hits[0].score > hits[1].score);
+
+        assert!(search(&configs, &corpus, "btrfs", DEFAULT_SEARCH_RESULTS).is_empty());
+    }
+
+    #[test]
+    fn test_option_usage() {
+        let configs = vec![
+            stored(
+                "a",
+                &[
+                    "/dev/sdb1 /data ext4 defaults,noatime",
+                    "/dev/sdb2 /srv ext4 noatime",
+                ],
+            ),
+            stored("b", &["/dev/sdc1 /backup ext4 defaults"]),
+            stored("c", &["/dev/sdd1 /mnt/x xfs noatime"]),
+        ];
+
+        let usage = option_usage(&configs, "ext4");
+        assert_eq!(usage[0].option, "defaults");
+        assert_eq!(usage[0].percent, 100);
+        assert_eq!(
+            usage[1],
+            OptionUsage {
+                option: "noatime".to_string(),
+                configs: 1,
+                percent: 50,
+            }
+        );
+        assert!(option_usage(&configs, "btrfs").is_empty());
+    }
+
+    #[test]
+    fn test_remove_config_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        for config in [
+            stored("abc123", &["/dev/sdb1 /data ext4 defaults"]),
+            stored("abd456", &["/dev/sdc1 /srv ext4 defaults"]),
+        ] {
+            fs::write(
+                dir.path().join(format!("{}.json", config.id)),
+                serde_json::to_string(&config).unwrap(),
+            )
+            .unwrap();
+        }
+
+        assert!(remove_config(dir.path(), "ab").is_err());
+        assert!(remove_config(dir.path(), "zzz").is_err());
+        assert_eq!(load_configs(dir.path()).unwrap().len(), 2);
+
+        assert_eq!(remove_config(dir.path(), "abc").unwrap().id, "abc123");
+        let remaining = load_configs(dir.path()).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, "abd456");
+    }
+
+    #[test]
+    fn test_search_recall() {
+        // Every entry should be found by searching for its own contents
+        let mut configs = Vec::new();
+        for i in 0..60 {
+            let line = format!("/dev/disk{i} /srv/vol{i} ext4 defaults,data{i}");
+            configs.push(stored(&format!("cfg{i:02}"), &[line.as_str()]));
+        }
+        let corpus = Corpus::build(&configs).unwrap();
+
+        for config in &configs {
+            let hits = search(&configs, &corpus, &config.entries[0].text(), 5);
+            assert_eq!(hits[0].config.id, config.id);
+        }
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CORPUS_FILE);
+        let configs = library();
+
+        let corpus = Corpus::build(&configs).unwrap();
+        corpus.save(&path).unwrap();
+        let loaded = Corpus::load(&path).unwrap();
+
+        assert_eq!(loaded.metadata().cardinality, 3);
+        assert_eq!(loaded.config_ids, corpus.config_ids);
+        let documents = loaded.documents.read().unwrap();
+        assert!(documents.contains_key("b#0"));
+        assert!(!documents.contains_key("b#1"));
+        drop(documents);
+        assert_eq!(
+            loaded.search_text("nfs", 5)[0].0,
+            corpus.search_text("nfs", 5)[0].0
+        );
+
+        // Only each entry's own terms are stored, not the whole vocabulary
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let indices = saved["documents"][0]["vector"]["indices"]
+            .as_array()
+            .unwrap();
+        assert_eq!(indices.len(), 5);
+        assert!(indices.len() < corpus.metadata().dimensionality);
+    }
+
+    #[test]
+    fn test_saved_corpus_rebuilt_when_library_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut configs = vec![stored("a", &["/dev/sdb1 /data ext4 defaults"])];
+
+        load_or_build(dir.path(), &configs).unwrap();
+        assert!(dir.path().join(CORPUS_FILE).exists());
+
+        configs.push(stored("b", &["/dev/sdc1 /backup xfs noatime"]));
+        let rebuilt = load_or_build(dir.path(), &configs).unwrap();
+        assert_eq!(rebuilt.metadata().cardinality, 2);
+        assert_eq!(
+            Corpus::load(&dir.path().join(CORPUS_FILE))
+                .unwrap()
+                .config_ids,
+            ["a", "b"]
+        );
+    }
+}
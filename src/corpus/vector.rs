@@ -1,94 +1,70 @@
 /// Vector Space Model Implementation
 ///
-/// Provides dense and sparse vector representations with optimized
-/// linear algebra operations utilizing SIMD when available.
-use std::ops::{Add, Mul};
+/// Sparse term-weight vectors with the dot product and cosine similarity
+/// used for ranking.
+use serde::{Deserialize, Serialize};
 
-/// Dense vector with contiguous memory layout for cache efficiency
-#[derive(Debug, Clone)]
-pub struct DenseVector {
-    data: Vec<f64>,
-    dimension: usize,
-}
-
-/// Sparse vector using Coordinate List (COO) format
+/// Sparse vector using Coordinate List (COO) format, indices ascending
 /// Memory: O(nnz) where nnz is number of non-zero elements
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SparseVector {
     indices: Vec<usize>,
     values: Vec<f64>,
     dimension: usize,
 }
 
-impl DenseVector {
-    /// Creates zero vector with specified dimension
-    pub fn zeros(dimension: usize) -> Self {
+impl SparseVector {
+    /// Creates sparse vector from indices and values
+    pub fn new(indices: Vec<usize>, values: Vec<f64>, dimension: usize) -> Self {
+        assert_eq!(indices.len(), values.len());
         Self {
-            data: vec![0.0; dimension],
+            indices,
+            values,
             dimension,
         }
     }
 
-    /// Computes L2 norm: ||v|| = √(Σvᵢ²)
-    ///
-    /// # Complexity
-    /// - Time: O(d)
-    /// - Space: O(1)
-    pub fn l2_norm(&self) -> f64 {
-        self.data.iter().map(|&x| x * x).sum::<f64>().sqrt()
+    /// Builds a vector from (index, value) pairs in ascending index order,
+    /// dropping zeros
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (usize, f64)>, dimension: usize) -> Self {
+        let (indices, values) = pairs.into_iter().filter(|&(_, value)| value != 0.0).unzip();
+        Self::new(indices, values, dimension)
     }
 
-    /// Computes L1 norm: ||v|| = Σ|vᵢ|
-    pub fn l1_norm(&self) -> f64 {
-        self.data.iter().map(|&x| x.abs()).sum()
+    /// Value at `index`, zero when it isn't stored
+    #[cfg(test)]
+    pub fn get(&self, index: usize) -> f64 {
+        self.indices
+            .binary_search(&index)
+            .map_or(0.0, |position| self.values[position])
     }
 
-    /// Normalizes vector to unit length
-    /// v̂ = v / ||v||
-    pub fn normalize(&mut self) {
-        let norm = self.l2_norm();
-        if norm > 0.0 {
-            for x in &mut self.data {
-                *x /= norm;
-            }
-        }
+    /// Indices of the non-zero elements
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
     }
 
-    /// Computes dot product with SIMD optimization
-    /// a · b = Σ aᵢbᵢ
-    pub fn dot(&self, other: &DenseVector) -> f64 {
-        assert_eq!(self.dimension, other.dimension);
-
-        self.data
+    /// (index, value) pairs of the non-zero elements
+    pub fn iter(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.indices
             .iter()
-            .zip(other.data.iter())
-            .map(|(&a, &b)| a * b)
-            .sum()
+            .copied()
+            .zip(self.values.iter().copied())
     }
-}
 
-impl SparseVector {
-    /// Creates sparse vector from indices and values
-    pub fn new(indices: Vec<usize>, values: Vec<f64>, dimension: usize) -> Self {
-        assert_eq!(indices.len(), values.len());
-        Self {
-            indices,
-            values,
-            dimension,
-        }
+    /// Computes L2 norm over the stored values
+    pub fn l2_norm(&self) -> f64 {
+        self.values.iter().map(|&x| x * x).sum::<f64>().sqrt()
     }
 
-    /// Converts to dense representation
-    /// Complexity: O(d + nnz)
-    pub fn to_dense(&self) -> DenseVector {
-        let mut data = vec![0.0; self.dimension];
-        for (&idx, &val) in self.indices.iter().zip(self.values.iter()) {
-            data[idx] = val;
-        }
-        DenseVector {
-            data,
-            dimension: self.dimension,
+    /// Cosine similarity: cos(θ) = (A·B) / (||A|| ||B||), zero for a zero vector
+    /// Complexity: O(nnz₁ + nnz₂)
+    pub fn cosine(&self, other: &SparseVector) -> f64 {
+        let norms = self.l2_norm() * other.l2_norm();
+        if norms == 0.0 {
+            return 0.0;
         }
+        self.dot(other) / norms
     }
 
     /// Sparse dot product
@@ -113,67 +89,3 @@ impl SparseVector {
         result
     }
 }
-
-impl Add for DenseVector {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        assert_eq!(self.dimension, other.dimension);
-        let data = self
-            .data
-            .iter()
-            .zip(other.data.iter())
-            .map(|(&a, &b)| a + b)
-            .collect();
-        Self {
-            data,
-            dimension: self.dimension,
-        }
-    }
-}
-
-impl Mul<f64> for DenseVector {
-    type Output = Self;
-
-    fn mul(self, scalar: f64) -> Self {
-        let data = self.data.iter().map(|&x| x * scalar).collect();
-        Self {
-            data,
-            dimension: self.dimension,
-        }
-    }
-}
-
-/// Matrix operations for dimensionality reduction
-pub struct Matrix {
-    data: Vec<Vec<f64>>,
-    rows: usize,
-    cols: usize,
-}
-
-impl Matrix {
-    /// Performs Singular Value Decomposition (SVD)
-    /// A = UΣVᵀ
-    ///
-    /// Used for Latent Semantic Analysis (LSA)
-    /// Complexity: O(min(m²n, mn²))
-    pub fn svd(&self) -> (Matrix, Vec<f64>, Matrix) {
-        // Placeholder for SVD implementation
-        unimplemented!("SVD requires LAPACK bindings")
-    }
-
-    /// Matrix-vector multiplication
-    /// y = Ax
-    /// Complexity: O(mn)
-    pub fn matvec(&self, x: &DenseVector) -> DenseVector {
-        assert_eq!(self.cols, x.dimension);
-
-        let mut result = DenseVector::zeros(self.rows);
-        for i in 0..self.rows {
-            for j in 0..self.cols {
-                result.data[i] += self.data[i][j] * x.data[j];
-            }
-        }
-        result
-    }
-}
@@ -1,21 +1,394 @@
 use anyhow::{Context, Result};
 use colored::*;
+use serde::Serialize;
 use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::Path;
 
-/// Display a colored diff between two files
-pub fn diff_files(path1: &str, path2: &str) -> Result<()> {
-    let content1 = fs::read_to_string(path1)
+use crate::fstab::{self, FstabEntry};
+
+/// How `catdog diff` presents differences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffMode {
+    /// Colored line diff with line numbers
+    Pretty,
+    /// Entry-level comparison that ignores ordering and option order
+    Semantic,
+    /// Standard patch format with the given number of context lines
+    Unified { context: usize },
+    /// Old and new files in two columns fitted to the given total width
+    SideBySide { width: usize },
+}
+
+/// Context lines in unified output unless `-U` says otherwise
+pub const DEFAULT_UNIFIED_CONTEXT: usize = 3;
+
+/// Side-by-side width when the terminal size can't be detected
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Narrowest column side-by-side output will use, however small the width
+const MIN_COLUMN_WIDTH: usize = 10;
+
+/// Width of the current terminal, or [`DEFAULT_WIDTH`] when it isn't one
+pub fn terminal_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(w), _)| w as usize)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Display a diff between two files
+///
+/// With `ignore_comments`, comment and blank lines are dropped from both
+/// sides first so only entry changes show up.
+pub fn diff_files(path1: &str, path2: &str, ignore_comments: bool, mode: DiffMode) -> Result<()> {
+    let mut content1 = fs::read_to_string(path1)
         .with_context(|| format!("Failed to read first file: {}", path1))?;
-    let content2 = fs::read_to_string(path2)
+    let mut content2 = fs::read_to_string(path2)
         .with_context(|| format!("Failed to read second file: {}", path2))?;
 
-    display_diff(&content1, &content2, path1, path2);
+    if ignore_comments {
+        content1 = strip_comments(&content1);
+        content2 = strip_comments(&content2);
+    }
+
+    match mode {
+        DiffMode::Pretty => display_diff(&content1, &content2, path1, path2),
+        DiffMode::Semantic => {
+            let old = fstab::parse_entries(&content1).entries;
+            let new = fstab::parse_entries(&content2).entries;
+            display_semantic_diff(&semantic_diff(&old, &new), path1, path2);
+        }
+        DiffMode::Unified { context } => {
+            display_unified_diff(&unified_diff(&content1, &content2, path1, path2, context))
+        }
+        DiffMode::SideBySide { width } => {
+            display_side_by_side(&content1, &content2, path1, path2, width)
+        }
+    }
 
     Ok(())
 }
 
+/// Render a unified diff (`--- a`, `+++ b`, `@@ -l,s +l,s @@` hunks); empty when nothing changed
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(context)
+        .header(old_label, new_label)
+        .to_string()
+}
+
+/// Print a unified diff, coloring it unless color is turned off
+fn display_unified_diff(patch: &str) {
+    for line in patch.lines() {
+        let styled = if line.starts_with("---") || line.starts_with("+++") {
+            line.bold()
+        } else if line.starts_with("@@") {
+            line.cyan()
+        } else if line.starts_with('+') {
+            line.green()
+        } else if line.starts_with('-') {
+            line.red()
+        } else {
+            line.normal()
+        };
+        println!("{}", styled);
+    }
+}
+
+/// One printed row of a side-by-side diff
+#[derive(Debug, Clone, PartialEq)]
+pub struct SideBySideRow {
+    pub left: String,
+    /// ` ` unchanged, `|` changed, `<` only on the left, `>` only on the right
+    pub marker: char,
+    pub right: String,
+}
+
+/// Pair up old and new lines following the diff's change ops, wrapping each
+/// side to `column` characters
+pub fn side_by_side_rows(old: &str, new: &str, column: usize) -> Vec<SideBySideRow> {
+    let diff = TextDiff::from_lines(old, new);
+    let old_lines: Vec<String> = old.lines().map(expand_tabs).collect();
+    let new_lines: Vec<String> = new.lines().map(expand_tabs).collect();
+    let mut rows = Vec::new();
+
+    for op in diff.ops() {
+        let old_range = op.old_range();
+        let new_range = op.new_range();
+        let pairs = old_range.len().max(new_range.len());
+
+        for i in 0..pairs {
+            let left = old_lines
+                .get(old_range.start + i)
+                .filter(|_| i < old_range.len());
+            let right = new_lines
+                .get(new_range.start + i)
+                .filter(|_| i < new_range.len());
+            let marker = match (left, right) {
+                (Some(_), Some(_)) if op.tag() == similar::DiffTag::Equal => ' ',
+                (Some(_), Some(_)) => '|',
+                (Some(_), None) => '<',
+                _ => '>',
+            };
+
+            let left = wrap(left.map_or("", String::as_str), column);
+            let right = wrap(right.map_or("", String::as_str), column);
+            for line in 0..left.len().max(right.len()) {
+                rows.push(SideBySideRow {
+                    left: left.get(line).cloned().unwrap_or_default(),
+                    marker: if line == 0 { marker } else { ' ' },
+                    right: right.get(line).cloned().unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    rows
+}
+
+/// Tabs are common in fstab and would throw the columns out of line
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::new();
+    for c in line.chars() {
+        if c == '\t' {
+            let pad = 8 - expanded.chars().count() % 8;
+            expanded.push_str(&" ".repeat(pad));
+        } else {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+/// Split a line into chunks of at most `width` characters
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(width).map(|c| c.iter().collect()).collect()
+}
+
+/// Display old and new files in two columns
+fn display_side_by_side(old: &str, new: &str, old_label: &str, new_label: &str, width: usize) {
+    // Each row is `left marker right` with a space either side of the marker
+    let column = (width.saturating_sub(3) / 2).max(MIN_COLUMN_WIDTH);
+    let truncate = |label: &str| -> String { label.chars().take(column).collect() };
+
+    println!(
+        "{:<column$}   {}",
+        truncate(old_label).cyan().bold(),
+        truncate(new_label).cyan().bold()
+    );
+    println!("{}", "=".repeat(column * 2 + 3).bright_black());
+
+    for row in side_by_side_rows(old, new, column) {
+        let left = format!("{:<column$}", row.left);
+        let (left, marker, right) = match row.marker {
+            '|' => (left.yellow(), "|".yellow().bold(), row.right.yellow()),
+            '<' => (left.red(), "<".red().bold(), row.right.normal()),
+            '>' => (left.normal(), ">".green().bold(), row.right.green()),
+            _ => (left.normal(), " ".normal(), row.right.normal()),
+        };
+        println!("{} {} {}", left, marker, right);
+    }
+}
+
+/// A difference in one field of an fstab entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldChange {
+    Changed {
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    OptionAdded(String),
+    OptionRemoved(String),
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FieldChange::Changed { field, old, new } => {
+                write!(f, "{} changed '{}' -> '{}'", field, old, new)
+            }
+            FieldChange::OptionAdded(option) => write!(f, "option added '{}'", option),
+            FieldChange::OptionRemoved(option) => write!(f, "option removed '{}'", option),
+        }
+    }
+}
+
+/// How an fstab entry differs between two files
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryChange {
+    Added(FstabEntry),
+    Removed(FstabEntry),
+    Modified {
+        key: String,
+        changes: Vec<FieldChange>,
+    },
+}
+
+/// What identifies an entry across files: its mount point, or its device for
+/// swap and other entries without one
+fn entry_key(entry: &FstabEntry) -> String {
+    let mount_point = entry.decoded_mount_point();
+    if mount_point == "none" || mount_point == "swap" {
+        entry.decoded_device()
+    } else {
+        mount_point
+    }
+}
+
+/// Compare two sets of fstab entries by mount point, field by field
+///
+/// Entry order doesn't matter, and options are compared as an unordered set.
+pub fn semantic_diff(old: &[FstabEntry], new: &[FstabEntry]) -> Vec<EntryChange> {
+    let new_by_key: HashMap<String, &FstabEntry> = new.iter().map(|e| (entry_key(e), e)).collect();
+    let old_by_key: HashMap<String, &FstabEntry> = old.iter().map(|e| (entry_key(e), e)).collect();
+    let mut changes = Vec::new();
+
+    for old_entry in old {
+        let key = entry_key(old_entry);
+        let Some(new_entry) = new_by_key.get(&key) else {
+            changes.push(EntryChange::Removed(old_entry.clone()));
+            continue;
+        };
+
+        let mut field_changes = Vec::new();
+        for (field, old_value, new_value) in [
+            ("device", &old_entry.device, &new_entry.device),
+            (
+                "mount point",
+                &old_entry.mount_point,
+                &new_entry.mount_point,
+            ),
+            ("type", &old_entry.fs_type, &new_entry.fs_type),
+            ("dump", &old_entry.dump, &new_entry.dump),
+            ("pass", &old_entry.pass, &new_entry.pass),
+        ] {
+            if old_value != new_value {
+                field_changes.push(FieldChange::Changed {
+                    field,
+                    old: old_value.clone(),
+                    new: new_value.clone(),
+                });
+            }
+        }
+
+        let old_options: BTreeSet<String> = fstab::split_options(&old_entry.options)
+            .into_iter()
+            .collect();
+        let new_options: BTreeSet<String> = fstab::split_options(&new_entry.options)
+            .into_iter()
+            .collect();
+        field_changes.extend(
+            new_options
+                .difference(&old_options)
+                .map(|o| FieldChange::OptionAdded(o.clone())),
+        );
+        field_changes.extend(
+            old_options
+                .difference(&new_options)
+                .map(|o| FieldChange::OptionRemoved(o.clone())),
+        );
+
+        if !field_changes.is_empty() {
+            changes.push(EntryChange::Modified {
+                key,
+                changes: field_changes,
+            });
+        }
+    }
+
+    for new_entry in new {
+        if !old_by_key.contains_key(&entry_key(new_entry)) {
+            changes.push(EntryChange::Added(new_entry.clone()));
+        }
+    }
+
+    changes
+}
+
+/// Display entry-level changes between two fstab files
+pub fn display_semantic_diff(changes: &[EntryChange], old_label: &str, new_label: &str) {
+    println!(
+        "{} {}",
+        "Comparing entries:".cyan().bold(),
+        format!("{} <-> {}", old_label, new_label).bright_white()
+    );
+    println!("{}", "=".repeat(80).bright_black());
+
+    let (mut added, mut removed, mut modified) = (0, 0, 0);
+    for change in changes {
+        match change {
+            EntryChange::Added(entry) => {
+                added += 1;
+                println!(
+                    "{} entry for {}: {}",
+                    "+".green().bold(),
+                    entry_key(entry).bright_white(),
+                    entry.fields().join(" ").green()
+                );
+            }
+            EntryChange::Removed(entry) => {
+                removed += 1;
+                println!(
+                    "{} entry for {}: {}",
+                    "-".red().bold(),
+                    entry_key(entry).bright_white(),
+                    entry.fields().join(" ").red()
+                );
+            }
+            EntryChange::Modified { key, changes } => {
+                modified += 1;
+                for field_change in changes {
+                    println!(
+                        "{} entry for {}: {}",
+                        "~".yellow().bold(),
+                        key.bright_white(),
+                        field_change.to_string().yellow()
+                    );
+                }
+            }
+        }
+    }
+
+    println!("{}", "=".repeat(80).bright_black());
+
+    if changes.is_empty() {
+        println!("{} No differences found", "✓".green().bold());
+    } else {
+        println!(
+            "\n{} {} added, {} removed, {} modified",
+            "Summary:".cyan().bold(),
+            added.to_string().green(),
+            removed.to_string().red(),
+            modified.to_string().yellow()
+        );
+    }
+}
+
+/// Remove comment (`#`) and blank lines
+fn strip_comments(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            !trimmed.is_empty() && !trimmed.starts_with('#')
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
 /// Display a colored diff between two strings
 pub fn display_diff(old: &str, new: &str, old_label: &str, new_label: &str) {
     let diff = TextDiff::from_lines(old, new);
@@ -70,9 +443,15 @@ pub fn display_diff(old: &str, new: &str, old_label: &str, new_label: &str) {
     }
 }
 
-struct DiffStats {
-    additions: usize,
-    deletions: usize,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffStats {
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Count added and removed lines between two strings
+pub fn line_stats(old: &str, new: &str) -> DiffStats {
+    diff_stats(&TextDiff::from_lines(old, new))
 }
 
 fn diff_stats<'a>(diff: &TextDiff<'a, 'a, 'a, str>) -> DiffStats {
@@ -94,14 +473,14 @@ fn diff_stats<'a>(diff: &TextDiff<'a, 'a, 'a, str>) -> DiffStats {
 }
 
 /// Compare the current fstab with a backup or other file
-pub fn compare_with_current(other_file: &str) -> Result<()> {
+pub fn compare_with_current(other_file: &str, ignore_comments: bool, mode: DiffMode) -> Result<()> {
     let fstab_path = "/etc/fstab";
 
     if !Path::new(fstab_path).exists() {
         anyhow::bail!("/etc/fstab does not exist on this system");
     }
 
-    diff_files(fstab_path, other_file)
+    diff_files(fstab_path, other_file, ignore_comments, mode)
 }
 
 #[cfg(test)]
@@ -137,6 +516,111 @@ mod tests {
         assert_eq!(stats.deletions, 1);
     }
 
+    #[test]
+    fn test_diff_ignore_comments() {
+        let old = "# Generated at: 2024-01-01 10:00:00 UTC\n#\n/dev/sda1 / ext4 defaults 0 1\n";
+        let new = "# Generated at: 2024-06-30 18:45:12 UTC\n\n/dev/sda1 / ext4 defaults 0 1\n";
+
+        let diff = TextDiff::from_lines(old, new);
+        assert!(diff_stats(&diff).additions > 0);
+
+        let (old, new) = (strip_comments(old), strip_comments(new));
+        let diff = TextDiff::from_lines(&old, &new);
+        let stats = diff_stats(&diff);
+        assert_eq!(stats.additions, 0);
+        assert_eq!(stats.deletions, 0);
+    }
+
+    #[test]
+    fn test_line_stats_against_missing_file() {
+        let backup = "/dev/sda1 / ext4 defaults 0 1\n/dev/sda2 /home ext4 defaults 0 2\n";
+        assert_eq!(
+            line_stats("", backup),
+            DiffStats {
+                additions: 2,
+                deletions: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_semantic_diff() {
+        let old = fstab::parse_entries(
+            "/dev/sda1 / ext4 rw,noatime 0 1\n\
+             /dev/sda2 /home ext4 defaults 0 2\n\
+             /dev/sda3 none swap sw 0 0\n\
+             /dev/sdb1 /old xfs defaults 0 2\n",
+        )
+        .entries;
+        // Reordered, options shuffled, one option added, one entry swapped out
+        let new = fstab::parse_entries(
+            "/dev/sda3 none swap sw 0 0\n\
+             /dev/sda2 /home ext4 defaults,noatime 0 2\n\
+             /dev/sda1 / ext4 noatime,rw 0 1\n\
+             /dev/sdc1 /data ext4 defaults 0 2\n",
+        )
+        .entries;
+
+        let changes = semantic_diff(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes[0],
+            EntryChange::Modified {
+                key: "/home".to_string(),
+                changes: vec![FieldChange::OptionAdded("noatime".to_string())],
+            }
+        );
+        assert!(matches!(&changes[1], EntryChange::Removed(e) if e.mount_point == "/old"));
+        assert!(matches!(&changes[2], EntryChange::Added(e) if e.mount_point == "/data"));
+        assert_eq!(
+            FieldChange::OptionAdded("noatime".to_string()).to_string(),
+            "option added 'noatime'"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\n";
+
+        assert_eq!(
+            unified_diff(old, new, "old", "new", 1),
+            "--- old\n+++ new\n@@ -3,3 +3,3 @@\n c\n-d\n+D\n e\n"
+        );
+        assert!(
+            unified_diff(old, new, "old", "new", DEFAULT_UNIFIED_CONTEXT)
+                .contains("@@ -1,7 +1,7 @@")
+        );
+        assert_eq!(unified_diff(old, old, "old", "new", 3), "");
+    }
+
+    #[test]
+    fn test_side_by_side_rows() {
+        let old = "same\nold line\nremoved\n";
+        let new = "same\nnew line\n";
+        let rows = side_by_side_rows(old, new, 20);
+        let markers: Vec<char> = rows.iter().map(|r| r.marker).collect();
+
+        assert_eq!(markers, vec![' ', '|', '<']);
+        assert_eq!(rows[1].left, "old line");
+        assert_eq!(rows[1].right, "new line");
+        assert_eq!(rows[2].right, "");
+
+        let rows = side_by_side_rows("", "a\tb\n", 20);
+        assert_eq!(rows[0].marker, '>');
+        assert_eq!(rows[0].right, "a       b");
+    }
+
+    #[test]
+    fn test_side_by_side_wraps_long_lines() {
+        let rows = side_by_side_rows("", "UUID=0123456789abcdef /data\n", 10);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].right, "UUID=01234");
+        assert_eq!(rows[0].marker, '>');
+        assert_eq!(rows[1].marker, ' ');
+        assert_eq!(rows[2].right, "f /data");
+    }
+
     #[test]
     fn test_diff_changes() {
         let old = "line1\nold line\nline3\n";
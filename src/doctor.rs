@@ -0,0 +1,626 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::backup;
+use crate::config::Config;
+use crate::package;
+use crate::service::{self, ServiceInfo, ServiceManager, ServiceStatus};
+use crate::state;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn label(&self) -> ColoredString {
+        match self {
+            CheckStatus::Pass => "PASS".green().bold(),
+            CheckStatus::Warn => "WARN".yellow().bold(),
+            CheckStatus::Fail => "FAIL".red().bold(),
+        }
+    }
+}
+
+/// Outcome of a single doctor check
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub summary: String,
+    pub details: Vec<String>,
+}
+
+impl DoctorCheck {
+    fn new(name: &str, status: CheckStatus, summary: String, details: Vec<String>) -> Self {
+        DoctorCheck {
+            name: name.to_string(),
+            status,
+            summary,
+            details,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Whether a tool that core commands can't work without is missing
+    ///
+    /// Only this makes `doctor` exit non-zero; other failures are reported
+    /// but describe the system rather than catdog's ability to run.
+    pub fn missing_required_tool(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|c| c.name == TOOLS_CHECK && c.status == CheckStatus::Fail)
+    }
+
+    /// Worst status across all checks
+    pub fn overall(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass)
+    }
+
+    pub fn display(&self) {
+        for check in &self.checks {
+            println!(
+                "  [{}] {:<28} {}",
+                check.status.label(),
+                check.name.bright_white(),
+                check.summary
+            );
+            for detail in &check.details {
+                println!("         {} {}", "•".bright_black(), detail);
+            }
+        }
+
+        println!();
+        match self.overall() {
+            CheckStatus::Pass => println!("{} System looks healthy", "✅".green()),
+            CheckStatus::Warn => println!("{} System is degraded", "⚠️ ".yellow()),
+            CheckStatus::Fail => println!("{} System has failures", "❌".red()),
+        }
+    }
+}
+
+/// An external program catdog shells out to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExternalTool {
+    pub command: &'static str,
+    /// Core commands can't work without it, rather than just one feature
+    pub required: bool,
+    pub enables: &'static str,
+}
+
+const fn tool(command: &'static str, required: bool, enables: &'static str) -> ExternalTool {
+    ExternalTool {
+        command,
+        required,
+        enables,
+    }
+}
+
+/// The external programs catdog uses on `os`, as named by `std::env::consts::OS`
+pub fn platform_tools(os: &str) -> Vec<ExternalTool> {
+    if os == "windows" {
+        // Discovery and system info go through PowerShell's CIM cmdlets
+        return vec![
+            tool("powershell", true, "discover, suggest, generate, info"),
+            tool("smartctl", false, "disk-health and SMART checks in monitor"),
+        ];
+    }
+
+    let mut tools = match os {
+        "linux" => vec![
+            tool("lsblk", true, "discover, suggest, generate"),
+            tool(
+                "systemctl",
+                false,
+                "service commands and doctor unit checks (systemd)",
+            ),
+        ],
+        "macos" => vec![
+            tool("diskutil", true, "discover, suggest, generate"),
+            tool("launchctl", false, "service commands (launchd)"),
+            tool("sw_vers", false, "OS version in info"),
+        ],
+        _ => Vec::new(),
+    };
+    tools.extend([
+        tool("df", true, "monitor, check, disk usage in info"),
+        tool("mount", true, "mount, unmount, list"),
+        tool("smartctl", false, "disk-health and SMART checks in monitor"),
+        tool(
+            "sudo",
+            false,
+            "package and service changes as a regular user",
+        ),
+    ]);
+    tools
+}
+
+const TOOLS_CHECK: &str = "External tools";
+
+/// Report which external tools are present; a missing required one fails the check
+pub fn check_tools(tools: &[ExternalTool], available: impl Fn(&str) -> bool) -> DoctorCheck {
+    let mut missing_required = 0;
+    let mut missing_optional = 0;
+    let details = tools
+        .iter()
+        .map(|tool| {
+            let state = if available(tool.command) {
+                "found"
+            } else if tool.required {
+                missing_required += 1;
+                "missing (required)"
+            } else {
+                missing_optional += 1;
+                "missing (optional)"
+            };
+            format!("{}: {} ({})", tool.command, state, tool.enables)
+        })
+        .collect();
+
+    let (status, summary) = if missing_required > 0 {
+        (
+            CheckStatus::Fail,
+            format!("{} required tool(s) missing", missing_required),
+        )
+    } else if missing_optional > 0 {
+        (
+            CheckStatus::Warn,
+            format!("{} optional tool(s) missing", missing_optional),
+        )
+    } else {
+        (CheckStatus::Pass, "All tools found".to_string())
+    };
+    DoctorCheck::new(TOOLS_CHECK, status, summary, details)
+}
+
+/// Package commands need at least one package manager
+pub fn check_package_managers(found: &[package::PackageManager]) -> DoctorCheck {
+    if found.is_empty() {
+        return DoctorCheck::new(
+            "Package managers",
+            CheckStatus::Warn,
+            "None found; pkg commands are unavailable".to_string(),
+            Vec::new(),
+        );
+    }
+    let names: Vec<&str> = found.iter().map(|pm| pm.name()).collect();
+    DoctorCheck::new(
+        "Package managers",
+        CheckStatus::Pass,
+        names.join(", "),
+        Vec::new(),
+    )
+}
+
+pub fn check_fstab(path: &Path) -> DoctorCheck {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            let entries = contents
+                .lines()
+                .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+                .count();
+            DoctorCheck::new(
+                "fstab",
+                CheckStatus::Pass,
+                format!("{} is readable ({} entries)", path.display(), entries),
+                Vec::new(),
+            )
+        }
+        Err(e) => DoctorCheck::new(
+            "fstab",
+            CheckStatus::Fail,
+            format!("Can't read {}", path.display()),
+            vec![e.to_string()],
+        ),
+    }
+}
+
+/// Whether the config file loads; `config_path` is shown to the user
+pub fn check_config(loaded: &Result<Config>, config_path: &str) -> DoctorCheck {
+    match loaded {
+        Ok(_) => DoctorCheck::new(
+            "Configuration",
+            CheckStatus::Pass,
+            format!("{} parses", config_path),
+            Vec::new(),
+        ),
+        Err(e) => DoctorCheck::new(
+            "Configuration",
+            CheckStatus::Fail,
+            format!("{} doesn't load", config_path),
+            vec![
+                format!("{:#}", e),
+                "Fix it with: catdog config edit".to_string(),
+            ],
+        ),
+    }
+}
+
+/// Create each directory if needed and prove a file can be written in it
+pub fn check_writable_dirs(dirs: &[PathBuf]) -> DoctorCheck {
+    let failures: Vec<String> = dirs
+        .iter()
+        .filter_map(|dir| {
+            let probe = dir.join(".catdog-doctor");
+            fs::create_dir_all(dir)
+                .and_then(|_| fs::write(&probe, b""))
+                .and_then(|_| fs::remove_file(&probe))
+                .err()
+                .map(|e| format!("{}: {}", dir.display(), e))
+        })
+        .collect();
+
+    if failures.is_empty() {
+        let names: Vec<String> = dirs.iter().map(|d| d.display().to_string()).collect();
+        DoctorCheck::new(
+            "State directories",
+            CheckStatus::Pass,
+            format!("Writable: {}", names.join(", ")),
+            Vec::new(),
+        )
+    } else {
+        DoctorCheck::new(
+            "State directories",
+            CheckStatus::Fail,
+            format!(
+                "{} of {} directories not writable",
+                failures.len(),
+                dirs.len()
+            ),
+            failures,
+        )
+    }
+}
+
+/// Flag any units the service manager reports as failed
+pub fn check_failed_units(services: &[ServiceInfo]) -> DoctorCheck {
+    let failed: Vec<String> = services
+        .iter()
+        .filter(|s| s.status == ServiceStatus::Failed)
+        .map(|s| s.name.clone())
+        .collect();
+
+    if failed.is_empty() {
+        DoctorCheck::new(
+            "Failed units",
+            CheckStatus::Pass,
+            "No failed units".to_string(),
+            Vec::new(),
+        )
+    } else {
+        let details = failed
+            .iter()
+            .map(|name| format!("{} (inspect with: catdog service status {})", name, name))
+            .collect();
+        DoctorCheck::new(
+            "Failed units",
+            CheckStatus::Fail,
+            format!("{} unit(s) failed", failed.len()),
+            details,
+        )
+    }
+}
+
+/// Flag services that are enabled at boot but not currently running
+///
+/// Services in `idle` (oneshot, or started by a timer or socket) aren't
+/// expected to be running and are left out.
+pub fn check_enabled_not_running(
+    services: &[ServiceInfo],
+    enabled: &[String],
+    idle: &HashSet<String>,
+) -> DoctorCheck {
+    let stopped: Vec<String> = enabled
+        .iter()
+        .filter(|name| !idle.contains(*name))
+        .filter(|name| {
+            services
+                .iter()
+                .find(|s| &s.name == *name)
+                .is_none_or(|s| s.status != ServiceStatus::Running)
+        })
+        .cloned()
+        .collect();
+
+    if stopped.is_empty() {
+        DoctorCheck::new(
+            "Enabled services",
+            CheckStatus::Pass,
+            "All enabled services are running".to_string(),
+            Vec::new(),
+        )
+    } else {
+        let details = stopped
+            .iter()
+            .map(|name| format!("{} is enabled but not running", name))
+            .collect();
+        DoctorCheck::new(
+            "Enabled services",
+            CheckStatus::Warn,
+            format!("{} enabled service(s) not running", stopped.len()),
+            details,
+        )
+    }
+}
+
+/// Flag zombie (defunct) processes, given as (pid, command) pairs
+pub fn check_zombies(zombies: &[(u32, String)]) -> DoctorCheck {
+    if zombies.is_empty() {
+        return DoctorCheck::new(
+            "Zombie processes",
+            CheckStatus::Pass,
+            "No zombie processes".to_string(),
+            Vec::new(),
+        );
+    }
+
+    let details = zombies
+        .iter()
+        .map(|(pid, comm)| format!("pid {} ({}) - its parent has not reaped it", pid, comm))
+        .collect();
+    DoctorCheck::new(
+        "Zombie processes",
+        CheckStatus::Warn,
+        format!("{} zombie process(es)", zombies.len()),
+        details,
+    )
+}
+
+/// Find zombie processes via `/proc`, falling back to `ps` elsewhere
+pub fn find_zombies() -> Vec<(u32, String)> {
+    if let Ok(entries) = fs::read_dir("/proc") {
+        let mut zombies = Vec::new();
+        for entry in entries.flatten() {
+            let pid = match entry.file_name().to_string_lossy().parse::<u32>() {
+                Ok(pid) => pid,
+                Err(_) => continue,
+            };
+            if let Ok(stat) = fs::read_to_string(entry.path().join("stat")) {
+                if let Some((comm, state)) = parse_proc_stat(&stat) {
+                    if state == 'Z' {
+                        zombies.push((pid, comm));
+                    }
+                }
+            }
+        }
+        return zombies;
+    }
+
+    let output = match Command::new("ps")
+        .args(["-axo", "pid=,stat=,comm="])
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return Vec::new(),
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse::<u32>().ok()?;
+            let stat = parts.next()?;
+            let comm = parts.collect::<Vec<_>>().join(" ");
+            stat.starts_with('Z').then_some((pid, comm))
+        })
+        .collect()
+}
+
+/// Extract the command name and state from a `/proc/<pid>/stat` line
+///
+/// The command is wrapped in parentheses and may itself contain spaces or
+/// parentheses, so the state is read after the last `)`.
+fn parse_proc_stat(stat: &str) -> Option<(String, char)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let comm = stat.get(open + 1..close)?.to_string();
+    let state = stat[close + 1..].trim_start().chars().next()?;
+    Some((comm, state))
+}
+
+/// Run all doctor checks: catdog's own dependencies first, then system health
+pub fn run_doctor() -> Result<DoctorReport> {
+    let mut report = DoctorReport::default();
+
+    report.checks.push(check_tools(
+        &platform_tools(env::consts::OS),
+        service::is_command_available,
+    ));
+    report
+        .checks
+        .push(check_package_managers(&package::detect_package_managers()));
+    if env::consts::OS != "windows" {
+        report.checks.push(check_fstab(Path::new("/etc/fstab")));
+    }
+    report
+        .checks
+        .push(check_config(&Config::load(), &Config::display_path()));
+
+    let dirs = [
+        state::home_dir().map(|home| home.join(".catdog")),
+        backup::backup_base(),
+    ];
+    report
+        .checks
+        .push(match dirs.into_iter().collect::<Result<Vec<_>>>() {
+            Ok(dirs) => check_writable_dirs(&dirs),
+            Err(e) => DoctorCheck::new(
+                "State directories",
+                CheckStatus::Fail,
+                format!("{:#}", e),
+                Vec::new(),
+            ),
+        });
+
+    let sm = service::detect_service_manager()?;
+    if sm != ServiceManager::Unknown {
+        let services = service::list_services(&sm, false)?;
+        let enabled = service::list_enabled_services(&sm)?;
+        report.checks.push(check_failed_units(&services));
+        if sm == ServiceManager::Systemd {
+            // If the unit properties can't be read, every stopped service is reported
+            let idle = service::idle_by_design(&enabled).unwrap_or_default();
+            report
+                .checks
+                .push(check_enabled_not_running(&services, &enabled, &idle));
+        }
+    }
+
+    report.checks.push(check_zombies(&find_zombies()));
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, status: ServiceStatus) -> ServiceInfo {
+        ServiceInfo {
+            name: name.to_string(),
+            status,
+            enabled: None,
+            pid: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_failed_unit_degrades_report() {
+        let services = vec![
+            service("cron", ServiceStatus::Running),
+            service("nginx", ServiceStatus::Failed),
+        ];
+        let enabled = vec!["cron".to_string(), "nginx".to_string()];
+
+        let report = DoctorReport {
+            checks: vec![
+                check_failed_units(&services),
+                check_enabled_not_running(&services, &enabled, &HashSet::new()),
+                check_zombies(&[]),
+            ],
+        };
+
+        assert_eq!(report.checks[0].status, CheckStatus::Fail);
+        assert!(report.checks[0].details[0].starts_with("nginx"));
+        assert_eq!(report.checks[1].status, CheckStatus::Warn);
+        assert_eq!(report.overall(), CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_healthy_report_passes() {
+        let services = vec![service("cron", ServiceStatus::Running)];
+        let report = DoctorReport {
+            checks: vec![
+                check_failed_units(&services),
+                check_enabled_not_running(&services, &["cron".to_string()], &HashSet::new()),
+            ],
+        };
+        assert_eq!(report.overall(), CheckStatus::Pass);
+
+        // Timer-driven and oneshot services are stopped between runs
+        let enabled = vec!["cron".to_string(), "fstrim".to_string()];
+        let idle = HashSet::from(["fstrim".to_string()]);
+        let check = check_enabled_not_running(&services, &enabled, &idle);
+        assert_eq!(check.status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_only_missing_tools_are_fatal() {
+        let fail =
+            |name: &str| DoctorCheck::new(name, CheckStatus::Fail, String::new(), Vec::new());
+        let report = DoctorReport {
+            checks: vec![fail("Failed units"), fail("fstab")],
+        };
+        assert_eq!(report.overall(), CheckStatus::Fail);
+        assert!(!report.missing_required_tool());
+
+        let tools = platform_tools("linux");
+        let report = DoctorReport {
+            checks: vec![check_tools(&tools, |cmd| cmd != "lsblk")],
+        };
+        assert!(report.missing_required_tool());
+    }
+
+    #[test]
+    fn test_missing_required_tool_fails() {
+        let tools = platform_tools("linux");
+        assert!(tools.iter().any(|t| t.command == "lsblk" && t.required));
+
+        let everything = check_tools(&tools, |_| true);
+        assert_eq!(everything.status, CheckStatus::Pass);
+        assert_eq!(everything.details.len(), tools.len());
+
+        let no_smartctl = check_tools(&tools, |cmd| cmd != "smartctl");
+        assert_eq!(no_smartctl.status, CheckStatus::Warn);
+
+        let no_lsblk = check_tools(&tools, |cmd| cmd != "lsblk" && cmd != "smartctl");
+        assert_eq!(no_lsblk.status, CheckStatus::Fail);
+        assert_eq!(no_lsblk.summary, "1 required tool(s) missing");
+
+        // Windows has none of the Unix tools and doesn't need them
+        let windows = platform_tools("windows");
+        assert!(windows
+            .iter()
+            .all(|t| !["df", "mount", "sudo"].contains(&t.command)));
+        assert_eq!(
+            check_tools(&windows, |cmd| cmd == "powershell").status,
+            CheckStatus::Warn
+        );
+    }
+
+    #[test]
+    fn test_fstab_and_dir_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let fstab = dir.path().join("fstab");
+        fs::write(&fstab, "# comment\nUUID=abc / ext4 defaults 0 1\n\n").unwrap();
+
+        let readable = check_fstab(&fstab);
+        assert_eq!(readable.status, CheckStatus::Pass);
+        assert!(readable.summary.ends_with("(1 entries)"));
+        assert_eq!(
+            check_fstab(&dir.path().join("missing")).status,
+            CheckStatus::Fail
+        );
+
+        let state = dir.path().join("state");
+        assert_eq!(
+            check_writable_dirs(std::slice::from_ref(&state)).status,
+            CheckStatus::Pass
+        );
+        assert!(state.is_dir());
+
+        // A plain file where a directory should be can't be written into
+        let blocked = check_writable_dirs(std::slice::from_ref(&fstab));
+        assert_eq!(blocked.status, CheckStatus::Fail);
+        assert_eq!(blocked.details.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let stat = "1234 (my (weird) proc) Z 1 1234 1234 0 -1";
+        assert_eq!(
+            parse_proc_stat(stat),
+            Some(("my (weird) proc".to_string(), 'Z'))
+        );
+    }
+}
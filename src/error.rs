@@ -52,9 +52,31 @@ impl std::error::Error for UserError {}
 
 /// Convert from anyhow::Error to UserError with better messages
 pub fn to_user_error(error: anyhow::Error) -> UserError {
-    let error_str = error.to_string();
+    // Errors that are already user-facing keep their message and suggestion
+    let error = match error.downcast::<UserError>() {
+        Ok(user_error) => return user_error,
+        Err(error) => error,
+    };
+
+    // Include the whole context chain so underlying causes are detected and shown
+    let error_str = format!("{:#}", error);
 
     // Detect common errors and provide helpful suggestions
+    if error_str.contains("Command timed out") {
+        // Show the timeout itself, not contexts like "Make sure lsblk is installed"
+        let message = error
+            .chain()
+            .map(|cause| cause.to_string())
+            .find(|cause| cause.starts_with("Command timed out"))
+            .unwrap_or(error_str);
+        return UserError::new(message)
+            .with_suggestion(format!(
+                "A stuck mount (such as an unreachable NFS server) can hang system tools; check `mount`, or raise [general] command_timeout_seconds in {}",
+                crate::config::Config::display_path()
+            ))
+            .with_exit_code(exit_codes::TIMED_OUT);
+    }
+
     if error_str.contains("Permission denied") {
         return UserError::new("Permission denied")
             .with_suggestion("Try running with sudo: sudo catdog <command>")
@@ -113,7 +135,14 @@ pub mod exit_codes {
     pub const SUCCESS: i32 = 0;
     pub const GENERAL_ERROR: i32 = 1;
     pub const NO_SUCH_FILE: i32 = 2;
+    /// Some items of a batch operation failed
+    pub const PARTIAL_FAILURE: i32 = 3;
+    /// `check` raised new critical alerts
+    pub const CRITICAL_ALERTS: i32 = 4;
     pub const PERMISSION_DENIED: i32 = 13;
+    /// An external command ran past `[general] command_timeout_seconds`, as with timeout(1)
+    pub const TIMED_OUT: i32 = 124;
+    /// Input could not be parsed, or `validate` found critical issues
     pub const DATA_ERROR: i32 = 65;
     pub const CONFIG_ERROR: i32 = 78;
     pub const COMMAND_NOT_FOUND: i32 = 127;
@@ -158,4 +187,32 @@ mod tests {
         let user_err = to_user_error(anyhow_err);
         assert_eq!(user_err.exit_code(), 127);
     }
+
+    #[test]
+    fn test_timeout_detection() {
+        let anyhow_err = anyhow!("Command timed out: lsblk did not finish within 30s")
+            .context("Failed to run lsblk. Make sure lsblk is installed.");
+        let user_err = to_user_error(anyhow_err);
+        assert_eq!(user_err.exit_code(), exit_codes::TIMED_OUT);
+        assert_eq!(
+            user_err.to_string(),
+            "Command timed out: lsblk did not finish within 30s"
+        );
+        assert!(user_err
+            .suggestion
+            .unwrap()
+            .contains("command_timeout_seconds"));
+    }
+
+    #[test]
+    fn test_user_error_passes_through() {
+        let err: anyhow::Error = UserError::new("sudo is not available")
+            .with_suggestion("Re-run as root")
+            .with_exit_code(13)
+            .into();
+        let user_err = to_user_error(err);
+        assert_eq!(user_err.to_string(), "sudo is not available");
+        assert_eq!(user_err.exit_code(), 13);
+        assert!(user_err.suggestion.is_some());
+    }
 }
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Used until `[general] command_timeout_seconds` has been applied
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+
+static DEFAULT_TIMEOUT: Mutex<Duration> = Mutex::new(Duration::from_secs(DEFAULT_TIMEOUT_SECONDS));
+
+/// Use `timeout` for every later [`run_command`] in this process
+pub fn set_default_timeout(timeout: Duration) {
+    *DEFAULT_TIMEOUT.lock().unwrap_or_else(|e| e.into_inner()) = timeout;
+}
+
+pub fn default_timeout() -> Duration {
+    *DEFAULT_TIMEOUT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// [`run_command_with_timeout`] with the configured default timeout
+pub fn run_command(cmd: &str, args: &[&str]) -> Result<Output> {
+    run_command_with_timeout(cmd, args, default_timeout())
+}
+
+/// Run a program to completion, killing it if it runs longer than `timeout`
+///
+/// Tools like `lsblk` and `df` can block forever on a stuck network mount,
+/// so discovery and monitoring go through here rather than `Command::output`.
+/// A missing program surfaces as an `io::Error` of kind `NotFound` in the chain.
+pub fn run_command_with_timeout(cmd: &str, args: &[&str], timeout: Duration) -> Result<Output> {
+    let mut command = Command::new(cmd);
+    command.args(args);
+
+    let (status, stdout, stderr) = wait_with_timeout(&mut command, timeout)
+        .with_context(|| format!("Failed to run {}", cmd))?;
+
+    match status {
+        Some(status) => Ok(Output {
+            status,
+            stdout,
+            stderr,
+        }),
+        None => anyhow::bail!(
+            "Command timed out: {} did not finish within {}s",
+            cmd,
+            timeout.as_secs_f64()
+        ),
+    }
+}
+
+/// How long to keep reading output after a timed-out process was killed
+///
+/// Something that escaped the kill may still hold the pipes open; its output
+/// is abandoned rather than waited for.
+const DRAIN_GRACE: Duration = Duration::from_millis(500);
+
+/// Spawn `command` with piped output and wait up to `timeout` for it to exit
+///
+/// Returns the exit status, or `None` if the process was killed for running
+/// too long, along with whatever it wrote to stdout and stderr. On Unix the
+/// process runs in its own process group, so a timeout also kills anything it
+/// forked, such as the commands of a shell script.
+pub fn wait_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> std::io::Result<(Option<ExitStatus>, Vec<u8>, Vec<u8>)> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = command.spawn()?;
+
+    // Drain pipes on separate threads so a chatty process can't block on a full pipe
+    let stdout_reader = drain(child.stdout.take());
+    let stderr_reader = drain(child.stderr.take());
+
+    let status = wait_or_kill(&mut child, timeout)?;
+    let deadline = match status {
+        Some(_) => None,
+        None => Some(Instant::now() + DRAIN_GRACE),
+    };
+
+    Ok((
+        status,
+        collect(stdout_reader, deadline),
+        collect(stderr_reader, deadline),
+    ))
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        let _ = sender.send(buf);
+    });
+    receiver
+}
+
+/// Output from a reader thread, giving up at `deadline` if there is one
+fn collect(reader: mpsc::Receiver<Vec<u8>>, deadline: Option<Instant>) -> Vec<u8> {
+    match deadline {
+        Some(deadline) => reader
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .unwrap_or_default(),
+        None => reader.recv().unwrap_or_default(),
+    }
+}
+
+fn wait_or_kill(child: &mut Child, timeout: Duration) -> std::io::Result<Option<ExitStatus>> {
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if started.elapsed() >= timeout {
+            kill_process_group(child);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Kill the group `wait_with_timeout` started the child in
+#[cfg(unix)]
+fn kill_process_group(child: &Child) {
+    // The child leads its own group, so its pid is the group id
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_child: &Child) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_slow_command_is_killed() {
+        let started = Instant::now();
+        let error = run_command_with_timeout("sleep", &["10"], Duration::from_millis(200))
+            .err()
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(error.to_string().starts_with("Command timed out: sleep"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_kills_forked_children() {
+        let started = Instant::now();
+        let error =
+            run_command_with_timeout("sh", &["-c", "sleep 10; true"], Duration::from_millis(200))
+                .err()
+                .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(error.to_string().starts_with("Command timed out: sh"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fast_command_returns_output() {
+        let output = run_command_with_timeout("echo", &["catdog"], Duration::from_secs(5)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "catdog\n");
+    }
+
+    #[test]
+    fn test_missing_command_keeps_io_error() {
+        let error = run_command("catdog-no-such-program", &[]).err().unwrap();
+        let io_error = error.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io_error.kind(), std::io::ErrorKind::NotFound);
+    }
+}
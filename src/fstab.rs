@@ -0,0 +1,888 @@
+use anyhow::{Context, Result};
+use colored::*;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::backup;
+use crate::config::BackupConfig;
+use crate::diff;
+use crate::state;
+
+/// Device, mount point, type and options are required; dump and pass are optional
+const MIN_FIELDS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FstabEntry {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    pub options: String,
+    pub dump: String,
+    pub pass: String,
+    /// 1-based line in the file the entry was read from, 0 if built in memory
+    pub line_number: usize,
+    /// Fields written on the line, 4 to 6; omitted dump and pass default to "0"
+    #[serde(skip)]
+    pub field_count: usize,
+}
+
+impl FstabEntry {
+    /// Build an entry from the whitespace-separated fstab fields
+    ///
+    /// As in fstab(5), dump and pass may be left off and default to 0, so
+    /// four to six fields are accepted.
+    pub fn from_fields(parts: &[&str]) -> Option<Self> {
+        if parts.len() < MIN_FIELDS {
+            return None;
+        }
+        let optional = |index: usize| parts.get(index).copied().unwrap_or("0").to_string();
+
+        Some(FstabEntry {
+            device: parts[0].to_string(),
+            mount_point: parts[1].to_string(),
+            fs_type: parts[2].to_string(),
+            options: parts[3].to_string(),
+            dump: optional(4),
+            pass: optional(5),
+            line_number: 0,
+            field_count: parts.len().min(6),
+        })
+    }
+
+    /// Whether dump or pass was left off the line and filled in with the default
+    pub fn has_default_fields(&self) -> bool {
+        self.field_count < 6
+    }
+
+    /// The mount point as a real path, with `\040`-style escapes decoded
+    pub fn decoded_mount_point(&self) -> String {
+        decode_field(&self.mount_point)
+    }
+
+    /// The device spec with `\040`-style escapes decoded
+    pub fn decoded_device(&self) -> String {
+        decode_field(&self.device)
+    }
+
+    /// Whether `target` names this entry's mount point or device, escaped or not
+    pub fn matches(&self, target: &str) -> bool {
+        self.mount_point == target
+            || self.device == target
+            || self.decoded_mount_point() == target
+            || self.decoded_device() == target
+    }
+
+    pub fn fields(&self) -> [&str; 6] {
+        [
+            &self.device,
+            &self.mount_point,
+            &self.fs_type,
+            &self.options,
+            &self.dump,
+            &self.pass,
+        ]
+    }
+}
+
+/// Decode the octal escapes `mount(8)` uses for whitespace and backslashes in
+/// fstab fields, e.g. `/mnt/My\040Disk` -> `/mnt/My Disk`
+///
+/// Anything that isn't a backslash followed by three octal digits is kept as is.
+pub fn decode_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let digits = &bytes[i + 1..i + 4];
+            if digits.iter().all(|d| (b'0'..=b'7').contains(d)) {
+                let value = digits
+                    .iter()
+                    .fold(0u32, |acc, d| acc * 8 + u32::from(d - b'0'));
+                if let Ok(byte) = u8::try_from(value) {
+                    decoded.push(byte);
+                    i += 4;
+                    continue;
+                }
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Escape characters that would otherwise split an fstab field
+pub fn encode_field(field: &str) -> String {
+    let mut encoded = String::with_capacity(field.len());
+
+    for c in field.chars() {
+        match c {
+            ' ' => encoded.push_str("\\040"),
+            '\t' => encoded.push_str("\\011"),
+            '\n' => encoded.push_str("\\012"),
+            '\\' => encoded.push_str("\\134"),
+            _ => encoded.push(c),
+        }
+    }
+
+    encoded
+}
+
+/// A problem noticed while parsing, reported alongside the entries
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseWarning {
+    pub line_number: usize,
+    pub code: String,
+    pub message: String,
+}
+
+impl ParseWarning {
+    fn new(line_number: usize, code: &str, message: String) -> Self {
+        ParseWarning {
+            line_number,
+            code: code.to_string(),
+            message,
+        }
+    }
+
+    /// Print the warning to stderr in the usual human format
+    pub fn display(&self) {
+        eprintln!(
+            "{} Line {}: {}",
+            "Warning:".yellow(),
+            self.line_number,
+            self.message
+        );
+    }
+}
+
+/// Entries parsed from an fstab, plus any warnings about lines that were skipped or suspect
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ParsedFstab {
+    pub entries: Vec<FstabEntry>,
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// Read an fstab, replacing bytes that aren't valid UTF-8 (e.g. latin-1 labels)
+/// rather than failing outright
+pub fn read_fstab(path: &str) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Parse fstab contents, collecting warnings instead of printing them
+pub fn parse_entries(contents: &str) -> ParsedFstab {
+    let mut parsed = ParsedFstab::default();
+
+    for (line_num, line) in contents.lines().enumerate() {
+        let line_number = line_num + 1;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+
+        let mut entry = match FstabEntry::from_fields(&parts) {
+            Some(entry) => entry,
+            None => {
+                parsed.warnings.push(ParseWarning::new(
+                    line_number,
+                    "field_count",
+                    format!(
+                        "Expected at least {} fields, found {} - skipping",
+                        MIN_FIELDS,
+                        parts.len()
+                    ),
+                ));
+                continue;
+            }
+        };
+        entry.line_number = line_number;
+
+        if entry.dump.parse::<u32>().is_err() {
+            parsed.warnings.push(ParseWarning::new(
+                line_number,
+                "bad_dump",
+                format!("Dump field '{}' is not a number", entry.dump),
+            ));
+        }
+        if entry.pass.parse::<u32>().is_err() {
+            parsed.warnings.push(ParseWarning::new(
+                line_number,
+                "bad_pass",
+                format!("Pass field '{}' is not a number", entry.pass),
+            ));
+        }
+
+        parsed.entries.push(entry);
+    }
+
+    parsed
+}
+
+/// A single line of an fstab file
+#[derive(Debug, Clone)]
+pub enum FstabLine {
+    /// Comment, blank or unparseable line, written back verbatim
+    Raw(String),
+    /// A parsed entry along with the text it was read from
+    Entry {
+        entry: FstabEntry,
+        raw: String,
+        modified: bool,
+    },
+}
+
+/// Line-oriented fstab model that preserves comments and layout on write
+#[derive(Debug, Clone, Default)]
+pub struct FstabDocument {
+    pub lines: Vec<FstabLine>,
+}
+
+impl FstabDocument {
+    pub fn parse(contents: &str) -> Self {
+        let lines = contents
+            .lines()
+            .enumerate()
+            .map(|(line_num, line)| {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    return FstabLine::Raw(line.to_string());
+                }
+
+                let parts: Vec<&str> = trimmed.split_whitespace().collect();
+                match FstabEntry::from_fields(&parts) {
+                    Some(entry) => FstabLine::Entry {
+                        entry: FstabEntry {
+                            line_number: line_num + 1,
+                            ..entry
+                        },
+                        raw: line.to_string(),
+                        modified: false,
+                    },
+                    None => FstabLine::Raw(line.to_string()),
+                }
+            })
+            .collect();
+
+        FstabDocument { lines }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &FstabEntry> {
+        self.lines.iter().filter_map(|line| match line {
+            FstabLine::Entry { entry, .. } => Some(entry),
+            FstabLine::Raw(_) => None,
+        })
+    }
+
+    /// Find an entry by mount point for editing; the entry is re-rendered on write
+    pub fn entry_mut(&mut self, mount_point: &str) -> Option<&mut FstabEntry> {
+        self.lines.iter_mut().find_map(|line| match line {
+            FstabLine::Entry {
+                entry, modified, ..
+            } if entry.mount_point == mount_point => {
+                *modified = true;
+                Some(entry)
+            }
+            _ => None,
+        })
+    }
+
+    /// Find the single entry whose mount point or device is `target`
+    ///
+    /// Errors if nothing matches or if the target is ambiguous.
+    pub fn find_entry(&self, target: &str) -> Result<&FstabEntry> {
+        match &self.lines[self.find_index(target)?] {
+            FstabLine::Entry { entry, .. } => Ok(entry),
+            FstabLine::Raw(_) => unreachable!("matched index is always an entry"),
+        }
+    }
+
+    /// Like `find_entry`, but marks the entry for re-rendering
+    pub fn find_entry_mut(&mut self, target: &str) -> Result<&mut FstabEntry> {
+        let index = self.find_index(target)?;
+        match &mut self.lines[index] {
+            FstabLine::Entry {
+                entry, modified, ..
+            } => {
+                *modified = true;
+                Ok(entry)
+            }
+            FstabLine::Raw(_) => unreachable!("matched index is always an entry"),
+        }
+    }
+
+    fn find_index(&self, target: &str) -> Result<usize> {
+        let matches: Vec<usize> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| match line {
+                FstabLine::Entry { entry, .. } if entry.matches(target) => Some(index),
+                _ => None,
+            })
+            .collect();
+
+        match matches.as_slice() {
+            [] => anyhow::bail!("No fstab entry matches '{}'", target),
+            [index] => Ok(*index),
+            _ => {
+                let mount_points: Vec<&str> = matches
+                    .iter()
+                    .filter_map(|&index| match &self.lines[index] {
+                        FstabLine::Entry { entry, .. } => Some(entry.mount_point.as_str()),
+                        FstabLine::Raw(_) => None,
+                    })
+                    .collect();
+                anyhow::bail!(
+                    "'{}' matches {} entries ({}); use a mount point to pick one",
+                    target,
+                    matches.len(),
+                    mount_points.join(", ")
+                )
+            }
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        for line in &self.lines {
+            match line {
+                FstabLine::Raw(text) => output.push_str(text),
+                FstabLine::Entry {
+                    entry,
+                    raw,
+                    modified,
+                } => {
+                    if *modified {
+                        output.push_str(&render_entry(entry, raw));
+                    } else {
+                        output.push_str(raw);
+                    }
+                }
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// Re-render an entry, reusing the whitespace of its original line so columns stay aligned
+fn render_entry(entry: &FstabEntry, raw: &str) -> String {
+    let fields = entry.fields();
+    let mut output = String::new();
+    let mut field_index = 0;
+    let mut in_field = false;
+
+    for c in raw.chars() {
+        if c.is_whitespace() {
+            in_field = false;
+            output.push(c);
+            continue;
+        }
+
+        if !in_field {
+            in_field = true;
+            field_index += 1;
+            if let Some(field) = fields.get(field_index - 1) {
+                output.push_str(field);
+            }
+        }
+
+        // Anything past the sixth field is kept as written
+        if field_index > fields.len() {
+            output.push(c);
+        }
+    }
+
+    // Omitted dump/pass fields default to 0; write them out once they hold anything else
+    let missing = &fields[field_index.min(fields.len())..];
+    if missing.iter().any(|field| *field != "0") {
+        output.truncate(output.trim_end().len());
+        for field in missing {
+            output.push(' ');
+            output.push_str(field);
+        }
+    }
+
+    output
+}
+
+/// Split a mount option string into its individual options
+pub fn split_options(options: &str) -> Vec<String> {
+    options
+        .split(',')
+        .map(|o| o.trim())
+        .filter(|o| !o.is_empty())
+        .map(|o| o.to_string())
+        .collect()
+}
+
+fn option_key(option: &str) -> &str {
+    option.split('=').next().unwrap_or(option)
+}
+
+/// Apply set-wise additions and removals to a mount option string
+///
+/// Existing options keep their order. Adding `key=value` replaces an existing
+/// value for the same key; removing a bare key drops any `key=...` form too.
+pub fn edit_options(options: &str, add: &[String], remove: &[String]) -> String {
+    let mut result: Vec<String> = split_options(options)
+        .into_iter()
+        .filter(|o| {
+            !remove
+                .iter()
+                .any(|r| o == r || (!r.contains('=') && option_key(o) == r))
+        })
+        .collect();
+
+    for option in add {
+        if result.contains(option) {
+            continue;
+        }
+
+        if option.contains('=') {
+            if let Some(existing) = result
+                .iter_mut()
+                .find(|o| option_key(o) == option_key(option))
+            {
+                *existing = option.clone();
+                continue;
+            }
+        }
+
+        result.push(option.clone());
+    }
+
+    // "defaults" is redundant once other options are present
+    if result.len() > 1 {
+        result.retain(|o| o != "defaults");
+    }
+
+    if result.is_empty() {
+        "defaults".to_string()
+    } else {
+        result.join(",")
+    }
+}
+
+fn is_mountable(entry: &FstabEntry) -> bool {
+    entry.mount_point != "none" && entry.mount_point != "swap" && entry.fs_type != "swap"
+}
+
+/// A child mount point listed before the mount point it lives under
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderingProblem<'a> {
+    pub child: &'a FstabEntry,
+    pub parent: &'a FstabEntry,
+}
+
+/// Find entries that appear before an entry for one of their parent directories
+///
+/// Mounting `/var/log` before `/var` hides it under the later mount, so parents must come first.
+pub fn ordering_problems(entries: &[FstabEntry]) -> Vec<OrderingProblem<'_>> {
+    let mut problems = Vec::new();
+
+    for (i, child) in entries.iter().enumerate() {
+        if !is_mountable(child) {
+            continue;
+        }
+        let child_path = Path::new(&child.mount_point);
+
+        if let Some(parent) = entries[i + 1..].iter().find(|parent| {
+            is_mountable(parent)
+                && parent.mount_point != child.mount_point
+                && child_path.starts_with(&parent.mount_point)
+        }) {
+            problems.push(OrderingProblem { child, parent });
+        }
+    }
+
+    problems
+}
+
+/// Mount points in a safe order: shallower paths first, file order otherwise kept
+pub fn suggested_order(entries: &[FstabEntry]) -> Vec<&FstabEntry> {
+    let mut ordered: Vec<&FstabEntry> = entries.iter().filter(|e| is_mountable(e)).collect();
+    ordered.sort_by_key(|e| Path::new(&e.mount_point).components().count());
+    ordered
+}
+
+/// Pairs of entries that mount different devices at the same path
+pub fn conflicting_mounts(entries: &[FstabEntry]) -> Vec<(&FstabEntry, &FstabEntry)> {
+    let mut conflicts = Vec::new();
+
+    for (i, first) in entries.iter().enumerate() {
+        if !is_mountable(first) {
+            continue;
+        }
+        for second in &entries[i + 1..] {
+            if second.mount_point == first.mount_point && second.device != first.device {
+                conflicts.push((first, second));
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// Basic sanity checks run on an edited document before it is written
+pub fn check_document(doc: &FstabDocument) -> Result<()> {
+    for entry in doc.entries() {
+        if entry.mount_point != "none"
+            && entry.mount_point != "swap"
+            && !entry.mount_point.starts_with('/')
+        {
+            anyhow::bail!("Mount point '{}' doesn't start with /", entry.mount_point);
+        }
+        if entry.options.is_empty() {
+            anyhow::bail!("Entry for {} has no mount options", entry.mount_point);
+        }
+        if entry.dump.parse::<u32>().is_err() || entry.pass.parse::<u32>().is_err() {
+            anyhow::bail!(
+                "Entry for {} has invalid dump/pass fields",
+                entry.mount_point
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Append a new entry, refusing fields that don't make sense or a mount point already in use
+///
+/// The new line is tab-separated; existing lines are left exactly as they were.
+/// Returns the line that was added.
+pub fn append_entry(doc: &mut FstabDocument, entry: FstabEntry) -> Result<String> {
+    if entry.device.trim().is_empty() {
+        anyhow::bail!("Device can't be empty");
+    }
+    if entry.fs_type.trim().is_empty() {
+        anyhow::bail!("Filesystem type can't be empty");
+    }
+    let swap_without_path = entry.fs_type == "swap" && entry.mount_point == "none";
+    if !(entry.mount_point.starts_with('/') || swap_without_path) {
+        anyhow::bail!(
+            "Mount point '{}' must be an absolute path",
+            entry.mount_point
+        );
+    }
+    if entry.options.trim().is_empty() {
+        anyhow::bail!("Mount options can't be empty; use 'defaults'");
+    }
+    for (name, value) in [("dump", &entry.dump), ("pass", &entry.pass)] {
+        if value.parse::<u32>().is_err() {
+            anyhow::bail!("Invalid {} '{}': expected a number", name, value);
+        }
+    }
+
+    if is_mountable(&entry) {
+        if let Some(existing) = doc
+            .entries()
+            .find(|e| is_mountable(e) && e.decoded_mount_point() == entry.mount_point)
+        {
+            anyhow::bail!(
+                "{} already has an entry on line {} ({})",
+                entry.mount_point,
+                existing.line_number,
+                existing.device
+            );
+        }
+    }
+
+    let entry = FstabEntry {
+        device: encode_field(&entry.device),
+        mount_point: encode_field(&entry.mount_point),
+        line_number: doc.lines.len() + 1,
+        field_count: 6,
+        ..entry
+    };
+    let raw = entry.fields().join("\t");
+
+    doc.lines.push(FstabLine::Entry {
+        entry,
+        raw: raw.clone(),
+        modified: false,
+    });
+
+    Ok(raw)
+}
+
+/// Load an fstab, apply an edit, validate, back up and write it back
+///
+/// The backup is cleaned up according to `retention`. In dry-run mode the resulting diff is shown and nothing is written.
+/// Returns whether the file content changed.
+pub fn edit_transaction<F>(
+    path: &str,
+    retention: &BackupConfig,
+    dry_run: bool,
+    edit: F,
+) -> Result<bool>
+where
+    F: FnOnce(&mut FstabDocument) -> Result<()>,
+{
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    // Writing back a lossy conversion would silently alter untouched lines
+    let original = String::from_utf8(bytes).map_err(|_| {
+        anyhow::anyhow!(
+            "{} contains bytes that aren't valid UTF-8; refusing to rewrite it",
+            path
+        )
+    })?;
+    let mut doc = FstabDocument::parse(&original);
+
+    edit(&mut doc)?;
+
+    let updated = doc.render();
+    if updated == original {
+        return Ok(false);
+    }
+
+    check_document(&FstabDocument::parse(&updated))
+        .context("Edited fstab failed validation, nothing was written")?;
+
+    if dry_run {
+        println!("{} Would update {}\n", "[DRY-RUN]".yellow().bold(), path);
+        diff::display_diff(&original, &updated, path, "proposed");
+        return Ok(true);
+    }
+
+    let backup_metadata = backup::create_backup(
+        path,
+        backup::BackupReason::PreFstabModification,
+        retention,
+        false,
+    )?;
+    println!(
+        "{} Backup created: {}",
+        "✓".green(),
+        backup_metadata.backup_path.bright_white()
+    );
+
+    state::write_atomic(Path::new(path), &updated)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_four_field_swap_line() {
+        let parsed = parse_entries("/dev/sda3 none swap sw\n");
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(parsed.entries.len(), 1);
+
+        let entry = &parsed.entries[0];
+        assert_eq!(entry.fs_type, "swap");
+        assert_eq!(entry.options, "sw");
+        assert_eq!((entry.dump.as_str(), entry.pass.as_str()), ("0", "0"));
+        assert!(entry.has_default_fields());
+    }
+
+    #[test]
+    fn test_five_field_entry() {
+        let parsed = parse_entries("UUID=abc /data ext4 defaults 1\n");
+        assert!(parsed.warnings.is_empty());
+
+        let entry = &parsed.entries[0];
+        assert_eq!((entry.dump.as_str(), entry.pass.as_str()), ("1", "0"));
+        assert_eq!(entry.field_count, 5);
+    }
+
+    #[test]
+    fn test_short_lines_warn_and_are_skipped() {
+        let parsed = parse_entries("/dev/sdb1 /data ext4\n/dev/sda3 none swap sw 0 0\n");
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(parsed.warnings[0].code, "field_count");
+        assert!(!parsed.entries[0].has_default_fields());
+    }
+
+    #[test]
+    fn test_render_writes_out_omitted_fields_only_when_set() {
+        let mut doc = FstabDocument::parse("/dev/sdb1  /data  ext4  defaults\n");
+        doc.entry_mut("/data").unwrap().options = "noatime".to_string();
+        assert_eq!(doc.render(), "/dev/sdb1  /data  ext4  noatime\n");
+
+        doc.entry_mut("/data").unwrap().pass = "2".to_string();
+        assert_eq!(doc.render(), "/dev/sdb1  /data  ext4  noatime 0 2\n");
+    }
+
+    #[test]
+    fn test_edit_options_add_only() {
+        let result = edit_options("defaults,discard", &opts(&["noatime", "discard"]), &[]);
+        assert_eq!(result, "discard,noatime");
+    }
+
+    #[test]
+    fn test_edit_options_remove_only() {
+        let result = edit_options("rw,discard,noatime", &[], &opts(&["discard"]));
+        assert_eq!(result, "rw,noatime");
+
+        let result = edit_options("discard", &[], &opts(&["discard"]));
+        assert_eq!(result, "defaults");
+    }
+
+    #[test]
+    fn test_edit_options_add_and_remove() {
+        let result = edit_options(
+            "rw,uid=1000,discard",
+            &opts(&["noatime", "uid=1001"]),
+            &opts(&["discard"]),
+        );
+        assert_eq!(result, "rw,uid=1001,noatime");
+    }
+
+    #[test]
+    fn test_mount_point_with_escaped_space() {
+        let parsed = parse_entries("/dev/sdb1 /mnt/My\\040Disk ext4 defaults 0 2\n");
+        let entry = &parsed.entries[0];
+
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(entry.mount_point, "/mnt/My\\040Disk");
+        assert_eq!(entry.decoded_mount_point(), "/mnt/My Disk");
+        assert_eq!(
+            encode_field(&entry.decoded_mount_point()),
+            entry.mount_point
+        );
+    }
+
+    #[test]
+    fn test_label_with_escaped_tab() {
+        let contents = "LABEL=Backup\\011Drive /mnt/back\\134up ext4 defaults 0 2\n";
+        let doc = FstabDocument::parse(contents);
+        let entry = doc.entries().next().unwrap();
+
+        assert_eq!(entry.decoded_device(), "LABEL=Backup\tDrive");
+        assert_eq!(entry.decoded_mount_point(), "/mnt/back\\up");
+        assert_eq!(encode_field("LABEL=Backup\tDrive"), entry.device);
+        // Escapes are written back exactly as read
+        assert_eq!(doc.render(), contents);
+
+        // Not an escape: kept verbatim
+        assert_eq!(decode_field("/mnt/a\\9b\\04"), "/mnt/a\\9b\\04");
+    }
+
+    #[test]
+    fn test_nested_mount_ordering() {
+        let parsed = parse_entries(
+            "UUID=root /        ext4 defaults 0 1\n\
+             /dev/sdb1 /var/log ext4 defaults 0 2\n\
+             /dev/sdc1 /var     ext4 defaults 0 2\n\
+             /dev/sdd1 /variety ext4 defaults 0 2\n",
+        );
+
+        let problems = ordering_problems(&parsed.entries);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].child.line_number, 2);
+        assert_eq!(problems[0].parent.line_number, 3);
+
+        let order: Vec<&str> = suggested_order(&parsed.entries)
+            .iter()
+            .map(|e| e.mount_point.as_str())
+            .collect();
+        assert_eq!(order, vec!["/", "/var", "/variety", "/var/log"]);
+    }
+
+    #[test]
+    fn test_conflicting_devices_at_same_path() {
+        let parsed = parse_entries(
+            "/dev/sdb1 /data ext4 defaults 0 2\n\
+             /dev/sdb1 /data ext4 defaults 0 2\n\
+             /dev/sdc1 /data xfs  defaults 0 2\n",
+        );
+
+        let conflicts = conflicting_mounts(&parsed.entries);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].0.line_number, 1);
+        assert_eq!(conflicts[0].1.line_number, 3);
+    }
+
+    #[test]
+    fn test_find_entry_by_mount_point_or_device() {
+        let contents = "/dev/sda1 /     ext4 defaults 0 1\n\
+                        /dev/sdb1 /data ext4 defaults 0 2\n\
+                        /dev/sdb1 /mirror ext4 defaults 0 2\n";
+        let mut doc = FstabDocument::parse(contents);
+
+        assert_eq!(doc.find_entry_mut("/data").unwrap().device, "/dev/sdb1");
+        assert_eq!(doc.find_entry_mut("/dev/sda1").unwrap().mount_point, "/");
+
+        let err = doc.find_entry_mut("/dev/sdb1").unwrap_err().to_string();
+        assert!(err.contains("matches 2 entries"));
+        assert!(doc.find_entry_mut("/missing").is_err());
+    }
+
+    #[test]
+    fn test_document_preserves_comments() {
+        let contents =
+            "# root\nUUID=abc /     ext4 defaults 0 1\n\n/dev/sdb1 /data ext4 discard 0 2\n";
+        let mut doc = FstabDocument::parse(contents);
+        assert_eq!(doc.entries().count(), 2);
+        assert_eq!(doc.render(), contents);
+
+        let entry = doc.entry_mut("/data").unwrap();
+        entry.options = edit_options(&entry.options, &opts(&["noatime"]), &opts(&["discard"]));
+
+        assert_eq!(
+            doc.render(),
+            "# root\nUUID=abc /     ext4 defaults 0 1\n\n/dev/sdb1 /data ext4 noatime 0 2\n"
+        );
+    }
+
+    fn new_entry(device: &str, mount_point: &str, fs_type: &str) -> FstabEntry {
+        FstabEntry::from_fields(&[device, mount_point, fs_type, "defaults", "0", "2"]).unwrap()
+    }
+
+    #[test]
+    fn test_append_entry_keeps_existing_lines() {
+        let original = "# root\nUUID=abc /     ext4 defaults 0 1\n";
+        let mut doc = FstabDocument::parse(original);
+
+        let added = append_entry(&mut doc, new_entry("UUID=def", "/mnt/my data", "ext4")).unwrap();
+        assert_eq!(added, "UUID=def\t/mnt/my\\040data\text4\tdefaults\t0\t2");
+
+        let rendered = doc.render();
+        assert_eq!(rendered, format!("{}{}\n", original, added));
+
+        let reparsed = parse_entries(&rendered);
+        assert!(reparsed.warnings.is_empty());
+        assert_eq!(reparsed.entries[1].decoded_mount_point(), "/mnt/my data");
+        assert_eq!(reparsed.entries[1].line_number, 3);
+    }
+
+    #[test]
+    fn test_append_entry_rejects_bad_fields() {
+        let mut doc = FstabDocument::parse("UUID=abc / ext4 defaults 0 1\n");
+
+        assert!(append_entry(&mut doc, new_entry("UUID=def", "mnt/data", "ext4")).is_err());
+        assert!(append_entry(&mut doc, new_entry("UUID=def", "/mnt/data", " ")).is_err());
+        let mut bad_pass = new_entry("UUID=def", "/mnt/data", "ext4");
+        bad_pass.pass = "two".to_string();
+        assert!(append_entry(&mut doc, bad_pass).is_err());
+
+        let duplicate = append_entry(&mut doc, new_entry("UUID=def", "/", "xfs")).unwrap_err();
+        assert!(duplicate
+            .to_string()
+            .contains("already has an entry on line 1"));
+
+        // Several swap entries can all use "none"
+        append_entry(&mut doc, new_entry("/dev/sda2", "none", "swap")).unwrap();
+        append_entry(&mut doc, new_entry("/dev/sdb2", "none", "swap")).unwrap();
+        assert_eq!(doc.entries().count(), 3);
+    }
+}
@@ -4,46 +4,144 @@ use log::info;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::process::{self, Command};
 
 mod alerts;
+mod audit;
 mod backup;
+mod cli;
+mod completions;
 mod config;
 mod corpus;
 mod diff;
+mod doctor;
 mod error;
+mod exec;
+mod fstab;
 mod monitor;
+mod mount;
+mod outcome;
 mod package;
+mod privilege;
 mod service;
+mod smart;
+mod state;
 mod sysinfo;
 
-use alerts::{display_alert_detail, display_alerts, AlertManager, AlertStatus};
-use config::Config;
-use error::{to_user_error, UserError};
+use alerts::{
+    display_alert_detail, display_alerts, display_alerts_compact, display_alerts_highlighting,
+    AlertCounts, AlertFilter, AlertManager, AlertSeverity, AlertStatus, MetadataMatcher,
+};
+use config::{BackupConfig, Config};
+use error::{exit_codes, to_user_error};
+use fstab::FstabEntry;
+use outcome::{run_batch, run_together, CommandOutcome};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
 
+/// How read-only commands print their results
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    /// Colored tables and reports
+    Table,
+    Json,
+    /// Uncolored, one tab-separated record per line where a command lists records
+    Plain,
+}
+
+impl OutputFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "table" => Some(OutputFormat::Table),
+            "json" => Some(OutputFormat::Json),
+            "plain" => Some(OutputFormat::Plain),
+            _ => None,
+        }
+    }
+}
+
+/// `--color`: whether to color output
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is unset
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    fn enabled(self, no_color_env: bool, stdout_is_tty: bool) -> bool {
+        match self {
+            ColorChoice::Auto => !no_color_env && stdout_is_tty,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct CliConfig {
-    json_output: bool,
-    no_color: bool,
+    output: OutputFormat,
     verbose: bool,
     dry_run: bool,
+    no_sudo: bool,
+    quiet: bool,
+    /// Package manager named with `--backend`, skipping detection
+    backend: Option<String>,
+    /// `--user`: act on systemd user units instead of system ones
+    user_units: bool,
+    /// `--yes`: skip confirmation prompts
+    assume_yes: bool,
+    /// The command line after `catdog`, as recorded in the audit log
+    args: Vec<String>,
     app_config: Config,
 }
 
-#[derive(Debug, Clone)]
-struct FstabEntry {
-    device: String,
-    mount_point: String,
-    fs_type: String,
-    options: String,
-    dump: String,
-    pass: String,
+impl CliConfig {
+    fn json_output(&self) -> bool {
+        self.output == OutputFormat::Json
+    }
 }
 
+/// Commands that only make sense with an fstab
+const FSTAB_COMMANDS: &[&str] = &[
+    "cat",
+    "dog",
+    "list",
+    "ls",
+    "find",
+    "validate",
+    "set-option",
+    "edit",
+    "fstab",
+    "mount",
+    "unmount",
+    "umount",
+    "mkmount",
+    "backup",
+    "restore",
+    "list-backups",
+    "backup-diff",
+    "backup-stats",
+    "backup-health",
+    "backup-drill",
+    "suggest",
+    "generate",
+    "generate-fstab",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct BlockDevice {
     device: String,
@@ -51,15 +149,18 @@ struct BlockDevice {
     partuuid: Option<String>,
     label: Option<String>,
     fs_type: Option<String>,
+    /// Size for display, as formatted from `size_bytes` where that is known
     size: Option<String>,
+    size_bytes: Option<u64>,
     mount_point: Option<String>,
     is_removable: bool,
     is_ssd: bool,
+    /// Physical disk this device lives on, if it is a partition or volume
+    parent: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 struct MountSuggestion {
-    device: BlockDevice,
     suggested_device_id: String,
     suggested_mount_point: String,
     suggested_options: Vec<String>,
@@ -80,36 +181,93 @@ fn main() {
 }
 
 fn run() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
+    let argv: Vec<String> = env::args().collect();
+    let cli = match cli::parse(&argv) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e.to_string().red());
+            eprintln!("Run 'catdog help' for usage");
+            process::exit(1);
+        }
+    };
+    let globals = &cli.globals;
+
+    // `--output` wins over `--json`, which is shorthand for `--output json`
+    let output = match globals.output.as_deref() {
+        Some(name) => OutputFormat::from_name(name).with_context(|| {
+            format!("Unknown output format '{}': use table, json or plain", name)
+        })?,
+        None if globals.json => OutputFormat::Json,
+        None => OutputFormat::Table,
+    };
+
+    // Decide on color once, before anything is printed
+    let color = match globals.color.as_deref() {
+        Some(name) => ColorChoice::from_name(name).with_context(|| {
+            format!(
+                "Unknown color setting '{}': use auto, always or never",
+                name
+            )
+        })?,
+        None if globals.no_color || output == OutputFormat::Plain => ColorChoice::Never,
+        None => ColorChoice::Auto,
+    };
+    let use_color = color.enabled(
+        env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+        std::io::stdout().is_terminal(),
+    );
+    colored::control::set_override(use_color);
 
-    // Handle version early
-    if args.len() >= 2 && (args[1] == "--version" || args[1] == "-V" || args[1] == "version") {
+    // Handle version and help early
+    if cli.globals.version || cli.command() == Some("version") {
         print_version();
         return Ok(());
     }
-
-    if args.len() < 2 {
+    if cli.globals.help {
         print_help();
-        process::exit(1);
+        return Ok(());
     }
 
-    // Load application config
-    let app_config = Config::load().context("Failed to load configuration")?;
+    let command = match cli.command() {
+        Some(command) => command,
+        None => {
+            print_help();
+            process::exit(1);
+        }
+    };
+
+    // Config location override: --config <path> wins over CATDOG_CONFIG
+    let config_path = match &cli.globals.config {
+        Some(path) => Some(PathBuf::from(path)),
+        None => env::var_os("CATDOG_CONFIG").map(PathBuf::from),
+    };
+    Config::set_path_override(config_path);
+
+    // Load application config; `config edit` must still run when the file is
+    // missing or broken, since that is how it gets fixed, and `doctor` reports it
+    let editing_config = command == "config" && cli.word(1) == Some("edit");
+    let app_config = match Config::load() {
+        Ok(app_config) => app_config,
+        Err(_) if editing_config || command == "doctor" => Config::default(),
+        Err(e) => return Err(e.context("Failed to load configuration")),
+    };
+    exec::set_default_timeout(std::time::Duration::from_secs(
+        app_config.general.command_timeout_seconds,
+    ));
 
-    // Parse global flags
     let config = CliConfig {
-        json_output: args.contains(&"--json".to_string()),
-        no_color: args.contains(&"--no-color".to_string()) || env::var("NO_COLOR").is_ok(),
-        verbose: args.contains(&"-v".to_string()) || args.contains(&"--verbose".to_string()),
-        dry_run: args.contains(&"--dry-run".to_string()),
+        output,
+        verbose: globals.verbose,
+        dry_run: globals.dry_run,
+        no_sudo: globals.no_sudo,
+        quiet: globals.quiet,
+        backend: globals.backend.clone(),
+        user_units: globals.user,
+        assume_yes: globals.yes,
+        args: argv[1..].to_vec(),
         app_config,
     };
 
-    // Disable colors if requested
-    if config.no_color {
-        colored::control::set_override(false);
-    }
-
     // Show dry-run notice
     if config.dry_run {
         println!(
@@ -118,155 +276,471 @@ fn run() -> Result<()> {
         );
     }
 
-    // Filter out flags to get the actual command and args
-    let non_flag_args: Vec<String> = args
-        .iter()
-        .filter(|a| !a.starts_with("--") && !a.starts_with("-v") && !a.starts_with("-V"))
-        .map(|s| s.clone())
-        .collect();
+    // Command, subcommand and positionals, with flags taken out
+    let words = &cli.words;
 
-    if non_flag_args.len() < 2 {
-        print_help();
+    info!("Executing command: {}", command);
+
+    if env::consts::OS == "windows" && FSTAB_COMMANDS.contains(&command) {
+        eprintln!(
+            "{} '{}' works on /etc/fstab and mount points, which are not applicable on Windows",
+            "ℹ️".blue(),
+            command
+        );
         process::exit(1);
     }
 
-    let command = &non_flag_args[1];
-
-    info!("Executing command: {}", command);
-
-    let result = match command.as_str() {
+    let result = match command {
         "cat" => cat_fstab(),
-        "dog" => dog_fstab(),
-        "list" | "ls" => list_mounts(),
+        "dog" => dog_fstab(&config),
+        "list" | "ls" => list_mounts(&config),
         "find" => {
-            if args.len() < 3 {
+            if words.len() < 2 {
                 eprintln!("{}", "Usage: catdog find <device|mount_point>".red());
                 process::exit(1);
             }
-            find_entry(&args[2])
+            find_entry(&words[1], &config)
         }
-        "validate" => validate_fstab(),
-        "discover" => discover_devices(&config),
-        "backup" => {
-            if non_flag_args.len() < 3 {
-                backup_file_cmd("/etc/fstab", config.dry_run)
-            } else {
-                backup_file_cmd(&non_flag_args[2], config.dry_run)
+        "validate" => validate_fstab(&config),
+        "set-option" => {
+            let positional = cli.positionals();
+            let add = flag_values(&cli, "--add");
+            let remove = flag_values(&cli, "--remove");
+            if positional.is_empty() || (add.is_empty() && remove.is_empty()) {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog set-option <mount_point> [--add <opt>] [--remove <opt>]".red()
+                );
+                process::exit(1);
+            }
+            finish_audited(
+                "set-option",
+                &positional[0],
+                set_option_cmd(
+                    "/etc/fstab",
+                    &positional[0],
+                    &add,
+                    &remove,
+                    &config.app_config.backup,
+                    config.dry_run,
+                ),
+                &config,
+            )
+        }
+        "edit" => {
+            let positional = cli.positionals();
+            let option_edits = flag_values(&cli, "--option");
+            let set_pass = cli.value("--set-pass");
+            if positional.is_empty() || (option_edits.is_empty() && set_pass.is_none()) {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog edit <mount_point|device> [--option add:<opt>|remove:<opt>] [--set-pass <n>]"
+                        .red()
+                );
+                process::exit(1);
+            }
+            finish_audited(
+                "edit",
+                &positional[0],
+                edit_cmd(
+                    "/etc/fstab",
+                    &positional[0],
+                    &option_edits,
+                    set_pass,
+                    &config.app_config.backup,
+                    config.dry_run,
+                ),
+                &config,
+            )
+        }
+        "fstab" => match cli.word(1) {
+            Some("add") => {
+                let (Some(device), Some(mount_point), Some(fs_type)) = (
+                    cli.value("--device"),
+                    cli.value("--mount"),
+                    cli.value("--type"),
+                ) else {
+                    eprintln!(
+                        "{}",
+                        "Usage: catdog fstab add --device <spec> --mount <path> --type <fs> [--options <opts>] [--dump <n>] [--pass <n>]"
+                            .red()
+                    );
+                    process::exit(1);
+                };
+                let entry = FstabEntry {
+                    device: device.to_string(),
+                    mount_point: mount_point.to_string(),
+                    fs_type: fs_type.to_string(),
+                    options: cli.value("--options").unwrap_or("defaults").to_string(),
+                    dump: cli.value("--dump").unwrap_or("0").to_string(),
+                    pass: cli.value("--pass").unwrap_or("0").to_string(),
+                    line_number: 0,
+                    field_count: 6,
+                };
+                finish_audited(
+                    "fstab add",
+                    mount_point,
+                    fstab_add_cmd(
+                        "/etc/fstab",
+                        entry,
+                        &config.app_config.backup,
+                        config.dry_run,
+                    ),
+                    &config,
+                )
+            }
+            _ => {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog fstab add --device <spec> --mount <path> --type <fs>".red()
+                );
+                process::exit(1);
+            }
+        },
+        "mount" | "unmount" | "umount" => {
+            if words.len() < 2 {
+                eprintln!(
+                    "{}",
+                    format!("Usage: catdog {} <mount_point|device> [--force]", command).red()
+                );
+                process::exit(1);
             }
+            let force = cli.has("--force");
+            let mount = command == "mount";
+            let create_dirs = mount && cli.has("--create-dirs");
+            finish_audited(
+                if mount { "mount" } else { "unmount" },
+                &words[1],
+                mount_cmd("/etc/fstab", &words[1], mount, force, create_dirs, &config),
+                &config,
+            )
+        }
+        "mkmount" => {
+            let positional = cli.positionals();
+            if positional.is_empty() {
+                eprintln!("{}", "Usage: catdog mkmount <mount_point> [--force]".red());
+                process::exit(1);
+            }
+            finish_audited(
+                "mkmount",
+                &positional[0],
+                mkmount_cmd(&positional[0], cli.has("--force"), &config),
+                &config,
+            )
+        }
+        "discover" => discover_devices(&config, cli.has("--flat"), cli.has("--bytes")),
+        "backup" => {
+            let encrypt = cli.has("--encrypt");
+            let path = words.get(1).map_or("/etc/fstab", String::as_str);
+            audited(
+                "backup",
+                path,
+                &config,
+                backup_file_cmd(path, encrypt, &config.app_config.backup, config.dry_run),
+            )
         }
         "restore" => {
-            if args.len() < 3 {
-                eprintln!("{}", "Usage: catdog restore <backup_path> [--force]".red());
+            let positional = cli.positionals();
+            if positional.is_empty() {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog restore <backup_path> [--to <path>] [--force] [--ignore-missing]"
+                        .red()
+                );
                 process::exit(1);
             }
-            let force = args.contains(&"--force".to_string());
-            restore_backup_cmd(&args[2], config.dry_run, force)
+            let target = cli.value("--to");
+            let force = cli.has("--force");
+            let ignore_missing = cli.has("--ignore-missing");
+            audited(
+                "restore",
+                &positional[0],
+                &config,
+                restore_backup_cmd(&positional[0], target, force, ignore_missing, &config),
+            )
         }
         "list-backups" => {
-            if args.len() < 3 {
+            if words.len() < 2 {
                 eprintln!("{}", "Usage: catdog list-backups <file>".red());
                 process::exit(1);
             }
-            list_backups_cmd(&args[2])
+            list_backups_cmd(&words[1], &config)
         }
-        "backup-stats" => backup_stats_cmd(),
+        "backup-diff" => {
+            if words.len() < 2 {
+                eprintln!("{}", "Usage: catdog backup-diff <backup_path>".red());
+                process::exit(1);
+            }
+            backup_diff_cmd(&words[1], &config)
+        }
+        "backup-stats" => backup_stats_cmd(&config),
         "backup-health" => backup_health_cmd(),
         "backup-drill" => backup_drill_cmd(),
         "suggest" => {
-            let device_filter = if args.len() >= 3 {
-                Some(args[2].as_str())
+            let device_filter = if words.len() >= 2 {
+                Some(words[1].as_str())
             } else {
                 None
             };
-            suggest_mounts(device_filter)
+            suggest_mounts(device_filter, &fs_filter(&cli))
         }
         "generate" | "generate-fstab" => {
-            let output_file = if args.len() >= 3 {
-                Some(args[2].as_str())
-            } else {
-                None
+            let positional = cli.positionals();
+            let output_file = positional.first().map(String::as_str);
+            let merge_path = cli.value("--merge");
+            let fs_types = fs_filter(&cli);
+            let split_by_fs = match cli.value("--split-by") {
+                None => false,
+                Some("fs" | "filesystem") => true,
+                Some(other) => anyhow::bail!("Unknown --split-by value '{}': use fs", other),
             };
-            generate_fstab(output_file, config.dry_run)
+            let outcome = generate_fstab(&GenerateOptions {
+                output_file,
+                merge_path,
+                fs_types: &fs_types,
+                split_by_fs,
+                under: cli.value("--under"),
+                retention: &config.app_config.backup,
+                dry_run: config.dry_run,
+            });
+            // Printing to stdout changes nothing, so only writes are audited
+            match output_file {
+                Some(path) => finish_audited("generate", path, outcome, &config),
+                None => finish_outcome(outcome, &config),
+            }
         }
         // Bark (alert) commands
         "monitor" => {
-            let interval = if args.len() >= 3 {
-                args[2].parse::<u64>().unwrap_or(300)
-            } else {
-                300
-            };
-            start_monitoring(interval)
+            let positional = cli.positionals();
+            let interval = positional
+                .first()
+                .and_then(|i| i.parse::<u64>().ok())
+                .unwrap_or(300);
+            let prometheus_path = cli.value("--export-prometheus").map(PathBuf::from);
+            start_monitoring(interval, prometheus_path, &config)
         }
-        "check" => run_health_check(),
+        "watch" => {
+            // `catdog watch info 5` reads the same as `catdog watch 5`
+            let positional = cli.positionals();
+            let interval = positional
+                .iter()
+                .find(|p| p.as_str() != "info")
+                .map(|i| i.parse::<u64>())
+                .transpose()
+                .context("Interval must be a number of seconds")?
+                .unwrap_or(2)
+                .max(1);
+            watch_cmd(interval, &config)
+        }
+        "disk-health" => {
+            let positional = cli.positionals();
+            disk_health_cmd(positional, &config)
+        }
+        "check" => run_health_check(&config),
         "barks" | "alerts" => {
-            let status_filter = if args.len() >= 3 {
-                match args[2].as_str() {
-                    "firing" => Some(AlertStatus::Firing),
-                    "acknowledged" => Some(AlertStatus::Acknowledged),
-                    "resolved" => Some(AlertStatus::Resolved),
-                    "silenced" => Some(AlertStatus::Silenced),
-                    _ => None,
-                }
+            if cli.word(1) == Some("test") {
+                return test_notifications_cmd(cli.value("--channel"), &config);
+            }
+            if cli.word(1) == Some("stats") {
+                return alert_stats_cmd(&config);
+            }
+            if cli.word(1) == Some("prune") {
+                // retain_resolved_days = 0 keeps barks forever, so there is no default age
+                let older_than = match (
+                    cli.value("--older-than"),
+                    config.app_config.alerts.retain_resolved_days,
+                ) {
+                    (Some(age), _) => age.to_string(),
+                    (None, 0) => {
+                        eprintln!(
+                            "{}",
+                            "retain_resolved_days is 0 (keep forever); pass --older-than <age> to prune"
+                                .red()
+                        );
+                        process::exit(1);
+                    }
+                    (None, days) => format!("{}d", days),
+                };
+                let result = prune_alerts_cmd(&older_than, &config);
+                return audited("barks prune", &older_than, &config, result);
+            }
+            let positional = cli.positionals();
+            let status_filter = positional
+                .first()
+                .and_then(|status| AlertStatus::from_name(status));
+            let view = if cli.has("--count") {
+                AlertView::Count
+            } else if cli.has("--compact") {
+                AlertView::Compact
             } else {
-                None
+                AlertView::Table
+            };
+            let since = match cli.value("--since") {
+                Some(value) => Some(alerts::parse_since(value, chrono::Utc::now())?),
+                None => None,
             };
-            list_alerts(status_filter)
+            let metadata = cli
+                .values("--where")
+                .into_iter()
+                .map(MetadataMatcher::parse)
+                .collect::<Result<Vec<_>>>()?;
+            if cli.has("--watch") {
+                let interval = cli
+                    .value("--watch")
+                    .map_or(Ok(DEFAULT_BARKS_WATCH_INTERVAL), str::parse::<u64>)
+                    .context("Watch interval must be a number of seconds")?
+                    .max(1);
+                // Watching is for incidents, so it shows firing barks unless told otherwise
+                let status_filter = status_filter.or(Some(AlertStatus::Firing));
+                return watch_alerts(status_filter, since, &metadata, interval, &config);
+            }
+            list_alerts(status_filter, since, &metadata, view, &config)
         }
         "bark" | "alert" => {
-            if args.len() < 3 {
+            if words.len() < 2 {
                 eprintln!("{}", "Usage: catdog bark <bark_id>".red());
                 process::exit(1);
             }
-            show_alert(&args[2])
+            show_alert(&words[1], &config)
         }
         "ack" | "acknowledge" | "pet" => {
-            if args.len() < 3 {
-                eprintln!("{}", "Usage: catdog ack <bark_id>".red());
+            if let Some(filter) = bulk_alert_filter(&cli)? {
+                let result = bulk_alert_cmd(BulkAlertAction::Acknowledge, &filter, &cli, &config);
+                return audited("ack", &bulk_alert_target(&cli), &config, result);
+            }
+            let positional = cli.positionals();
+            if positional.is_empty() {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog ack <bark_id> | --all | --severity <level> | --status <status>"
+                        .red()
+                );
                 process::exit(1);
             }
-            acknowledge_alert(&args[2])
+            audited(
+                "ack",
+                &positional[0],
+                &config,
+                acknowledge_alert(&positional[0], &config),
+            )
         }
         "resolve" | "quiet" => {
-            if args.len() < 3 {
-                eprintln!("{}", "Usage: catdog resolve <bark_id>".red());
+            if let Some(filter) = bulk_alert_filter(&cli)? {
+                let result = bulk_alert_cmd(BulkAlertAction::Resolve, &filter, &cli, &config);
+                return audited("resolve", &bulk_alert_target(&cli), &config, result);
+            }
+            let positional = cli.positionals();
+            // Bark IDs are UUIDs, so anything shaped like a device spec is a device lookup
+            if command == "resolve" && positional.first().is_some_and(|id| is_device_spec(id)) {
+                return resolve_cmd(&positional[0], &config);
+            }
+            if positional.is_empty() {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog resolve <bark_id|UUID=...|LABEL=...|PARTUUID=...|/dev/...> | --all | --severity <level> | --status <status>"
+                        .red()
+                );
                 process::exit(1);
             }
-            resolve_alert(&args[2])
+            audited(
+                "resolve",
+                &positional[0],
+                &config,
+                resolve_alert(&positional[0], &config),
+            )
         }
         "silence" | "hush" => {
-            if args.len() < 3 {
+            if words.len() < 2 {
                 eprintln!("{}", "Usage: catdog silence <bark_id>".red());
                 process::exit(1);
             }
-            silence_alert(&args[2])
+            audited(
+                "silence",
+                &words[1],
+                &config,
+                silence_alert(&words[1], &config),
+            )
         }
         // Corpus commands
         "corpus" => {
-            if args.len() < 3 {
-                eprintln!("{}", "Usage: catdog corpus <ingest|search|stats>".red());
+            if words.len() < 2 {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog corpus <ingest|list|remove|search|stats|recommend|export|import>".red()
+                );
                 process::exit(1);
             }
-            match args[2].as_str() {
+            match words[1].as_str() {
                 "ingest" => {
-                    if args.len() < 4 {
+                    if words.len() < 3 {
                         eprintln!("{}", "Usage: catdog corpus ingest <file>".red());
                         process::exit(1);
                     }
-                    corpus_ingest(&args[3])
+                    audited(
+                        "corpus ingest",
+                        &words[2],
+                        &config,
+                        corpus_ingest(&words[2]),
+                    )
                 }
                 "search" => {
-                    if args.len() < 4 {
-                        eprintln!("{}", "Usage: catdog corpus search <query>".red());
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog corpus search <query> [--limit N | --all]".red()
+                        );
+                        process::exit(1);
+                    }
+                    let query = words[2..].join(" ");
+                    corpus_search(
+                        &query,
+                        list_limit(&cli, Some(corpus::DEFAULT_SEARCH_RESULTS))?,
+                    )
+                }
+                "list" | "ls" => corpus_list(),
+                "remove" | "rm" => {
+                    if words.len() < 3 {
+                        eprintln!("{}", "Usage: catdog corpus remove <config_id>".red());
                         process::exit(1);
                     }
-                    let query = args[3..].join(" ");
-                    corpus_search(&query)
+                    audited(
+                        "corpus remove",
+                        &words[2],
+                        &config,
+                        corpus_remove(&words[2]),
+                    )
                 }
                 "stats" => corpus_stats(),
+                "recommend" => {
+                    if words.len() < 3 {
+                        eprintln!("{}", "Usage: catdog corpus recommend <device>".red());
+                        process::exit(1);
+                    }
+                    corpus_recommend(&words[2])
+                }
+                "export" => {
+                    if words.len() < 3 {
+                        eprintln!("{}", "Usage: catdog corpus export <file.tar.gz>".red());
+                        process::exit(1);
+                    }
+                    corpus_export(&words[2])
+                }
+                "import" => {
+                    if words.len() < 3 {
+                        eprintln!("{}", "Usage: catdog corpus import <file.tar.gz>".red());
+                        process::exit(1);
+                    }
+                    audited(
+                        "corpus import",
+                        &words[2],
+                        &config,
+                        corpus_import(&words[2]),
+                    )
+                }
                 _ => {
                     eprintln!(
                         "{}",
-                        "Unknown corpus command. Try: ingest, search, stats".red()
+                        "Unknown corpus command. Try: ingest, list, remove, search, stats, recommend, export, import".red()
                     );
                     process::exit(1);
                 }
@@ -274,61 +748,155 @@ fn run() -> Result<()> {
         }
         // Service management commands
         "service" | "svc" => {
-            if args.len() < 3 {
+            if words.len() < 2 {
                 eprintln!(
                     "{}",
-                    "Usage: catdog service <start|stop|restart|enable|disable|status|list>".red()
+                    "Usage: catdog service <start|stop|restart|enable|disable|mask|unmask|status|list|logs>"
+                        .red()
                 );
                 process::exit(1);
             }
-            match args[2].as_str() {
+            match words[1].as_str() {
                 "start" => {
-                    if args.len() < 4 {
-                        eprintln!("{}", "Usage: catdog service start <service>".red());
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service start <service> [service2...]".red()
+                        );
                         process::exit(1);
                     }
-                    service_start(&args[3], &config)
+                    finish_audited(
+                        "service start",
+                        &words[2..].join(" "),
+                        service_start(&words[2..], &config),
+                        &config,
+                    )
                 }
                 "stop" => {
-                    if args.len() < 4 {
-                        eprintln!("{}", "Usage: catdog service stop <service>".red());
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service stop <service> [service2...]".red()
+                        );
                         process::exit(1);
                     }
-                    service_stop(&args[3], &config)
+                    finish_audited(
+                        "service stop",
+                        &words[2..].join(" "),
+                        service_stop(&words[2..], &config),
+                        &config,
+                    )
                 }
                 "restart" => {
-                    if args.len() < 4 {
-                        eprintln!("{}", "Usage: catdog service restart <service>".red());
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service restart <service> [service2...]".red()
+                        );
                         process::exit(1);
                     }
-                    service_restart(&args[3], &config)
+                    finish_audited(
+                        "service restart",
+                        &words[2..].join(" "),
+                        service_restart(&words[2..], &config),
+                        &config,
+                    )
                 }
                 "enable" => {
-                    if args.len() < 4 {
-                        eprintln!("{}", "Usage: catdog service enable <service>".red());
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service enable <service> [service2...]".red()
+                        );
                         process::exit(1);
                     }
-                    service_enable(&args[3], &config)
+                    finish_audited(
+                        "service enable",
+                        &words[2..].join(" "),
+                        service_enable(&words[2..], &config),
+                        &config,
+                    )
                 }
                 "disable" => {
-                    if args.len() < 4 {
-                        eprintln!("{}", "Usage: catdog service disable <service>".red());
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service disable <service> [service2...]".red()
+                        );
+                        process::exit(1);
+                    }
+                    finish_audited(
+                        "service disable",
+                        &words[2..].join(" "),
+                        service_disable(&words[2..], &config),
+                        &config,
+                    )
+                }
+                "mask" => {
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service mask <service> [service2...] [--force]".red()
+                        );
+                        process::exit(1);
+                    }
+                    let force = cli.has("--force");
+                    finish_audited(
+                        "service mask",
+                        &words[2..].join(" "),
+                        service_mask(&words[2..], force, &config),
+                        &config,
+                    )
+                }
+                "unmask" => {
+                    if words.len() < 3 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service unmask <service> [service2...]".red()
+                        );
                         process::exit(1);
                     }
-                    service_disable(&args[3], &config)
+                    finish_audited(
+                        "service unmask",
+                        &words[2..].join(" "),
+                        service_unmask(&words[2..], &config),
+                        &config,
+                    )
                 }
                 "status" => {
-                    if args.len() < 4 {
+                    if words.len() < 3 {
                         eprintln!("{}", "Usage: catdog service status <service>".red());
                         process::exit(1);
                     }
-                    service_status(&args[3], &config)
+                    service_status(&words[2], &config)
+                }
+                "list" => service_list(
+                    cli.has("--enabled-only"),
+                    list_limit(&cli, Some(DEFAULT_LIST_LIMIT))?,
+                    &config,
+                ),
+                "logs" => {
+                    let positional = cli.positionals();
+                    if positional.len() < 2 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog service logs <service> [--lines N] [--follow]".red()
+                        );
+                        process::exit(1);
+                    }
+                    let lines = match cli.value("--lines") {
+                        Some(value) => value
+                            .parse::<usize>()
+                            .with_context(|| format!("Invalid --lines value: {}", value))?,
+                        None => service::DEFAULT_LOG_LINES,
+                    };
+                    let follow = cli.has("--follow");
+                    service_logs(&positional[1], lines, follow, &config)
                 }
-                "list" => service_list(&config),
                 _ => {
                     eprintln!(
                         "{}",
-                        "Unknown service command. Try: start, stop, restart, enable, disable, status, list"
+                        "Unknown service command. Try: start, stop, restart, enable, disable, mask, unmask, status, list, logs"
                             .red()
                     );
                     process::exit(1);
@@ -336,61 +904,119 @@ fn run() -> Result<()> {
             }
         }
         // System information command
-        "info" | "sysinfo" => sys_info(&config),
+        "info" | "sysinfo" => {
+            if cli.has("--top-processes") {
+                let count = cli
+                    .value("--top-processes")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(10);
+                let sort = match cli.value("--sort") {
+                    Some(name) => match sysinfo::ProcessSort::from_name(name) {
+                        Some(sort) => sort,
+                        None => {
+                            eprintln!(
+                                "{}",
+                                "Usage: catdog info --top-processes [N] [--sort cpu|mem]".red()
+                            );
+                            process::exit(1);
+                        }
+                    },
+                    None => sysinfo::ProcessSort::Memory,
+                };
+                top_processes_cmd(count, sort, &config)
+            } else {
+                sys_info(&config)
+            }
+        }
+        "uptime" => quick_uptime(&config),
+        "hostname" => quick_hostname(&config),
+        "kernel" => quick_kernel(&config),
+        "doctor" => doctor_cmd(&config),
         // Package management commands
         "pkg" | "package" => {
-            if args.len() < 3 {
+            if words.len() < 2 {
                 eprintln!(
                     "{}",
-                    "Usage: catdog pkg <install|remove|update|upgrade|search|list|info>".red()
+                    "Usage: catdog pkg <install|remove|update|upgrade|upgradable|snapshot|snapshot-diff|search|list|info>"
+                        .red()
                 );
                 process::exit(1);
             }
-            match args[2].as_str() {
+            match words[1].as_str() {
                 "install" | "add" => {
-                    if args.len() < 4 {
+                    if words.len() < 3 {
                         eprintln!(
                             "{}",
                             "Usage: catdog pkg install <package1> [package2...]".red()
                         );
                         process::exit(1);
                     }
-                    let packages: Vec<String> = args[3..].to_vec();
-                    pkg_install(&packages, &config)
+                    finish_audited(
+                        "pkg install",
+                        &words[2..].join(" "),
+                        pkg_install(&words[2..], &config),
+                        &config,
+                    )
                 }
                 "remove" | "uninstall" | "delete" => {
-                    if args.len() < 4 {
+                    if words.len() < 3 {
                         eprintln!(
                             "{}",
                             "Usage: catdog pkg remove <package1> [package2...]".red()
                         );
                         process::exit(1);
                     }
-                    let packages: Vec<String> = args[3..].to_vec();
-                    pkg_remove(&packages, &config)
+                    finish_audited(
+                        "pkg remove",
+                        &words[2..].join(" "),
+                        pkg_remove(&words[2..], &config),
+                        &config,
+                    )
+                }
+                "update" | "refresh" => {
+                    audited("pkg update", "package index", &config, pkg_update(&config))
+                }
+                "upgrade" => {
+                    if cli.has("--plan") {
+                        pkg_upgrade_plan(&config)
+                    } else {
+                        audited("pkg upgrade", "all packages", &config, pkg_upgrade(&config))
+                    }
                 }
-                "update" | "refresh" => pkg_update(&config),
-                "upgrade" => pkg_upgrade(&config),
                 "search" => {
-                    if args.len() < 4 {
+                    if words.len() < 3 {
                         eprintln!("{}", "Usage: catdog pkg search <query>".red());
                         process::exit(1);
                     }
-                    let query = args[3..].join(" ");
-                    pkg_search(&query, &config)
+                    let query = words[2..].join(" ");
+                    pkg_search(&query, list_limit(&cli, Some(DEFAULT_LIST_LIMIT))?, &config)
+                }
+                "list" | "installed" if cli.has("--upgradable") => pkg_upgradable(&config),
+                // Listing installed packages has always shown all of them unless limited
+                "list" | "installed" => pkg_list(list_limit(&cli, None)?, &config),
+                "upgradable" | "outdated" => pkg_upgradable(&config),
+                "snapshot" => pkg_snapshot(&config),
+                "snapshot-diff" => {
+                    if words.len() < 4 {
+                        eprintln!(
+                            "{}",
+                            "Usage: catdog pkg snapshot-diff <snapshot1> <snapshot2>".red()
+                        );
+                        process::exit(1);
+                    }
+                    pkg_snapshot_diff(&words[2], &words[3], &config)
                 }
-                "list" | "installed" => pkg_list(&config),
                 "info" | "check" => {
-                    if args.len() < 4 {
+                    if words.len() < 3 {
                         eprintln!("{}", "Usage: catdog pkg info <package>".red());
                         process::exit(1);
                     }
-                    pkg_info(&args[3], &config)
+                    pkg_info(&words[2], &config)
                 }
                 _ => {
                     eprintln!(
                         "{}",
-                        "Unknown package command. Try: install, remove, update, upgrade, search, list, info"
+                        "Unknown package command. Try: install, remove, update, upgrade, upgradable, snapshot, snapshot-diff, search, list, info"
                             .red()
                     );
                     process::exit(1);
@@ -398,20 +1024,92 @@ fn run() -> Result<()> {
             }
         }
         "diff" => {
-            if args.len() < 4 {
-                eprintln!("{}", "Usage: catdog diff <file1> <file2>".red());
+            let ignore_comments = cli.has("--ignore-comments");
+            let current = cli.has("--current");
+            let context = cli
+                .value("-U")
+                .map(|n| n.parse::<usize>())
+                .transpose()
+                .context("-U takes a number of context lines")?;
+            let width = cli
+                .value("--width")
+                .map(|n| n.parse::<usize>())
+                .transpose()
+                .context("--width takes a number of columns")?;
+            let mode = if cli.has("--semantic") {
+                diff::DiffMode::Semantic
+            } else if cli.has("--side-by-side") {
+                diff::DiffMode::SideBySide {
+                    width: width.unwrap_or_else(diff::terminal_width),
+                }
+            } else if cli.has("--unified") || context.is_some() {
+                diff::DiffMode::Unified {
+                    context: context.unwrap_or(diff::DEFAULT_UNIFIED_CONTEXT),
+                }
+            } else {
+                diff::DiffMode::Pretty
+            };
+            let positional = cli.positionals();
+            if positional.len() < if current { 1 } else { 2 } {
+                eprintln!(
+                    "{}",
+                    "Usage: catdog diff [--semantic | --unified [-U <n>] | --side-by-side [--width <n>]] <file1> <file2> [--ignore-comments]"
+                        .red()
+                );
                 eprintln!(
                     "       catdog diff --current <file>   {}",
                     "(compare with /etc/fstab)".truecolor(150, 150, 150)
                 );
                 process::exit(1);
             }
-            if args[2] == "--current" {
-                diff::compare_with_current(&args[3])
+            if current {
+                diff::compare_with_current(&positional[0], ignore_comments, mode)
             } else {
-                diff::diff_files(&args[2], &args[3])
+                diff::diff_files(&positional[0], &positional[1], ignore_comments, mode)
             }
         }
+        "config" => match cli.word(1) {
+            Some("show") => config_show(&config),
+            Some("path") => {
+                println!("{}", Config::display_path());
+                Ok(())
+            }
+            Some("edit") => audited(
+                "config edit",
+                &Config::display_path(),
+                &config,
+                config_edit(),
+            ),
+            _ => {
+                eprintln!("{}", "Usage: catdog config <show|path|edit>".red());
+                process::exit(1);
+            }
+        },
+        "audit" => match cli.word(1) {
+            Some("tail") => {
+                let count = match words.get(2) {
+                    Some(n) => n
+                        .parse::<usize>()
+                        .with_context(|| format!("Invalid entry count: {}", n))?,
+                    None => audit::DEFAULT_TAIL,
+                };
+                audit_tail_cmd(count, &config)
+            }
+            _ => {
+                eprintln!("{}", "Usage: catdog audit tail [n]".red());
+                process::exit(1);
+            }
+        },
+        "completions" => match cli.word(1).and_then(completions::Shell::from_name) {
+            Some(shell) => {
+                print!("{}", completions::script(shell));
+                Ok(())
+            }
+            None => {
+                eprintln!("{}", "Usage: catdog completions <bash|zsh|fish>".red());
+                process::exit(1);
+            }
+        },
         "version" | "--version" | "-V" => {
             print_version();
             Ok(())
@@ -432,19 +1130,36 @@ fn run() -> Result<()> {
 
 fn cat_fstab() -> Result<()> {
     let fstab_path = "/etc/fstab";
-    let contents =
-        fs::read_to_string(fstab_path).with_context(|| format!("Failed to read {}", fstab_path))?;
+    let contents = fstab::read_fstab(fstab_path)?;
     print!("{}", contents);
     Ok(())
 }
 
-fn dog_fstab() -> Result<()> {
+fn dog_fstab(config: &CliConfig) -> Result<()> {
+    let fstab_path = "/etc/fstab";
+    let parsed = load_fstab(fstab_path)?;
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&fstab_json(fstab_path, &parsed))?
+        );
+        return Ok(());
+    }
+    if config.output == OutputFormat::Plain {
+        print_plain_entries(&parsed.entries);
+        return Ok(());
+    }
+
     println!("{} Fetching and parsing /etc/fstab...\n", "🐕".bold());
 
-    let entries = parse_fstab()?;
+    let entries = &parsed.entries;
 
     if entries.is_empty() {
         println!("{}", "No entries found in /etc/fstab".yellow());
+        for warning in &parsed.warnings {
+            warning.display();
+        }
         return Ok(());
     }
 
@@ -459,7 +1174,7 @@ fn dog_fstab() -> Result<()> {
     );
     println!("{}", "=".repeat(120).bright_black());
 
-    for entry in &entries {
+    for entry in entries {
         let device = if entry.device.starts_with("UUID=") {
             entry.device.bright_yellow()
         } else if entry.device.starts_with("/dev/") {
@@ -490,80 +1205,232 @@ fn dog_fstab() -> Result<()> {
         "🐕".bold(),
         entries.len().to_string().green().bold()
     );
-    Ok(())
-}
 
-fn parse_fstab() -> Result<Vec<FstabEntry>> {
-    let fstab_path = "/etc/fstab";
-    parse_fstab_from_path(fstab_path)
+    for warning in &parsed.warnings {
+        warning.display();
+    }
+    Ok(())
 }
 
 fn parse_fstab_from_path(path: &str) -> Result<Vec<FstabEntry>> {
-    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+    let parsed = load_fstab(path)?;
 
-    let mut entries = Vec::new();
+    for warning in &parsed.warnings {
+        warning.display();
+    }
 
-    for (line_num, line) in contents.lines().enumerate() {
-        let trimmed = line.trim();
+    Ok(parsed.entries)
+}
 
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
-        }
+/// Read and parse an fstab, leaving it to the caller to report warnings
+fn load_fstab(path: &str) -> Result<fstab::ParsedFstab> {
+    let contents = fstab::read_fstab(path)?;
+    Ok(fstab::parse_entries(&contents))
+}
 
-        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+/// `--output plain` for fstab entries: the six fields, tab-separated
+fn print_plain_entries(entries: &[FstabEntry]) {
+    for entry in entries {
+        println!("{}", entry.fields().join("\t"));
+    }
+}
 
-        if parts.len() < 6 {
-            eprintln!(
-                "{} Line {}: Expected 6 fields, found {} - skipping",
-                "Warning:".yellow(),
-                line_num + 1,
-                parts.len()
-            );
-            continue;
-        }
+/// One `--output plain` record: tab-separated fields, `-` for a missing value
+///
+/// Tabs and newlines inside a value become spaces so every record stays on
+/// one line with the same number of fields.
+fn plain_record(fields: &[Option<&str>]) -> String {
+    fields
+        .iter()
+        .map(|field| match field {
+            Some(value) if !value.is_empty() => value.replace(['\t', '\n', '\r'], " "),
+            _ => "-".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
 
-        entries.push(FstabEntry {
-            device: parts[0].to_string(),
-            mount_point: parts[1].to_string(),
-            fs_type: parts[2].to_string(),
-            options: parts[3].to_string(),
-            dump: parts[4].to_string(),
-            pass: parts[5].to_string(),
-        });
+/// Flatten a JSON value into `key<TAB>value` pairs, nesting keys with dots
+/// and array positions, e.g. `cpu.per_core.0`
+fn plain_pairs(prefix: &str, value: &serde_json::Value, pairs: &mut Vec<(String, String)>) {
+    let key = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        }
+    };
+    match value {
+        serde_json::Value::Object(map) => {
+            for (name, value) in map {
+                plain_pairs(&key(name), value, pairs);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                plain_pairs(&key(&index.to_string()), value, pairs);
+            }
+        }
+        serde_json::Value::String(s) => pairs.push((prefix.to_string(), s.clone())),
+        serde_json::Value::Null => pairs.push((prefix.to_string(), String::new())),
+        other => pairs.push((prefix.to_string(), other.to_string())),
     }
+}
 
-    Ok(entries)
+/// JSON view of a parsed fstab used by `dog` and `list`
+fn fstab_json(path: &str, parsed: &fstab::ParsedFstab) -> serde_json::Value {
+    serde_json::json!({
+        "path": path,
+        "count": parsed.entries.len(),
+        "entries": parsed.entries,
+        "warnings": parsed.warnings,
+    })
 }
 
-fn list_mounts() -> Result<()> {
-    let entries = parse_fstab()?;
+/// Print the summary line for a write command and exit non-zero if anything failed
+fn finish_outcome(outcome: Result<CommandOutcome>, config: &CliConfig) -> Result<()> {
+    let outcome = outcome?;
 
-    if entries.is_empty() {
-        println!("{}", "No mount points found".yellow());
-        return Ok(());
+    if !config.quiet && !config.json_output() {
+        outcome.display();
     }
 
-    println!("{}\n", "Mount points in /etc/fstab:".cyan().bold());
-    for entry in entries {
-        println!(
-            "  {} {} {} {}",
-            entry.device.bright_blue(),
-            "->".bright_black(),
-            entry.mount_point.bright_green(),
+    if outcome.failed > 0 {
+        process::exit(outcome.exit_code());
+    }
+
+    Ok(())
+}
+
+/// Record a mutating command in the audit log, passing its result through
+///
+/// Failing to write the log is reported but doesn't fail the command itself.
+fn audited<T>(action: &str, target: &str, config: &CliConfig, result: Result<T>) -> Result<T> {
+    warn_on_audit_failure(audit::log_action(
+        action,
+        target,
+        &config.args,
+        &result,
+        config.dry_run,
+    ));
+    result
+}
+
+/// `finish_outcome` for batch commands that also go to the audit log
+fn finish_audited(
+    action: &str,
+    target: &str,
+    outcome: Result<CommandOutcome>,
+    config: &CliConfig,
+) -> Result<()> {
+    warn_on_audit_failure(audit::log_outcome(
+        action,
+        target,
+        &config.args,
+        &outcome,
+        config.dry_run,
+    ));
+    finish_outcome(outcome, config)
+}
+
+fn warn_on_audit_failure(result: Result<()>) {
+    if let Err(e) = result {
+        eprintln!("{} Failed to write audit log: {:#}", "Warning:".yellow(), e);
+    }
+}
+
+/// Collect every value given for a flag, e.g. `--add a --add b,c` yields `[a, b, c]`
+fn flag_values(cli: &cli::ParsedArgs, flag: &str) -> Vec<String> {
+    cli.values(flag)
+        .into_iter()
+        .flat_map(fstab::split_options)
+        .collect()
+}
+
+/// Filesystem types named with `--fs` (or `--filesystem`), comma-separated or repeated
+fn fs_filter(cli: &cli::ParsedArgs) -> Vec<String> {
+    let mut types = flag_values(cli, "--fs");
+    types.extend(flag_values(cli, "--filesystem"));
+    types
+}
+
+/// Whether a device passes a `--fs` filter; an empty filter lets everything through
+fn matches_fs_filter(device: &BlockDevice, fs_types: &[String]) -> bool {
+    fs_types.is_empty()
+        || device
+            .fs_type
+            .as_deref()
+            .is_some_and(|fs| fs_types.iter().any(|t| t.eq_ignore_ascii_case(fs)))
+}
+
+fn list_mounts(config: &CliConfig) -> Result<()> {
+    let fstab_path = "/etc/fstab";
+    let parsed = load_fstab(fstab_path)?;
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&fstab_json(fstab_path, &parsed))?
+        );
+        return Ok(());
+    }
+    if config.output == OutputFormat::Plain {
+        print_plain_entries(&parsed.entries);
+        return Ok(());
+    }
+
+    if parsed.entries.is_empty() {
+        println!("{}", "No mount points found".yellow());
+    } else {
+        println!("{}\n", "Mount points in /etc/fstab:".cyan().bold());
+    }
+    for entry in &parsed.entries {
+        println!(
+            "  {} {} {} {}",
+            entry.device.bright_blue(),
+            "->".bright_black(),
+            entry.mount_point.bright_green(),
             format!("({})", entry.fs_type).truecolor(180, 180, 180)
         );
     }
+
+    for warning in &parsed.warnings {
+        warning.display();
+    }
     Ok(())
 }
 
-fn find_entry(search: &str) -> Result<()> {
-    let entries = parse_fstab()?;
-    let mut found = Vec::new();
+fn find_entry(search: &str, config: &CliConfig) -> Result<()> {
+    let fstab_path = "/etc/fstab";
+    let parsed = load_fstab(fstab_path)?;
+    let found: Vec<&FstabEntry> = parsed
+        .entries
+        .iter()
+        .filter(|entry| entry.device.contains(search) || entry.mount_point.contains(search))
+        .collect();
 
-    for entry in entries {
-        if entry.device.contains(search) || entry.mount_point.contains(search) {
-            found.push(entry);
+    match config.output {
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "path": fstab_path,
+                "search": search,
+                "count": found.len(),
+                "entries": found,
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+            return Ok(());
         }
+        OutputFormat::Plain => {
+            for entry in found {
+                println!("{}", entry.fields().join("\t"));
+            }
+            return Ok(());
+        }
+        OutputFormat::Table => {}
+    }
+
+    for warning in &parsed.warnings {
+        warning.display();
     }
 
     if found.is_empty() {
@@ -600,141 +1467,849 @@ fn find_entry(search: &str) -> Result<()> {
     Ok(())
 }
 
-fn validate_fstab() -> Result<()> {
-    println!("{} Validating /etc/fstab...\n", "🔍".bold());
+/// How serious a validate finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Critical,
+    Warning,
+    Info,
+}
 
-    let entries = parse_fstab()?;
-    let mut issues = 0;
-    let mut warnings = 0;
+/// A single problem (or, for live checks, status) reported by `validate`
+#[derive(Debug, Clone, Serialize)]
+struct Finding {
+    /// Line in the fstab file
+    line: usize,
+    /// 1-based position among the parsed entries
+    entry: usize,
+    severity: Severity,
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggestion: Option<String>,
+    /// Comes from comparing against the live mount table rather than the file itself
+    live: bool,
+    /// Values in `message` to pick out on screen, in the order they appear
+    #[serde(skip)]
+    highlights: Vec<String>,
+    /// Human line in place of "Entry N: message", for findings about several lines
+    #[serde(skip)]
+    heading: Option<String>,
+}
 
-    // Check if fstab is empty
-    if entries.is_empty() {
-        println!(
-            "{}",
-            "⚠️  /etc/fstab is empty or contains no valid entries".yellow()
-        );
-        return Ok(());
+impl Finding {
+    fn new(index: usize, entry: &FstabEntry, severity: Severity, code: &'static str) -> Self {
+        Finding {
+            line: entry.line_number,
+            entry: index + 1,
+            severity,
+            code,
+            message: String::new(),
+            suggestion: None,
+            live: false,
+            highlights: Vec::new(),
+            heading: None,
+        }
+    }
+
+    fn message(mut self, message: String) -> Self {
+        self.message = message;
+        self
+    }
+
+    fn highlight(mut self, values: &[&str]) -> Self {
+        self.highlights = values.iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    fn marker(&self) -> ColoredString {
+        match (self.live, self.severity, self.code) {
+            (true, Severity::Info, "MOUNTED") => "✓".green(),
+            (true, Severity::Critical | Severity::Warning, _) => "🔥".red(),
+            (_, Severity::Critical, _) => "❌".red(),
+            (_, Severity::Warning, "MISSING_MOUNT_DIR") => "ℹ️ ".blue(),
+            (_, Severity::Warning, _) => "⚠️ ".yellow(),
+            (_, Severity::Info, _) => "ℹ️ ".blue(),
+        }
+    }
+
+    /// `message` with the highlighted values in bright white
+    fn highlighted_message(&self) -> String {
+        let mut text = String::new();
+        let mut rest = self.message.as_str();
+        for value in &self.highlights {
+            if let Some(at) = rest.find(value.as_str()) {
+                text.push_str(&rest[..at]);
+                text.push_str(&value.bright_white().to_string());
+                rest = &rest[at + value.len()..];
+            }
+        }
+        text.push_str(rest);
+        text
+    }
+
+    fn display(&self, show_suggestion: bool) {
+        match &self.heading {
+            Some(heading) => println!("{} {}", self.marker(), heading),
+            None => println!(
+                "{} Entry {}: {}",
+                self.marker(),
+                self.entry,
+                self.highlighted_message()
+            ),
+        }
+        if let Some(suggestion) = self.suggestion.as_ref().filter(|_| show_suggestion) {
+            println!("   {} {}", "Suggestion:".yellow().bold(), suggestion);
+        }
+    }
+}
+
+/// Totals shown in the validate summary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+struct FindingCounts {
+    critical: usize,
+    warning: usize,
+    info: usize,
+    /// Live-check problems, kept apart from the static file checks
+    active: usize,
+}
+
+impl FindingCounts {
+    fn from_findings(findings: &[Finding]) -> Self {
+        let mut counts = FindingCounts::default();
+        for finding in findings {
+            match (finding.live, finding.severity) {
+                (_, Severity::Info) => counts.info += 1,
+                (true, _) => counts.active += 1,
+                (false, Severity::Critical) => counts.critical += 1,
+                (false, Severity::Warning) => counts.warning += 1,
+            }
+        }
+        counts
     }
+}
+
+/// Static checks on the fstab contents
+fn check_entries(entries: &[FstabEntry]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let index_of = |entry: &FstabEntry| {
+        entries
+            .iter()
+            .position(|e| std::ptr::eq(e, entry))
+            .unwrap_or(0)
+    };
 
-    // Check for duplicate mount points
+    // Check for duplicate mount points (different devices are reported as conflicts below)
     let mut mount_points = std::collections::HashSet::new();
     for (i, entry) in entries.iter().enumerate() {
-        if entry.mount_point != "none" && entry.mount_point != "swap" {
-            if !mount_points.insert(&entry.mount_point) {
-                println!(
-                    "{} Entry {}: Duplicate mount point '{}'",
-                    "⚠️ ".yellow(),
-                    i + 1,
-                    entry.mount_point.bright_white()
-                );
-                issues += 1;
-            }
+        if entry.mount_point != "none"
+            && entry.mount_point != "swap"
+            && !mount_points.insert((&entry.mount_point, &entry.device))
+        {
+            findings.push(
+                Finding::new(i, entry, Severity::Warning, "DUP_MOUNT")
+                    .message(format!("Duplicate mount point '{}'", entry.mount_point))
+                    .highlight(&[&entry.mount_point]),
+            );
+        }
+    }
+
+    // Check for different devices competing for the same mount point
+    for (first, second) in fstab::conflicting_mounts(entries) {
+        let mut finding = Finding::new(
+            index_of(second),
+            second,
+            Severity::Critical,
+            "MOUNT_CONFLICT",
+        )
+        .message(format!(
+            "'{}' and '{}' are both mounted at '{}' (lines {} and {})",
+            first.device, second.device, first.mount_point, first.line_number, second.line_number
+        ));
+        finding.heading = Some(format!(
+            "Lines {} and {}: '{}' and '{}' are both mounted at '{}'",
+            first.line_number,
+            second.line_number,
+            first.device.bright_white(),
+            second.device.bright_white(),
+            first.mount_point.bright_white()
+        ));
+        findings.push(finding);
+    }
+
+    // Check that parent mount points come before the mount points nested under them
+    let ordering_problems = fstab::ordering_problems(entries);
+    if !ordering_problems.is_empty() {
+        let order: Vec<&str> = fstab::suggested_order(entries)
+            .iter()
+            .map(|e| e.mount_point.as_str())
+            .collect();
+        for problem in &ordering_problems {
+            let mut finding = Finding::new(
+                index_of(problem.child),
+                problem.child,
+                Severity::Warning,
+                "MOUNT_ORDER",
+            )
+            .message(format!(
+                "'{}' is listed before its parent '{}' (line {})",
+                problem.child.mount_point, problem.parent.mount_point, problem.parent.line_number
+            ));
+            finding.heading = Some(format!(
+                "Line {}: '{}' is listed before its parent '{}' (line {})",
+                problem.child.line_number,
+                problem.child.mount_point.bright_white(),
+                problem.parent.mount_point.bright_white(),
+                problem.parent.line_number
+            ));
+            finding.suggestion = Some(format!(
+                "list mount points in this order: {}",
+                order.join(", ")
+            ));
+            findings.push(finding);
         }
     }
 
     // Check each entry for common issues
     for (i, entry) in entries.iter().enumerate() {
-        // Check root filesystem pass value
-        if entry.mount_point == "/" && entry.pass != "1" {
-            println!(
-                "{} Entry {}: Root filesystem should have pass=1, found pass={}",
-                "⚠️ ".yellow(),
-                i + 1,
-                entry.pass.bright_white()
+        let finding = |severity, code| Finding::new(i, entry, severity, code);
+
+        // Check root filesystem pass value; XFS and btrfs roots are never
+        // fsck'd at boot, so pass 0 is right for them
+        let pass_zero_root =
+            matches!(entry.fs_type.as_str(), "xfs" | "btrfs") && entry.pass.parse::<u32>() == Ok(0);
+        if entry.mount_point == "/" && entry.pass != "1" && !pass_zero_root {
+            let root_pass = if entry.has_default_fields() {
+                finding(Severity::Warning, "ROOT_PASS").message(
+                    "Root filesystem should have pass=1, pass is omitted and defaults to 0"
+                        .to_string(),
+                )
+            } else {
+                finding(Severity::Warning, "ROOT_PASS")
+                    .message(format!(
+                        "Root filesystem should have pass=1, found pass={}",
+                        entry.pass
+                    ))
+                    .highlight(&[&entry.pass])
+            };
+            findings.push(root_pass);
+        } else if entry.has_default_fields() {
+            findings.push(
+                finding(Severity::Info, "DEFAULT_DUMP_PASS")
+                    .message(format!(
+                        "{} leaves out dump/pass, which default to 0",
+                        entry.mount_point
+                    ))
+                    .highlight(&[&entry.mount_point]),
             );
-            issues += 1;
         }
 
         // Check mount point format
-        if entry.mount_point != "none" && entry.mount_point != "swap" {
-            if !entry.mount_point.starts_with('/') {
-                println!(
-                    "{} Entry {}: Mount point '{}' doesn't start with /",
-                    "❌".red(),
-                    i + 1,
-                    entry.mount_point.bright_white()
-                );
-                issues += 1;
-            }
+        if entry.mount_point != "none"
+            && entry.mount_point != "swap"
+            && !entry.mount_point.starts_with('/')
+        {
+            findings.push(
+                finding(Severity::Critical, "RELATIVE_MOUNT")
+                    .message(format!(
+                        "Mount point '{}' doesn't start with /",
+                        entry.mount_point
+                    ))
+                    .highlight(&[&entry.mount_point]),
+            );
         }
 
         // Check swap partition configuration
         if entry.fs_type == "swap" && entry.mount_point != "none" && entry.mount_point != "swap" {
-            println!(
-                "{} Entry {}: Swap partition should have mount point 'none' or 'swap'",
-                "⚠️ ".yellow(),
-                i + 1
-            );
-            issues += 1;
+            findings
+                .push(finding(Severity::Warning, "SWAP_MOUNT").message(
+                    "Swap partition should have mount point 'none' or 'swap'".to_string(),
+                ));
         }
 
         // Check for potentially dangerous options
         if entry.options.contains("noauto") && entry.mount_point == "/" {
-            println!(
-                "{} Entry {}: Root filesystem with 'noauto' option will not mount at boot!",
-                "❌".red(),
-                i + 1
-            );
-            issues += 1;
+            findings.push(finding(Severity::Critical, "ROOT_NOAUTO").message(
+                "Root filesystem with 'noauto' option will not mount at boot!".to_string(),
+            ));
         }
 
         // Check pass value validity
-        if let Err(_) = entry.pass.parse::<u32>() {
-            println!(
-                "{} Entry {}: Invalid pass value '{}' (should be 0, 1, or 2)",
-                "❌".red(),
-                i + 1,
-                entry.pass.bright_white()
+        if entry.pass.parse::<u32>().is_err() {
+            findings.push(
+                finding(Severity::Critical, "BAD_PASS")
+                    .message(format!(
+                        "Invalid pass value '{}' (should be 0, 1, or 2)",
+                        entry.pass
+                    ))
+                    .highlight(&[&entry.pass]),
             );
-            issues += 1;
         }
 
         // Check dump value validity
-        if let Err(_) = entry.dump.parse::<u32>() {
-            println!(
-                "{} Entry {}: Invalid dump value '{}' (should be 0 or 1)",
-                "⚠️ ".yellow(),
-                i + 1,
-                entry.dump.bright_white()
+        if entry.dump.parse::<u32>().is_err() {
+            findings.push(
+                finding(Severity::Warning, "BAD_DUMP")
+                    .message(format!(
+                        "Invalid dump value '{}' (should be 0 or 1)",
+                        entry.dump
+                    ))
+                    .highlight(&[&entry.dump]),
             );
-            warnings += 1;
         }
 
         // Warn about missing mount points
-        if entry.mount_point != "none" && entry.mount_point != "swap" {
-            if !Path::new(&entry.mount_point).exists() {
+        if entry.mount_point != "none"
+            && entry.mount_point != "swap"
+            && !Path::new(&entry.decoded_mount_point()).exists()
+        {
+            findings.push(
+                finding(Severity::Warning, "MISSING_MOUNT_DIR")
+                    .message(format!(
+                        "Mount point directory '{}' does not exist",
+                        entry.mount_point
+                    ))
+                    .highlight(&[&entry.mount_point]),
+            );
+        }
+    }
+
+    findings
+}
+
+/// Kernel disk names that are handed out in probe order and can change between boots
+const KERNEL_DEVICE_PREFIXES: &[&str] = &[
+    "/dev/sd",
+    "/dev/hd",
+    "/dev/vd",
+    "/dev/xvd",
+    "/dev/nvme",
+    "/dev/mmcblk",
+];
+
+/// Flag entries that name a disk by kernel name instead of UUID, LABEL or PARTUUID
+///
+/// `devices` is the discovered device list, used to suggest the UUID to switch
+/// to; it may be empty when discovery isn't available.
+fn check_device_names(entries: &[FstabEntry], devices: &[BlockDevice]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        if !KERNEL_DEVICE_PREFIXES
+            .iter()
+            .any(|prefix| entry.device.starts_with(prefix))
+        {
+            continue;
+        }
+
+        let mut finding = Finding::new(i, entry, Severity::Info, "UNSTABLE_DEVICE")
+            .message(format!(
+                "{} is a kernel device name and can point at a different disk after a reboot",
+                entry.device
+            ))
+            .highlight(&[&entry.device]);
+        finding.suggestion = Some(
+            match resolve_device(&entry.device, devices).and_then(|d| d.uuid) {
+                Some(uuid) => format!("use UUID={} instead of {}", uuid, entry.device),
+                None => "refer to the device by UUID=, LABEL= or PARTUUID=".to_string(),
+            },
+        );
+        findings.push(finding);
+    }
+
+    findings
+}
+
+/// Compare entries with what is actually mounted right now
+fn check_live_mounts(entries: &[FstabEntry], active: &[mount::ActiveMount]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.fs_type == "swap" || entry.mount_point == "none" {
+            continue;
+        }
+
+        let finding = |severity, code| Finding::new(i, entry, severity, code);
+        let mut finding = match mount::mount_state(entry, active) {
+            mount::MountState::Mounted => finding(Severity::Info, "MOUNTED")
+                .message(format!("{} is mounted", entry.mount_point))
+                .highlight(&[&entry.mount_point]),
+            mount::MountState::OtherDevice(other) => finding(Severity::Warning, "OTHER_DEVICE")
+                .message(format!(
+                    "{} has {} mounted instead of {}",
+                    entry.mount_point, other, entry.device
+                ))
+                .highlight(&[&entry.mount_point, &other, &entry.device]),
+            mount::MountState::MountedElsewhere(paths) => {
+                let paths = paths.join(", ");
+                finding(Severity::Warning, "MOUNTED_ELSEWHERE")
+                    .message(format!(
+                        "{} is mounted at {} instead of {}",
+                        entry.device, paths, entry.mount_point
+                    ))
+                    .highlight(&[&entry.device, &paths, &entry.mount_point])
+            }
+            mount::MountState::NotMounted if entry.options.contains("noauto") => {
+                finding(Severity::Info, "NOT_MOUNTED_NOAUTO")
+                    .message(format!("{} is not mounted (noauto)", entry.mount_point))
+                    .highlight(&[&entry.mount_point])
+            }
+            mount::MountState::NotMounted => finding(Severity::Warning, "NOT_MOUNTED")
+                .message(format!("{} is not mounted", entry.mount_point))
+                .highlight(&[&entry.mount_point]),
+        };
+        finding.live = true;
+        findings.push(finding);
+    }
+
+    findings
+}
+
+fn validate_fstab(config: &CliConfig) -> Result<()> {
+    let fstab_path = "/etc/fstab";
+
+    if config.output == OutputFormat::Table {
+        println!("{} Validating /etc/fstab...\n", "🔍".bold());
+    }
+
+    let parsed = load_fstab(fstab_path)?;
+    let entries = &parsed.entries;
+
+    let mut findings = check_entries(entries);
+    if entries.iter().any(|e| {
+        KERNEL_DEVICE_PREFIXES
+            .iter()
+            .any(|p| e.device.starts_with(p))
+    }) {
+        // Discovery only sharpens the suggestion, so it failing isn't worth reporting
+        let devices = discover_block_devices().unwrap_or_default();
+        findings.extend(check_device_names(entries, &devices));
+    }
+    let live_check = if entries.is_empty() {
+        Ok(())
+    } else {
+        mount::read_active_mounts().map(|active| {
+            findings.extend(check_live_mounts(entries, &active));
+        })
+    };
+
+    match config.output {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&validate_json(fstab_path, &parsed, &findings))?
+        ),
+        OutputFormat::Plain => {
+            for finding in &findings {
+                println!("{}\t{}\t{}", finding.line, finding.code, finding.message);
+            }
+        }
+        OutputFormat::Table => {
+            if entries.is_empty() {
                 println!(
-                    "{} Entry {}: Mount point directory '{}' does not exist",
-                    "ℹ️ ".blue(),
-                    i + 1,
-                    entry.mount_point.bright_white()
+                    "{}",
+                    "⚠️  /etc/fstab is empty or contains no valid entries".yellow()
                 );
-                warnings += 1;
+            } else {
+                display_validate_report(&findings, &live_check);
+            }
+
+            for warning in &parsed.warnings {
+                warning.display();
+            }
+        }
+    }
+
+    let code = validate_exit_code(&findings);
+    if code != exit_codes::SUCCESS {
+        process::exit(code);
+    }
+    Ok(())
+}
+
+/// Critical problems in the file fail `validate`; warnings and the live
+/// mount state are reported but don't change the exit code
+fn validate_exit_code(findings: &[Finding]) -> i32 {
+    if FindingCounts::from_findings(findings).critical > 0 {
+        exit_codes::DATA_ERROR
+    } else {
+        exit_codes::SUCCESS
+    }
+}
+
+/// Human renderer for validate findings
+fn display_validate_report(findings: &[Finding], live_check: &Result<()>) {
+    let file_findings: Vec<&Finding> = findings.iter().filter(|f| !f.live).collect();
+    for (i, finding) in file_findings.iter().enumerate() {
+        // A suggestion shared by a run of findings, like the mount order, is shown once after the run
+        let repeated = file_findings
+            .get(i + 1)
+            .is_some_and(|next| next.suggestion == finding.suggestion);
+        finding.display(!repeated);
+    }
+
+    match live_check {
+        Ok(()) => {
+            println!("\n{}", "Live mount status:".cyan().bold());
+            for finding in findings.iter().filter(|f| f.live) {
+                finding.display(true);
             }
         }
+        Err(e) => println!("\n{} Skipping live mount check: {:#}", "ℹ️ ".blue(), e),
     }
 
     // Summary
+    let counts = FindingCounts::from_findings(findings);
     println!();
-    if issues == 0 && warnings == 0 {
+    if counts.critical == 0 && counts.warning == 0 && counts.active == 0 {
         println!("{} No issues found! /etc/fstab looks good.", "✅".green());
+        return;
+    }
+    if counts.active > 0 {
+        println!(
+            "{} Found {} active issue(s) with current mounts",
+            "🔥".red(),
+            counts.active.to_string().red().bold()
+        );
+    }
+    if counts.critical > 0 {
+        println!(
+            "{} Found {} critical issue(s)",
+            "❌".red(),
+            counts.critical.to_string().red().bold()
+        );
+    }
+    if counts.warning > 0 {
+        println!(
+            "{} Found {} warning(s)",
+            "⚠️ ".yellow(),
+            counts.warning.to_string().yellow().bold()
+        );
+    }
+}
+
+/// JSON renderer for validate findings
+///
+/// `issues`/`notices`/`active_issues` are kept alongside `counts` for existing consumers.
+fn validate_json(
+    path: &str,
+    parsed: &fstab::ParsedFstab,
+    findings: &[Finding],
+) -> serde_json::Value {
+    let counts = FindingCounts::from_findings(findings);
+    serde_json::json!({
+        "path": path,
+        "entries": parsed.entries.len(),
+        "findings": findings,
+        "counts": counts,
+        "issues": counts.critical,
+        "notices": counts.warning,
+        "active_issues": counts.active,
+        "warnings": parsed.warnings,
+    })
+}
+
+fn set_option_cmd(
+    fstab_path: &str,
+    mount_point: &str,
+    add: &[String],
+    remove: &[String],
+    retention: &BackupConfig,
+    dry_run: bool,
+) -> Result<CommandOutcome> {
+    println!(
+        "{} Editing options for {}\n",
+        "🔧".bold(),
+        mount_point.bright_white()
+    );
+
+    let mut before = String::new();
+    let mut after = String::new();
+
+    let changed = fstab::edit_transaction(fstab_path, retention, dry_run, |doc| {
+        let entry = doc
+            .entry_mut(mount_point)
+            .ok_or_else(|| anyhow::anyhow!("No fstab entry for mount point '{}'", mount_point))?;
+        before = entry.options.clone();
+        entry.options = fstab::edit_options(&entry.options, add, remove);
+        after = entry.options.clone();
+        Ok(())
+    })?;
+
+    if !changed {
+        println!(
+            "{} Options already up to date: {}",
+            "✓".green().bold(),
+            before.bright_white()
+        );
+        return Ok(CommandOutcome::skipped(1));
+    }
+
+    println!("  {} {}", "Before:".cyan(), before.truecolor(150, 150, 150));
+    println!("  {} {}", "After: ".cyan(), after.bright_white());
+
+    if !dry_run {
+        println!("\n{} Updated {}", "✓".green().bold(), fstab_path);
+    }
+
+    Ok(CommandOutcome::changed(1))
+}
+
+fn fstab_add_cmd(
+    fstab_path: &str,
+    entry: FstabEntry,
+    retention: &BackupConfig,
+    dry_run: bool,
+) -> Result<CommandOutcome> {
+    if !device_resolvable(&entry.device, &entry.fs_type) {
+        anyhow::bail!(
+            "Device '{}' doesn't match any block device on this system",
+            entry.device
+        );
+    }
+
+    println!(
+        "{} Adding fstab entry for {}\n",
+        "🔧".bold(),
+        entry.mount_point.bright_white()
+    );
+
+    let mut added = String::new();
+    fstab::edit_transaction(fstab_path, retention, dry_run, |doc| {
+        added = fstab::append_entry(doc, entry)?;
+        Ok(())
+    })?;
+
+    if !dry_run {
+        println!("  {}", format!("+ {}", added).green());
+        println!("\n{} Updated {}", "✓".green().bold(), fstab_path);
+    }
+
+    Ok(CommandOutcome::changed(1))
+}
+
+/// Whether an fstab device spec points at something that exists right now
+///
+/// Network and pseudo filesystems name a server or nothing in particular, so
+/// only local paths and `UUID=`-style tags are checked.
+fn device_resolvable(device: &str, fs_type: &str) -> bool {
+    let tagged = ["UUID=", "LABEL=", "PARTUUID=", "PARTLABEL="]
+        .iter()
+        .any(|tag| device.starts_with(tag));
+    let network = matches!(fs_type, "nfs" | "nfs4" | "cifs" | "smb3" | "sshfs")
+        || fs_type.starts_with("fuse");
+
+    if !(tagged || device.starts_with('/')) || network {
+        return true;
+    }
+
+    Path::new(&mount::canonical_device(device)).exists()
+}
+
+/// Split `--option add:x` / `--option remove:y` values into additions and removals
+///
+/// A bare value continues the previous action, so `add:noatime,nodiratime` adds both.
+fn parse_option_edits(values: &[String]) -> Result<(Vec<String>, Vec<String>)> {
+    let mut add = Vec::new();
+    let mut remove = Vec::new();
+    let mut adding = None;
+
+    for value in values {
+        let option = if let Some(option) = value.strip_prefix("add:") {
+            adding = Some(true);
+            option
+        } else if let Some(option) = value.strip_prefix("remove:") {
+            adding = Some(false);
+            option
+        } else {
+            value.as_str()
+        };
+
+        match adding {
+            Some(true) => add.push(option.to_string()),
+            Some(false) => remove.push(option.to_string()),
+            None => anyhow::bail!(
+                "Invalid --option '{}': expected add:<option> or remove:<option>",
+                value
+            ),
+        }
+    }
+
+    Ok((add, remove))
+}
+
+fn edit_cmd(
+    fstab_path: &str,
+    target: &str,
+    option_edits: &[String],
+    set_pass: Option<&str>,
+    retention: &BackupConfig,
+    dry_run: bool,
+) -> Result<CommandOutcome> {
+    let (add, remove) = parse_option_edits(option_edits)?;
+    if let Some(pass) = set_pass {
+        if pass.parse::<u32>().is_err() {
+            anyhow::bail!("Invalid --set-pass '{}': expected a number", pass);
+        }
+    }
+
+    println!(
+        "{} Editing fstab entry {}\n",
+        "🔧".bold(),
+        target.bright_white()
+    );
+
+    let mut before = None;
+    let mut after = None;
+
+    let changed = fstab::edit_transaction(fstab_path, retention, dry_run, |doc| {
+        let entry = doc.find_entry_mut(target)?;
+        before = Some(entry.clone());
+        if !add.is_empty() || !remove.is_empty() {
+            entry.options = fstab::edit_options(&entry.options, &add, &remove);
+        }
+        if let Some(pass) = set_pass {
+            entry.pass = pass.to_string();
+        }
+        after = Some(entry.clone());
+        Ok(())
+    })?;
+
+    if !changed {
+        println!("{} Entry already up to date", "✓".green().bold());
+        return Ok(CommandOutcome::skipped(1));
+    }
+
+    if let (Some(before), Some(after)) = (before, after) {
+        if before.options != after.options {
+            println!(
+                "  {} {} -> {}",
+                "Options:".cyan(),
+                before.options.truecolor(150, 150, 150),
+                after.options.bright_white()
+            );
+        }
+        if before.pass != after.pass {
+            println!(
+                "  {}    {} -> {}",
+                "Pass:".cyan(),
+                before.pass.truecolor(150, 150, 150),
+                after.pass.bright_white()
+            );
+        }
+    }
+
+    if !dry_run {
+        println!("\n{} Updated {}", "✓".green().bold(), fstab_path);
+    }
+
+    Ok(CommandOutcome::changed(1))
+}
+
+/// Create a mount point directory, refusing paths outside the usual mount roots
+fn mkmount_cmd(path: &str, force: bool, config: &CliConfig) -> Result<CommandOutcome> {
+    mount::check_mount_dir(path, &config.app_config.general.mount_dir_prefixes, force)?;
+
+    if Path::new(path).is_dir() {
+        println!(
+            "{} {} already exists",
+            "✓".green().bold(),
+            path.bright_white()
+        );
+        return Ok(CommandOutcome::skipped(1));
+    }
+
+    let use_sudo = privilege::resolve_sudo(!privilege::is_root(), config.no_sudo, config.dry_run)?;
+    mount::create_mount_dir(path, use_sudo, config.dry_run, config.verbose)?;
+
+    if !config.dry_run {
+        println!("{} Created {}", "✓".green().bold(), path.bright_white());
+    }
+
+    Ok(CommandOutcome::changed(1))
+}
+
+/// Mount or unmount a filesystem listed in fstab
+///
+/// With `create_dirs`, a missing mount point directory is created first,
+/// subject to the same path checks as `mkmount`.
+fn mount_cmd(
+    fstab_path: &str,
+    target: &str,
+    mount: bool,
+    force: bool,
+    create_dirs: bool,
+    config: &CliConfig,
+) -> Result<CommandOutcome> {
+    let (verb, done) = if mount {
+        ("Mounting", "mounted")
     } else {
-        if issues > 0 {
+        ("Unmounting", "unmounted")
+    };
+    println!("{} {} {}...\n", "💿".bold(), verb, target.bright_white());
+
+    let doc = fstab::FstabDocument::parse(&fstab::read_fstab(fstab_path)?);
+    let mount_point = match doc.find_entry(target) {
+        Ok(entry) => entry.decoded_mount_point(),
+        // Anything mounted can be unmounted, listed in fstab or not
+        Err(_) if !mount && target.starts_with('/') => target.to_string(),
+        Err(e) => return Err(e),
+    };
+
+    if mount_point == "/" && !force {
+        return Err(error::UserError::new(format!(
+            "Refusing to {} the root filesystem",
+            if mount { "mount" } else { "unmount" }
+        ))
+        .with_suggestion("Pass --force if you really mean it")
+        .into());
+    }
+
+    if let Ok(active) = mount::read_active_mounts() {
+        let is_mounted = active.iter().any(|m| m.mount_point == mount_point);
+        if is_mounted == mount {
             println!(
-                "{} Found {} critical issue(s)",
-                "❌".red(),
-                issues.to_string().red().bold()
+                "{} {} is already {}",
+                "✓".green().bold(),
+                mount_point.bright_white(),
+                done
             );
+            return Ok(CommandOutcome::skipped(1));
         }
-        if warnings > 0 {
+    }
+
+    let use_sudo = privilege::resolve_sudo(true, config.no_sudo, config.dry_run)?;
+    if create_dirs && !Path::new(&mount_point).is_dir() {
+        mount::check_mount_dir(
+            &mount_point,
+            &config.app_config.general.mount_dir_prefixes,
+            force,
+        )?;
+        mount::create_mount_dir(&mount_point, use_sudo, config.dry_run, config.verbose)?;
+        if !config.dry_run {
             println!(
-                "{} Found {} warning(s)",
-                "⚠️ ".yellow(),
-                warnings.to_string().yellow().bold()
+                "{} Created {}",
+                "✓".green().bold(),
+                mount_point.bright_white()
             );
         }
     }
-    Ok(())
+    if mount {
+        mount::mount_path(&mount_point, use_sudo, config.dry_run, config.verbose)?;
+    } else {
+        mount::unmount_path(&mount_point, use_sudo, config.dry_run, config.verbose)?;
+    }
+
+    if !config.dry_run {
+        println!(
+            "{} {} {}",
+            "✓".green().bold(),
+            mount_point.bright_white(),
+            done
+        );
+    }
+
+    Ok(CommandOutcome::changed(1))
 }
 
 fn discover_block_devices() -> Result<Vec<BlockDevice>> {
@@ -743,6 +2318,7 @@ fn discover_block_devices() -> Result<Vec<BlockDevice>> {
     match os {
         "macos" => discover_macos_devices(),
         "linux" => discover_linux_devices(),
+        "windows" => discover_windows_devices(),
         _ => {
             eprintln!(
                 "{} Device discovery not supported on {}",
@@ -755,57 +2331,80 @@ fn discover_block_devices() -> Result<Vec<BlockDevice>> {
 }
 
 fn discover_macos_devices() -> Result<Vec<BlockDevice>> {
-    let output = Command::new("diskutil")
-        .arg("list")
-        .arg("-plist")
-        .output()
+    let output = exec::run_command("diskutil", &["list", "-plist"])
         .context("Failed to run diskutil list")?;
 
     if !output.status.success() {
         anyhow::bail!("diskutil command failed");
     }
 
-    // Parse the output and get disk info
-    let list_output = Command::new("diskutil")
-        .arg("list")
-        .output()
-        .context("Failed to run diskutil list")?;
-
-    let list_str = String::from_utf8_lossy(&list_output.stdout);
     let mut devices = Vec::new();
+    for disk_id in parse_diskutil_list_plist(&output.stdout)? {
+        if let Ok(device) = get_macos_device_info(&disk_id) {
+            // Only add devices with a filesystem
+            if device.fs_type.is_some() {
+                devices.push(device);
+            }
+        }
+    }
 
-    // Parse disk identifiers from the output
-    for line in list_str.lines() {
-        if line.contains("disk") && !line.starts_with("/") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if let Some(disk_id) = parts.last() {
-                if disk_id.starts_with("disk") {
-                    if let Ok(device) = get_macos_device_info(disk_id) {
-                        // Only add devices with a filesystem
-                        if device.fs_type.is_some() {
-                            devices.push(device);
-                        }
-                    }
+    Ok(devices)
+}
+
+/// Device identifiers from `diskutil list -plist`, in disk order
+///
+/// Each whole disk is followed by its partitions and, for APFS containers,
+/// the volumes inside them.
+fn parse_diskutil_list_plist(plist_data: &[u8]) -> Result<Vec<String>> {
+    let root = plist::Value::from_reader(std::io::Cursor::new(plist_data))
+        .context("Failed to parse diskutil plist output")?;
+    let root = root
+        .as_dictionary()
+        .context("diskutil plist is not a dictionary")?;
+
+    let identifier = |entry: &plist::Value| {
+        entry
+            .as_dictionary()
+            .and_then(|d| d.get("DeviceIdentifier"))
+            .and_then(|id| id.as_string())
+            .map(String::from)
+    };
+
+    let mut identifiers = Vec::new();
+    if let Some(disks) = root.get("AllDisksAndPartitions").and_then(|d| d.as_array()) {
+        for disk in disks {
+            identifiers.extend(identifier(disk));
+            let Some(disk) = disk.as_dictionary() else {
+                continue;
+            };
+            for key in ["Partitions", "APFSVolumes"] {
+                for child in disk
+                    .get(key)
+                    .and_then(|c| c.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    identifiers.extend(identifier(child));
                 }
             }
         }
+    } else if let Some(all) = root.get("AllDisks").and_then(|d| d.as_array()) {
+        identifiers.extend(all.iter().filter_map(|id| id.as_string().map(String::from)));
     }
 
-    Ok(devices)
+    Ok(identifiers)
 }
 
 fn get_macos_device_info(disk_id: &str) -> Result<BlockDevice> {
-    let output = Command::new("diskutil")
-        .arg("info")
-        .arg(disk_id)
-        .output()
-        .context("Failed to run diskutil info")?;
+    let output =
+        exec::run_command("diskutil", &["info", disk_id]).context("Failed to run diskutil info")?;
 
     let info_str = String::from_utf8_lossy(&output.stdout);
     let mut uuid = None;
     let mut label = None;
     let mut fs_type = None;
     let mut size = None;
+    let mut size_bytes = None;
     let mut mount_point = None;
     let mut is_removable = false;
     let is_ssd = false; // Would need additional detection
@@ -832,6 +2431,7 @@ fn get_macos_device_info(disk_id: &str) -> Result<BlockDevice> {
             }
         } else if line.starts_with("Disk Size:") || line.starts_with("Total Size:") {
             size = line.split(':').nth(1).map(|s| s.trim().to_string());
+            size_bytes = size.as_deref().and_then(diskutil_size_bytes);
         } else if line.starts_with("Mount Point:") {
             let mp = line.split(':').nth(1).map(|s| s.trim().to_string());
             if let Some(ref m) = mp {
@@ -850,23 +2450,87 @@ fn get_macos_device_info(disk_id: &str) -> Result<BlockDevice> {
         partuuid: None,
         label,
         fs_type,
-        size,
+        size: size_bytes.map(sysinfo::format_bytes).or(size),
+        size_bytes,
         mount_point,
         is_removable,
         is_ssd,
+        parent: macos_parent_disk(disk_id),
     })
 }
 
+/// Exact size from a diskutil value such as `500.3 GB (500277792768 Bytes) (exactly ...)`
+///
+/// Falls back to the rounded human size when there is no byte count.
+fn diskutil_size_bytes(value: &str) -> Option<u64> {
+    value
+        .split('(')
+        .filter_map(|part| part.split_once(" Bytes)"))
+        .find_map(|(bytes, _)| bytes.trim().parse().ok())
+        .or_else(|| parse_size(value.split('(').next()?.trim()))
+}
+
+/// Physical disk for a macOS slice identifier, e.g. `disk0s2` -> `/dev/disk0`
+fn macos_parent_disk(disk_id: &str) -> Option<String> {
+    let rest = disk_id.strip_prefix("disk")?;
+    let (disk_num, slice) = rest.split_once('s')?;
+    if disk_num.is_empty() || slice.is_empty() {
+        return None;
+    }
+    Some(format!("/dev/disk{}", disk_num))
+}
+
+fn discover_windows_devices() -> Result<Vec<BlockDevice>> {
+    let disks = sysinfo::query_cim(
+        "Win32_LogicalDisk",
+        &["DeviceID", "VolumeName", "FileSystem", "Size", "DriveType"],
+    )?;
+    Ok(parse_windows_logical_disks(&disks))
+}
+
+/// Map Win32_LogicalDisk instances to devices, with the drive letter as the device
+fn parse_windows_logical_disks(disks: &[serde_json::Value]) -> Vec<BlockDevice> {
+    // DriveType 2 is removable media, 3 a local disk
+    const REMOVABLE: u64 = 2;
+
+    disks
+        .iter()
+        .filter_map(|disk| {
+            let device = disk["DeviceID"].as_str()?.to_string();
+            Some(BlockDevice {
+                mount_point: Some(format!("{}\\", device)),
+                device,
+                uuid: None,
+                partuuid: None,
+                label: disk["VolumeName"]
+                    .as_str()
+                    .filter(|l| !l.is_empty())
+                    .map(String::from),
+                fs_type: disk["FileSystem"].as_str().map(String::from),
+                size: sysinfo::cim_u64(disk, "Size").map(sysinfo::format_bytes),
+                size_bytes: sysinfo::cim_u64(disk, "Size"),
+                is_removable: sysinfo::cim_u64(disk, "DriveType") == Some(REMOVABLE),
+                is_ssd: false,
+                parent: None,
+            })
+        })
+        .collect()
+}
+
 fn discover_linux_devices() -> Result<Vec<BlockDevice>> {
     // Use lsblk to get block device information
-    let output = Command::new("lsblk")
-        .args(&[
+    let output = exec::run_command(
+        "lsblk",
+        &[
             "-J",
+            "-b",
+            // Full paths, so LVM and LUKS volumes show up under /dev/mapper
+            "-p",
             "-o",
             "NAME,UUID,PARTUUID,LABEL,FSTYPE,SIZE,MOUNTPOINT,RM,ROTA",
-        ])
-        .output()
-        .context("Failed to run lsblk. Make sure lsblk is installed.")?;
+        ],
+    )
+    .context("Failed to run lsblk. Make sure lsblk is installed.")?;
 
     if !output.status.success() {
         anyhow::bail!("lsblk command failed");
@@ -876,18 +2540,26 @@ fn discover_linux_devices() -> Result<Vec<BlockDevice>> {
     let parsed: serde_json::Value =
         serde_json::from_str(&json_str).context("Failed to parse lsblk JSON output")?;
 
+    Ok(parse_lsblk_devices(&parsed))
+}
+
+fn parse_lsblk_devices(parsed: &serde_json::Value) -> Vec<BlockDevice> {
     let mut devices = Vec::new();
 
     if let Some(blockdevices) = parsed["blockdevices"].as_array() {
         for device in blockdevices {
-            parse_linux_device(device, &mut devices);
+            parse_linux_device(device, None, &mut devices);
         }
     }
 
-    Ok(devices)
+    devices
 }
 
-fn parse_linux_device(device: &serde_json::Value, devices: &mut Vec<BlockDevice>) {
+fn parse_linux_device(
+    device: &serde_json::Value,
+    disk: Option<&str>,
+    devices: &mut Vec<BlockDevice>,
+) {
     let name = device["name"].as_str().unwrap_or("");
     let device_path = if name.starts_with("/dev/") {
         name.to_string()
@@ -895,50 +2567,301 @@ fn parse_linux_device(device: &serde_json::Value, devices: &mut Vec<BlockDevice>
         format!("/dev/{}", name)
     };
 
+    let size_bytes = lsblk_size(&device["size"]);
     let block_device = BlockDevice {
         device: device_path,
         uuid: device["uuid"].as_str().map(String::from),
         partuuid: device["partuuid"].as_str().map(String::from),
         label: device["label"].as_str().map(String::from),
         fs_type: device["fstype"].as_str().map(String::from),
-        size: device["size"].as_str().map(String::from),
+        size: size_bytes
+            .map(sysinfo::format_bytes)
+            .or_else(|| device["size"].as_str().map(String::from)),
+        size_bytes,
         mount_point: device["mountpoint"].as_str().map(String::from),
         is_removable: device["rm"].as_str() == Some("1"),
         is_ssd: device["rota"].as_str() == Some("0"), // Non-rotating = SSD
+        parent: disk.map(String::from),
     };
 
+    // Children (partitions) are all grouped under the top-level disk
+    let child_disk = disk.unwrap_or(&block_device.device).to_string();
+
     // Only add if it has a filesystem
     if block_device.fs_type.is_some() {
         devices.push(block_device);
     }
 
-    // Recursively parse children (partitions)
     if let Some(children) = device["children"].as_array() {
         for child in children {
-            parse_linux_device(child, devices);
+            parse_linux_device(child, Some(&child_disk), devices);
+        }
+    }
+}
+
+/// Bytes from an lsblk `SIZE` value
+///
+/// With `-b` this is a number, or a numeric string on older util-linux; a
+/// human size such as `512G` is parsed as a fallback.
+fn lsblk_size(value: &serde_json::Value) -> Option<u64> {
+    value.as_u64().or_else(|| {
+        let size = value.as_str()?;
+        size.parse().ok().or_else(|| parse_size(size))
+    })
+}
+
+/// LVM and LUKS volumes, whose space is already counted on the partition
+/// underneath
+fn is_mapped_volume(device: &BlockDevice) -> bool {
+    device.device.starts_with("/dev/mapper/") || device.device.starts_with("/dev/dm-")
+}
+
+/// Bytes on one disk, leaving out volumes that live inside its partitions
+fn disk_subtotal(members: &[&BlockDevice]) -> u64 {
+    members
+        .iter()
+        .filter(|d| !is_mapped_volume(d))
+        .filter_map(|d| d.size_bytes)
+        .sum()
+}
+
+/// Group devices under their physical disk, disks sorted by name
+fn group_by_disk(devices: &[BlockDevice]) -> Vec<(String, Vec<&BlockDevice>)> {
+    let mut groups: Vec<(String, Vec<&BlockDevice>)> = Vec::new();
+
+    for device in devices {
+        let disk = device.parent.as_deref().unwrap_or(&device.device);
+        match groups.iter_mut().find(|(name, _)| name == disk) {
+            Some((_, members)) => members.push(device),
+            None => groups.push((disk.to_string(), vec![device])),
+        }
+    }
+
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    groups
+}
+
+/// Every spec that names this device: its path, then `UUID=`, `PARTUUID=` and `LABEL=`
+fn device_identifiers(device: &BlockDevice) -> Vec<String> {
+    let mut specs = vec![device.device.clone()];
+    if let Some(uuid) = &device.uuid {
+        specs.push(format!("UUID={}", uuid));
+    }
+    if let Some(partuuid) = &device.partuuid {
+        specs.push(format!("PARTUUID={}", partuuid));
+    }
+    if let Some(label) = &device.label {
+        specs.push(format!("LABEL={}", label));
+    }
+    specs
+}
+
+/// Find the discovered device a `/dev` path or `UUID=`/`LABEL=`/`PARTUUID=` spec refers to
+///
+/// A bare kernel name such as `sda1` is taken as `/dev/sda1`, and paths that
+/// are symlinks (e.g. under `/dev/disk/by-id`) are followed.
+fn resolve_device(identifier: &str, devices: &[BlockDevice]) -> Option<BlockDevice> {
+    let spec = fstab::decode_field(identifier).replace('"', "");
+    let spec = if spec.contains('=') || spec.starts_with('/') {
+        spec
+    } else {
+        format!("/dev/{}", spec)
+    };
+
+    let matches = |candidate: &str| {
+        devices.iter().find(|device| {
+            device_identifiers(device)
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(candidate))
+        })
+    };
+
+    matches(&spec)
+        .or_else(|| matches(&mount::canonical_device(&spec)))
+        .cloned()
+}
+
+/// Whether a `resolve` argument names a device rather than a bark
+fn is_device_spec(identifier: &str) -> bool {
+    identifier.starts_with("/dev/") || identifier.contains('=')
+}
+
+fn resolve_cmd(identifier: &str, config: &CliConfig) -> Result<()> {
+    let devices = discover_block_devices()?;
+    let device = resolve_device(identifier, &devices).ok_or_else(|| {
+        anyhow::anyhow!("No block device with a filesystem matches '{}'", identifier)
+    })?;
+
+    match config.output {
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "identifier": identifier,
+                "device": device.device,
+                "uuid": device.uuid,
+                "partuuid": device.partuuid,
+                "label": device.label,
+                "filesystem": device.fs_type,
+                "mount_point": device.mount_point,
+                "identifiers": device_identifiers(&device),
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Plain => {
+            for spec in device_identifiers(&device) {
+                println!("{}", spec);
+            }
+        }
+        OutputFormat::Table => {
+            println!(
+                "{} {} resolves to {}\n",
+                "🔎".bold(),
+                identifier.bright_white(),
+                device.device.bright_cyan().bold()
+            );
+            let row = |name: &str, value: Option<&str>| {
+                println!(
+                    "  {:<12} {}",
+                    name.cyan(),
+                    value.unwrap_or("-").bright_white()
+                );
+            };
+            row("Device", Some(&device.device));
+            row("UUID", device.uuid.as_deref());
+            row("PARTUUID", device.partuuid.as_deref());
+            row("LABEL", device.label.as_deref());
+            row("Filesystem", device.fs_type.as_deref());
+            row("Mounted on", device.mount_point.as_deref());
+        }
+    }
+
+    Ok(())
+}
+
+/// Aggregate capacity shown under `discover`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+struct CapacitySummary {
+    devices: usize,
+    total_bytes: u64,
+    ssd: usize,
+    ssd_bytes: u64,
+    hdd_bytes: u64,
+    removable: usize,
+    /// Devices counted but left out of the byte totals
+    unknown_size: usize,
+}
+
+impl CapacitySummary {
+    /// Device-mapper volumes (LUKS, LVM) sit on a listed partition, so they are
+    /// left out to avoid counting the same space twice
+    fn from_devices(devices: &[BlockDevice]) -> Self {
+        let mut summary = CapacitySummary::default();
+
+        for device in devices.iter().filter(|d| !is_mapped_volume(d)) {
+            summary.devices += 1;
+            if device.is_ssd {
+                summary.ssd += 1;
+            }
+            if device.is_removable {
+                summary.removable += 1;
+            }
+            match device.size_bytes {
+                Some(bytes) if device.is_ssd => summary.ssd_bytes += bytes,
+                Some(bytes) => summary.hdd_bytes += bytes,
+                None => summary.unknown_size += 1,
+            }
         }
+
+        summary.total_bytes = summary.ssd_bytes + summary.hdd_bytes;
+        summary
+    }
+
+    fn display(&self, bytes: bool) {
+        let mut details = vec![
+            format!("{} SSD", self.ssd),
+            format!("{} removable", self.removable),
+        ];
+        if self.unknown_size > 0 {
+            details.push(format!("{} unknown size", self.unknown_size));
+        }
+
+        println!(
+            "{} {} across {} device(s) ({})",
+            "Total:".cyan().bold(),
+            format_size(self.total_bytes, bytes).bright_white().bold(),
+            self.devices,
+            details.join(", ")
+        );
+        println!(
+            "       SSD {}, HDD {}",
+            format_size(self.ssd_bytes, bytes),
+            format_size(self.hdd_bytes, bytes)
+        );
     }
 }
 
-fn discover_devices(config: &CliConfig) -> Result<()> {
+/// Parse a human size such as `931.5G`, `512M` or `500.3 GB` into bytes
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.replace(' ', "");
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier: u64 = match unit.trim_end_matches("iB").trim_end_matches('B') {
+        "" => 1,
+        "K" | "k" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        "P" => 1 << 50,
+        _ => return None,
+    };
+
+    Some((number * multiplier as f64) as u64)
+}
+
+/// `--bytes` prints exact byte counts instead of rounded sizes
+fn discover_devices(config: &CliConfig, flat: bool, bytes: bool) -> Result<()> {
     let devices = discover_block_devices()?;
 
     if devices.is_empty() {
-        if config.json_output {
-            println!(
+        match config.output {
+            OutputFormat::Json => println!(
                 "{}",
                 serde_json::json!({
                     "devices": [],
-                    "count": 0
+                    "count": 0,
+                    "summary": CapacitySummary::default()
                 })
+            ),
+            OutputFormat::Plain => {}
+            OutputFormat::Table => println!("No block devices found"),
+        }
+        return Ok(());
+    }
+
+    // DEVICE UUID LABEL TYPE SIZE_BYTES MOUNT_POINT DISK, one device per line
+    if config.output == OutputFormat::Plain {
+        for d in &devices {
+            let size = d.size_bytes.map(|b| b.to_string());
+            println!(
+                "{}",
+                plain_record(&[
+                    Some(&d.device),
+                    d.uuid.as_deref(),
+                    d.label.as_deref(),
+                    d.fs_type.as_deref(),
+                    size.as_deref(),
+                    d.mount_point.as_deref(),
+                    d.parent.as_deref(),
+                ])
             );
-        } else {
-            println!("No block devices found");
         }
         return Ok(());
     }
 
-    if config.json_output {
+    if config.json_output() {
         // JSON output for automation
         let json_devices: Vec<serde_json::Value> = devices
             .iter()
@@ -950,9 +2873,11 @@ fn discover_devices(config: &CliConfig) -> Result<()> {
                     "label": d.label,
                     "filesystem": d.fs_type,
                     "size": d.size,
+                    "size_bytes": d.size_bytes,
                     "mount_point": d.mount_point,
                     "is_ssd": d.is_ssd,
-                    "is_removable": d.is_removable
+                    "is_removable": d.is_removable,
+                    "parent": d.parent
                 })
             })
             .collect();
@@ -961,7 +2886,8 @@ fn discover_devices(config: &CliConfig) -> Result<()> {
             "{}",
             serde_json::to_string_pretty(&serde_json::json!({
                 "devices": json_devices,
-                "count": devices.len()
+                "count": devices.len(),
+                "summary": CapacitySummary::from_devices(&devices)
             }))?
         );
     } else {
@@ -969,68 +2895,129 @@ fn discover_devices(config: &CliConfig) -> Result<()> {
         println!("Discovering block devices...\n");
 
         println!(
-            "{:<20} {:<38} {:<20} {:<10} {:<10} {:<20}",
+            "{:<20} {:<38} {:<20} {:<10} {:<size_width$} {:<20}",
             "DEVICE".cyan().bold(),
             "UUID".cyan().bold(),
             "LABEL".cyan().bold(),
             "TYPE".cyan().bold(),
             "SIZE".cyan().bold(),
-            "MOUNT POINT".cyan().bold()
+            "MOUNT POINT".cyan().bold(),
+            size_width = size_column_width(bytes)
         );
         println!("{}", "=".repeat(140).bright_black());
 
-        for device in &devices {
-            let uuid_display = device.uuid.as_deref().unwrap_or("-");
-            let label_display = device.label.as_deref().unwrap_or("-");
-            let fs_display = device.fs_type.as_deref().unwrap_or("-");
-            let size_display = device.size.as_deref().unwrap_or("-");
-            let mount_display = device.mount_point.as_deref().unwrap_or("-");
-
-            let device_color = if device.is_removable {
-                device.device.bright_magenta()
-            } else if device.is_ssd {
-                device.device.bright_cyan()
-            } else {
-                device.device.bright_blue()
-            };
-
-            let mut tags = Vec::new();
-            if device.is_ssd {
-                tags.push("SSD".green());
-            }
-            if device.is_removable {
-                tags.push("REMOVABLE".magenta());
+        if flat {
+            for device in &devices {
+                print_device_row(device, "", bytes);
             }
-
-            print!(
-                "{:<20} {:<38} {:<20} {:<10} {:<10} {:<20}",
-                device_color.to_string(),
-                uuid_display.truecolor(150, 150, 150).to_string(),
-                label_display.bright_white().to_string(),
-                fs_display.yellow().to_string(),
-                size_display,
-                mount_display.green().to_string()
-            );
-
-            if !tags.is_empty() {
-                print!(" [");
-                for (i, tag) in tags.iter().enumerate() {
-                    if i > 0 {
-                        print!(", ");
-                    }
-                    print!("{}", tag);
+        } else {
+            for (disk, members) in group_by_disk(&devices) {
+                let subtotal = disk_subtotal(&members);
+                println!(
+                    "{} {} {}",
+                    "💽".bold(),
+                    disk.bright_white().bold(),
+                    format!(
+                        "({} in {} device(s))",
+                        format_size(subtotal, bytes),
+                        members.len()
+                    )
+                    .truecolor(150, 150, 150)
+                );
+                for device in members {
+                    print_device_row(device, "  ", bytes);
                 }
-                print!("]");
             }
-            println!();
         }
 
         println!("\nFound {} block device(s)", devices.len());
+        CapacitySummary::from_devices(&devices).display(bytes);
     }
     Ok(())
 }
 
+fn size_column_width(bytes: bool) -> usize {
+    if bytes {
+        16
+    } else {
+        10
+    }
+}
+
+fn format_size(size_bytes: u64, bytes: bool) -> String {
+    if bytes {
+        format!("{} B", size_bytes)
+    } else {
+        sysinfo::format_bytes(size_bytes)
+    }
+}
+
+fn print_device_row(device: &BlockDevice, indent: &str, bytes: bool) {
+    let uuid_display = device.uuid.as_deref().unwrap_or("-");
+    let label_display = device.label.as_deref().unwrap_or("-");
+    let fs_display = device.fs_type.as_deref().unwrap_or("-");
+    let size_display = match (bytes, device.size_bytes) {
+        (true, Some(size_bytes)) => format_size(size_bytes, true),
+        _ => device.size.clone().unwrap_or_else(|| "-".to_string()),
+    };
+    let mount_display = device.mount_point.as_deref().unwrap_or("-");
+
+    let device_color = if device.is_removable {
+        device.device.bright_magenta()
+    } else if device.is_ssd {
+        device.device.bright_cyan()
+    } else {
+        device.device.bright_blue()
+    };
+
+    let mut tags = Vec::new();
+    if device.is_ssd {
+        tags.push("SSD".green());
+    }
+    if device.is_removable {
+        tags.push("REMOVABLE".magenta());
+    }
+
+    print!(
+        "{}{:<width$} {:<38} {:<20} {:<10} {:<size_width$} {:<20}",
+        indent,
+        device_color.to_string(),
+        uuid_display.truecolor(150, 150, 150).to_string(),
+        label_display.bright_white().to_string(),
+        fs_display.yellow().to_string(),
+        size_display,
+        mount_display.green().to_string(),
+        width = 20 - indent.len(),
+        size_width = size_column_width(bytes)
+    );
+
+    if !tags.is_empty() {
+        print!(" [");
+        for (i, tag) in tags.iter().enumerate() {
+            if i > 0 {
+                print!(", ");
+            }
+            print!("{}", tag);
+        }
+        print!("]");
+    }
+    println!();
+}
+
+fn owner_rationale(uid: u32, gid: u32) -> String {
+    format!(
+        "uid={},gid={}: files are owned by the user running catdog (detected)",
+        uid, gid
+    )
+}
+
 fn suggest_mount_options(device: &BlockDevice) -> MountSuggestion {
+    suggest_mount_options_for(device, privilege::current_uid_gid())
+}
+
+/// Suggestions for `device`, with FAT and NTFS files owned by `owner` (uid, gid)
+fn suggest_mount_options_for(device: &BlockDevice, owner: (u32, u32)) -> MountSuggestion {
+    let (uid, gid) = owner;
     let fs_type = device.fs_type.as_deref().unwrap_or("unknown");
     let mut options = Vec::new();
     let mut rationale = Vec::new();
@@ -1073,19 +3060,21 @@ fn suggest_mount_options(device: &BlockDevice) -> MountSuggestion {
         "ntfs" | "ntfs3" => {
             options.clear();
             options.push("defaults".to_string());
-            options.push("uid=1000".to_string());
-            options.push("gid=1000".to_string());
+            options.push(format!("uid={}", uid));
+            options.push(format!("gid={}", gid));
             options.push("umask=0022".to_string());
             rationale.push("NTFS with user permissions set".to_string());
+            rationale.push(owner_rationale(uid, gid));
         }
         "vfat" | "exfat" => {
             options.clear();
             options.push("defaults".to_string());
-            options.push("uid=1000".to_string());
-            options.push("gid=1000".to_string());
+            options.push(format!("uid={}", uid));
+            options.push(format!("gid={}", gid));
             options.push("umask=0022".to_string());
             options.push("utf8".to_string());
             rationale.push("FAT filesystem with UTF-8 and user permissions".to_string());
+            rationale.push(owner_rationale(uid, gid));
         }
         _ => {}
     }
@@ -1097,10 +3086,11 @@ fn suggest_mount_options(device: &BlockDevice) -> MountSuggestion {
     }
 
     // Determine device identifier preference
+    // Both are written as fstab fields, so whitespace in labels must be escaped
     let suggested_device_id = if let Some(uuid) = &device.uuid {
         format!("UUID={}", uuid)
     } else if let Some(label) = &device.label {
-        format!("LABEL={}", label)
+        format!("LABEL={}", fstab::encode_field(label))
     } else {
         device.device.clone()
     };
@@ -1111,7 +3101,10 @@ fn suggest_mount_options(device: &BlockDevice) -> MountSuggestion {
 
     // Suggest mount point
     let suggested_mount_point = if let Some(label) = &device.label {
-        format!("/mnt/{}", label.to_lowercase().replace(" ", "_"))
+        format!(
+            "/mnt/{}",
+            fstab::encode_field(&label.to_lowercase().replace(" ", "_"))
+        )
     } else if let Some(uuid) = &device.uuid {
         format!("/mnt/disk_{}", &uuid[..8])
     } else {
@@ -1120,7 +3113,6 @@ fn suggest_mount_options(device: &BlockDevice) -> MountSuggestion {
     };
 
     MountSuggestion {
-        device: device.clone(),
         suggested_device_id,
         suggested_mount_point,
         suggested_options: options,
@@ -1129,7 +3121,85 @@ fn suggest_mount_options(device: &BlockDevice) -> MountSuggestion {
     }
 }
 
-fn suggest_mounts(device_filter: Option<&str>) -> Result<()> {
+fn print_mount_suggestion(device: &BlockDevice, suggestion: &MountSuggestion) {
+    println!("{}", "─".repeat(100).bright_black());
+    println!(
+        "{} {}",
+        "Device:".cyan().bold(),
+        device.device.bright_white()
+    );
+
+    if let Some(uuid) = &device.uuid {
+        println!(
+            "  {} {}",
+            "UUID:".truecolor(150, 150, 150),
+            uuid.truecolor(150, 150, 150)
+        );
+    }
+    if let Some(label) = &device.label {
+        println!("  {} {}", "Label:".cyan(), label.bright_white());
+    }
+    println!(
+        "  {} {}",
+        "Type:".cyan(),
+        suggestion.suggested_fs_type.yellow()
+    );
+    if let Some(size) = &device.size {
+        println!("  {} {}", "Size:".cyan(), size);
+    }
+
+    println!("\n{}", "Suggested fstab entry:".green().bold());
+    println!(
+        "  {} {} {} {} {} {}",
+        suggestion.suggested_device_id.bright_yellow(),
+        suggestion.suggested_mount_point.bright_green(),
+        suggestion.suggested_fs_type.yellow(),
+        suggestion
+            .suggested_options
+            .join(",")
+            .truecolor(180, 180, 180),
+        "0".truecolor(150, 150, 150),
+        "2".truecolor(150, 150, 150)
+    );
+
+    if !suggestion.rationale.is_empty() {
+        println!("\n{}", "Rationale:".blue().bold());
+        for reason in &suggestion.rationale {
+            println!("  {} {}", "•".blue(), reason.truecolor(200, 200, 200));
+        }
+    }
+}
+
+/// Library share at which `corpus recommend` adopts an option
+const LIBRARY_OPTION_THRESHOLD: u8 = 50;
+
+/// Mutually exclusive access-time options; adopting one replaces the others
+const ATIME_OPTIONS: &[&str] = &["atime", "noatime", "relatime", "strictatime"];
+
+/// Add options that most library configs use for this filesystem
+fn blend_library_options(suggestion: &mut MountSuggestion, usage: &[corpus::OptionUsage]) {
+    for option in usage {
+        if option.percent < LIBRARY_OPTION_THRESHOLD || option.option == "defaults" {
+            continue;
+        }
+
+        if ATIME_OPTIONS.contains(&option.option.as_str()) {
+            suggestion
+                .suggested_options
+                .retain(|o| o == &option.option || !ATIME_OPTIONS.contains(&o.as_str()));
+        }
+        if !suggestion.suggested_options.contains(&option.option) {
+            suggestion.suggested_options.push(option.option.clone());
+        }
+
+        suggestion.rationale.push(format!(
+            "{}% of library configs use {} for {}",
+            option.percent, option.option, suggestion.suggested_fs_type
+        ));
+    }
+}
+
+fn suggest_mounts(device_filter: Option<&str>, fs_types: &[String]) -> Result<()> {
     println!("{} Generating mount suggestions...\n", "💡".bold());
 
     let devices = discover_block_devices()?;
@@ -1146,13 +3216,16 @@ fn suggest_mounts(device_filter: Option<&str>) -> Result<()> {
 
             let matches_filter = if let Some(filter) = device_filter {
                 d.device.contains(filter)
-                    || d.label.as_ref().map_or(false, |l| l.contains(filter))
-                    || d.uuid.as_ref().map_or(false, |u| u.contains(filter))
+                    || d.label.as_ref().is_some_and(|l| l.contains(filter))
+                    || d.uuid.as_ref().is_some_and(|u| u.contains(filter))
             } else {
                 true
             };
 
-            not_system_mounted && matches_filter && d.fs_type.is_some()
+            not_system_mounted
+                && matches_filter
+                && matches_fs_filter(d, fs_types)
+                && d.fs_type.is_some()
         })
         .collect();
 
@@ -1166,54 +3239,7 @@ fn suggest_mounts(device_filter: Option<&str>) -> Result<()> {
 
     for device in unmounted {
         let suggestion = suggest_mount_options(&device);
-
-        println!("{}", "─".repeat(100).bright_black());
-        println!(
-            "{} {}",
-            "Device:".cyan().bold(),
-            device.device.bright_white()
-        );
-
-        if let Some(uuid) = &device.uuid {
-            println!(
-                "  {} {}",
-                "UUID:".truecolor(150, 150, 150),
-                uuid.truecolor(150, 150, 150)
-            );
-        }
-        if let Some(label) = &device.label {
-            println!("  {} {}", "Label:".cyan(), label.bright_white());
-        }
-        println!(
-            "  {} {}",
-            "Type:".cyan(),
-            suggestion.suggested_fs_type.yellow()
-        );
-        if let Some(size) = &device.size {
-            println!("  {} {}", "Size:".cyan(), size);
-        }
-
-        println!("\n{}", "Suggested fstab entry:".green().bold());
-        println!(
-            "  {} {} {} {} {} {}",
-            suggestion.suggested_device_id.bright_yellow(),
-            suggestion.suggested_mount_point.bright_green(),
-            suggestion.suggested_fs_type.yellow(),
-            suggestion
-                .suggested_options
-                .join(",")
-                .truecolor(180, 180, 180),
-            "0".truecolor(150, 150, 150),
-            "2".truecolor(150, 150, 150)
-        );
-
-        if !suggestion.rationale.is_empty() {
-            println!("\n{}", "Rationale:".blue().bold());
-            for reason in &suggestion.rationale {
-                println!("  {} {}", "•".blue(), reason.truecolor(200, 200, 200));
-            }
-        }
-
+        print_mount_suggestion(&device, &suggestion);
         println!();
     }
 
@@ -1237,34 +3263,260 @@ fn print_version() {
     println!("Build: {}", env!("CARGO_PKG_VERSION"));
 }
 
-fn get_storage_path() -> PathBuf {
-    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".catdog").join("alerts.json")
+fn get_storage_path() -> Result<PathBuf> {
+    Ok(state::state_dir()?.join("alerts.json"))
+}
+
+/// Open the alert store with the notification channels from the config file
+fn alert_manager(config: &CliConfig) -> Result<AlertManager> {
+    AlertManager::from_config(get_storage_path()?, &config.app_config)
+}
+
+fn start_monitoring(
+    interval: u64,
+    prometheus_path: Option<PathBuf>,
+    config: &CliConfig,
+) -> Result<()> {
+    let storage_path = get_storage_path()?;
+    monitor::start_monitoring(&storage_path, interval, &config.app_config, prometheus_path)
+}
+
+fn run_health_check(config: &CliConfig) -> Result<()> {
+    let storage_path = get_storage_path()?;
+    let new_critical = monitor::check_once(&storage_path, &config.app_config)?;
+
+    let code = check_exit_code(new_critical);
+    if code != exit_codes::SUCCESS {
+        eprintln!(
+            "{} {} new critical bark(s) fired",
+            "Error:".red().bold(),
+            new_critical
+        );
+        process::exit(code);
+    }
+    Ok(())
+}
+
+/// `check` fails when the run raised new critical alerts; ones already
+/// firing before it started don't count
+fn check_exit_code(new_critical: usize) -> i32 {
+    if new_critical > 0 {
+        exit_codes::CRITICAL_ALERTS
+    } else {
+        exit_codes::SUCCESS
+    }
+}
+
+/// How `barks` presents the alert list
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AlertView {
+    Table,
+    Compact,
+    Count,
+}
+
+/// Alerts with the given status, created since `since`, matching every `--where`
+fn matching_alerts<'a>(
+    manager: &'a AlertManager,
+    status_filter: Option<AlertStatus>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    metadata: &[MetadataMatcher],
+) -> Vec<&'a alerts::Alert> {
+    manager
+        .get_alerts_filtered(status_filter, metadata)
+        .into_iter()
+        .filter(|a| since.is_none_or(|since| a.created_at >= since))
+        .collect()
+}
+
+fn list_alerts(
+    status_filter: Option<AlertStatus>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    metadata: &[MetadataMatcher],
+    view: AlertView,
+    config: &CliConfig,
+) -> Result<()> {
+    let manager = alert_manager(config)?;
+
+    let alerts = matching_alerts(&manager, status_filter, since, metadata);
+    match view {
+        AlertView::Table => display_alerts(&alerts),
+        AlertView::Compact => display_alerts_compact(&alerts),
+        AlertView::Count => println!("{}", AlertCounts::from_alerts(&alerts).status_summary()),
+    }
+
+    Ok(())
+}
+
+/// Send a throwaway alert through each configured channel and report how each did
+fn test_notifications_cmd(channel: Option<&str>, config: &CliConfig) -> Result<()> {
+    if let Some(kind) = channel {
+        if !alerts::NOTIFICATION_CHANNEL_KINDS.contains(&kind) {
+            anyhow::bail!(
+                "Unknown notification channel '{}' (expected one of: {})",
+                kind,
+                alerts::NOTIFICATION_CHANNEL_KINDS.join(", ")
+            );
+        }
+    }
+
+    let manager = alert_manager(config)?;
+    let results = manager.test_notifications(channel);
+    if results.is_empty() {
+        return Err(error::UserError::new(match channel {
+            Some(kind) => format!("No {} notification channel is configured", kind),
+            None => "No notification channels are configured".to_string(),
+        })
+        .with_suggestion(
+            "List the channel in [alerts] enabled_channels and give it its own config section",
+        )
+        .into());
+    }
+
+    match config.output {
+        OutputFormat::Json => {
+            let json: Vec<_> = results
+                .iter()
+                .map(|(kind, result)| match result {
+                    Ok(detail) => serde_json::json!({
+                        "channel": kind,
+                        "success": true,
+                        "detail": detail,
+                    }),
+                    Err(e) => serde_json::json!({
+                        "channel": kind,
+                        "success": false,
+                        "detail": format!("{:#}", e),
+                    }),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Plain | OutputFormat::Table => {
+            for (kind, result) in &results {
+                match result {
+                    Ok(detail) => println!("{} {:<10} {}", "✓".green().bold(), kind, detail),
+                    Err(e) => println!(
+                        "{} {:<10} {}",
+                        "✗".red().bold(),
+                        kind,
+                        format!("{:#}", e).red()
+                    ),
+                }
+            }
+        }
+    }
+
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} notification channel(s) failed",
+            failed,
+            results.len()
+        );
+    }
+    Ok(())
+}
+
+fn alert_stats_cmd(config: &CliConfig) -> Result<()> {
+    let stats = alert_manager(config)?.compute_stats();
+    match config.output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        OutputFormat::Plain | OutputFormat::Table => stats.display(),
+    }
+    Ok(())
+}
+
+/// Drop resolved and silenced barks that last changed more than `older_than` ago
+fn prune_alerts_cmd(older_than: &str, config: &CliConfig) -> Result<()> {
+    let cutoff = alerts::parse_since(older_than, chrono::Utc::now())?;
+    let mut manager = alert_manager(config)?;
+
+    if config.dry_run {
+        let count = manager.stale_alerts(cutoff).len();
+        println!(
+            "{} Would prune {} resolved or silenced bark(s) older than {}",
+            "[DRY-RUN]".yellow().bold(),
+            count.to_string().bright_white(),
+            older_than
+        );
+        return Ok(());
+    }
+
+    let count = manager.prune_stale(cutoff)?;
+    println!(
+        "{} Pruned {} resolved or silenced bark(s) older than {}",
+        "✓".green().bold(),
+        count.to_string().bright_white(),
+        older_than
+    );
+    Ok(())
 }
 
-fn start_monitoring(interval: u64) -> Result<()> {
-    let storage_path = get_storage_path();
-    monitor::start_monitoring(&storage_path, interval)
-}
+/// Seconds between refreshes of `barks --watch`
+const DEFAULT_BARKS_WATCH_INTERVAL: u64 = 5;
+
+/// Redraw the alert table every `interval` seconds until Ctrl-C
+///
+/// Alerts that weren't there on the previous refresh are highlighted.
+fn watch_alerts(
+    status_filter: Option<AlertStatus>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    metadata: &[MetadataMatcher],
+    interval: u64,
+    config: &CliConfig,
+) -> Result<()> {
+    use std::io::Write;
 
-fn run_health_check() -> Result<()> {
-    let storage_path = get_storage_path();
-    monitor::check_once(&storage_path)
-}
+    let stop = monitor::stop_on_ctrl_c()?;
+    let mut previous: Option<std::collections::HashSet<String>> = None;
+    // Piped or redirected output gets each refresh appended instead
+    let clear_screen = std::io::stdout().is_terminal();
+
+    while !stop.load(std::sync::atomic::Ordering::SeqCst) {
+        // A fresh manager rereads the store; reading never takes the store lock,
+        // and nothing is held while sleeping
+        let manager = alert_manager(config)?;
+        let alerts = matching_alerts(&manager, status_filter.clone(), since, metadata);
+        let ids: std::collections::HashSet<String> = alerts.iter().map(|a| a.id.clone()).collect();
+        let new_ids: std::collections::HashSet<String> = match &previous {
+            Some(previous) => ids.difference(previous).cloned().collect(),
+            None => std::collections::HashSet::new(),
+        };
 
-fn list_alerts(status_filter: Option<AlertStatus>) -> Result<()> {
-    let storage_path = get_storage_path();
-    let manager = AlertManager::new(storage_path)?;
+        if clear_screen {
+            print!("\x1b[2J\x1b[H");
+        }
+        println!(
+            "{} Watching {} barks every {}s, last refresh {} (Ctrl-C to stop)\n",
+            "👀".bold(),
+            status_filter
+                .as_ref()
+                .map_or("all".to_string(), |s| format!("{:?}", s).to_lowercase()),
+            interval,
+            chrono::Local::now().format("%H:%M:%S")
+        );
+        display_alerts_highlighting(&alerts, &new_ids);
+        if !new_ids.is_empty() {
+            println!(
+                "{} {} new since the last refresh",
+                "🔔".bold(),
+                new_ids.len().to_string().green().bold()
+            );
+        }
+        std::io::stdout().flush()?;
 
-    let alerts = manager.get_alerts(status_filter);
-    display_alerts(&alerts);
+        previous = Some(ids);
+        drop(manager);
+        monitor::sleep_unless_stopped(std::time::Duration::from_secs(interval), &stop);
+    }
 
+    println!();
     Ok(())
 }
 
-fn show_alert(alert_id: &str) -> Result<()> {
-    let storage_path = get_storage_path();
-    let manager = AlertManager::new(storage_path)?;
+fn show_alert(alert_id: &str, config: &CliConfig) -> Result<()> {
+    let manager = alert_manager(config)?;
 
     match manager.get_alert(alert_id) {
         Some(alert) => {
@@ -1278,9 +3530,8 @@ fn show_alert(alert_id: &str) -> Result<()> {
     }
 }
 
-fn acknowledge_alert(alert_id: &str) -> Result<()> {
-    let storage_path = get_storage_path();
-    let mut manager = AlertManager::new(storage_path)?;
+fn acknowledge_alert(alert_id: &str, config: &CliConfig) -> Result<()> {
+    let mut manager = alert_manager(config)?;
 
     manager.acknowledge_alert(alert_id)?;
     println!("{} Alert {} acknowledged", "✓".green().bold(), alert_id);
@@ -1288,9 +3539,8 @@ fn acknowledge_alert(alert_id: &str) -> Result<()> {
     Ok(())
 }
 
-fn resolve_alert(alert_id: &str) -> Result<()> {
-    let storage_path = get_storage_path();
-    let mut manager = AlertManager::new(storage_path)?;
+fn resolve_alert(alert_id: &str, config: &CliConfig) -> Result<()> {
+    let mut manager = alert_manager(config)?;
 
     manager.resolve_alert(alert_id)?;
     println!("{} Alert {} resolved", "✓".green().bold(), alert_id);
@@ -1298,9 +3548,136 @@ fn resolve_alert(alert_id: &str) -> Result<()> {
     Ok(())
 }
 
-fn silence_alert(alert_id: &str) -> Result<()> {
-    let storage_path = get_storage_path();
-    let mut manager = AlertManager::new(storage_path)?;
+/// Bulk operations available on `ack` and `resolve`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BulkAlertAction {
+    Acknowledge,
+    Resolve,
+}
+
+/// Build a bulk filter from `--all`, `--severity` and `--status`
+///
+/// Returns `None` when none of them is present, i.e. a single bark id is expected.
+fn bulk_alert_filter(cli: &cli::ParsedArgs) -> Result<Option<AlertFilter>> {
+    let all = cli.has("--all");
+    let severity = cli.value("--severity");
+    let status = cli.value("--status");
+
+    if !all && severity.is_none() && status.is_none() {
+        return Ok(None);
+    }
+
+    let mut filter = AlertFilter::default();
+    if let Some(name) = severity {
+        filter.severity = Some(AlertSeverity::from_name(name).with_context(|| {
+            format!("Unknown severity '{}': use critical, warning or info", name)
+        })?);
+    }
+    if let Some(name) = status {
+        filter.status = Some(AlertStatus::from_name(name).with_context(|| {
+            format!(
+                "Unknown status '{}': use firing, acknowledged, silenced or resolved",
+                name
+            )
+        })?);
+    }
+
+    Ok(Some(filter))
+}
+
+fn bulk_alert_cmd(
+    action: BulkAlertAction,
+    filter: &AlertFilter,
+    cli: &cli::ParsedArgs,
+    config: &CliConfig,
+) -> Result<()> {
+    let verb = match action {
+        BulkAlertAction::Acknowledge => "Acknowledge",
+        BulkAlertAction::Resolve => "Resolve",
+    };
+
+    if *filter == AlertFilter::default() {
+        let prompt = format!("{} ALL barks? [y/N] ", verb);
+        if !confirm(&prompt, config.assume_yes || cli.has("--force")) {
+            return Err(error::UserError::new(format!(
+                "Aborted: confirmation declined, no barks were {}d",
+                verb.to_lowercase()
+            ))
+            .with_suggestion("Pass --yes to proceed non-interactively")
+            .into());
+        }
+    }
+
+    let mut manager = alert_manager(config)?;
+
+    let count = match action {
+        BulkAlertAction::Acknowledge => manager.acknowledge_matching(filter)?,
+        BulkAlertAction::Resolve => manager.resolve_matching(filter)?,
+    };
+    println!(
+        "{} {}d {} bark(s)",
+        "✓".green().bold(),
+        verb,
+        count.to_string().bright_white()
+    );
+
+    Ok(())
+}
+
+/// Audit log target for a bulk ack/resolve, e.g. `severity=critical status=firing`
+fn bulk_alert_target(cli: &cli::ParsedArgs) -> String {
+    let filters: Vec<String> = [("severity", "--severity"), ("status", "--status")]
+        .into_iter()
+        .filter_map(|(name, flag)| cli.value(flag).map(|v| format!("{}={}", name, v)))
+        .collect();
+    if filters.is_empty() {
+        "all barks".to_string()
+    } else {
+        filters.join(" ")
+    }
+}
+
+/// Ask a yes/no question before a destructive action; anything but `y`/`yes` is a no
+///
+/// `assume_yes` (from `--yes`) answers without asking. When stdin isn't a
+/// terminal there is nobody to ask, so the answer is no unless `--yes` was given.
+fn confirm(prompt: &str, assume_yes: bool) -> bool {
+    let stdin = std::io::stdin();
+    let interactive = stdin.is_terminal();
+    confirm_with(prompt, assume_yes, interactive, stdin.lock())
+}
+
+fn confirm_with(
+    prompt: &str,
+    assume_yes: bool,
+    interactive: bool,
+    mut input: impl std::io::BufRead,
+) -> bool {
+    use std::io::Write;
+
+    if assume_yes {
+        return true;
+    }
+    if !interactive {
+        eprintln!(
+            "{} stdin is not a terminal, so pass --yes to confirm",
+            "Can't ask for confirmation:".yellow().bold()
+        );
+        return false;
+    }
+
+    print!("{}", prompt.yellow().bold());
+    let _ = std::io::stdout().flush();
+
+    let mut answer = String::new();
+    if input.read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn silence_alert(alert_id: &str, config: &CliConfig) -> Result<()> {
+    let mut manager = alert_manager(config)?;
 
     manager.silence_alert(alert_id)?;
     println!("{} Alert {} silenced", "✓".green().bold(), alert_id);
@@ -1308,15 +3685,14 @@ fn silence_alert(alert_id: &str) -> Result<()> {
     Ok(())
 }
 
-fn get_corpus_path() -> PathBuf {
-    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".catdog").join("corpus")
+fn get_corpus_path() -> Result<PathBuf> {
+    Ok(state::state_dir()?.join("corpus"))
 }
 
 fn corpus_ingest(file_path: &str) -> Result<()> {
     println!("{} Adding fstab configuration to library...", "📚".bold());
 
-    let content =
+    let _content =
         fs::read_to_string(file_path).with_context(|| format!("Failed to read {}", file_path))?;
 
     // Parse the fstab
@@ -1328,30 +3704,36 @@ fn corpus_ingest(file_path: &str) -> Result<()> {
     }
 
     // Create corpus storage directory
-    let corpus_path = get_corpus_path();
+    let corpus_path = get_corpus_path()?;
     fs::create_dir_all(&corpus_path)?;
 
     // Create a storage file for this config
     let config_id = uuid::Uuid::new_v4().to_string();
     let storage_file = corpus_path.join(format!("{}.json", config_id));
 
-    // Store metadata
-    let metadata = serde_json::json!({
-        "id": config_id,
-        "source_file": file_path,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "entry_count": entries.len(),
-        "entries": entries.iter().map(|e| serde_json::json!({
-            "device": e.device,
-            "mount_point": e.mount_point,
-            "fs_type": e.fs_type,
-            "options": e.options,
-            "dump": e.dump,
-            "pass": e.pass,
-        })).collect::<Vec<_>>(),
-    });
+    let stored = corpus::StoredConfig {
+        id: config_id.clone(),
+        source_file: file_path.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        entry_count: entries.len(),
+        entries: entries
+            .iter()
+            .map(|e| corpus::StoredEntry {
+                device: e.device.clone(),
+                mount_point: e.mount_point.clone(),
+                fs_type: e.fs_type.clone(),
+                options: e.options.clone(),
+                dump: e.dump.clone(),
+                pass: e.pass.clone(),
+            })
+            .collect(),
+    };
 
-    fs::write(&storage_file, serde_json::to_string_pretty(&metadata)?)?;
+    state::write_atomic(&storage_file, serde_json::to_string_pretty(&stored)?)?;
+
+    // Rebuild and save the index now so the next search doesn't have to
+    let configs = corpus::load_configs(&corpus_path)?;
+    corpus::load_or_build(&corpus_path, &configs)?;
 
     println!(
         "{} Successfully added to configuration library",
@@ -1368,14 +3750,75 @@ fn corpus_ingest(file_path: &str) -> Result<()> {
     Ok(())
 }
 
-fn corpus_search(query: &str) -> Result<()> {
+fn corpus_list() -> Result<()> {
+    let configs = corpus::load_configs(&get_corpus_path()?)?;
+
+    if configs.is_empty() {
+        println!("{}", "No configurations in library yet.".yellow());
+        println!(
+            "  Use {} to add fstab files",
+            "catdog corpus ingest <file>".bright_white()
+        );
+        return Ok(());
+    }
+
+    println!("{} Configuration library\n", "📚".bold());
+    println!(
+        "{:<10} {:<40} {:<20} {:>7}",
+        "ID".cyan().bold(),
+        "SOURCE".cyan().bold(),
+        "INGESTED".cyan().bold(),
+        "ENTRIES".cyan().bold()
+    );
+    println!("{}", "=".repeat(80).bright_black());
+
+    for config in &configs {
+        let ingested = chrono::DateTime::parse_from_rfc3339(&config.timestamp)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| config.timestamp.clone());
+        let id: String = config.id.chars().take(8).collect();
+        println!(
+            "{:<10} {:<40} {:<20} {:>7}",
+            id.bright_white(),
+            config.source_file,
+            ingested.truecolor(150, 150, 150),
+            config.entries.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn corpus_remove(prefix: &str) -> Result<()> {
+    let corpus_path = get_corpus_path()?;
+    let removed = corpus::remove_config(&corpus_path, prefix)?;
+
+    // Keep the saved index in step with the library
+    let configs = corpus::load_configs(&corpus_path)?;
+    corpus::load_or_build(&corpus_path, &configs)?;
+
+    println!(
+        "{} Removed {} ({}, {} entries)",
+        "✓".green().bold(),
+        removed.id.bright_white(),
+        removed.source_file,
+        removed.entries.len()
+    );
+    Ok(())
+}
+
+fn corpus_search(query: &str, limit: Option<usize>) -> Result<()> {
     println!(
         "{} Searching configuration library for: {}\n",
         "🔍".bold(),
         query.bright_white()
     );
 
-    let corpus_path = get_corpus_path();
+    let corpus_path = get_corpus_path()?;
 
     if !corpus_path.exists() {
         println!("{}", "No configurations in library yet.".yellow());
@@ -1386,47 +3829,9 @@ fn corpus_search(query: &str) -> Result<()> {
         return Ok(());
     }
 
-    let query_lower = query.to_lowercase();
-    let mut matches = Vec::new();
-
-    // Read all stored configurations
-    for entry in fs::read_dir(&corpus_path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) != Some("json") {
-            continue;
-        }
-
-        let content = fs::read_to_string(&path)?;
-        let config: serde_json::Value = serde_json::from_str(&content)?;
-
-        // Search through entries
-        if let Some(entries) = config["entries"].as_array() {
-            for (idx, entry) in entries.iter().enumerate() {
-                let device = entry["device"].as_str().unwrap_or("");
-                let mount_point = entry["mount_point"].as_str().unwrap_or("");
-                let fs_type = entry["fs_type"].as_str().unwrap_or("");
-                let options = entry["options"].as_str().unwrap_or("");
-
-                // Check if query matches any field
-                if device.to_lowercase().contains(&query_lower)
-                    || mount_point.to_lowercase().contains(&query_lower)
-                    || fs_type.to_lowercase().contains(&query_lower)
-                    || options.to_lowercase().contains(&query_lower)
-                {
-                    matches.push((
-                        config["id"].as_str().unwrap_or("unknown").to_string(),
-                        config["source_file"]
-                            .as_str()
-                            .unwrap_or("unknown")
-                            .to_string(),
-                        entry.clone(),
-                    ));
-                }
-            }
-        }
-    }
+    let configs = corpus::load_configs(&corpus_path)?;
+    let library = corpus::load_or_build(&corpus_path, &configs)?;
+    let matches = corpus::search(&configs, &library, query, usize::MAX);
 
     if matches.is_empty() {
         println!("{}", "No matching configurations found.".yellow());
@@ -1434,41 +3839,28 @@ fn corpus_search(query: &str) -> Result<()> {
     }
 
     println!(
-        "{} Found {} matching configuration(s):\n",
+        "{} Found {} matching configuration(s), most relevant first:\n",
         "✓".green().bold(),
         matches.len()
     );
 
-    for (config_id, source, entry) in matches {
+    let shown = limited(&matches, limit);
+    for hit in shown {
         println!("{}", "─".repeat(80).bright_black());
         println!(
-            "{} {} {}",
+            "{} {} {} {}",
             "From:".cyan().bold(),
-            source.bright_white(),
-            format!("({})", &config_id[..8]).truecolor(150, 150, 150)
-        );
-        println!(
-            "  {} {}",
-            "Device:".cyan(),
-            entry["device"].as_str().unwrap_or("")
-        );
-        println!(
-            "  {} {}",
-            "Mount:".cyan(),
-            entry["mount_point"].as_str().unwrap_or("")
-        );
-        println!(
-            "  {} {}",
-            "Type:".cyan(),
-            entry["fs_type"].as_str().unwrap_or("")
-        );
-        println!(
-            "  {} {}",
-            "Options:".cyan(),
-            entry["options"].as_str().unwrap_or("")
+            hit.config.source_file.bright_white(),
+            format!("({})", &hit.config.id[..8]).truecolor(150, 150, 150),
+            format!("score {:.2}", hit.score).truecolor(150, 150, 150)
         );
+        println!("  {} {}", "Device:".cyan(), hit.entry.device);
+        println!("  {} {}", "Mount:".cyan(), hit.entry.mount_point);
+        println!("  {} {}", "Type:".cyan(), hit.entry.fs_type);
+        println!("  {} {}", "Options:".cyan(), hit.entry.options);
         println!();
     }
+    print_limit_note(shown.len(), matches.len(), "matches");
 
     Ok(())
 }
@@ -1476,7 +3868,7 @@ fn corpus_search(query: &str) -> Result<()> {
 fn corpus_stats() -> Result<()> {
     println!("{} Configuration Library Statistics\n", "📊".bold());
 
-    let corpus_path = get_corpus_path();
+    let corpus_path = get_corpus_path()?;
 
     if !corpus_path.exists() {
         println!("{}", "No configurations in library yet.".yellow());
@@ -1487,44 +3879,19 @@ fn corpus_stats() -> Result<()> {
         return Ok(());
     }
 
-    let mut total_configs = 0;
-    let mut total_entries = 0;
-    let mut fs_types: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-    let mut mount_options: std::collections::HashMap<String, usize> =
-        std::collections::HashMap::new();
-
-    // Read all stored configurations
-    for entry in fs::read_dir(&corpus_path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) != Some("json") {
-            continue;
-        }
-
-        total_configs += 1;
-
-        let content = fs::read_to_string(&path)?;
-        let config: serde_json::Value = serde_json::from_str(&content)?;
+    let configs = corpus::load_configs(&corpus_path)?;
+    let library = corpus::load_or_build(&corpus_path, &configs)?;
 
-        if let Some(entries) = config["entries"].as_array() {
-            total_entries += entries.len();
-
-            for entry in entries {
-                // Count filesystem types
-                if let Some(fs_type) = entry["fs_type"].as_str() {
-                    *fs_types.entry(fs_type.to_string()).or_insert(0) += 1;
-                }
+    let total_configs = configs.len();
+    let entries: Vec<&corpus::StoredEntry> = configs.iter().flat_map(|c| &c.entries).collect();
+    let total_entries = entries.len();
 
-                // Count mount options
-                if let Some(options) = entry["options"].as_str() {
-                    for opt in options.split(',') {
-                        *mount_options.entry(opt.trim().to_string()).or_insert(0) += 1;
-                    }
-                }
-            }
-        }
+    // Count filesystem types and mount options
+    let mut fs_types: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for entry in &entries {
+        *fs_types.entry(entry.fs_type.clone()).or_insert(0) += 1;
     }
+    let mount_options = corpus::count_options(entries.iter().copied());
 
     println!("{}", "Library Overview:".cyan().bold());
     println!(
@@ -1537,6 +3904,16 @@ fn corpus_stats() -> Result<()> {
         "Total Entries:".truecolor(150, 150, 150),
         total_entries.to_string().bright_white()
     );
+    println!(
+        "  {} {} terms",
+        "Vocabulary:".truecolor(150, 150, 150),
+        library.metadata().dimensionality.to_string().bright_white()
+    );
+    println!(
+        "  {} {:.2} bits",
+        "Token Entropy:".truecolor(150, 150, 150),
+        library.metadata().entropy
+    );
 
     if !fs_types.is_empty() {
         println!("\n{}", "Filesystem Types:".cyan().bold());
@@ -1572,12 +3949,297 @@ fn corpus_stats() -> Result<()> {
             .truecolor(150, 150, 150)
     );
 
-    Ok(())
+    Ok(())
+}
+
+fn corpus_recommend(device_name: &str) -> Result<()> {
+    println!(
+        "{} Recommending mount options for {}...\n",
+        "💡".bold(),
+        device_name.bright_white()
+    );
+
+    let device = discover_block_devices()?
+        .into_iter()
+        .find(|d| {
+            d.device == device_name
+                || d.device.trim_start_matches("/dev/") == device_name
+                || d.label.as_deref() == Some(device_name)
+                || d.uuid.as_deref() == Some(device_name)
+        })
+        .with_context(|| format!("No device found matching {}", device_name))?;
+    let fs_type = device
+        .fs_type
+        .clone()
+        .with_context(|| format!("{} has no filesystem", device.device))?;
+
+    let mut suggestion = suggest_mount_options(&device);
+    let configs = corpus::load_configs(&get_corpus_path()?)?;
+    let usage = corpus::option_usage(&configs, &fs_type);
+
+    if configs.is_empty() {
+        println!(
+            "{} Library is empty; using built-in heuristics",
+            "Note:".yellow().bold()
+        );
+    } else if usage.is_empty() {
+        println!(
+            "{} No library configs use {}; using built-in heuristics",
+            "Note:".yellow().bold(),
+            fs_type
+        );
+    } else {
+        blend_library_options(&mut suggestion, &usage);
+    }
+
+    print_mount_suggestion(&device, &suggestion);
+    Ok(())
+}
+
+fn corpus_export(archive: &str) -> Result<()> {
+    let corpus_path = get_corpus_path()?;
+    let count = corpus::archive::export(&corpus_path, Path::new(archive))?;
+
+    if count == 0 {
+        println!(
+            "{} Library is empty; wrote an empty archive to {}",
+            "Note:".yellow().bold(),
+            archive
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Exported {} configuration(s) to {}",
+        "✓".green().bold(),
+        count,
+        archive.bright_white()
+    );
+    Ok(())
+}
+
+fn corpus_import(archive: &str) -> Result<()> {
+    println!(
+        "{} Importing configuration library from {}...",
+        "📚".bold(),
+        archive.bright_white()
+    );
+
+    let corpus_path = get_corpus_path()?;
+    let summary = corpus::archive::import(&corpus_path, Path::new(archive))?;
+
+    let configs = corpus::load_configs(&corpus_path)?;
+    corpus::load_or_build(&corpus_path, &configs)?;
+
+    println!(
+        "{} Imported {} configuration(s) with {} entries",
+        "✓".green().bold(),
+        summary.configs,
+        summary.entries
+    );
+    if summary.duplicates > 0 {
+        println!(
+            "  {} {} duplicate configuration(s) already in the library",
+            "Skipped".yellow(),
+            summary.duplicates
+        );
+    }
+    Ok(())
+}
+
+fn config_show(config: &CliConfig) -> Result<()> {
+    let path = Config::path()?;
+    let raw = fs::read_to_string(&path).ok();
+    let settings = config.app_config.settings(raw.as_deref())?;
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "path": path,
+                "exists": raw.is_some(),
+                "settings": settings
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Configuration {}\n",
+        "⚙️".bold(),
+        format!("({})", path.display()).truecolor(150, 150, 150)
+    );
+
+    let mut section = "";
+    for setting in &settings {
+        let (table, key) = setting.key.split_once('.').unwrap_or(("", &setting.key));
+        if table != section {
+            if !section.is_empty() {
+                println!();
+            }
+            println!("{}", format!("[{}]", table).cyan().bold());
+            section = table;
+        }
+
+        let source = if setting.explicit {
+            "set".green()
+        } else {
+            "default".truecolor(150, 150, 150)
+        };
+        println!(
+            "  {:<32} {:<40} {}",
+            key.bright_white(),
+            setting.value.to_string(),
+            source
+        );
+    }
+
+    Ok(())
+}
+
+/// Open the config file in `$VISUAL`/`$EDITOR`, writing the defaults first if it doesn't exist
+fn config_edit() -> Result<()> {
+    let path = Config::path()?;
+    if !path.exists() {
+        Config::default().save()?;
+        println!(
+            "{} Created {} with default settings",
+            "✓".green().bold(),
+            path.display()
+        );
+    }
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // The editor may carry its own arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().context("$EDITOR is empty")?;
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}", editor, status);
+    }
+
+    // Catch mistakes while the file is still fresh in mind
+    Config::load().map(|_| ())
+}
+
+// Service management functions
+/// User units belong to the caller, so only system units go through sudo
+fn service_sudo(sm: &service::ServiceManager, config: &CliConfig) -> Result<bool> {
+    if config.user_units {
+        return Ok(false);
+    }
+    privilege::resolve_sudo(sm.requires_sudo(), config.no_sudo, config.dry_run)
+}
+
+fn service_start(services: &[String], config: &CliConfig) -> Result<CommandOutcome> {
+    println!("{} Starting service...\n", "⚙️".bold());
+
+    let sm = service::detect_service_manager()?;
+    println!(
+        "{} {}",
+        "Detected service manager:".cyan(),
+        sm.name().bright_white()
+    );
+
+    if sm == service::ServiceManager::Unknown {
+        anyhow::bail!("Unable to detect service manager on this system");
+    }
+
+    println!();
+    let use_sudo = service_sudo(&sm, config)?;
+    let outcome = run_batch(services, |service_name| {
+        service::start_service(
+            service_name,
+            &sm,
+            use_sudo,
+            config.user_units,
+            config.dry_run,
+            config.verbose,
+        )?;
+        if !config.dry_run {
+            println!(
+                "{} Service {} started",
+                "✓".green().bold(),
+                service_name.bright_white()
+            );
+        }
+        Ok(())
+    });
+
+    Ok(outcome)
+}
+
+/// Ask before stopping, disabling or masking services; dry runs never ask
+fn confirm_service_change(verb: &str, services: &[String], config: &CliConfig) -> Result<()> {
+    if config.dry_run || services.is_empty() {
+        return Ok(());
+    }
+    let prompt = format!("{} {}? [y/N] ", verb, services.join(", "));
+    if !confirm(&prompt, config.assume_yes) {
+        anyhow::bail!("Aborted: no services were changed");
+    }
+    Ok(())
+}
+
+fn service_stop(services: &[String], config: &CliConfig) -> Result<CommandOutcome> {
+    println!("{} Stopping service...\n", "⚙️".bold());
+
+    let sm = service::detect_service_manager()?;
+    println!(
+        "{} {}",
+        "Detected service manager:".cyan(),
+        sm.name().bright_white()
+    );
+
+    if sm == service::ServiceManager::Unknown {
+        anyhow::bail!("Unable to detect service manager on this system");
+    }
+
+    println!();
+    // Only services that are actually running need a confirmation to stop
+    let running: Vec<String> = services
+        .iter()
+        .filter(|name| {
+            !matches!(
+                service::get_service_status(name, &sm, config.user_units).map(|info| info.status),
+                Ok(service::ServiceStatus::Stopped | service::ServiceStatus::Failed)
+            )
+        })
+        .cloned()
+        .collect();
+    confirm_service_change("Stop", &running, config)?;
+    let use_sudo = service_sudo(&sm, config)?;
+    let outcome = run_batch(services, |service_name| {
+        service::stop_service(
+            service_name,
+            &sm,
+            use_sudo,
+            config.user_units,
+            config.dry_run,
+            config.verbose,
+        )?;
+        if !config.dry_run {
+            println!(
+                "{} Service {} stopped",
+                "✓".green().bold(),
+                service_name.bright_white()
+            );
+        }
+        Ok(())
+    });
+
+    Ok(outcome)
 }
 
-// Service management functions
-fn service_start(service_name: &str, config: &CliConfig) -> Result<()> {
-    println!("{} Starting service...\n", "⚙️".bold());
+fn service_restart(services: &[String], config: &CliConfig) -> Result<CommandOutcome> {
+    println!("{} Restarting service...\n", "🔄".bold());
 
     let sm = service::detect_service_manager()?;
     println!(
@@ -1591,21 +4253,31 @@ fn service_start(service_name: &str, config: &CliConfig) -> Result<()> {
     }
 
     println!();
-    service::start_service(service_name, &sm, config.dry_run, config.verbose)?;
-
-    if !config.dry_run {
-        println!(
-            "\n{} Service {} started",
-            "✓".green().bold(),
-            service_name.bright_white()
-        );
-    }
+    let use_sudo = service_sudo(&sm, config)?;
+    let outcome = run_batch(services, |service_name| {
+        service::restart_service(
+            service_name,
+            &sm,
+            use_sudo,
+            config.user_units,
+            config.dry_run,
+            config.verbose,
+        )?;
+        if !config.dry_run {
+            println!(
+                "{} Service {} restarted",
+                "✓".green().bold(),
+                service_name.bright_white()
+            );
+        }
+        Ok(())
+    });
 
-    Ok(())
+    Ok(outcome)
 }
 
-fn service_stop(service_name: &str, config: &CliConfig) -> Result<()> {
-    println!("{} Stopping service...\n", "⚙️".bold());
+fn service_enable(services: &[String], config: &CliConfig) -> Result<CommandOutcome> {
+    println!("{} Enabling service...\n", "⚙️".bold());
 
     let sm = service::detect_service_manager()?;
     println!(
@@ -1619,21 +4291,31 @@ fn service_stop(service_name: &str, config: &CliConfig) -> Result<()> {
     }
 
     println!();
-    service::stop_service(service_name, &sm, config.dry_run, config.verbose)?;
-
-    if !config.dry_run {
-        println!(
-            "\n{} Service {} stopped",
-            "✓".green().bold(),
-            service_name.bright_white()
-        );
-    }
+    let use_sudo = service_sudo(&sm, config)?;
+    let outcome = run_batch(services, |service_name| {
+        service::enable_service(
+            service_name,
+            &sm,
+            use_sudo,
+            config.user_units,
+            config.dry_run,
+            config.verbose,
+        )?;
+        if !config.dry_run {
+            println!(
+                "{} Service {} enabled",
+                "✓".green().bold(),
+                service_name.bright_white()
+            );
+        }
+        Ok(())
+    });
 
-    Ok(())
+    Ok(outcome)
 }
 
-fn service_restart(service_name: &str, config: &CliConfig) -> Result<()> {
-    println!("{} Restarting service...\n", "🔄".bold());
+fn service_disable(services: &[String], config: &CliConfig) -> Result<CommandOutcome> {
+    println!("{} Disabling service...\n", "⚙️".bold());
 
     let sm = service::detect_service_manager()?;
     println!(
@@ -1647,21 +4329,32 @@ fn service_restart(service_name: &str, config: &CliConfig) -> Result<()> {
     }
 
     println!();
-    service::restart_service(service_name, &sm, config.dry_run, config.verbose)?;
-
-    if !config.dry_run {
-        println!(
-            "\n{} Service {} restarted",
-            "✓".green().bold(),
-            service_name.bright_white()
-        );
-    }
+    confirm_service_change("Disable", services, config)?;
+    let use_sudo = service_sudo(&sm, config)?;
+    let outcome = run_batch(services, |service_name| {
+        service::disable_service(
+            service_name,
+            &sm,
+            use_sudo,
+            config.user_units,
+            config.dry_run,
+            config.verbose,
+        )?;
+        if !config.dry_run {
+            println!(
+                "{} Service {} disabled",
+                "✓".green().bold(),
+                service_name.bright_white()
+            );
+        }
+        Ok(())
+    });
 
-    Ok(())
+    Ok(outcome)
 }
 
-fn service_enable(service_name: &str, config: &CliConfig) -> Result<()> {
-    println!("{} Enabling service...\n", "⚙️".bold());
+fn service_mask(services: &[String], force: bool, config: &CliConfig) -> Result<CommandOutcome> {
+    println!("{} Masking service...\n", "🚫".bold());
 
     let sm = service::detect_service_manager()?;
     println!(
@@ -1674,22 +4367,48 @@ fn service_enable(service_name: &str, config: &CliConfig) -> Result<()> {
         anyhow::bail!("Unable to detect service manager on this system");
     }
 
-    println!();
-    service::enable_service(service_name, &sm, config.dry_run, config.verbose)?;
-
-    if !config.dry_run {
+    if !service::supports_masking(&sm) {
         println!(
-            "\n{} Service {} enabled",
-            "✓".green().bold(),
-            service_name.bright_white()
+            "\n{} {} has no equivalent of masking; use 'catdog service disable' instead",
+            "⚠️".yellow(),
+            sm.name()
         );
+        return Ok(CommandOutcome::skipped(services.len()));
     }
 
-    Ok(())
+    println!();
+    confirm_service_change("Mask", services, config)?;
+    let use_sudo = service_sudo(&sm, config)?;
+    let outcome = run_batch(services, |service_name| {
+        if service::is_critical_unit(service_name) && !force {
+            anyhow::bail!(
+                "refusing to mask critical unit {} (use --force to override)",
+                service_name
+            );
+        }
+        service::mask_service(
+            service_name,
+            &sm,
+            use_sudo,
+            config.user_units,
+            config.dry_run,
+            config.verbose,
+        )?;
+        if !config.dry_run {
+            println!(
+                "{} Service {} masked",
+                "✓".green().bold(),
+                service_name.bright_white()
+            );
+        }
+        Ok(())
+    });
+
+    Ok(outcome)
 }
 
-fn service_disable(service_name: &str, config: &CliConfig) -> Result<()> {
-    println!("{} Disabling service...\n", "⚙️".bold());
+fn service_unmask(services: &[String], config: &CliConfig) -> Result<CommandOutcome> {
+    println!("{} Unmasking service...\n", "⚙️".bold());
 
     let sm = service::detect_service_manager()?;
     println!(
@@ -1702,18 +4421,37 @@ fn service_disable(service_name: &str, config: &CliConfig) -> Result<()> {
         anyhow::bail!("Unable to detect service manager on this system");
     }
 
-    println!();
-    service::disable_service(service_name, &sm, config.dry_run, config.verbose)?;
-
-    if !config.dry_run {
+    if !service::supports_masking(&sm) {
         println!(
-            "\n{} Service {} disabled",
-            "✓".green().bold(),
-            service_name.bright_white()
+            "\n{} {} has no equivalent of masking; nothing to unmask",
+            "⚠️".yellow(),
+            sm.name()
         );
+        return Ok(CommandOutcome::skipped(services.len()));
     }
 
-    Ok(())
+    println!();
+    let use_sudo = service_sudo(&sm, config)?;
+    let outcome = run_batch(services, |service_name| {
+        service::unmask_service(
+            service_name,
+            &sm,
+            use_sudo,
+            config.user_units,
+            config.dry_run,
+            config.verbose,
+        )?;
+        if !config.dry_run {
+            println!(
+                "{} Service {} unmasked",
+                "✓".green().bold(),
+                service_name.bright_white()
+            );
+        }
+        Ok(())
+    });
+
+    Ok(outcome)
 }
 
 fn service_status(service_name: &str, config: &CliConfig) -> Result<()> {
@@ -1723,9 +4461,9 @@ fn service_status(service_name: &str, config: &CliConfig) -> Result<()> {
         anyhow::bail!("Unable to detect service manager on this system");
     }
 
-    let info = service::get_service_status(service_name, &sm)?;
+    let info = service::get_service_status(service_name, &sm, config.user_units)?;
 
-    if config.json_output {
+    if config.json_output() {
         println!("{}", serde_json::to_string_pretty(&info)?);
     } else {
         println!("{} Service Status\n", "ℹ️".bold());
@@ -1757,8 +4495,69 @@ fn service_status(service_name: &str, config: &CliConfig) -> Result<()> {
     Ok(())
 }
 
-fn service_list(config: &CliConfig) -> Result<()> {
-    println!("{} Listing services...\n", "📋".bold());
+fn service_logs(service_name: &str, lines: usize, follow: bool, config: &CliConfig) -> Result<()> {
+    let sm = service::detect_service_manager()?;
+
+    if config.verbose {
+        println!(
+            "{} {}",
+            "Detected service manager:".cyan(),
+            sm.name().bright_white()
+        );
+    }
+
+    service::show_logs(
+        service_name,
+        &sm,
+        lines,
+        follow,
+        config.user_units,
+        config.verbose,
+    )
+}
+
+/// Rows shown by list-style commands unless `--limit` or `--all` says otherwise
+const DEFAULT_LIST_LIMIT: usize = 50;
+
+/// The row limit for a list: `--all` means none, `--limit N` overrides `default`
+fn list_limit(cli: &cli::ParsedArgs, default: Option<usize>) -> Result<Option<usize>> {
+    if cli.has("--all") {
+        return Ok(None);
+    }
+    match cli.value("--limit") {
+        Some(value) => match value.parse::<usize>() {
+            Ok(limit) if limit > 0 => Ok(Some(limit)),
+            _ => anyhow::bail!("Invalid --limit '{}': expected a positive number", value),
+        },
+        None => Ok(default),
+    }
+}
+
+/// The first `limit` items, or all of them
+fn limited<T>(items: &[T], limit: Option<usize>) -> &[T] {
+    match limit {
+        Some(limit) => &items[..items.len().min(limit)],
+        None => items,
+    }
+}
+
+/// Footer for a table cut short by [`limited`]
+fn print_limit_note(shown: usize, total: usize, noun: &str) {
+    if shown < total {
+        println!(
+            "\n{} Showing {} of {} {} (--limit N or --all for more)",
+            "ℹ️".blue(),
+            shown,
+            total,
+            noun
+        );
+    }
+}
+
+fn service_list(enabled_only: bool, limit: Option<usize>, config: &CliConfig) -> Result<()> {
+    if config.output == OutputFormat::Table {
+        println!("{} Listing services...\n", "📋".bold());
+    }
 
     let sm = service::detect_service_manager()?;
 
@@ -1766,14 +4565,27 @@ fn service_list(config: &CliConfig) -> Result<()> {
         anyhow::bail!("Unable to detect service manager on this system");
     }
 
-    let services = service::list_services(&sm)?;
+    let mut services = service::list_services(&sm, config.user_units)?;
+    if enabled_only {
+        services.retain(|svc| svc.enabled == Some(true));
+    }
 
-    if services.is_empty() {
+    if services.is_empty() && config.output == OutputFormat::Table {
         println!("{}", "No services found".yellow());
         return Ok(());
     }
 
-    if config.json_output {
+    // JSON and plain always carry every service; the limit only applies to the table
+    if config.output == OutputFormat::Plain {
+        // NAME STATUS ENABLED
+        for svc in &services {
+            let enabled = svc.enabled.map(|e| if e { "yes" } else { "no" });
+            println!(
+                "{}",
+                plain_record(&[Some(&svc.name), Some(svc.status.as_str()), enabled])
+            );
+        }
+    } else if config.json_output() {
         println!(
             "{}",
             serde_json::to_string_pretty(&serde_json::json!({
@@ -1784,25 +4596,378 @@ fn service_list(config: &CliConfig) -> Result<()> {
     } else {
         println!("{} {} service(s):\n", "✓".green().bold(), services.len());
 
-        println!("{:<40} {}", "SERVICE".cyan().bold(), "STATUS".cyan().bold());
+        println!(
+            "{:<40} {:<10} {}",
+            "SERVICE".cyan().bold(),
+            "STATUS".cyan().bold(),
+            "ENABLED".cyan().bold()
+        );
         println!("{}", "=".repeat(60).bright_black());
 
-        for svc in services.iter().take(50) {
+        let shown = limited(&services, limit);
+        for svc in shown {
             let status_str = match svc.status {
                 service::ServiceStatus::Running => "running".green(),
                 service::ServiceStatus::Stopped => "stopped".yellow(),
                 service::ServiceStatus::Failed => "failed".red(),
                 service::ServiceStatus::Unknown => "unknown".bright_black(),
             };
+            let enabled_str = match svc.enabled {
+                Some(true) => "yes".green(),
+                Some(false) => "no".yellow(),
+                None => "-".bright_black(),
+            };
+
+            println!(
+                "  {:<38} {:<10} {}",
+                svc.name.bright_white(),
+                status_str,
+                enabled_str
+            );
+        }
+
+        print_limit_note(shown.len(), services.len(), "services");
+    }
+
+    Ok(())
+}
+
+/// Run the doctor checks; exits non-zero only when a required tool is missing
+fn doctor_cmd(config: &CliConfig) -> Result<()> {
+    let report = doctor::run_doctor()?;
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "overall": report.overall(),
+                "checks": report.checks,
+            }))?
+        );
+    } else {
+        println!("{} Checking catdog and system health...\n", "🩺".bold());
+        report.display();
+    }
+
+    // Only a missing hard dependency fails the command; other problems are reported
+    if report.missing_required_tool() {
+        process::exit(exit_codes::GENERAL_ERROR);
+    }
+    Ok(())
+}
+
+fn quick_uptime(config: &CliConfig) -> Result<()> {
+    let info = sysinfo::get_uptime_info()?;
+
+    if config.json_output() {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    print!("{} {}", "up".cyan(), info.uptime.bright_white());
+    if let Some([one, five, fifteen]) = info.load_average {
+        print!(
+            ", {} {:.2} {:.2} {:.2}",
+            "load average:".cyan(),
+            one,
+            five,
+            fifteen
+        );
+    }
+    println!();
+    Ok(())
+}
+
+fn quick_hostname(config: &CliConfig) -> Result<()> {
+    let hostname = sysinfo::get_hostname()?;
+
+    if config.json_output() {
+        println!("{}", serde_json::json!({ "hostname": hostname }));
+    } else {
+        println!("{}", hostname);
+    }
+    Ok(())
+}
+
+fn quick_kernel(config: &CliConfig) -> Result<()> {
+    let kernel = sysinfo::get_kernel_version()?;
 
-            println!("  {:<38} {}", svc.name.bright_white(), status_str);
+    if config.json_output() {
+        println!("{}", serde_json::json!({ "kernel": kernel }));
+    } else {
+        println!("{}", kernel);
+    }
+    Ok(())
+}
+
+/// Refresh a compact system summary every `interval` seconds until Ctrl-C
+fn watch_cmd(interval: u64, config: &CliConfig) -> Result<()> {
+    use std::io::Write;
+
+    let stop = monitor::stop_on_ctrl_c()?;
+
+    // On a terminal, clear once, then redraw from the top-left so frames don't
+    // flicker; piped or redirected output gets each frame appended instead
+    let redraw = std::io::stdout().is_terminal();
+    if redraw {
+        print!("\x1b[2J");
+    }
+    while !stop.load(std::sync::atomic::Ordering::SeqCst) {
+        let info = sysinfo::gather_system_info()?;
+        let firing = alert_manager(config)?
+            .get_alerts(Some(AlertStatus::Firing), None)
+            .len();
+        let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        let lines = render_watch_frame(&info, firing, &now, interval);
+        if redraw {
+            // Each line clears its own tail so shorter redraws leave no residue
+            let frame: String = lines
+                .iter()
+                .map(|line| format!("{}\x1b[K\n", line))
+                .collect();
+            print!("\x1b[H{}\x1b[J", frame);
+        } else {
+            println!("{}\n", lines.join("\n"));
         }
+        std::io::stdout().flush()?;
+
+        monitor::sleep_unless_stopped(std::time::Duration::from_secs(interval), &stop);
+    }
+
+    println!();
+    Ok(())
+}
 
-        if services.len() > 50 {
+/// The lines of one dashboard frame
+fn render_watch_frame(
+    info: &sysinfo::SystemInfo,
+    firing_alerts: usize,
+    timestamp: &str,
+    interval: u64,
+) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "{} {}  {}  {}",
+            "🐾 catdog watch".bold(),
+            info.hostname.bright_white(),
+            timestamp,
+            format!("(every {}s, Ctrl-C to stop)", interval).bright_black()
+        ),
+        String::new(),
+    ];
+
+    let cpu = match info.cpu.utilization_percent {
+        Some(percent) => format!("{:>5.1}%  {}", percent, usage_bar(percent)),
+        None => format!("{:>6}", "n/a"),
+    };
+    let load = match info.load_average {
+        Some([one, five, fifteen]) => format!("  load {:.2} {:.2} {:.2}", one, five, fifteen),
+        None => String::new(),
+    };
+    lines.push(format!("{:<8}{}{}", "CPU".cyan().bold(), cpu, load));
+
+    lines.push(format!(
+        "{:<8}{:>5.1}%  {}  {} / {}",
+        "MEM".cyan().bold(),
+        info.memory.percent_used,
+        usage_bar(info.memory.percent_used),
+        info.memory.used,
+        info.memory.total
+    ));
+    if info.memory.swap_total != "0 B" {
+        lines.push(format!(
+            "{:<8}{:>5.1}%  {}  {} / {}",
+            "SWAP".cyan().bold(),
+            info.memory.swap_percent,
+            usage_bar(info.memory.swap_percent),
+            info.memory.swap_used,
+            info.memory.swap_total
+        ));
+    }
+
+    for (i, disk) in info.disks.iter().enumerate() {
+        let label = if i == 0 { "DISK" } else { "" };
+        lines.push(format!(
+            "{:<8}{:>5.1}%  {}  {}",
+            label.cyan().bold(),
+            disk.percent_used,
+            usage_bar(disk.percent_used),
+            disk.mount_point
+        ));
+    }
+
+    let alerts = if firing_alerts == 0 {
+        "none firing".green()
+    } else {
+        format!("{} firing", firing_alerts).red().bold()
+    };
+    lines.push(format!("{:<8}{}", "ALERTS".cyan().bold(), alerts));
+
+    lines
+}
+
+/// A 20-cell usage bar, coloured by the same thresholds as the disk alerts
+fn usage_bar(percent: f64) -> String {
+    const WIDTH: usize = 20;
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * WIDTH as f64).round() as usize;
+    let bar = format!("[{}{}]", "#".repeat(filled), ".".repeat(WIDTH - filled));
+
+    if percent >= 90.0 {
+        bar.red().to_string()
+    } else if percent >= 80.0 {
+        bar.yellow().to_string()
+    } else {
+        bar.green().to_string()
+    }
+}
+
+fn top_processes_cmd(count: usize, sort: sysinfo::ProcessSort, config: &CliConfig) -> Result<()> {
+    let processes = sysinfo::top_processes(count, sort)?;
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "count": processes.len(),
+                "processes": processes
+            }))?
+        );
+        return Ok(());
+    }
+    if config.output == OutputFormat::Plain {
+        // PID USER %CPU %MEM COMMAND
+        for process in &processes {
             println!(
-                "\n{} Showing 50 of {} services",
-                "ℹ️".blue(),
-                services.len()
+                "{}",
+                plain_record(&[
+                    Some(&process.pid.to_string()),
+                    Some(&process.user),
+                    Some(&format!("{:.1}", process.cpu_percent)),
+                    Some(&format!("{:.1}", process.mem_percent)),
+                    Some(&process.command),
+                ])
+            );
+        }
+        return Ok(());
+    }
+
+    let by = match sort {
+        sysinfo::ProcessSort::Cpu => "CPU",
+        sysinfo::ProcessSort::Memory => "memory",
+    };
+    println!(
+        "{} Top {} processes by {}\n",
+        "📊".bold(),
+        processes.len(),
+        by
+    );
+    println!(
+        "{:<8} {:<12} {:>6} {:>6}  {}",
+        "PID".cyan().bold(),
+        "USER".cyan().bold(),
+        "%CPU".cyan().bold(),
+        "%MEM".cyan().bold(),
+        "COMMAND".cyan().bold()
+    );
+    println!("{}", "=".repeat(80).bright_black());
+
+    for process in &processes {
+        let command: String = process.command.chars().take(46).collect();
+        println!(
+            "{:<8} {:<12} {:>6.1} {:>6.1}  {}",
+            process.pid,
+            process.user,
+            process.cpu_percent,
+            process.mem_percent,
+            command.bright_white()
+        );
+    }
+
+    Ok(())
+}
+
+fn disk_health_cmd(devices: &[String], config: &CliConfig) -> Result<()> {
+    if !smart::smartctl_available() {
+        let message = smart::SmartUnavailable::NotInstalled.message();
+        if config.json_output() {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "disks": [],
+                    "error": message
+                }))?
+            );
+        } else {
+            println!("{} {}", "Note:".yellow().bold(), message);
+        }
+        return Ok(());
+    }
+
+    let devices = if devices.is_empty() {
+        smart::list_disks()?
+    } else {
+        devices.to_vec()
+    };
+
+    let mut results = Vec::new();
+    let mut skipped = Vec::new();
+    for device in &devices {
+        match smart::check_device(device)? {
+            Ok(health) => results.push(health),
+            Err(reason) => skipped.push((device.clone(), reason)),
+        }
+    }
+
+    if config.json_output() {
+        let skipped: Vec<_> = skipped
+            .iter()
+            .map(|(device, reason)| serde_json::json!({"device": device, "reason": reason.message()}))
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "disks": results,
+                "skipped": skipped
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!("{} SMART disk health\n", "🩺".bold());
+    println!(
+        "{:<20} {:<10} {:>12} {:>10}",
+        "DEVICE".cyan().bold(),
+        "HEALTH".cyan().bold(),
+        "REALLOCATED".cyan().bold(),
+        "PENDING".cyan().bold()
+    );
+    println!("{}", "=".repeat(60).bright_black());
+
+    let count = |value: Option<u64>| value.map_or("-".to_string(), |v| v.to_string());
+    for health in &results {
+        let status = match health.passed {
+            Some(true) => format!("{:<10}", "PASSED").green(),
+            Some(false) => format!("{:<10}", "FAILED").red().bold(),
+            None => format!("{:<10}", "unknown").bright_black(),
+        };
+        println!(
+            "{:<20} {} {:>12} {:>10}",
+            health.device.bright_white(),
+            status,
+            count(health.reallocated_sectors),
+            count(health.pending_sectors)
+        );
+    }
+
+    if !skipped.is_empty() {
+        println!();
+        for (device, reason) in &skipped {
+            println!(
+                "{} {}: {}",
+                "Note:".yellow().bold(),
+                device,
+                reason.message()
             );
         }
     }
@@ -1812,12 +4977,21 @@ fn service_list(config: &CliConfig) -> Result<()> {
 
 // System information function
 fn sys_info(config: &CliConfig) -> Result<()> {
-    println!("{} Gathering system information...\n", "💻".bold());
+    if config.output == OutputFormat::Table {
+        println!("{} Gathering system information...\n", "💻".bold());
+    }
 
     let info = sysinfo::gather_system_info()?;
 
-    if config.json_output {
+    if config.json_output() {
         println!("{}", serde_json::to_string_pretty(&info)?);
+    } else if config.output == OutputFormat::Plain {
+        // One KEY VALUE record per field, keys as in the JSON output
+        let mut pairs = Vec::new();
+        plain_pairs("", &serde_json::to_value(&info)?, &mut pairs);
+        for (key, value) in &pairs {
+            println!("{}", plain_record(&[Some(key), Some(value)]));
+        }
     } else {
         // OS Information
         println!("{}", "═".repeat(60).bright_blue());
@@ -1843,6 +5017,13 @@ fn sys_info(config: &CliConfig) -> Result<()> {
         if let Some(uptime) = info.uptime {
             println!("{:<20} {}", "Uptime:".cyan(), uptime.bright_white());
         }
+        if let Some([one, five, fifteen]) = info.load_average {
+            println!(
+                "{:<20} {}",
+                "Load Average:".cyan(),
+                format!("{:.2}, {:.2}, {:.2}", one, five, fifteen).bright_white()
+            );
+        }
 
         // CPU Information
         println!("\n{}", "═".repeat(60).bright_blue());
@@ -1864,6 +5045,18 @@ fn sys_info(config: &CliConfig) -> Result<()> {
         if let Some(freq) = info.cpu.frequency {
             println!("{:<20} {}", "Frequency:".cyan(), freq.bright_white());
         }
+        if let Some(utilization) = info.cpu.utilization_percent {
+            println!("{:<20} {:.1}%", "Utilization:".cyan(), utilization);
+        }
+        if !info.cpu.per_core.is_empty() {
+            let per_core: Vec<String> = info
+                .cpu
+                .per_core
+                .iter()
+                .map(|u| format!("{:.0}%", u))
+                .collect();
+            println!("{:<20} {}", "Per Core:".cyan(), per_core.join(" "));
+        }
 
         // Memory Information
         println!("\n{}", "═".repeat(60).bright_blue());
@@ -1881,6 +5074,57 @@ fn sys_info(config: &CliConfig) -> Result<()> {
             info.memory.available.bright_white()
         );
         println!("{:<20} {:.1}%", "Usage:".cyan(), info.memory.percent_used);
+        if info.memory.swap_total == "0 B" {
+            println!("{:<20} {}", "Swap:".cyan(), "none".bright_black());
+        } else {
+            println!(
+                "{:<20} {} / {} ({:.1}%)",
+                "Swap:".cyan(),
+                info.memory.swap_used.bright_white(),
+                info.memory.swap_total.bright_white(),
+                info.memory.swap_percent
+            );
+        }
+
+        // GPU Information
+        if !info.gpus.is_empty() {
+            println!("\n{}", "═".repeat(60).bright_blue());
+            println!("{}", "GPU".cyan().bold());
+            println!("{}", "═".repeat(60).bright_blue());
+
+            for gpu in &info.gpus {
+                println!("\n{} {}", "GPU:".cyan(), gpu.name.bright_white());
+                if let Some(ref vendor) = gpu.vendor {
+                    println!("  {:<18} {}", "Vendor:".truecolor(150, 150, 150), vendor);
+                }
+                if let Some(ref memory) = gpu.memory {
+                    println!("  {:<18} {}", "Memory:".truecolor(150, 150, 150), memory);
+                }
+            }
+        }
+
+        // Temperatures
+        if !info.temperatures.is_empty() {
+            println!("\n{}", "═".repeat(60).bright_blue());
+            println!("{}", "TEMPERATURES".cyan().bold());
+            println!("{}", "═".repeat(60).bright_blue());
+
+            for reading in &info.temperatures {
+                let celsius = format!("{:.1}°C", reading.celsius);
+                let colored_celsius = if reading.celsius >= 85.0 {
+                    celsius.red()
+                } else if reading.celsius >= 70.0 {
+                    celsius.yellow()
+                } else {
+                    celsius.green()
+                };
+                println!(
+                    "{:<20} {}",
+                    format!("{}:", reading.label).cyan(),
+                    colored_celsius
+                );
+            }
+        }
 
         // Disk Information
         if !info.disks.is_empty() {
@@ -1931,13 +5175,27 @@ fn sys_info(config: &CliConfig) -> Result<()> {
 
             for iface in &info.network.interfaces {
                 // Skip loopback and other virtual interfaces for cleaner output
-                if iface.name.starts_with("lo") || iface.ip_address.is_none() {
+                if iface.name.starts_with("lo") || !iface.has_addresses() {
                     continue;
                 }
 
                 println!("\n{} {}", "Interface:".cyan(), iface.name.bright_white());
-                if let Some(ref ip) = iface.ip_address {
-                    println!("  {:<18} {}", "IP Address:".truecolor(150, 150, 150), ip);
+                if let Some(ref state) = iface.state {
+                    let state_str = if state == "up" {
+                        state.green()
+                    } else {
+                        state.yellow()
+                    };
+                    println!("  {:<18} {}", "State:".truecolor(150, 150, 150), state_str);
+                }
+                if let Some(mtu) = iface.mtu {
+                    println!("  {:<18} {}", "MTU:".truecolor(150, 150, 150), mtu);
+                }
+                for ip in &iface.ipv4_addresses {
+                    println!("  {:<18} {}", "IPv4 Address:".truecolor(150, 150, 150), ip);
+                }
+                for ip in &iface.ipv6_addresses {
+                    println!("  {:<18} {}", "IPv6 Address:".truecolor(150, 150, 150), ip);
                 }
                 if let Some(ref mac) = iface.mac_address {
                     println!("  {:<18} {}", "MAC Address:".truecolor(150, 150, 150), mac);
@@ -1952,78 +5210,129 @@ fn sys_info(config: &CliConfig) -> Result<()> {
 }
 
 // Package management functions
-fn pkg_install(packages: &[String], config: &CliConfig) -> Result<()> {
+/// Show the package manager in use and any app stores available alongside it
+fn print_package_manager(pm: &package::PackageManager, config: &CliConfig) {
+    let label = if config.backend.is_some() {
+        "Package manager (--backend):"
+    } else {
+        "Detected package manager:"
+    };
+    println!("{} {}", label.cyan(), pm.name().bright_white());
+
+    let others: Vec<String> = package::detect_package_managers()
+        .iter()
+        .filter(|other| *other != pm)
+        .map(|other| other.name().to_string())
+        .collect();
+    if !others.is_empty() {
+        println!(
+            "{} {}",
+            "Also available:".cyan(),
+            others.join(", ").truecolor(150, 150, 150)
+        );
+    }
+}
+
+fn pkg_install(packages: &[String], config: &CliConfig) -> Result<CommandOutcome> {
     println!("{} Installing packages...\n", "📦".bold());
 
-    let pm = package::detect_package_manager()?;
-    println!(
-        "{} {}",
-        "Detected package manager:".cyan(),
-        pm.name().bright_white()
-    );
+    let pm = package::select_package_manager(config.backend.as_deref())?;
+    print_package_manager(&pm, config);
 
     if pm == package::PackageManager::Unknown {
         anyhow::bail!("Unable to detect package manager on this system");
     }
 
     println!();
-    package::install_packages(packages, &pm, config.dry_run, config.verbose)?;
-
-    if !config.dry_run {
+    let use_sudo = privilege::resolve_sudo(pm.requires_sudo(), config.no_sudo, config.dry_run)?;
+    let (installed, pending): (Vec<String>, Vec<String>) = packages
+        .iter()
+        .cloned()
+        .partition(|name| package::is_package_installed(name, &pm).unwrap_or(false));
+    for name in &installed {
         println!(
-            "\n{} Successfully installed {} package(s)",
-            "✓".green().bold(),
-            packages.len()
+            "{} {} is already installed",
+            "-".bright_black(),
+            name.bright_white()
         );
     }
 
-    Ok(())
+    // One package manager call for everything; on failure, whatever did get
+    // installed still counts as changed
+    let (mut outcome, changed) = run_together(
+        &pending,
+        |batch| package::install_packages(batch, &pm, use_sudo, config.dry_run, config.verbose),
+        |name| package::is_package_installed(name, &pm).unwrap_or(false),
+    );
+    if !config.dry_run {
+        for name in changed {
+            println!("{} {} installed", "✓".green().bold(), name.bright_white());
+        }
+    }
+    outcome.skipped += installed.len();
+
+    Ok(outcome)
 }
 
-fn pkg_remove(packages: &[String], config: &CliConfig) -> Result<()> {
+fn pkg_remove(packages: &[String], config: &CliConfig) -> Result<CommandOutcome> {
     println!("{} Removing packages...\n", "📦".bold());
 
-    let pm = package::detect_package_manager()?;
-    println!(
-        "{} {}",
-        "Detected package manager:".cyan(),
-        pm.name().bright_white()
-    );
+    let pm = package::select_package_manager(config.backend.as_deref())?;
+    print_package_manager(&pm, config);
 
     if pm == package::PackageManager::Unknown {
         anyhow::bail!("Unable to detect package manager on this system");
     }
 
     println!();
-    package::remove_packages(packages, &pm, config.dry_run, config.verbose)?;
+    let prompt = format!("Remove {}? [y/N] ", packages.join(", "));
+    if !config.dry_run && !confirm(&prompt, config.assume_yes) {
+        anyhow::bail!("Aborted: no packages were removed");
+    }
 
-    if !config.dry_run {
+    let use_sudo = privilege::resolve_sudo(pm.requires_sudo(), config.no_sudo, config.dry_run)?;
+    let (pending, missing): (Vec<String>, Vec<String>) = packages
+        .iter()
+        .cloned()
+        .partition(|name| package::is_package_installed(name, &pm).unwrap_or(false));
+    for name in &missing {
         println!(
-            "\n{} Successfully removed {} package(s)",
-            "✓".green().bold(),
-            packages.len()
+            "{} {} is not installed",
+            "-".bright_black(),
+            name.bright_white()
         );
     }
 
-    Ok(())
+    // One package manager call for everything; on failure, whatever did get
+    // removed still counts as changed
+    let (mut outcome, changed) = run_together(
+        &pending,
+        |batch| package::remove_packages(batch, &pm, use_sudo, config.dry_run, config.verbose),
+        |name| !package::is_package_installed(name, &pm).unwrap_or(true),
+    );
+    if !config.dry_run {
+        for name in changed {
+            println!("{} {} removed", "✓".green().bold(), name.bright_white());
+        }
+    }
+    outcome.skipped += missing.len();
+
+    Ok(outcome)
 }
 
 fn pkg_update(config: &CliConfig) -> Result<()> {
     println!("{} Updating package cache...\n", "🔄".bold());
 
-    let pm = package::detect_package_manager()?;
-    println!(
-        "{} {}",
-        "Detected package manager:".cyan(),
-        pm.name().bright_white()
-    );
+    let pm = package::select_package_manager(config.backend.as_deref())?;
+    print_package_manager(&pm, config);
 
     if pm == package::PackageManager::Unknown {
         anyhow::bail!("Unable to detect package manager on this system");
     }
 
     println!();
-    package::update_cache(&pm, config.dry_run, config.verbose)?;
+    let use_sudo = privilege::resolve_sudo(pm.requires_sudo(), config.no_sudo, config.dry_run)?;
+    package::update_cache(&pm, use_sudo, config.dry_run, config.verbose)?;
 
     if !config.dry_run {
         println!("\n{} Package cache updated", "✓".green().bold());
@@ -2035,19 +5344,33 @@ fn pkg_update(config: &CliConfig) -> Result<()> {
 fn pkg_upgrade(config: &CliConfig) -> Result<()> {
     println!("{} Upgrading all packages...\n", "⬆️".bold());
 
-    let pm = package::detect_package_manager()?;
-    println!(
-        "{} {}",
-        "Detected package manager:".cyan(),
-        pm.name().bright_white()
-    );
+    let pm = package::select_package_manager(config.backend.as_deref())?;
+    print_package_manager(&pm, config);
 
     if pm == package::PackageManager::Unknown {
         anyhow::bail!("Unable to detect package manager on this system");
     }
 
     println!();
-    package::upgrade_packages(&pm, config.dry_run, config.verbose)?;
+    let use_sudo = privilege::resolve_sudo(pm.requires_sudo(), config.no_sudo, config.dry_run)?;
+
+    // Record what was installed so a bad upgrade can be diffed afterwards
+    if config.dry_run {
+        println!(
+            "{} Would snapshot installed packages to {}",
+            "[DRY-RUN]".yellow().bold(),
+            package::snapshot_dir()?.display()
+        );
+    } else {
+        let path = take_package_snapshot(&pm)?;
+        println!(
+            "{} Saved package snapshot: {}\n",
+            "✓".green().bold(),
+            path.display().to_string().bright_white()
+        );
+    }
+
+    package::upgrade_packages(&pm, use_sudo, config.dry_run, config.verbose)?;
 
     if !config.dry_run {
         println!("\n{} All packages upgraded", "✓".green().bold());
@@ -2056,111 +5379,406 @@ fn pkg_upgrade(config: &CliConfig) -> Result<()> {
     Ok(())
 }
 
-fn pkg_search(query: &str, config: &CliConfig) -> Result<()> {
+fn pkg_upgrade_plan(config: &CliConfig) -> Result<()> {
+    let pm = package::select_package_manager(config.backend.as_deref())?;
+
+    if pm == package::PackageManager::Unknown {
+        anyhow::bail!("Unable to detect package manager on this system");
+    }
+
+    let plan = match package::plan_upgrade(&pm)? {
+        Some(plan) => plan,
+        None => {
+            println!(
+                "{} Upgrade plan not supported for {}",
+                "ℹ️".blue(),
+                pm.name().bright_white()
+            );
+            return Ok(());
+        }
+    };
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "package_manager": pm.name(),
+                "plan": plan
+            }))?
+        );
+        return Ok(());
+    }
+
     println!(
-        "{} Searching for packages matching: {}\n",
-        "🔍".bold(),
-        query.bright_white()
+        "{} Upgrade plan ({})\n",
+        "📋".bold(),
+        pm.name().bright_white()
+    );
+
+    let sections = [
+        ("Upgraded", &plan.upgraded, "↑".green()),
+        ("Newly installed", &plan.newly_installed, "+".blue()),
+        ("Removed", &plan.removed, "-".red()),
+    ];
+
+    for (title, changes, marker) in sections {
+        if changes.is_empty() {
+            continue;
+        }
+        println!("{} ({})", title.cyan().bold(), changes.len());
+        for change in changes {
+            print!("  {} {:<36}", marker, change.name.bright_white());
+            match (&change.from_version, &change.to_version) {
+                (Some(from), Some(to)) => {
+                    print!(" {} → {}", from.truecolor(150, 150, 150), to.green())
+                }
+                (None, Some(to)) => print!(" {}", to.green()),
+                (Some(from), None) => print!(" {}", from.truecolor(150, 150, 150)),
+                (None, None) => {}
+            }
+            println!();
+        }
+        println!();
+    }
+
+    if !plan.held_back.is_empty() {
+        println!("{} ({})", "Held back".yellow().bold(), plan.held_back.len());
+        for name in &plan.held_back {
+            println!("  {} {}", "=".yellow(), name.bright_white());
+        }
+        println!();
+    }
+
+    println!(
+        "{} {} upgraded, {} newly installed, {} to remove, {} held back",
+        "Summary:".cyan().bold(),
+        plan.upgraded.len().to_string().green(),
+        plan.newly_installed.len().to_string().blue(),
+        plan.removed.len().to_string().red(),
+        plan.held_back.len().to_string().yellow()
+    );
+    println!(
+        "{} No changes were made. Run {} to apply.",
+        "ℹ️".blue(),
+        "catdog pkg upgrade".bright_white()
     );
 
-    let pm = package::detect_package_manager()?;
+    Ok(())
+}
+
+fn pkg_search(query: &str, limit: Option<usize>, config: &CliConfig) -> Result<()> {
+    if config.output == OutputFormat::Table {
+        println!(
+            "{} Searching for packages matching: {}\n",
+            "🔍".bold(),
+            query.bright_white()
+        );
+    }
+
+    let pm = package::select_package_manager(config.backend.as_deref())?;
+
+    if pm == package::PackageManager::Unknown {
+        anyhow::bail!("Unable to detect package manager on this system");
+    }
+
+    let packages = package::search_packages(query, &pm)?;
+
+    if packages.is_empty() && config.output == OutputFormat::Table {
+        println!("{}", "No packages found".yellow());
+        return Ok(());
+    }
+
+    // JSON and plain always carry every result; the limit only applies to the table
+    if config.output == OutputFormat::Plain {
+        // NAME VERSION DESCRIPTION
+        for pkg in &packages {
+            println!(
+                "{}",
+                plain_record(&[
+                    Some(&pkg.name),
+                    pkg.version.as_deref(),
+                    pkg.description.as_deref(),
+                ])
+            );
+        }
+    } else if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "query": query,
+                "count": packages.len(),
+                "packages": packages
+            }))?
+        );
+    } else {
+        println!(
+            "{} Found {} package(s):\n",
+            "✓".green().bold(),
+            packages.len()
+        );
+
+        let shown = limited(&packages, limit);
+        for pkg in shown {
+            print!("  {} {}", "•".blue(), pkg.name.bright_white());
+            if let Some(version) = &pkg.version {
+                print!(" {}", version.truecolor(150, 150, 150));
+            }
+            if let Some(description) = &pkg.description {
+                print!(" - {}", description.truecolor(180, 180, 180));
+            }
+            println!();
+        }
+
+        print_limit_note(shown.len(), packages.len(), "results");
+    }
+
+    Ok(())
+}
+
+fn pkg_list(limit: Option<usize>, config: &CliConfig) -> Result<()> {
+    if config.output == OutputFormat::Table {
+        println!("{} Listing installed packages...\n", "📋".bold());
+    }
+
+    let pm = package::select_package_manager(config.backend.as_deref())?;
+
+    if pm == package::PackageManager::Unknown {
+        anyhow::bail!("Unable to detect package manager on this system");
+    }
+
+    let packages = package::list_installed(&pm)?;
+
+    if packages.is_empty() && config.output == OutputFormat::Table {
+        println!("{}", "No packages installed".yellow());
+        return Ok(());
+    }
+
+    // JSON and plain always carry every package; the limit only applies to the table
+    if config.output == OutputFormat::Plain {
+        // NAME VERSION
+        for pkg in &packages {
+            println!(
+                "{}",
+                plain_record(&[Some(&pkg.name), pkg.version.as_deref()])
+            );
+        }
+    } else if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "count": packages.len(),
+                "packages": packages
+            }))?
+        );
+    } else {
+        println!(
+            "{} {} installed package(s):\n",
+            "✓".green().bold(),
+            packages.len()
+        );
+
+        println!(
+            "{:<40} {}",
+            "PACKAGE".cyan().bold(),
+            "VERSION".cyan().bold()
+        );
+        println!("{}", "=".repeat(60).bright_black());
+
+        let shown = limited(&packages, limit);
+        for pkg in shown {
+            print!("  {:<38}", pkg.name.bright_white());
+            if let Some(version) = &pkg.version {
+                print!(" {}", version.truecolor(150, 150, 150));
+            }
+            println!();
+        }
+
+        print_limit_note(shown.len(), packages.len(), "packages");
+        println!("\n{} Total: {} packages", "📦".bold(), packages.len());
+    }
+
+    Ok(())
+}
+
+fn take_package_snapshot(pm: &package::PackageManager) -> Result<PathBuf> {
+    let packages = package::list_installed(pm).context("Failed to snapshot installed packages")?;
+    package::save_snapshot(pm, packages, &package::snapshot_dir()?)
+}
+
+fn pkg_snapshot(config: &CliConfig) -> Result<()> {
+    let pm = package::select_package_manager(config.backend.as_deref())?;
 
     if pm == package::PackageManager::Unknown {
         anyhow::bail!("Unable to detect package manager on this system");
     }
 
-    let packages = package::search_packages(query, &pm)?;
+    if config.dry_run {
+        println!(
+            "{} Would snapshot installed packages to {}",
+            "[DRY-RUN]".yellow().bold(),
+            package::snapshot_dir()?.display()
+        );
+        return Ok(());
+    }
+
+    let path = take_package_snapshot(&pm)?;
+    let snapshot = package::load_snapshot(&path)?;
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "path": path,
+                "package_manager": snapshot.package_manager,
+                "count": snapshot.packages.len()
+            }))?
+        );
+    } else {
+        println!(
+            "{} Saved snapshot of {} {} package(s): {}",
+            "✓".green().bold(),
+            snapshot.packages.len(),
+            snapshot.package_manager,
+            path.display().to_string().bright_white()
+        );
+    }
+
+    Ok(())
+}
+
+fn pkg_snapshot_diff(old_name: &str, new_name: &str, config: &CliConfig) -> Result<()> {
+    let dir = package::snapshot_dir()?;
+    let old_path = package::resolve_snapshot(old_name, &dir)?;
+    let new_path = package::resolve_snapshot(new_name, &dir)?;
+    let old = package::load_snapshot(&old_path)?;
+    let new = package::load_snapshot(&new_path)?;
+    let diff = package::diff_snapshots(&old, &new);
+
+    if config.json_output() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "old": old_path,
+                "new": new_path,
+                "diff": diff
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        "Comparing:".cyan().bold(),
+        format!("{} <-> {}", old.timestamp, new.timestamp).bright_white()
+    );
+    println!("{}", "=".repeat(80).bright_black());
 
-    if packages.is_empty() {
-        println!("{}", "No packages found".yellow());
+    if diff.is_empty() {
+        println!("{} No package changes", "✓".green().bold());
         return Ok(());
     }
 
-    if config.json_output {
+    for pkg in &diff.added {
         println!(
-            "{}",
-            serde_json::to_string_pretty(&serde_json::json!({
-                "query": query,
-                "count": packages.len(),
-                "packages": packages
-            }))?
+            "  {} {:<38} {}",
+            "+".green().bold(),
+            pkg.name.bright_white(),
+            pkg.version.as_deref().unwrap_or("").green()
         );
-    } else {
+    }
+    for pkg in &diff.removed {
         println!(
-            "{} Found {} package(s):\n",
-            "✓".green().bold(),
-            packages.len()
+            "  {} {:<38} {}",
+            "-".red().bold(),
+            pkg.name.bright_white(),
+            pkg.version.as_deref().unwrap_or("").red()
+        );
+    }
+    for change in &diff.changed {
+        println!(
+            "  {} {:<38} {} → {}",
+            "~".yellow().bold(),
+            change.name.bright_white(),
+            change
+                .old_version
+                .as_deref()
+                .unwrap_or("-")
+                .truecolor(150, 150, 150),
+            change.new_version.as_deref().unwrap_or("-").green()
         );
-
-        for pkg in packages.iter().take(50) {
-            // Limit to first 50 results
-            print!("  {} {}", "•".blue(), pkg.name.bright_white());
-            if let Some(version) = &pkg.version {
-                print!(" {}", version.truecolor(150, 150, 150));
-            }
-            if let Some(description) = &pkg.description {
-                print!(" - {}", description.truecolor(180, 180, 180));
-            }
-            println!();
-        }
-
-        if packages.len() > 50 {
-            println!("\n{} Showing 50 of {} results", "ℹ️".blue(), packages.len());
-        }
     }
 
+    println!("{}", "=".repeat(80).bright_black());
+    println!(
+        "\n{} {} added, {} removed, {} changed",
+        "Summary:".cyan().bold(),
+        diff.added.len().to_string().green(),
+        diff.removed.len().to_string().red(),
+        diff.changed.len().to_string().yellow()
+    );
+
     Ok(())
 }
 
-fn pkg_list(config: &CliConfig) -> Result<()> {
-    println!("{} Listing installed packages...\n", "📋".bold());
-
-    let pm = package::detect_package_manager()?;
+fn pkg_upgradable(config: &CliConfig) -> Result<()> {
+    let pm = package::select_package_manager(config.backend.as_deref())?;
 
     if pm == package::PackageManager::Unknown {
         anyhow::bail!("Unable to detect package manager on this system");
     }
 
-    let packages = package::list_installed(&pm)?;
-
-    if packages.is_empty() {
-        println!("{}", "No packages installed".yellow());
-        return Ok(());
-    }
+    let packages = package::list_upgradable(&pm)?;
 
-    if config.json_output {
+    if config.json_output() {
         println!(
             "{}",
             serde_json::to_string_pretty(&serde_json::json!({
+                "package_manager": pm.name(),
                 "count": packages.len(),
                 "packages": packages
             }))?
         );
-    } else {
-        println!(
-            "{} {} installed package(s):\n",
-            "✓".green().bold(),
-            packages.len()
-        );
+        return Ok(());
+    }
 
-        println!(
-            "{:<40} {}",
-            "PACKAGE".cyan().bold(),
-            "VERSION".cyan().bold()
-        );
-        println!("{}", "=".repeat(60).bright_black());
+    println!(
+        "{} Pending updates ({})\n",
+        "⬆️".bold(),
+        pm.name().bright_white()
+    );
 
-        for pkg in &packages {
-            print!("  {:<38}", pkg.name.bright_white());
-            if let Some(version) = &pkg.version {
-                print!(" {}", version.truecolor(150, 150, 150));
-            }
-            println!();
-        }
+    if packages.is_empty() {
+        println!("{} Everything is up to date", "✓".green().bold());
+        return Ok(());
+    }
 
-        println!("\n{} Total: {} packages", "📦".bold(), packages.len());
+    println!(
+        "{:<40} {:<28} {}",
+        "PACKAGE".cyan().bold(),
+        "CURRENT".cyan().bold(),
+        "AVAILABLE".cyan().bold()
+    );
+    println!("{}", "=".repeat(90).bright_black());
+
+    for pkg in &packages {
+        println!(
+            "  {:<38} {:<28} {}",
+            pkg.name.bright_white(),
+            pkg.current_version
+                .as_deref()
+                .unwrap_or("-")
+                .truecolor(150, 150, 150),
+            pkg.available_version.as_deref().unwrap_or("-").green()
+        );
     }
 
+    println!(
+        "\n{} {} package(s) can be upgraded. Run {} to apply.",
+        "📦".bold(),
+        packages.len(),
+        "catdog pkg upgrade".bright_white()
+    );
+
     Ok(())
 }
 
@@ -2171,7 +5789,7 @@ fn pkg_info(package_name: &str, config: &CliConfig) -> Result<()> {
         package_name.bright_white()
     );
 
-    let pm = package::detect_package_manager()?;
+    let pm = package::select_package_manager(config.backend.as_deref())?;
 
     if pm == package::PackageManager::Unknown {
         anyhow::bail!("Unable to detect package manager on this system");
@@ -2179,7 +5797,7 @@ fn pkg_info(package_name: &str, config: &CliConfig) -> Result<()> {
 
     let is_installed = package::is_package_installed(package_name, &pm)?;
 
-    if config.json_output {
+    if config.json_output() {
         println!(
             "{}",
             serde_json::to_string_pretty(&serde_json::json!({
@@ -2206,143 +5824,355 @@ fn pkg_info(package_name: &str, config: &CliConfig) -> Result<()> {
     Ok(())
 }
 
-fn generate_fstab(output_file: Option<&str>, dry_run: bool) -> Result<()> {
-    println!("{} Generating fstab entries...\n", "🔧".bold());
+/// Find the existing fstab entry that already refers to a discovered device
+///
+/// Matches on the device path as well as any `UUID=`, `PARTUUID=` or `LABEL=` spec.
+fn existing_entry_for<'a>(
+    device: &BlockDevice,
+    existing: &'a [FstabEntry],
+) -> Option<&'a FstabEntry> {
+    let specs = device_identifiers(device);
+
+    existing.iter().find(|entry| {
+        let spec = entry.decoded_device().replace('"', "");
+        specs.iter().any(|s| s.eq_ignore_ascii_case(&spec))
+    })
+}
 
-    let devices = discover_block_devices()?;
+/// What `generate` produces and where it goes
+struct GenerateOptions<'a> {
+    output_file: Option<&'a str>,
+    merge_path: Option<&'a str>,
+    /// `--fs`: only devices with one of these filesystem types
+    fs_types: &'a [String],
+    /// `--split-by fs`: one snippet per filesystem type instead of a single file
+    split_by_fs: bool,
+    /// `--under <root>`: only devices whose suggested mount point is beneath this root
+    under: Option<&'a str>,
+    /// Cleanup for the backup taken before `--output` overwrites a file
+    retention: &'a BackupConfig,
+    dry_run: bool,
+}
 
-    if devices.is_empty() {
-        println!("{}", "No block devices found".yellow());
-        return Ok(());
+/// The commented fstab block for one device: device details, then the entry line
+fn format_generated_entry(device: &BlockDevice, suggestion: &MountSuggestion) -> String {
+    let mut text = String::new();
+
+    text.push_str(&format!("# Device: {}\n", device.device));
+    if let Some(ref label) = device.label {
+        text.push_str(&format!("# Label: {}\n", label));
+    }
+    if let Some(ref size) = device.size {
+        text.push_str(&format!("# Size: {}\n", size));
+    }
+    if device.is_ssd {
+        text.push_str("# Type: SSD (optimized options applied)\n");
+    }
+    if device.is_removable {
+        text.push_str("# Type: Removable (nofail option applied)\n");
+    }
+
+    text.push_str(&format!(
+        "{:<40} {:<20} {:<7} {:<22} {} {}\n",
+        suggestion.suggested_device_id,
+        suggestion.suggested_mount_point,
+        suggestion.suggested_fs_type,
+        suggestion.suggested_options.join(","),
+        "0",
+        if suggestion.suggested_mount_point == "/" {
+            "1"
+        } else {
+            "2"
+        }
+    ));
+    text.push('\n');
+
+    text
+}
+
+/// Whether a suggested (escaped) mount point lies beneath `root`
+fn mounts_under(mount_point: &str, root: &str) -> bool {
+    Path::new(&fstab::decode_field(mount_point)).starts_with(root)
+}
+
+/// Where the snippet for one filesystem goes: `fstab.new` becomes `fstab.new.ext4`
+fn split_output_path(output_file: &str, fs_type: &str) -> String {
+    format!("{}.{}", output_file, fs_type)
+}
+
+/// Write one generated file (backing up what was there), preview it in a dry
+/// run, or print it when there is no file; returns whether a file was written
+fn write_generated(
+    file_path: Option<&str>,
+    title: &str,
+    content: &str,
+    retention: &BackupConfig,
+    dry_run: bool,
+) -> Result<bool> {
+    let Some(file_path) = file_path else {
+        println!("{}", format!("Generated {}:", title).cyan().bold());
+        println!("{}", "=".repeat(100).bright_black());
+        print!("{}", content);
+        println!("{}", "=".repeat(100).bright_black());
+        return Ok(false);
+    };
+
+    if dry_run {
+        println!(
+            "{} Would write fstab to: {}",
+            "[DRY-RUN]".yellow().bold(),
+            file_path.bright_white()
+        );
+        println!("\n{}", "Preview of content:".cyan().bold());
+        println!("{}", "=".repeat(100).bright_black());
+        print!("{}", content);
+        println!("{}", "=".repeat(100).bright_black());
+        return Ok(false);
     }
 
-    // Build the fstab content
-    let mut fstab_content = String::new();
+    // Create backup before writing if file exists
+    if Path::new(file_path).exists() {
+        println!("{} Creating backup before modification...", "💾".blue());
+        let backup_metadata = backup::create_backup(
+            file_path,
+            backup::BackupReason::PreFstabModification,
+            retention,
+            false,
+        )?;
+        println!(
+            "{} Backup created: {}",
+            "✓".green(),
+            backup_metadata.backup_path.bright_white()
+        );
+    }
 
-    // Add header
-    fstab_content.push_str("# /etc/fstab: static file system information\n");
-    fstab_content.push_str("#\n");
-    fstab_content.push_str(
-        "# Generated by catdog - A filesystem utility that takes itself way too seriously\n",
+    state::write_atomic(Path::new(file_path), content)
+        .with_context(|| format!("Failed to write to {}", file_path))?;
+    println!(
+        "{} Generated fstab written to: {}",
+        "✓".green().bold(),
+        file_path.bright_white()
     );
-    fstab_content.push_str(&format!(
-        "# Generated at: {}\n",
-        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-    ));
-    fstab_content.push_str("#\n");
-    fstab_content.push_str("# <device>                                <mount point>    <type>  <options>              <dump> <pass>\n");
-    fstab_content.push_str("#\n\n");
 
-    let mut entry_count = 0;
+    Ok(true)
+}
+
+fn generate_fstab(opts: &GenerateOptions) -> Result<CommandOutcome> {
+    if opts.split_by_fs && opts.merge_path.is_some() {
+        anyhow::bail!("--split-by can't be combined with --merge");
+    }
+
+    println!("{} Generating fstab entries...\n", "🔧".bold());
+
+    let devices: Vec<BlockDevice> = discover_block_devices()?
+        .into_iter()
+        .filter(|d| matches_fs_filter(d, opts.fs_types))
+        .collect();
+
+    if devices.is_empty() {
+        if opts.fs_types.is_empty() {
+            println!("{}", "No block devices found".yellow());
+        } else {
+            println!(
+                "{}",
+                format!(
+                    "No block devices with filesystem {}",
+                    opts.fs_types.join(", ")
+                )
+                .yellow()
+            );
+        }
+        return Ok(CommandOutcome::default());
+    }
+
+    // Existing fstab to merge into: kept verbatim, only new devices are appended
+    let existing = match opts.merge_path {
+        Some(path) => {
+            let contents = fstab::read_fstab(path)?;
+            let entries = fstab::parse_entries(&contents).entries;
+            Some((path, contents, entries))
+        }
+        None => None,
+    };
+    let mut already_configured = Vec::new();
+
+    // Entry blocks with the filesystem each one is for
+    let mut generated: Vec<(String, String)> = Vec::new();
+    let mut skipped = 0;
 
     // Generate entries for each device
     for device in devices {
         // Skip devices that are already mounted at system locations
         if let Some(ref mp) = device.mount_point {
             if mp == "/" || mp == "/boot" || mp == "/boot/efi" {
+                skipped += 1;
                 continue;
             }
         }
 
         // Skip if no filesystem
         if device.fs_type.is_none() {
+            skipped += 1;
             continue;
         }
 
+        // Skip devices the merged fstab already has an entry for
+        if let Some((_, _, entries)) = &existing {
+            if let Some(entry) = existing_entry_for(&device, entries) {
+                already_configured.push(format!(
+                    "# {} (line {}: {} {})",
+                    device.device, entry.line_number, entry.device, entry.mount_point
+                ));
+                skipped += 1;
+                continue;
+            }
+        }
+
         let suggestion = suggest_mount_options(&device);
 
-        // Add comment with device info
-        fstab_content.push_str(&format!("# Device: {}\n", device.device));
-        if let Some(ref label) = device.label {
-            fstab_content.push_str(&format!("# Label: {}\n", label));
-        }
-        if let Some(ref size) = device.size {
-            fstab_content.push_str(&format!("# Size: {}\n", size));
-        }
-        if device.is_ssd {
-            fstab_content.push_str("# Type: SSD (optimized options applied)\n");
-        }
-        if device.is_removable {
-            fstab_content.push_str("# Type: Removable (nofail option applied)\n");
+        if let Some(root) = opts.under {
+            if !mounts_under(&suggestion.suggested_mount_point, root) {
+                continue;
+            }
         }
 
-        // Add the fstab entry
-        fstab_content.push_str(&format!(
-            "{:<40} {:<20} {:<7} {:<22} {} {}\n",
-            suggestion.suggested_device_id,
-            suggestion.suggested_mount_point,
-            suggestion.suggested_fs_type,
-            suggestion.suggested_options.join(","),
-            "0",
-            if suggestion.suggested_mount_point == "/" {
-                "1"
-            } else {
-                "2"
-            }
+        generated.push((
+            suggestion.suggested_fs_type.clone(),
+            format_generated_entry(&device, &suggestion),
         ));
-        fstab_content.push('\n');
-
-        entry_count += 1;
     }
 
+    let entry_count = generated.len();
     if entry_count == 0 {
         println!("{}", "No devices found that need fstab entries".yellow());
+        if existing.is_some() {
+            println!(
+                "  {} device(s) are already configured in the merged fstab.",
+                already_configured.len()
+            );
+        }
         println!("  Discovered devices are either already mounted at system locations");
         println!("  or don't have filesystems that can be mounted.");
-        return Ok(());
+        if let Some(root) = opts.under {
+            println!("  No suggested mount point falls under {}.", root);
+        }
+        return Ok(CommandOutcome::skipped(skipped));
     }
 
-    // Add footer
-    fstab_content.push_str("# End of generated fstab entries\n");
-    fstab_content.push_str(&format!("# Total entries generated: {}\n", entry_count));
-    fstab_content.push_str("#\n");
-    fstab_content.push_str("# IMPORTANT: Review these entries carefully before using!\n");
-    fstab_content.push_str("# 1. Create mount point directories: sudo mkdir -p <mount_point>\n");
-    fstab_content.push_str("# 2. Test with: sudo mount -a\n");
-    fstab_content.push_str("# 3. Check with: df -h\n");
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
 
-    // Output the result
-    match output_file {
-        Some(file_path) => {
-            if dry_run {
-                println!(
-                    "{} Would write fstab to: {}",
-                    "[DRY-RUN]".yellow().bold(),
-                    file_path.bright_white()
-                );
-                println!("\n{}", "Preview of content:".cyan().bold());
-                println!("{}", "=".repeat(100).bright_black());
-                print!("{}", fstab_content);
-                println!("{}", "=".repeat(100).bright_black());
-            } else {
-                // Create backup before writing if file exists
-                let path = Path::new(file_path);
-                if path.exists() {
-                    println!("{} Creating backup before modification...", "💾".blue());
-                    let backup_metadata = backup::create_backup(
-                        file_path,
-                        backup::BackupReason::PreFstabModification,
-                        false,
-                    )?;
-                    println!(
-                        "{} Backup created: {}",
-                        "✓".green(),
-                        backup_metadata.backup_path.bright_white()
-                    );
-                }
+    // Each output is a target file (or stdout), a title, its content and how many entries it holds
+    let mut outputs: Vec<(Option<String>, String, String, usize)> = Vec::new();
 
-                fs::write(file_path, &fstab_content)
-                    .with_context(|| format!("Failed to write to {}", file_path))?;
-                println!(
-                    "{} Generated fstab written to: {}",
-                    "✓".green().bold(),
-                    file_path.bright_white()
-                );
+    if opts.split_by_fs {
+        let mut by_fs: std::collections::BTreeMap<&str, Vec<&str>> =
+            std::collections::BTreeMap::new();
+        for (fs_type, text) in &generated {
+            by_fs.entry(fs_type).or_default().push(text);
+        }
+
+        for (fs_type, texts) in by_fs {
+            let mut content = format!(
+                "# {} entries generated by catdog at {}\n#\n\n",
+                fs_type, now
+            );
+            for text in &texts {
+                content.push_str(text);
+            }
+            content.push_str(&format!(
+                "# End of generated {} entries ({})\n",
+                fs_type,
+                texts.len()
+            ));
+            outputs.push((
+                opts.output_file.map(|f| split_output_path(f, fs_type)),
+                format!("{} entries", fs_type),
+                content,
+                texts.len(),
+            ));
+        }
+    } else {
+        // Build the fstab content
+        let mut fstab_content = String::new();
+
+        if let Some((path, contents, _)) = &existing {
+            fstab_content.push_str(contents);
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                fstab_content.push('\n');
+            }
+            fstab_content.push_str(&format!(
+                "\n# Added by catdog generate --merge {} at {}\n\n",
+                path, now
+            ));
+        } else {
+            // Add header
+            fstab_content.push_str("# /etc/fstab: static file system information\n");
+            fstab_content.push_str("#\n");
+            fstab_content.push_str(
+                "# Generated by catdog - A filesystem utility that takes itself way too seriously\n",
+            );
+            fstab_content.push_str(&format!("# Generated at: {}\n", now));
+            fstab_content.push_str("#\n");
+            fstab_content.push_str("# <device>                                <mount point>    <type>  <options>              <dump> <pass>\n");
+            fstab_content.push_str("#\n\n");
+        }
+
+        for (_, text) in &generated {
+            fstab_content.push_str(text);
+        }
+
+        // Add footer
+        if !already_configured.is_empty() {
+            fstab_content.push_str("# Skipped devices already present in the existing fstab:\n");
+            for line in &already_configured {
+                fstab_content.push_str(line);
+                fstab_content.push('\n');
             }
+            fstab_content.push_str("#\n");
+        }
+        fstab_content.push_str("# End of generated fstab entries\n");
+        fstab_content.push_str(&format!("# Total entries generated: {}\n", entry_count));
+        fstab_content.push_str("#\n");
+        fstab_content.push_str("# IMPORTANT: Review these entries carefully before using!\n");
+        fstab_content
+            .push_str("# 1. Create mount point directories: sudo mkdir -p <mount_point>\n");
+        fstab_content.push_str("# 2. Test with: sudo mount -a\n");
+        fstab_content.push_str("# 3. Check with: df -h\n");
+
+        outputs.push((
+            opts.output_file.map(String::from),
+            "fstab content".to_string(),
+            fstab_content,
+            entry_count,
+        ));
+    }
+
+    // Only writing a file counts as a change; previews leave everything untouched
+    let mut outcome = CommandOutcome::skipped(skipped);
+
+    for (file_path, title, content, count) in &outputs {
+        if write_generated(
+            file_path.as_deref(),
+            title,
+            content,
+            opts.retention,
+            opts.dry_run,
+        )? {
+            outcome.changed += count;
+        }
+    }
+
+    // Output the result
+    match opts.output_file {
+        Some(_) => {
+            let written: Vec<&str> = outputs
+                .iter()
+                .filter_map(|(path, _, _, _)| path.as_deref())
+                .collect();
             println!("\n{}", "Next steps:".cyan().bold());
             println!(
                 "  1. Review the file: {}",
-                format!("cat {}", file_path).bright_white()
+                format!("cat {}", written.join(" ")).bright_white()
             );
             println!("  2. Create mount directories for each entry");
             println!(
@@ -2357,11 +6187,6 @@ fn generate_fstab(output_file: Option<&str>, dry_run: bool) -> Result<()> {
             );
         }
         None => {
-            // Print to stdout
-            println!("{}", "Generated fstab content:".cyan().bold());
-            println!("{}", "=".repeat(100).bright_black());
-            print!("{}", fstab_content);
-            println!("{}", "=".repeat(100).bright_black());
             println!("\n{}", "To save to a file, use:".cyan().bold());
             println!("  {}", "catdog generate fstab.new".bright_white());
             println!(
@@ -2372,14 +6197,30 @@ fn generate_fstab(output_file: Option<&str>, dry_run: bool) -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(outcome)
 }
 
 // Backup command handlers
-fn backup_file_cmd(file_path: &str, dry_run: bool) -> Result<()> {
+fn backup_file_cmd(
+    file_path: &str,
+    encrypt: bool,
+    retention: &BackupConfig,
+    dry_run: bool,
+) -> Result<()> {
     println!("{} Creating backup...\n", "💾".bold());
 
-    let metadata = backup::create_backup(file_path, backup::BackupReason::Manual, dry_run)?;
+    let passphrase = if encrypt && !dry_run {
+        Some(backup::read_passphrase(true)?)
+    } else {
+        None
+    };
+    let metadata = backup::create_backup_with_passphrase(
+        file_path,
+        backup::BackupReason::Manual,
+        passphrase.as_deref(),
+        retention,
+        dry_run,
+    )?;
 
     if !dry_run {
         println!("{} Backup created successfully", "✓".green().bold());
@@ -2389,19 +6230,77 @@ fn backup_file_cmd(file_path: &str, dry_run: bool) -> Result<()> {
     Ok(())
 }
 
-fn restore_backup_cmd(backup_path: &str, dry_run: bool, force: bool) -> Result<()> {
+fn restore_backup_cmd(
+    backup_path: &str,
+    target: Option<&str>,
+    force: bool,
+    ignore_missing: bool,
+    config: &CliConfig,
+) -> Result<()> {
+    let dry_run = config.dry_run;
+    let destination = backup::restore_destination(backup_path, target)?;
+    if !dry_run && destination.exists() {
+        let prompt = format!(
+            "Overwrite {} with {}? [y/N] ",
+            destination.display(),
+            backup_path
+        );
+        if !confirm(&prompt, config.assume_yes) {
+            anyhow::bail!("Aborted: {} was not changed", destination.display());
+        }
+    }
+
     println!("{} Restoring from backup...\n", "♻️".bold());
 
-    backup::restore_backup(backup_path, dry_run, force)?;
+    backup::restore_backup(
+        backup_path,
+        target,
+        &config.app_config.backup,
+        dry_run,
+        force,
+        ignore_missing,
+    )?;
 
     if !dry_run {
-        println!("\n{} Backup restored successfully", "✓".green().bold());
+        match target {
+            Some(target) => println!(
+                "\n{} Backup restored to {}",
+                "✓".green().bold(),
+                target.bright_white()
+            ),
+            None => println!("\n{} Backup restored successfully", "✓".green().bold()),
+        }
     }
 
     Ok(())
 }
 
-fn list_backups_cmd(file_path: &str) -> Result<()> {
+fn list_backups_cmd(file_path: &str, config: &CliConfig) -> Result<()> {
+    match config.output {
+        OutputFormat::Json => {
+            let backups = backup::list_backups(file_path)?;
+            println!("{}", serde_json::to_string_pretty(&backups)?);
+            return Ok(());
+        }
+        OutputFormat::Plain => {
+            // TIMESTAMP BACKUP_PATH SIZE_BYTES ENCRYPTED REASON
+            for backup in backup::list_backups(file_path)? {
+                println!(
+                    "{}",
+                    plain_record(&[
+                        Some(&backup.timestamp),
+                        Some(&backup.backup_path),
+                        Some(&backup.size_bytes.to_string()),
+                        Some(&backup.encrypted.to_string()),
+                        Some(&backup.reason.description()),
+                    ])
+                );
+            }
+            return Ok(());
+        }
+        OutputFormat::Table => {}
+    }
+
     println!(
         "{} Listing backups for: {}\n",
         "📋".bold(),
@@ -2414,9 +6313,94 @@ fn list_backups_cmd(file_path: &str) -> Result<()> {
     Ok(())
 }
 
-fn backup_stats_cmd() -> Result<()> {
+fn audit_tail_cmd(count: usize, config: &CliConfig) -> Result<()> {
+    let path = audit::audit_log_path()?;
+    let entries = audit::tail(&path, count)?;
+
+    match config.output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+        OutputFormat::Plain => {
+            for entry in &entries {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    entry.timestamp.to_rfc3339(),
+                    entry.user,
+                    entry.action,
+                    entry.target,
+                    if entry.success { "ok" } else { "failed" },
+                    entry.dry_run
+                );
+            }
+        }
+        OutputFormat::Table => {
+            println!(
+                "{} Last {} audit entries from {}\n",
+                "📜".bold(),
+                entries.len(),
+                path.display().to_string().bright_white()
+            );
+            audit::display_entries(&entries);
+        }
+    }
+
+    Ok(())
+}
+
+/// Show what restoring a backup would change in the current file
+fn backup_diff_cmd(backup_path: &str, config: &CliConfig) -> Result<()> {
+    let (metadata, contents) = backup::read_backup(backup_path)?;
+    let backup_contents = String::from_utf8_lossy(&contents);
+
+    // A missing original diffs as empty, so the whole backup shows as additions
+    let original = Path::new(&metadata.original_path);
+    let current_exists = original.exists();
+    let current = if current_exists {
+        let bytes = fs::read(original)
+            .with_context(|| format!("Failed to read {}", metadata.original_path))?;
+        String::from_utf8_lossy(&bytes).into_owned()
+    } else {
+        String::new()
+    };
+
+    if config.json_output() {
+        let stats = diff::line_stats(&current, &backup_contents);
+        let output = serde_json::json!({
+            "backup": metadata.backup_path,
+            "original": metadata.original_path,
+            "timestamp": metadata.timestamp,
+            "current_exists": current_exists,
+            "additions": stats.additions,
+            "deletions": stats.deletions,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if !current_exists {
+        println!(
+            "{} {} no longer exists; showing the whole backup\n",
+            "⚠".yellow().bold(),
+            metadata.original_path.bright_white()
+        );
+    }
+
+    diff::display_diff(
+        &current,
+        &backup_contents,
+        "current",
+        &format!("backup@{}", metadata.timestamp),
+    );
+
+    Ok(())
+}
+
+fn backup_stats_cmd(config: &CliConfig) -> Result<()> {
     let stats = backup::get_backup_stats()?;
-    stats.display();
+    if config.json_output() {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        stats.display();
+    }
     Ok(())
 }
 
@@ -2517,24 +6501,66 @@ fn print_help() {
 
     println!("{}", "FLAGS:".cyan().bold());
     println!(
-        "    {}         Output in JSON format (for automation)",
+        "    {}  table (default), json or plain (tab-separated, no color)",
+        "--output <format>".bright_yellow()
+    );
+    println!(
+        "    {}         Output in JSON format, same as --output json",
         "--json".bright_yellow()
     );
     println!(
-        "    {}      Disable colored output",
+        "    {}  auto (default: color on a terminal unless NO_COLOR is set), always or never",
+        "--color <when>".bright_yellow()
+    );
+    println!(
+        "    {}      Disable colored output, same as --color never",
         "--no-color".bright_yellow()
     );
     println!(
-        "    {}       Show preview without making changes",
-        "--dry-run".bright_yellow()
+        "    {}   Show preview without making changes",
+        "-n, --dry-run".bright_yellow()
+    );
+    println!(
+        "    {}  Use this config file (or set CATDOG_CONFIG)",
+        "--config <path>".bright_yellow()
+    );
+    println!(
+        "    {}  Use this package manager instead of detecting one (apt, dnf, flatpak, snap, ...)",
+        "--backend <name>".bright_yellow()
+    );
+    println!(
+        "    {}      Run package/service commands without sudo",
+        "--no-sudo".bright_yellow()
+    );
+    println!(
+        "    {}         Manage systemd user units (systemctl --user)",
+        "--user".bright_yellow()
+    );
+    println!(
+        "    {}       Don't ask before removing, restoring over or stopping things (required without a terminal)",
+        "-y, --yes".bright_yellow()
+    );
+    println!(
+        "    {}     Skip the summary line after write commands",
+        "-q, --quiet".bright_yellow()
     );
     println!(
         "    {}    Enable verbose logging",
         "-v, --verbose".bright_yellow()
     );
     println!(
-        "    {}  Show version information",
-        "-V, --version".bright_yellow()
+        "    {}  Show version information",
+        "-V, --version".bright_yellow()
+    );
+    println!(
+        "\n    Flags go before or after the command, take values as {} or {},",
+        "--flag value".bright_white(),
+        "--flag=value".bright_white()
+    );
+    println!(
+        "    and short ones combine ({}). Arguments after {} are never flags.",
+        "-vn".bright_white(),
+        "--".bright_white()
     );
     println!();
 
@@ -2548,11 +6574,11 @@ fn print_help() {
         "cat".bright_yellow()
     );
     println!(
-        "    {}          Parse and display /etc/fstab in table format",
+        "    {}          Parse and display /etc/fstab in table format (supports --json)",
         "dog".bright_yellow()
     );
     println!(
-        "    {}     List all mount points",
+        "    {}     List all mount points (supports --json)",
         "list, ls".bright_yellow()
     );
     println!(
@@ -2560,27 +6586,51 @@ fn print_help() {
         "find <term>".bright_yellow()
     );
     println!(
-        "    {}     Check /etc/fstab for common issues",
+        "    {}     Check /etc/fstab for common issues (supports --json; exits 65 on critical issues)",
         "validate".bright_yellow()
     );
     println!(
-        "    {}    Discover available block devices (supports --json)",
+        "    {}   Add/remove mount options on an entry (--add <opt> --remove <opt>)",
+        "set-option".bright_yellow()
+    );
+    println!(
+        "    {}         Edit one entry by mount point or device (--option add:<opt>|remove:<opt>, --set-pass <n>)",
+        "edit".bright_yellow()
+    );
+    println!(
+        "    {}    Append a validated entry (--device <spec> --mount <path> --type <fs>, optional --options, --dump, --pass)",
+        "fstab add".bright_yellow()
+    );
+    println!(
+        "    {}     Mount or unmount an fstab entry (--force to allow /, --create-dirs makes a missing mount point)",
+        "mount|unmount <mp>".bright_yellow()
+    );
+    println!(
+        "    {}    Create a mount point directory under /mnt, /media or /srv (--force for elsewhere)",
+        "mkmount <mp>".bright_yellow()
+    );
+    println!(
+        "    {}    Discover block devices grouped by disk (--flat for a plain list, --bytes for exact sizes, supports --json)",
         "discover".bright_yellow()
     );
     println!(
-        "    {}       Generate smart mount suggestions for devices",
+        "    {}     Show every identifier (path, UUID, PARTUUID, LABEL) for one device",
+        "resolve <UUID=...|LABEL=...|/dev/...>".bright_yellow()
+    );
+    println!(
+        "    {}       Generate smart mount suggestions for devices (--fs <type[,type]> limits to filesystems)",
         "suggest [device]".bright_yellow()
     );
     println!(
-        "    {}       Generate complete fstab from discovered devices",
+        "    {}       Generate complete fstab from discovered devices (--merge <fstab> appends only new devices, --fs <type[,type]> limits to filesystems, --split-by fs writes <file>.<fs> per filesystem, --under <dir> keeps mount points beneath dir)",
         "generate [file]".bright_yellow()
     );
     println!(
-        "    {}        Create verified backup with metadata",
+        "    {}        Create verified backup with metadata (--encrypt prompts for a passphrase or reads CATDOG_BACKUP_KEY)",
         "backup [file]".bright_yellow()
     );
     println!(
-        "    {}      Restore from a backup (use --force to override)",
+        "    {}      Restore from a backup (--to <path>, --force, --ignore-missing)",
         "restore <backup>".bright_yellow()
     );
     println!(
@@ -2588,7 +6638,11 @@ fn print_help() {
         "list-backups <file>".bright_yellow()
     );
     println!(
-        "    {}   Show backup statistics and disk usage",
+        "    {}  Show what restoring a backup would change (supports --json)",
+        "backup-diff <backup>".bright_yellow()
+    );
+    println!(
+        "    {}   Show backup statistics and disk usage per file (supports --json)",
         "backup-stats".bright_yellow()
     );
     println!(
@@ -2600,9 +6654,21 @@ fn print_help() {
         "backup-drill".bright_yellow()
     );
     println!(
-        "    {}  Compare two fstab files with colored diff",
+        "    {}  Compare two fstab files with colored diff (--ignore-comments for entries only)",
         "diff <file1> <file2>".bright_yellow()
     );
+    println!(
+        "    {}  Compare entries by mount point, ignoring order and option order",
+        "diff --semantic <a> <b>".bright_yellow()
+    );
+    println!(
+        "    {}  Patch-format output with N context lines (default 3)",
+        "diff --unified [-U N] <a> <b>".bright_yellow()
+    );
+    println!(
+        "    {}  Two-column diff fitted to the terminal (--width N to override)",
+        "diff --side-by-side <a> <b>".bright_yellow()
+    );
 
     println!(
         "\n{} {} {}",
@@ -2611,17 +6677,49 @@ fn print_help() {
         "COMMANDS:".cyan().bold()
     );
     println!(
-        "    {}       Run filesystem health checks once",
+        "    {}       Run filesystem health checks once (exits 4 if new critical barks fire)",
         "check".bright_yellow()
     );
     println!(
         "    {}       Start continuous monitoring (default: 300s interval)",
         "monitor [interval]".bright_yellow()
     );
+    println!(
+        "    {}   Also write a Prometheus textfile after every check",
+        "monitor --export-prometheus <path>".bright_yellow()
+    );
     println!(
         "    {}        List all barks (optionally filter: firing/acknowledged/resolved/silenced)",
         "barks [status]".bright_yellow()
     );
+    println!(
+        "    {}   One line per bark plus counts, or only the per-status counts",
+        "barks --compact|--count".bright_yellow()
+    );
+    println!(
+        "    {}   Only barks created since a duration ago (30m, 6h, 7d) or a date (2024-01-01)",
+        "barks --since <when>".bright_yellow()
+    );
+    println!(
+        "    {}   Only barks whose metadata matches, e.g. mount_point=/var (repeat to AND)",
+        "barks --where <key=value>".bright_yellow()
+    );
+    println!(
+        "    {}   Redraw firing (or the given status) barks every n seconds (default 5), marking new ones",
+        "barks [status] --watch [n]".bright_yellow()
+    );
+    println!(
+        "    {}   Counts by status and severity, mean time to acknowledge/resolve, top sources",
+        "barks stats".bright_yellow()
+    );
+    println!(
+        "    {}   Remove resolved/silenced barks older than the given age (default: retain_resolved_days; required when it is 0)",
+        "barks prune [--older-than 7d]".bright_yellow()
+    );
+    println!(
+        "    {}   Send a test Info notification through the configured channel(s)",
+        "alerts test [--channel <kind>]".bright_yellow()
+    );
     println!(
         "    {}         Show detailed information about a bark",
         "bark <id>".bright_yellow()
@@ -2630,10 +6728,18 @@ fn print_help() {
         "    {}           Acknowledge a bark (alias: pet)",
         "ack <id>".bright_yellow()
     );
+    println!(
+        "    {}   Acknowledge every firing bark matching a filter (--all asks first unless --force)",
+        "ack --all|--severity <level>|--status <status>".bright_yellow()
+    );
     println!(
         "    {}      Resolve a bark (alias: quiet)",
         "resolve <id>".bright_yellow()
     );
+    println!(
+        "    {}   Resolve every bark matching a filter (--all asks first unless --force)",
+        "resolve --all|--severity <level>|--status <status>".bright_yellow()
+    );
     println!(
         "    {}     Silence a bark (alias: hush)",
         "silence <id>".bright_yellow()
@@ -2645,13 +6751,33 @@ fn print_help() {
         "corpus ingest <file>".bright_yellow()
     );
     println!(
-        "    {}       Search the corpus",
+        "    {}                List ingested configurations",
+        "corpus list".bright_yellow()
+    );
+    println!(
+        "    {}         Remove a configuration (ID prefix accepted)",
+        "corpus remove <id>".bright_yellow()
+    );
+    println!(
+        "    {}       Search the corpus (--limit N, default 20, or --all)",
         "corpus search <query>".bright_yellow()
     );
     println!(
         "    {}       Show corpus statistics",
         "corpus stats".bright_yellow()
     );
+    println!(
+        "    {}  Suggest mount options based on the library",
+        "corpus recommend <device>".bright_yellow()
+    );
+    println!(
+        "    {}       Bundle the library into a portable archive",
+        "corpus export <file>".bright_yellow()
+    );
+    println!(
+        "    {}       Merge an exported library, skipping duplicates",
+        "corpus import <file>".bright_yellow()
+    );
 
     println!(
         "\n{} {}",
@@ -2659,15 +6785,15 @@ fn print_help() {
         "MANAGEMENT:".cyan().bold()
     );
     println!(
-        "    {}       Start a service",
+        "    {}       Start one or more services",
         "service start <service>".bright_yellow()
     );
     println!(
-        "    {}        Stop a service",
+        "    {}        Stop one or more services",
         "service stop <service>".bright_yellow()
     );
     println!(
-        "    {}     Restart a service",
+        "    {}     Restart one or more services",
         "service restart <service>".bright_yellow()
     );
     println!(
@@ -2678,14 +6804,26 @@ fn print_help() {
         "    {}     Disable a service from starting on boot",
         "service disable <service>".bright_yellow()
     );
+    println!(
+        "    {}        Prevent a service from starting at all (--force for critical units)",
+        "service mask <service>".bright_yellow()
+    );
+    println!(
+        "    {}      Undo service mask",
+        "service unmask <service>".bright_yellow()
+    );
     println!(
         "    {}      Get service status",
         "service status <service>".bright_yellow()
     );
     println!(
-        "    {}       List all services (supports --json)",
+        "    {}       List services (--enabled-only for autostart ones; --limit N, default 50, or --all; supports --json)",
         "service list".bright_yellow()
     );
+    println!(
+        "    {}        Show recent logs (--lines N, default 50; --follow to tail)",
+        "service logs <service>".bright_yellow()
+    );
 
     println!(
         "\n{} {}",
@@ -2696,6 +6834,28 @@ fn print_help() {
         "    {}         Show comprehensive system information (supports --json)",
         "info".bright_yellow()
     );
+    println!(
+        "    {}   Live CPU, memory, disk and alert summary (default every 2s)",
+        "watch [interval]".bright_yellow()
+    );
+    println!(
+        "    {}  Show the N busiest processes (default 10; --sort cpu|mem)",
+        "info --top-processes [N]".bright_yellow()
+    );
+    println!(
+        "    {}  SMART health and sector counts per disk (needs smartctl; supports --json)",
+        "disk-health [device...]".bright_yellow()
+    );
+    println!(
+        "    {}       Check catdog's tools, fstab, config and state dirs, plus failed units and zombies (supports --json)",
+        "doctor".bright_yellow()
+    );
+
+    println!("\n{} {}", "QUICK".cyan().bold(), "FACTS:".cyan().bold());
+    println!(
+        "    {}  Single facts for scripts and prompts (support --json)",
+        "uptime, hostname, kernel".bright_yellow()
+    );
 
     println!(
         "\n{} {}",
@@ -2719,18 +6879,56 @@ fn print_help() {
         "pkg upgrade".bright_yellow()
     );
     println!(
-        "    {}       Search for packages",
+        "    {}       Preview what an upgrade would change (read-only)",
+        "pkg upgrade --plan".bright_yellow()
+    );
+    println!(
+        "    {}       Search for packages (--limit N, default 50, or --all)",
         "pkg search <query>".bright_yellow()
     );
     println!(
-        "    {}       List all installed packages (supports --json)",
+        "    {}       List installed packages (all by default, or --limit N; supports --json)",
         "pkg list".bright_yellow()
     );
+    println!(
+        "    {}       List packages with pending updates (alias: pkg list --upgradable; supports --json)",
+        "pkg upgradable".bright_yellow()
+    );
+    println!(
+        "    {}       Save the installed package list (pkg upgrade does this first)",
+        "pkg snapshot".bright_yellow()
+    );
+    println!(
+        "    {}       Show packages added, removed or changed between two snapshots",
+        "pkg snapshot-diff <snap1> <snap2>".bright_yellow()
+    );
     println!(
         "    {}       Check if a package is installed",
         "pkg info <package>".bright_yellow()
     );
 
+    println!("\n{}", "CONFIGURATION:".cyan().bold());
+    println!(
+        "    {}         Show effective settings, marking defaults (supports --json)",
+        "config show".bright_yellow()
+    );
+    println!(
+        "    {}         Print the config file location",
+        "config path".bright_yellow()
+    );
+    println!(
+        "    {}         Open the config file in $EDITOR, creating it if needed",
+        "config edit".bright_yellow()
+    );
+    println!(
+        "    {}        Show the last n mutating commands from ~/.catdog/audit.log (default 20)",
+        "audit tail [n]".bright_yellow()
+    );
+    println!(
+        "    {}  Print a completion script, e.g. source <(catdog completions bash)",
+        "completions <bash|zsh|fish>".bright_yellow()
+    );
+
     println!(
         "\n    {}         Show this help message",
         "help".bright_yellow()
@@ -2860,6 +7058,73 @@ mod tests {
     use super::*;
     use std::io::Write;
 
+    /// A bare unmounted device; tests set the fields they care about with `..`
+    fn test_device(path: &str, fs_type: &str) -> BlockDevice {
+        BlockDevice {
+            device: path.to_string(),
+            uuid: None,
+            partuuid: None,
+            label: None,
+            fs_type: Some(fs_type.to_string()),
+            size: None,
+            size_bytes: None,
+            mount_point: None,
+            is_removable: false,
+            is_ssd: false,
+            parent: None,
+        }
+    }
+
+    #[test]
+    fn test_confirm_yes_short_circuits() {
+        // `--yes` answers without reading input, even when not interactive
+        assert!(confirm_with(
+            "Remove vim? [y/N] ",
+            true,
+            false,
+            std::io::empty()
+        ));
+        assert!(confirm_with("Remove vim? [y/N] ", true, true, &b"n\n"[..]));
+
+        // Without it, only an explicit yes on a terminal goes ahead
+        assert!(confirm_with(
+            "Remove vim? [y/N] ",
+            false,
+            true,
+            &b"Yes\n"[..]
+        ));
+        assert!(!confirm_with("Remove vim? [y/N] ", false, true, &b"\n"[..]));
+        assert!(!confirm_with(
+            "Remove vim? [y/N] ",
+            false,
+            false,
+            &b"y\n"[..]
+        ));
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!(ColorChoice::from_name("ALWAYS"), Some(ColorChoice::Always));
+        assert_eq!(ColorChoice::from_name("sometimes"), None);
+
+        // auto: a terminal gets color unless NO_COLOR is set
+        assert!(ColorChoice::Auto.enabled(false, true));
+        assert!(!ColorChoice::Auto.enabled(true, true));
+        assert!(!ColorChoice::Auto.enabled(false, false));
+
+        // explicit settings ignore both
+        assert!(ColorChoice::Always.enabled(true, false));
+        assert!(!ColorChoice::Never.enabled(false, true));
+    }
+
+    #[test]
+    fn test_output_format_from_name() {
+        assert_eq!(OutputFormat::from_name("json"), Some(OutputFormat::Json));
+        assert_eq!(OutputFormat::from_name("Plain"), Some(OutputFormat::Plain));
+        assert_eq!(OutputFormat::from_name("table"), Some(OutputFormat::Table));
+        assert_eq!(OutputFormat::from_name("yaml"), None);
+    }
+
     fn create_test_fstab(content: &str) -> tempfile::NamedTempFile {
         let mut file = tempfile::NamedTempFile::new().unwrap();
         file.write_all(content.as_bytes()).unwrap();
@@ -2899,7 +7164,7 @@ tmpfs /tmp tmpfs defaults 0 0
     fn test_parse_fstab_with_invalid_lines() {
         let content = r#"
 UUID=abc-123 / ext4 defaults 0 1
-invalid only four fields
+invalid three-field line
 /dev/sda2 /home ext4 defaults 0 2
 "#;
         let file = create_test_fstab(content);
@@ -2924,4 +7189,675 @@ invalid only four fields
         assert_eq!(entry.dump, "0");
         assert_eq!(entry.pass, "2");
     }
+
+    #[test]
+    fn test_fstab_json_includes_parse_warnings() {
+        let content = r#"
+UUID=abc-123 / ext4 defaults 0 1
+/dev/sdb1 /data ext4
+/dev/sda2 /home ext4 defaults 0 2
+"#;
+        let file = create_test_fstab(content);
+        let path = file.path().to_str().unwrap();
+        let parsed = load_fstab(path).unwrap();
+        let json = fstab_json(path, &parsed);
+
+        assert_eq!(json["count"], 2);
+        let warnings = json["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["line_number"], 3);
+        assert_eq!(warnings[0]["code"], "field_count");
+        assert!(warnings[0]["message"].as_str().unwrap().contains("found 3"));
+    }
+
+    #[test]
+    fn test_discovered_devices_group_by_disk() {
+        let lsblk = serde_json::json!({
+            "blockdevices": [
+                {
+                    "name": "sdb", "fstype": null, "size": "2T", "rm": "0", "rota": "1",
+                    "children": [
+                        {"name": "sdb1", "fstype": "xfs", "size": "2T", "rm": "0", "rota": "1"}
+                    ]
+                },
+                {
+                    "name": "nvme0n1", "fstype": null, "size": "512G", "rm": "0", "rota": "0",
+                    "children": [
+                        {"name": "nvme0n1p1", "fstype": "vfat", "size": "512M", "rm": "0", "rota": "0"},
+                        {
+                            "name": "nvme0n1p2", "fstype": "crypto_LUKS", "size": "511.5G",
+                            "rm": "0", "rota": "0",
+                            "children": [
+                                {"name": "/dev/mapper/root", "fstype": "ext4", "size": "511.5G"}
+                            ]
+                        }
+                    ]
+                }
+            ]
+        });
+
+        let devices = parse_lsblk_devices(&lsblk);
+        assert_eq!(devices.len(), 4);
+        assert_eq!(devices[0].parent.as_deref(), Some("/dev/sdb"));
+        assert_eq!(devices[3].device, "/dev/mapper/root");
+        assert_eq!(devices[3].parent.as_deref(), Some("/dev/nvme0n1"));
+
+        let groups = group_by_disk(&devices);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "/dev/nvme0n1");
+        assert_eq!(groups[0].1.len(), 3);
+        assert_eq!(groups[1].0, "/dev/sdb");
+        assert_eq!(groups[1].1.len(), 1);
+
+        assert_eq!(devices[0].size_bytes, Some(2 << 40));
+        assert_eq!(devices[1].size_bytes, Some(512 << 20));
+
+        assert_eq!(parse_size("512M"), Some(512 * 1024 * 1024));
+        assert_eq!(parse_size("1.5 GB"), Some(1536 * 1024 * 1024));
+        assert_eq!(parse_size("bogus"), None);
+        assert_eq!(macos_parent_disk("disk0s2").as_deref(), Some("/dev/disk0"));
+        assert_eq!(macos_parent_disk("disk0"), None);
+    }
+
+    #[test]
+    fn test_capacity_skips_lvm_volumes() {
+        // lsblk -p output for a disk whose second partition is an LVM PV
+        let lsblk = serde_json::json!({
+            "blockdevices": [
+                {
+                    "name": "/dev/sda", "fstype": null, "size": 1099511627776u64,
+                    "rm": "0", "rota": "1",
+                    "children": [
+                        {"name": "/dev/sda1", "fstype": "vfat", "size": 536870912, "rm": "0", "rota": "1"},
+                        {
+                            "name": "/dev/sda2", "fstype": "LVM2_member", "size": 1098974756864u64,
+                            "rm": "0", "rota": "1",
+                            "children": [
+                                {"name": "/dev/mapper/vg-root", "fstype": "ext4", "size": 107374182400u64, "rm": "0", "rota": "1"},
+                                {"name": "/dev/mapper/vg-home", "fstype": "xfs", "size": 536870912000u64, "rm": "0", "rota": "1"}
+                            ]
+                        }
+                    ]
+                }
+            ]
+        });
+
+        let devices = parse_lsblk_devices(&lsblk);
+        assert_eq!(devices.len(), 4);
+        assert!(devices
+            .iter()
+            .all(|d| d.parent.as_deref() == Some("/dev/sda")));
+
+        let summary = CapacitySummary::from_devices(&devices);
+        assert_eq!(summary.devices, 2);
+        assert_eq!(summary.total_bytes, 1 << 40);
+
+        let groups = group_by_disk(&devices);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(disk_subtotal(&groups[0].1), summary.total_bytes);
+    }
+
+    #[test]
+    fn test_byte_sizes() {
+        let lsblk = serde_json::json!({
+            "blockdevices": [
+                {"name": "sda1", "fstype": "ext4", "size": 500107862016u64, "rm": "0", "rota": "1"},
+                {"name": "sda2", "fstype": "swap", "size": "8589934592", "rm": "0", "rota": "1"}
+            ]
+        });
+        let devices = parse_lsblk_devices(&lsblk);
+        assert_eq!(devices[0].size_bytes, Some(500107862016));
+        assert_eq!(devices[0].size.as_deref(), Some("465.76 GB"));
+        assert_eq!(devices[1].size_bytes, Some(8 << 30));
+
+        assert_eq!(
+            diskutil_size_bytes("500.3 GB (500277792768 Bytes) (exactly 977105064 512-Byte-Units)"),
+            Some(500277792768)
+        );
+        assert_eq!(diskutil_size_bytes("1.5 GB"), Some(1536 << 20));
+        assert_eq!(format_size(1024, true), "1024 B");
+        assert_eq!(format_size(1024, false), "1.00 KB");
+    }
+
+    #[test]
+    fn test_capacity_summary() {
+        let device =
+            |path: &str, size_bytes: Option<u64>, is_ssd: bool, is_removable: bool| BlockDevice {
+                size_bytes,
+                is_removable,
+                is_ssd,
+                ..test_device(path, "ext4")
+            };
+        let devices = vec![
+            device("/dev/nvme0n1p2", Some(500 << 30), true, false),
+            device("/dev/mapper/root", Some(499 << 30), true, false),
+            device("/dev/sda1", Some(2 << 40), false, false),
+            device("/dev/sdb1", Some(64 << 30), true, true),
+            device("/dev/sdc1", None, false, true),
+        ];
+
+        let summary = CapacitySummary::from_devices(&devices);
+        assert_eq!(summary.devices, 4);
+        assert_eq!(summary.ssd, 2);
+        assert_eq!(summary.removable, 2);
+        assert_eq!(summary.unknown_size, 1);
+        assert_eq!(summary.ssd_bytes, 564 << 30);
+        assert_eq!(summary.hdd_bytes, 2 << 40);
+        assert_eq!(summary.total_bytes, (564 << 30) + (2 << 40));
+
+        assert_eq!(
+            CapacitySummary::from_devices(&[]),
+            CapacitySummary::default()
+        );
+    }
+
+    #[test]
+    fn test_fs_filter() {
+        let devices = [
+            test_device("/dev/sda1", "ext4"),
+            test_device("/dev/sdb1", "xfs"),
+            test_device("/dev/sdc1", "vfat"),
+            BlockDevice {
+                fs_type: None,
+                ..test_device("/dev/sdd1", "")
+            },
+        ];
+        let kept = |filter: &[&str]| -> Vec<String> {
+            let filter: Vec<String> = filter.iter().map(|s| s.to_string()).collect();
+            devices
+                .iter()
+                .filter(|d| matches_fs_filter(d, &filter))
+                .map(|d| d.device.clone())
+                .collect()
+        };
+
+        assert_eq!(kept(&["EXT4"]), vec!["/dev/sda1"]);
+        assert_eq!(kept(&["ext4", "vfat"]), vec!["/dev/sda1", "/dev/sdc1"]);
+        assert_eq!(kept(&[]).len(), 4);
+        assert!(kept(&["btrfs"]).is_empty());
+
+        let cli = cli::parse(&[
+            "catdog".to_string(),
+            "suggest".to_string(),
+            "--fs".to_string(),
+            "ext4,xfs".to_string(),
+            "--filesystem".to_string(),
+            "vfat".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(fs_filter(&cli), vec!["ext4", "xfs", "vfat"]);
+    }
+
+    #[test]
+    fn test_format_generated_entry() {
+        let device = BlockDevice {
+            uuid: Some("1234-ABCD".to_string()),
+            label: Some("Backup Disk".to_string()),
+            size: Some("1.82 TB".to_string()),
+            size_bytes: Some(2 << 40),
+            is_removable: true,
+            ..test_device("/dev/sdb1", "ext4")
+        };
+        let suggestion = MountSuggestion {
+            suggested_device_id: "UUID=1234-ABCD".to_string(),
+            suggested_mount_point: "/mnt/backup_disk".to_string(),
+            suggested_options: vec!["defaults".to_string(), "nofail".to_string()],
+            suggested_fs_type: "ext4".to_string(),
+            rationale: Vec::new(),
+        };
+
+        let text = format_generated_entry(&device, &suggestion);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[..4],
+            [
+                "# Device: /dev/sdb1",
+                "# Label: Backup Disk",
+                "# Size: 1.82 TB",
+                "# Type: Removable (nofail option applied)"
+            ]
+        );
+        let entry = fstab::parse_entries(lines[4]).entries.remove(0);
+        assert_eq!(entry.device, "UUID=1234-ABCD");
+        assert_eq!(entry.mount_point, "/mnt/backup_disk");
+        assert_eq!(entry.options, "defaults,nofail");
+        assert_eq!((entry.dump.as_str(), entry.pass.as_str()), ("0", "2"));
+        assert!(text.ends_with("\n\n"));
+
+        assert!(mounts_under("/mnt/backup_disk", "/mnt"));
+        assert!(mounts_under("/media/My\\040Disk", "/media/My Disk"));
+        assert!(!mounts_under("/mnt2/data", "/mnt"));
+        assert_eq!(split_output_path("fstab.new", "vfat"), "fstab.new.vfat");
+    }
+
+    #[test]
+    fn test_list_limit() {
+        let parse = |args: &[&str]| {
+            let argv: Vec<String> = ["catdog", "pkg", "list"]
+                .iter()
+                .chain(args)
+                .map(|s| s.to_string())
+                .collect();
+            list_limit(&cli::parse(&argv).unwrap(), Some(DEFAULT_LIST_LIMIT))
+        };
+
+        assert_eq!(parse(&[]).unwrap(), Some(DEFAULT_LIST_LIMIT));
+        let unlimited = cli::parse(&["catdog".to_string(), "pkg".to_string(), "list".to_string()]);
+        assert_eq!(list_limit(&unlimited.unwrap(), None).unwrap(), None);
+        assert_eq!(parse(&["--limit", "5"]).unwrap(), Some(5));
+        assert_eq!(parse(&["--all"]).unwrap(), None);
+        assert!(parse(&["--limit", "0"]).is_err());
+        assert!(parse(&["--limit", "many"]).is_err());
+
+        let items: Vec<u32> = (0..120).collect();
+        assert_eq!(limited(&items, Some(5)), &[0, 1, 2, 3, 4]);
+        assert_eq!(limited(&items, Some(DEFAULT_LIST_LIMIT)).len(), 50);
+        assert_eq!(limited(&items, None).len(), 120);
+        assert_eq!(limited(&items[..3], Some(5)).len(), 3);
+    }
+
+    #[test]
+    fn test_usage_bar() {
+        assert!(usage_bar(50.0).contains("[##########..........]"));
+        assert!(usage_bar(0.0).contains("[....................]"));
+        assert!(usage_bar(130.0).contains("[####################]"));
+    }
+
+    #[test]
+    fn test_parse_diskutil_list_plist() {
+        let plist = br#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>AllDisks</key>
+    <array><string>disk0</string><string>disk0s1</string></array>
+    <key>AllDisksAndPartitions</key>
+    <array>
+        <dict>
+            <key>Content</key><string>GUID_partition_scheme</string>
+            <key>DeviceIdentifier</key><string>disk0</string>
+            <key>Partitions</key>
+            <array>
+                <dict><key>DeviceIdentifier</key><string>disk0s1</string></dict>
+                <dict><key>DeviceIdentifier</key><string>disk0s2</string></dict>
+            </array>
+        </dict>
+        <dict>
+            <key>APFSVolumes</key>
+            <array>
+                <dict><key>DeviceIdentifier</key><string>disk3s1</string><key>MountPoint</key><string>/</string></dict>
+                <dict><key>DeviceIdentifier</key><string>disk3s5</string><key>MountPoint</key><string>/System/Volumes/Data</string></dict>
+            </array>
+            <key>DeviceIdentifier</key><string>disk3</string>
+        </dict>
+    </array>
+</dict>
+</plist>"#;
+
+        assert_eq!(
+            parse_diskutil_list_plist(plist).unwrap(),
+            vec!["disk0", "disk0s1", "disk0s2", "disk3", "disk3s1", "disk3s5"]
+        );
+        assert!(parse_diskutil_list_plist(b"not a plist").is_err());
+    }
+
+    #[test]
+    fn test_windows_logical_disks() {
+        let disks = serde_json::json!([
+            {"DeviceID": "C:", "VolumeName": "", "FileSystem": "NTFS", "Size": 511101108224u64, "DriveType": 3},
+            {"DeviceID": "E:", "VolumeName": "BACKUP", "FileSystem": "exFAT", "Size": "62714675200", "DriveType": 2}
+        ]);
+        let devices = parse_windows_logical_disks(disks.as_array().unwrap());
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].device, "C:");
+        assert_eq!(devices[0].mount_point.as_deref(), Some("C:\\"));
+        assert_eq!(devices[0].label, None);
+        assert!(!devices[0].is_removable);
+        assert_eq!(devices[1].label.as_deref(), Some("BACKUP"));
+        assert_eq!(devices[1].fs_type.as_deref(), Some("exFAT"));
+        assert_eq!(devices[1].size.as_deref(), Some("58.41 GB"));
+        assert!(devices[1].is_removable);
+    }
+
+    #[test]
+    fn test_parse_fstab_with_latin1_label() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        // "LABEL=Donn\xe9es" written in latin-1, which is not valid UTF-8
+        file.write_all(b"LABEL=Donn\xe9es /data ext4 defaults 0 2\n")
+            .unwrap();
+        file.write_all(b"/dev/sdb1 /backup ext4 defaults 0 2\n")
+            .unwrap();
+
+        let entries = parse_fstab_from_path(file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].device, "LABEL=Donn\u{FFFD}es");
+        assert_eq!(entries[0].mount_point, "/data");
+        assert_eq!(entries[1].mount_point, "/backup");
+    }
+
+    #[test]
+    fn test_parse_option_edits() {
+        let values: Vec<String> = ["add:noatime", "nodiratime", "remove:discard"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (add, remove) = parse_option_edits(&values).unwrap();
+        assert_eq!(add, vec!["noatime", "nodiratime"]);
+        assert_eq!(remove, vec!["discard"]);
+
+        assert!(parse_option_edits(&["noatime".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_existing_entry_for_device() {
+        let content = "UUID=1234-abcd / ext4 defaults 0 1\n\
+                       LABEL=\"Backups\" /backup ext4 defaults 0 2\n\
+                       /dev/sdc1 /data xfs defaults 0 2\n";
+        let entries = fstab::parse_entries(content).entries;
+        let device = |path: &str, uuid: Option<&str>, label: Option<&str>| BlockDevice {
+            uuid: uuid.map(String::from),
+            label: label.map(String::from),
+            ..test_device(path, "ext4")
+        };
+
+        let by_uuid = device("/dev/sda1", Some("1234-ABCD"), None);
+        assert_eq!(
+            existing_entry_for(&by_uuid, &entries).unwrap().line_number,
+            1
+        );
+
+        let by_label = device("/dev/sdb1", Some("5678"), Some("Backups"));
+        assert_eq!(
+            existing_entry_for(&by_label, &entries).unwrap().line_number,
+            2
+        );
+
+        let by_path = device("/dev/sdc1", None, None);
+        assert_eq!(
+            existing_entry_for(&by_path, &entries).unwrap().line_number,
+            3
+        );
+
+        let new_device = device("/dev/sdd1", Some("9999"), Some("Scratch"));
+        assert!(existing_entry_for(&new_device, &entries).is_none());
+    }
+
+    #[test]
+    fn test_resolve_device() {
+        let device = |path: &str, uuid: &str, partuuid: &str, label: Option<&str>| BlockDevice {
+            uuid: Some(uuid.to_string()),
+            partuuid: Some(partuuid.to_string()),
+            label: label.map(String::from),
+            parent: Some("/dev/sda".to_string()),
+            ..test_device(path, "ext4")
+        };
+        let devices = vec![
+            device("/dev/sda1", "1234-ABCD", "aaaa-01", None),
+            device("/dev/sda2", "5678-EF00", "aaaa-02", Some("My Data")),
+        ];
+        let resolved = |id: &str| resolve_device(id, &devices).map(|d| d.device);
+
+        assert_eq!(resolved("UUID=1234-abcd").as_deref(), Some("/dev/sda1"));
+        assert_eq!(resolved("UUID=\"5678-EF00\"").as_deref(), Some("/dev/sda2"));
+        assert_eq!(resolved("PARTUUID=aaaa-02").as_deref(), Some("/dev/sda2"));
+        assert_eq!(resolved("LABEL=My\\040Data").as_deref(), Some("/dev/sda2"));
+        assert_eq!(resolved("/dev/sda1").as_deref(), Some("/dev/sda1"));
+        assert_eq!(resolved("sda2").as_deref(), Some("/dev/sda2"));
+        assert!(resolved("UUID=0000").is_none());
+        assert!(resolved("/dev/sdz9").is_none());
+
+        assert!(is_device_spec("LABEL=data") && is_device_spec("/dev/sda1"));
+        assert!(!is_device_spec("3f2b8c1e-9d4a-4c55-8e1f-0a7b6c5d4e3f"));
+
+        let found = resolve_device("/dev/sda2", &devices).unwrap();
+        assert_eq!(
+            device_identifiers(&found),
+            vec![
+                "/dev/sda2",
+                "UUID=5678-EF00",
+                "PARTUUID=aaaa-02",
+                "LABEL=My Data"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suggested_owner_ids() {
+        let device = |fs_type: &str| BlockDevice {
+            uuid: Some("ABCD-1234".to_string()),
+            is_removable: true,
+            ..test_device("/dev/sdb1", fs_type)
+        };
+
+        let fat = suggest_mount_options_for(&device("vfat"), (501, 20));
+        assert!(fat.suggested_options.contains(&"uid=501".to_string()));
+        assert!(fat.suggested_options.contains(&"gid=20".to_string()));
+        assert!(fat
+            .rationale
+            .iter()
+            .any(|r| r.starts_with("uid=501,gid=20")));
+
+        let ntfs = suggest_mount_options_for(&device("ntfs3"), (1001, 1001));
+        assert!(ntfs.suggested_options.contains(&"uid=1001".to_string()));
+        assert!(!ntfs.suggested_options.iter().any(|o| o == "uid=1000"));
+
+        let ext4 = suggest_mount_options_for(&device("ext4"), (501, 20));
+        assert!(!ext4.suggested_options.iter().any(|o| o.starts_with("uid=")));
+    }
+
+    #[test]
+    fn test_blend_library_options() {
+        let device = test_device("/dev/sdb1", "ext4");
+        let mut suggestion = suggest_mount_options(&device);
+        assert!(suggestion
+            .suggested_options
+            .contains(&"relatime".to_string()));
+
+        let usage = |option: &str, percent| corpus::OptionUsage {
+            option: option.to_string(),
+            configs: 1,
+            percent,
+        };
+        blend_library_options(
+            &mut suggestion,
+            &[
+                usage("defaults", 100),
+                usage("noatime", 73),
+                usage("errors=remount-ro", 20),
+            ],
+        );
+
+        assert_eq!(suggestion.suggested_options, vec!["defaults", "noatime"]);
+        assert!(suggestion
+            .rationale
+            .contains(&"73% of library configs use noatime for ext4".to_string()));
+    }
+
+    #[test]
+    fn test_validate_findings_json() {
+        let content = "UUID=root / ext4 defaults 0 2\n\
+                       /dev/sdb1 /data ext4 defaults x 2\n\
+                       /dev/sdb1 /data ext4 defaults 0 2\n";
+        let file = create_test_fstab(content);
+        let path = file.path().to_str().unwrap();
+        let parsed = load_fstab(path).unwrap();
+
+        let findings = check_entries(&parsed.entries);
+        let json = validate_json(path, &parsed, &findings);
+
+        let codes: Vec<&str> = json["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["code"].as_str().unwrap())
+            .collect();
+        assert!(codes.contains(&"DUP_MOUNT"));
+        assert!(codes.contains(&"ROOT_PASS"));
+        assert!(codes.contains(&"BAD_DUMP"));
+
+        let root_pass = &json["findings"][codes.iter().position(|c| *c == "ROOT_PASS").unwrap()];
+        assert_eq!(root_pass["line"], 1);
+        assert_eq!(root_pass["severity"], "warning");
+        assert!(root_pass["message"].as_str().unwrap().contains("pass=2"));
+
+        assert_eq!(json["counts"]["critical"], 0);
+        assert_eq!(json["issues"], 0);
+        assert_eq!(json["counts"]["active"], 0);
+    }
+
+    #[test]
+    fn test_plain_record() {
+        assert_eq!(
+            plain_record(&[Some("/dev/sda1"), None, Some(""), Some("ext4")]),
+            "/dev/sda1\t-\t-\text4"
+        );
+        // Embedded separators must not split a record
+        assert_eq!(
+            plain_record(&[Some("vim"), Some("Vi\tIMproved\neditor")]),
+            "vim\tVi IMproved editor"
+        );
+    }
+
+    #[test]
+    fn test_plain_pairs() {
+        let value = serde_json::json!({
+            "hostname": "box",
+            "cpu": {"cores": 4, "load": [0.5, 1.0]},
+            "swap": null
+        });
+        let mut pairs = Vec::new();
+        plain_pairs("", &value, &mut pairs);
+        let lines: Vec<String> = pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "cpu.cores=4",
+                "cpu.load.0=0.5",
+                "cpu.load.1=1.0",
+                "hostname=box",
+                "swap=",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_human_rendering() {
+        let content = "UUID=root / ext4 defaults 0 1\n\
+                       /dev/sdb1 /srv/missing ext4 defaults 0 2\n\
+                       /dev/sdb1 /srv/missing ext4 defaults 0 2\n\
+                       /dev/sdc1 /srv/missing xfs defaults 0 2\n";
+        let entries = fstab::parse_entries(content).entries;
+        let findings = check_entries(&entries);
+        let find = |code: &str| findings.iter().find(|f| f.code == code).unwrap();
+
+        let duplicate = find("DUP_MOUNT");
+        assert_eq!(duplicate.severity, Severity::Warning);
+        assert_eq!(&*duplicate.marker(), "⚠️ ");
+        assert_eq!(duplicate.highlights, vec!["/srv/missing"]);
+
+        let missing = find("MISSING_MOUNT_DIR");
+        assert_eq!(missing.severity, Severity::Warning);
+        assert_eq!(&*missing.marker(), "ℹ️ ");
+
+        let conflict = find("MOUNT_CONFLICT");
+        assert_eq!(&*conflict.marker(), "❌");
+        assert!(conflict
+            .heading
+            .as_deref()
+            .unwrap()
+            .starts_with("Lines 2 and 4: "));
+        assert!(conflict.message.ends_with("(lines 2 and 4)"));
+    }
+
+    #[test]
+    fn test_validate_short_entries() {
+        let content = "UUID=root / ext4 defaults\n/dev/sda3 none swap sw\n";
+        let file = create_test_fstab(content);
+        let parsed = load_fstab(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+
+        let findings = check_entries(&parsed.entries);
+        let root_pass = findings.iter().find(|f| f.code == "ROOT_PASS").unwrap();
+        assert!(root_pass.message.contains("omitted"));
+
+        let swap = findings.iter().find(|f| f.line == 2).unwrap();
+        assert_eq!(swap.code, "DEFAULT_DUMP_PASS");
+        assert_eq!(swap.severity, Severity::Info);
+    }
+
+    #[test]
+    fn test_validate_unstable_device_names() {
+        let content = "UUID=root / ext4 defaults 0 1\n\
+                       /dev/sda1 /data ext4 defaults 0 2\n\
+                       /dev/nvme0n1p3 none swap sw 0 0\n";
+        let entries = fstab::parse_entries(content).entries;
+        let devices = vec![BlockDevice {
+            uuid: Some("1234-ABCD".to_string()),
+            parent: Some("/dev/sda".to_string()),
+            ..test_device("/dev/sda1", "ext4")
+        }];
+
+        let findings = check_device_names(&entries, &devices);
+        let lines: Vec<usize> = findings.iter().map(|f| f.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+        assert!(findings
+            .iter()
+            .all(|f| f.code == "UNSTABLE_DEVICE" && f.severity == Severity::Info));
+        assert_eq!(
+            findings[0].suggestion.as_deref(),
+            Some("use UUID=1234-ABCD instead of /dev/sda1")
+        );
+        assert!(findings[1].suggestion.as_deref().unwrap().contains("UUID="));
+
+        // Without discovery the entry is still flagged from the fstab text alone
+        assert_eq!(check_device_names(&entries[1..2], &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_validate_exit_code() {
+        let content = "UUID=root / ext4 defaults,noauto 0 1\n";
+        let file = create_test_fstab(content);
+        let parsed = load_fstab(file.path().to_str().unwrap()).unwrap();
+        let entry = &parsed.entries[0];
+
+        let findings = check_entries(&parsed.entries);
+        assert_eq!(validate_exit_code(&findings), exit_codes::DATA_ERROR);
+
+        // Boots fine, so none of these fail the run
+        let content = "UUID=root / ext4 defaults 0 2\n\
+                       /dev/sdb1 /srv/data/cache ext4 defaults 0 2\n\
+                       /dev/sdb2 /srv/data ext4 defaults 0 2\n\
+                       /dev/sda3 /swap swap sw 0 0\n";
+        let findings = check_entries(&fstab::parse_entries(content).entries);
+        let codes: Vec<&str> = findings.iter().map(|f| f.code).collect();
+        assert!(["ROOT_PASS", "MOUNT_ORDER", "SWAP_MOUNT"]
+            .iter()
+            .all(|code| codes.contains(code)));
+        assert_eq!(validate_exit_code(&findings), exit_codes::SUCCESS);
+
+        // XFS and btrfs roots are never fsck'd, so pass 0 is correct
+        for root in [
+            "UUID=root / xfs defaults 0 0\n",
+            "UUID=root / btrfs defaults\n",
+        ] {
+            let findings = check_entries(&fstab::parse_entries(root).entries);
+            assert!(!findings.iter().any(|f| f.code == "ROOT_PASS"));
+            assert_eq!(validate_exit_code(&findings), exit_codes::SUCCESS);
+        }
+
+        // Warnings and live problems alone pass
+        let mut live = Finding::new(0, entry, Severity::Critical, "NOT_MOUNTED");
+        live.live = true;
+        let findings = vec![Finding::new(0, entry, Severity::Warning, "BAD_DUMP"), live];
+        assert_eq!(validate_exit_code(&findings), exit_codes::SUCCESS);
+        assert_eq!(validate_exit_code(&[]), exit_codes::SUCCESS);
+    }
+
+    #[test]
+    fn test_check_exit_code() {
+        assert_eq!(check_exit_code(0), exit_codes::SUCCESS);
+        assert_eq!(check_exit_code(2), exit_codes::CRITICAL_ALERTS);
+    }
 }
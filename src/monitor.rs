@@ -1,21 +1,114 @@
 use anyhow::{Context, Result};
 use colored::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::alerts::{Alert, AlertManager, AlertSeverity};
+use crate::alerts::{Alert, AlertCounts, AlertManager, AlertSeverity, AlertStatus};
+use crate::config::Config;
+use crate::exec;
+use crate::fstab::{self, FstabEntry};
+use crate::mount::{self, ActiveMount, MountState};
+use crate::smart::{self, SmartHealth};
+use crate::state;
+use crate::sysinfo::{self, ProcessSort};
 
 pub struct FsMonitor {
     alert_manager: AlertManager,
+    hooks: Vec<PathBuf>,
+    hook_timeout: Duration,
+    prometheus_path: Option<PathBuf>,
+    metrics: MonitorMetrics,
+    /// Reallocated sector counts from the previous reading, by device
+    reallocated_sectors: HashMap<String, u64>,
+    /// Where those counts persist between runs, so one-shot checks can compare
+    smart_baseline_path: Option<PathBuf>,
+}
+
+/// Findings from the latest check cycle, exported for Prometheus
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonitorMetrics {
+    /// Usage percent per mount point
+    pub disk_usage: BTreeMap<String, u8>,
+    /// Malformed lines, or 1 when fstab is missing or unreadable
+    pub fstab_issues: usize,
+    pub firing_alerts: usize,
+}
+
+/// Why an fstab entry that should be mounted is not
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MountFailure {
+    DirectoryMissing,
+    NotMounted,
+}
+
+impl MountFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MountFailure::DirectoryMissing => "directory_missing",
+            MountFailure::NotMounted => "not_mounted",
+        }
+    }
+
+    /// How loudly to alert; `nofail` entries are allowed to be missing, so
+    /// they alert one level lower
+    pub fn severity(&self, entry: &FstabEntry) -> AlertSeverity {
+        let nofail = entry.options.split(',').any(|opt| opt == "nofail");
+        match (self, nofail) {
+            (MountFailure::NotMounted, false) => AlertSeverity::Critical,
+            (MountFailure::NotMounted, true) | (MountFailure::DirectoryMissing, false) => {
+                AlertSeverity::Warning
+            }
+            (MountFailure::DirectoryMissing, true) => AlertSeverity::Info,
+        }
+    }
+}
+
+/// Captured result of running a user check script
+#[derive(Debug, Clone)]
+pub struct HookOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
 }
 
 impl FsMonitor {
     pub fn new(alert_manager: AlertManager) -> Self {
-        FsMonitor { alert_manager }
+        FsMonitor {
+            alert_manager,
+            hooks: Vec::new(),
+            hook_timeout: Duration::from_secs(30),
+            prometheus_path: None,
+            metrics: MonitorMetrics::default(),
+            reallocated_sectors: HashMap::new(),
+            smart_baseline_path: None,
+        }
+    }
+
+    /// Keep reallocated sector counts in `path`, starting from what it holds
+    pub fn with_smart_baseline(mut self, path: PathBuf) -> Self {
+        self.reallocated_sectors = load_smart_baseline(&path);
+        self.smart_baseline_path = Some(path);
+        self
+    }
+
+    /// Write a Prometheus textfile with the findings after every check cycle
+    pub fn with_prometheus_export(mut self, path: Option<PathBuf>) -> Self {
+        self.prometheus_path = path;
+        self
+    }
+
+    pub fn with_hooks(mut self, hooks: Vec<PathBuf>, timeout: Duration) -> Self {
+        self.hooks = hooks;
+        self.hook_timeout = timeout;
+        self
     }
 
     pub fn run_checks(&mut self) -> Result<()> {
@@ -24,12 +117,37 @@ impl FsMonitor {
         self.check_disk_usage()?;
         self.check_fstab_validity()?;
         self.check_mount_failures()?;
+        self.check_smart_health()?;
+        self.check_hooks()?;
+
+        if let Some(path) = &self.prometheus_path {
+            self.metrics.firing_alerts = self
+                .alert_manager
+                .get_alerts(Some(AlertStatus::Firing), None)
+                .len();
+            export_metrics(path, &self.metrics)?;
+        }
 
         println!("{} Checks complete", "✓".green().bold());
         Ok(())
     }
 
+    /// Firing critical alerts created at or after `since`
+    pub fn critical_alerts_since(&self, since: chrono::DateTime<chrono::Utc>) -> usize {
+        self.alert_manager
+            .get_alerts(Some(AlertStatus::Firing), Some(since))
+            .iter()
+            .filter(|alert| alert.severity == AlertSeverity::Critical)
+            .count()
+    }
+
+    /// Run checks every `interval_seconds` until Ctrl-C
+    ///
+    /// The interrupt only sets a flag, so a check that is saving alerts always
+    /// finishes before the loop exits.
     pub fn monitor_loop(&mut self, interval_seconds: u64) -> Result<()> {
+        let stop = stop_on_ctrl_c()?;
+
         println!(
             "{} Starting filesystem monitoring (interval: {}s)",
             "🚀".bold(),
@@ -37,19 +155,61 @@ impl FsMonitor {
         );
         println!("Press Ctrl+C to stop\n");
 
-        loop {
+        let session_start = chrono::Utc::now();
+        let mut cycles = 0;
+        while !stop.load(Ordering::SeqCst) {
             if let Err(e) = self.run_checks() {
                 eprintln!("{} Check failed: {}", "Error:".red(), e);
             }
+            cycles += 1;
+
+            sleep_unless_stopped(Duration::from_secs(interval_seconds), &stop);
+        }
 
-            thread::sleep(Duration::from_secs(interval_seconds));
+        self.print_session_summary(cycles, session_start);
+        Ok(())
+    }
+
+    fn print_session_summary(&self, cycles: usize, session_start: chrono::DateTime<chrono::Utc>) {
+        let fired = self.alert_manager.get_alerts(None, Some(session_start));
+        let counts = AlertCounts::from_alerts(&fired);
+
+        println!("\n{} Monitoring stopped", "🛑".bold());
+        println!("{}", "=".repeat(60).bright_black());
+        println!("{} {}", "Check cycles:".cyan().bold(), cycles);
+        if fired.is_empty() {
+            println!("{} {}", "Barks fired:".cyan().bold(), "none".green());
+        } else {
+            println!(
+                "{} {} ({})",
+                "Barks fired:".cyan().bold(),
+                fired.len().to_string().bright_white(),
+                counts.severity_summary()
+            );
+            for alert in &fired {
+                println!("  {} {}", alert.severity.emoji(), alert.title);
+            }
         }
     }
 
     fn check_disk_usage(&mut self) -> Result<()> {
         let mounts = self.get_mounted_filesystems()?;
+        self.record_disk_usage(mounts)
+    }
+
+    /// Raise alerts for full filesystems and resolve those that have recovered
+    fn record_disk_usage(&mut self, mounts: HashMap<String, u8>) -> Result<()> {
+        self.metrics.disk_usage = mounts.iter().map(|(m, u)| (m.clone(), *u)).collect();
+        let mut critical = HashSet::new();
+        let mut high = HashSet::new();
 
         for (mount_point, usage) in mounts {
+            if usage >= 90 {
+                critical.insert(mount_point.clone());
+            } else if usage >= 80 {
+                high.insert(mount_point.clone());
+            }
+
             if usage >= 90 {
                 let mut alert = Alert::new(
                     format!("Critical disk usage on {}", mount_point),
@@ -59,6 +219,9 @@ impl FsMonitor {
                 );
                 alert.add_metadata("mount_point".to_string(), mount_point.clone());
                 alert.add_metadata("usage_percent".to_string(), usage.to_string());
+                if let Some(top) = top_processes_summary() {
+                    alert.add_metadata("top_processes".to_string(), top);
+                }
 
                 self.alert_manager.create_alert(alert)?;
             } else if usage >= 80 {
@@ -75,6 +238,46 @@ impl FsMonitor {
             }
         }
 
+        // A mount that drops from critical to high resolves its critical alert
+        // while the warning fires, and the other way round
+        self.auto_resolve_severity(
+            "disk_usage_monitor",
+            Some(&AlertSeverity::Critical),
+            "mount_point",
+            &critical,
+        )?;
+        self.auto_resolve_severity(
+            "disk_usage_monitor",
+            Some(&AlertSeverity::Warning),
+            "mount_point",
+            &high,
+        )
+    }
+
+    /// Resolve alerts from `source` whose condition, keyed by `key` metadata, has cleared
+    fn auto_resolve(&mut self, source: &str, key: &str, active: &HashSet<String>) -> Result<()> {
+        self.auto_resolve_severity(source, None, key, active)
+    }
+
+    /// [`Self::auto_resolve`] for the alerts of one severity only
+    fn auto_resolve_severity(
+        &mut self,
+        source: &str,
+        severity: Option<&AlertSeverity>,
+        key: &str,
+        active: &HashSet<String>,
+    ) -> Result<()> {
+        let resolved = self
+            .alert_manager
+            .auto_resolve_by_source_and_key(source, severity, key, active)?;
+        if resolved > 0 {
+            println!(
+                "{} Auto-resolved {} {} alert(s) whose condition cleared",
+                "✓".green().bold(),
+                resolved,
+                source
+            );
+        }
         Ok(())
     }
 
@@ -88,10 +291,7 @@ impl FsMonitor {
     }
 
     fn get_macos_disk_usage(&self) -> Result<HashMap<String, u8>> {
-        let output = Command::new("df")
-            .args(&["-H"])
-            .output()
-            .context("Failed to run df command")?;
+        let output = exec::run_command("df", &["-H"]).context("Failed to run df command")?;
 
         let output_str = String::from_utf8_lossy(&output.stdout);
         let mut usage_map = HashMap::new();
@@ -112,10 +312,7 @@ impl FsMonitor {
     }
 
     fn get_linux_disk_usage(&self) -> Result<HashMap<String, u8>> {
-        let output = Command::new("df")
-            .args(&["-h"])
-            .output()
-            .context("Failed to run df command")?;
+        let output = exec::run_command("df", &["-h"]).context("Failed to run df command")?;
 
         let output_str = String::from_utf8_lossy(&output.stdout);
         let mut usage_map = HashMap::new();
@@ -137,8 +334,10 @@ impl FsMonitor {
 
     fn check_fstab_validity(&mut self) -> Result<()> {
         let fstab_path = "/etc/fstab";
+        self.metrics.fstab_issues = 0;
 
         if !Path::new(fstab_path).exists() {
+            self.metrics.fstab_issues = 1;
             let alert = Alert::new(
                 "fstab file not found".to_string(),
                 format!("{} does not exist", fstab_path),
@@ -149,21 +348,23 @@ impl FsMonitor {
             return Ok(());
         }
 
-        let contents = match fs::read_to_string(fstab_path) {
+        let contents = match fstab::read_fstab(fstab_path) {
             Ok(c) => c,
             Err(e) => {
+                self.metrics.fstab_issues = 1;
                 let mut alert = Alert::new(
                     "Cannot read fstab file".to_string(),
-                    format!("Failed to read {}: {}", fstab_path, e),
+                    format!("{:#}", e),
                     AlertSeverity::Critical,
                     "fstab_monitor".to_string(),
                 );
-                alert.add_metadata("error".to_string(), e.to_string());
+                alert.add_metadata("error".to_string(), format!("{:#}", e));
                 self.alert_manager.create_alert(alert)?;
                 return Ok(());
             }
         };
 
+        let mut malformed_lines = HashSet::new();
         let mut line_num = 0;
         for line in contents.lines() {
             line_num += 1;
@@ -174,10 +375,15 @@ impl FsMonitor {
             }
 
             let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() < 6 {
+            if FstabEntry::from_fields(&parts).is_none() {
+                malformed_lines.insert(line_num.to_string());
                 let mut alert = Alert::new(
                     format!("Malformed fstab entry at line {}", line_num),
-                    format!("Line {} has {} fields, expected 6", line_num, parts.len()),
+                    format!(
+                        "Line {} has {} fields, expected at least 4",
+                        line_num,
+                        parts.len()
+                    ),
                     AlertSeverity::Warning,
                     "fstab_monitor".to_string(),
                 );
@@ -187,7 +393,8 @@ impl FsMonitor {
             }
         }
 
-        Ok(())
+        self.metrics.fstab_issues = malformed_lines.len();
+        self.auto_resolve("fstab_monitor", "line_number", &malformed_lines)
     }
 
     fn check_mount_failures(&mut self) -> Result<()> {
@@ -198,40 +405,197 @@ impl FsMonitor {
             return Ok(());
         }
 
-        let contents = fs::read_to_string(fstab_path)?;
-        let mut expected_mounts = Vec::new();
+        let contents = fstab::read_fstab(fstab_path)?;
+        let entries = fstab::parse_entries(&contents).entries;
+        // Without a mount table only missing directories can be detected
+        let active = mount::read_active_mounts().ok();
 
-        for line in contents.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
+        let mut failing = HashSet::new();
+        for entry in &entries {
+            let failure = match mount_failure(entry, active.as_deref()) {
+                Some(failure) => failure,
+                None => continue,
+            };
+            failing.insert(entry.mount_point.clone());
 
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() >= 6 {
-                let mount_point = parts[1];
-                if mount_point != "none" && mount_point != "swap" {
-                    expected_mounts.push((parts[0].to_string(), mount_point.to_string()));
+            let (title, description) = match failure {
+                MountFailure::DirectoryMissing => (
+                    format!("Mount point {} does not exist", entry.mount_point),
+                    format!(
+                        "The mount point directory {} for device {} does not exist",
+                        entry.mount_point, entry.device
+                    ),
+                ),
+                MountFailure::NotMounted => (
+                    format!("{} is not mounted", entry.mount_point),
+                    format!(
+                        "{} should be mounted at {} but is not in the mount table",
+                        entry.device, entry.mount_point
+                    ),
+                ),
+            };
+
+            let mut alert = Alert::new(
+                title,
+                description,
+                failure.severity(entry),
+                "mount_monitor".to_string(),
+            );
+            alert.add_metadata("device".to_string(), entry.device.clone());
+            alert.add_metadata("mount_point".to_string(), entry.mount_point.clone());
+            alert.add_metadata("failure".to_string(), failure.as_str().to_string());
+            self.alert_manager.create_alert(alert)?;
+        }
+
+        self.auto_resolve("mount_monitor", "mount_point", &failing)
+    }
+
+    fn check_smart_health(&mut self) -> Result<()> {
+        if !smart::smartctl_available() {
+            log::debug!("smartctl not found, skipping SMART health check");
+            return Ok(());
+        }
+
+        let mut results = Vec::new();
+        for device in smart::list_disks()? {
+            match smart::check_device(&device)? {
+                Ok(health) => results.push(health),
+                Err(reason) => {
+                    log::debug!("Skipping SMART check of {}: {}", device, reason.message())
                 }
             }
         }
+        self.record_smart_health(results)
+    }
+
+    /// Raise alerts for failed SMART self-assessments and rising reallocated sector counts
+    ///
+    /// A rising-count alert resolves once a later reading shows no further rise.
+    fn record_smart_health(&mut self, results: Vec<SmartHealth>) -> Result<()> {
+        let mut failing = HashSet::new();
+        let mut rising = HashSet::new();
 
-        // Check which mount points don't exist or aren't mounted
-        for (device, mount_point) in expected_mounts {
-            let path = Path::new(&mount_point);
+        for health in results {
+            if health.failed() {
+                failing.insert(health.device.clone());
 
-            if !path.exists() {
                 let mut alert = Alert::new(
-                    format!("Mount point {} does not exist", mount_point),
+                    format!("SMART health check failed on {}", health.device),
                     format!(
-                        "The mount point directory {} for device {} does not exist",
-                        mount_point, device
+                        "{} reports a failing SMART self-assessment; back up its data and replace the drive",
+                        health.device
                     ),
-                    AlertSeverity::Warning,
-                    "mount_monitor".to_string(),
+                    AlertSeverity::Critical,
+                    "smart_health".to_string(),
                 );
-                alert.add_metadata("device".to_string(), device);
-                alert.add_metadata("mount_point".to_string(), mount_point.clone());
+                alert.add_metadata("device".to_string(), health.device.clone());
+                self.alert_manager.create_alert(alert)?;
+            }
+
+            let Some(current) = health.reallocated_sectors else {
+                continue;
+            };
+            let previous = self
+                .reallocated_sectors
+                .insert(health.device.clone(), current);
+            if let Some(previous) = previous.filter(|previous| current > *previous) {
+                rising.insert(health.device.clone());
+                let mut alert = Alert::new(
+                    format!("Reallocated sectors rising on {}", health.device),
+                    format!(
+                        "Reallocated sector count on {} went from {} to {}",
+                        health.device, previous, current
+                    ),
+                    AlertSeverity::Critical,
+                    "smart_sectors".to_string(),
+                );
+                alert.add_metadata("device".to_string(), health.device.clone());
+                alert.add_metadata("previous".to_string(), previous.to_string());
+                alert.add_metadata("reallocated_sectors".to_string(), current.to_string());
+                if let Some(pending) = health.pending_sectors {
+                    alert.add_metadata("pending_sectors".to_string(), pending.to_string());
+                }
+                self.alert_manager.create_alert(alert)?;
+            }
+        }
+
+        if let Some(path) = &self.smart_baseline_path {
+            save_smart_baseline(path, &self.reallocated_sectors)?;
+        }
+
+        self.auto_resolve("smart_health", "device", &failing)?;
+        self.auto_resolve("smart_sectors", "device", &rising)
+    }
+
+    /// Run user check scripts and turn their results into alerts
+    ///
+    /// Each stdout line of the form `SEVERITY|title|description` creates an alert.
+    /// A script that exits non-zero without such lines, or that times out,
+    /// raises a warning for the hook itself.
+    fn check_hooks(&mut self) -> Result<()> {
+        for hook in self.hooks.clone() {
+            let hook_name = hook
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| hook.display().to_string());
+
+            let output = match run_hook(&hook, self.hook_timeout) {
+                Ok(output) => output,
+                Err(e) => {
+                    let mut alert = Alert::new(
+                        format!("Check hook {} could not run", hook_name),
+                        e.to_string(),
+                        AlertSeverity::Warning,
+                        format!("hook:{}", hook_name),
+                    );
+                    alert.add_metadata("hook".to_string(), hook.display().to_string());
+                    self.alert_manager.create_alert(alert)?;
+                    continue;
+                }
+            };
+
+            let mut alerts: Vec<Alert> = output
+                .stdout
+                .lines()
+                .filter_map(parse_hook_line)
+                .map(|(severity, title, description)| {
+                    Alert::new(title, description, severity, format!("hook:{}", hook_name))
+                })
+                .collect();
+
+            if output.timed_out {
+                alerts.push(Alert::new(
+                    format!("Check hook {} timed out", hook_name),
+                    format!(
+                        "{} did not finish within {}s and was killed",
+                        hook.display(),
+                        self.hook_timeout.as_secs()
+                    ),
+                    AlertSeverity::Warning,
+                    format!("hook:{}", hook_name),
+                ));
+            } else if alerts.is_empty() && output.exit_code != Some(0) {
+                let reason = output
+                    .stderr
+                    .lines()
+                    .next()
+                    .map(|l| l.to_string())
+                    .unwrap_or_else(|| format!("exited with code {:?}", output.exit_code));
+                alerts.push(Alert::new(
+                    format!("Check hook {} failed", hook_name),
+                    reason,
+                    AlertSeverity::Warning,
+                    format!("hook:{}", hook_name),
+                ));
+            }
+
+            for mut alert in alerts {
+                alert.add_metadata("hook".to_string(), hook.display().to_string());
+                alert.add_metadata("stdout".to_string(), output.stdout.clone());
+                alert.add_metadata("stderr".to_string(), output.stderr.clone());
+                if let Some(code) = output.exit_code {
+                    alert.add_metadata("exit_code".to_string(), code.to_string());
+                }
                 self.alert_manager.create_alert(alert)?;
             }
         }
@@ -240,14 +604,526 @@ impl FsMonitor {
     }
 }
 
-pub fn check_once(storage_path: &Path) -> Result<()> {
-    let alert_manager = AlertManager::new(storage_path.to_path_buf())?;
-    let mut monitor = FsMonitor::new(alert_manager);
-    monitor.run_checks()
+/// A flag that Ctrl-C sets instead of killing the process
+pub fn stop_on_ctrl_c() -> Result<Arc<AtomicBool>> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler_stop = Arc::clone(&stop);
+    ctrlc::set_handler(move || handler_stop.store(true, Ordering::SeqCst))
+        .context("Failed to install Ctrl-C handler")?;
+    Ok(stop)
+}
+
+/// Sleep for `duration` in short slices, returning early once `stop` is set
+///
+/// Returns whether the sleep was cut short.
+pub fn sleep_unless_stopped(duration: Duration, stop: &AtomicBool) -> bool {
+    const SLICE: Duration = Duration::from_millis(200);
+
+    let deadline = Instant::now() + duration;
+    while !stop.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep(SLICE.min(deadline - now));
+    }
+    true
+}
+
+/// Decide whether an fstab entry has failed to mount
+///
+/// Swap and `none` entries are ignored, and `noauto` entries are only expected
+/// to have a mount point directory. `active` is `None` when the mount table
+/// could not be read, in which case only the directory is checked.
+pub fn mount_failure(entry: &FstabEntry, active: Option<&[ActiveMount]>) -> Option<MountFailure> {
+    if entry.fs_type == "swap" || entry.mount_point == "none" || entry.mount_point == "swap" {
+        return None;
+    }
+
+    if !Path::new(&entry.decoded_mount_point()).exists() {
+        return Some(MountFailure::DirectoryMissing);
+    }
+
+    let noauto = entry.options.split(',').any(|opt| opt == "noauto");
+    match active {
+        Some(active) if !noauto && mount::mount_state(entry, active) != MountState::Mounted => {
+            Some(MountFailure::NotMounted)
+        }
+        _ => None,
+    }
+}
+
+/// Parse a `SEVERITY|title|description` line emitted by a check hook
+pub fn parse_hook_line(line: &str) -> Option<(AlertSeverity, String, String)> {
+    let mut parts = line.trim().splitn(3, '|');
+    let severity = AlertSeverity::from_name(parts.next()?)?;
+    let title = parts.next()?.trim().to_string();
+    let description = parts.next().unwrap_or("").trim().to_string();
+
+    if title.is_empty() {
+        return None;
+    }
+
+    Some((severity, title, description))
+}
+
+/// Run a hook script, killing it if it runs longer than `timeout`
+pub fn run_hook(path: &Path, timeout: Duration) -> Result<HookOutput> {
+    let (status, stdout, stderr) = exec::wait_with_timeout(&mut Command::new(path), timeout)
+        .with_context(|| format!("Failed to run hook {}", path.display()))?;
+
+    Ok(HookOutput {
+        exit_code: status.and_then(|s| s.code()),
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        timed_out: status.is_none(),
+    })
+}
+
+/// Render metrics in the Prometheus text exposition format
+///
+/// - `catdog_disk_usage_percent{mount="..."}`: usage of each mounted filesystem
+/// - `catdog_fstab_issues_total`: problems found in `/etc/fstab` by the last check
+/// - `catdog_firing_alerts_total`: barks currently in the firing state
+pub fn render_metrics(metrics: &MonitorMetrics) -> String {
+    let mut out = String::new();
+
+    out.push_str(
+        "# HELP catdog_disk_usage_percent Disk usage of a mounted filesystem in percent.\n",
+    );
+    out.push_str("# TYPE catdog_disk_usage_percent gauge\n");
+    for (mount, usage) in &metrics.disk_usage {
+        let _ = writeln!(
+            out,
+            "catdog_disk_usage_percent{{mount=\"{}\"}} {}",
+            escape_label_value(mount),
+            usage
+        );
+    }
+
+    out.push_str(
+        "# HELP catdog_fstab_issues_total Problems found in /etc/fstab by the last check.\n",
+    );
+    out.push_str("# TYPE catdog_fstab_issues_total gauge\n");
+    let _ = writeln!(out, "catdog_fstab_issues_total {}", metrics.fstab_issues);
+
+    out.push_str("# HELP catdog_firing_alerts_total Barks currently firing.\n");
+    out.push_str("# TYPE catdog_firing_alerts_total gauge\n");
+    let _ = writeln!(out, "catdog_firing_alerts_total {}", metrics.firing_alerts);
+
+    out
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write metrics to a `.prom` file for the node exporter's textfile collector
+///
+/// The file is replaced atomically so a scrape never sees a partial write.
+pub fn export_metrics(path: &Path, metrics: &MonitorMetrics) -> Result<()> {
+    state::write_atomic(path, render_metrics(metrics))
+        .context("Failed to export Prometheus metrics")
+}
+
+fn build_monitor(storage_path: &Path, config: &Config) -> Result<FsMonitor> {
+    let alert_manager = AlertManager::from_config(storage_path.to_path_buf(), config)?;
+    Ok(FsMonitor::new(alert_manager)
+        .with_hooks(
+            config.monitoring.hooks.clone(),
+            Duration::from_secs(config.monitoring.hook_timeout_seconds),
+        )
+        .with_smart_baseline(storage_path.with_file_name(SMART_BASELINE_FILE)))
+}
+
+/// Reallocated sector counts by device, kept next to the alert store
+const SMART_BASELINE_FILE: &str = "smart_sectors.json";
+
+/// Counts saved by an earlier run; a missing or unreadable file starts afresh
+fn load_smart_baseline(path: &Path) -> HashMap<String, u64> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_smart_baseline(path: &Path, counts: &HashMap<String, u64>) -> Result<()> {
+    let json = serde_json::to_string_pretty(counts)?;
+    state::write_atomic(path, json).context("Failed to save SMART sector baseline")
+}
+
+/// The heaviest processes by memory, as context for a critical alert
+fn top_processes_summary() -> Option<String> {
+    let processes = sysinfo::top_processes(3, ProcessSort::Memory).ok()?;
+    if processes.is_empty() {
+        return None;
+    }
+
+    Some(
+        processes
+            .iter()
+            .map(|p| {
+                format!(
+                    "{} {} ({:.1}% mem, {:.1}% cpu)",
+                    p.pid, p.command, p.mem_percent, p.cpu_percent
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; "),
+    )
+}
+
+/// Run the checks once and return how many new critical alerts they raised
+pub fn check_once(storage_path: &Path, config: &Config) -> Result<usize> {
+    let mut monitor = build_monitor(storage_path, config)?;
+    let started = chrono::Utc::now();
+    monitor.run_checks()?;
+    Ok(monitor.critical_alerts_since(started))
 }
 
-pub fn start_monitoring(storage_path: &Path, interval_seconds: u64) -> Result<()> {
-    let alert_manager = AlertManager::new(storage_path.to_path_buf())?;
-    let mut monitor = FsMonitor::new(alert_manager);
+pub fn start_monitoring(
+    storage_path: &Path,
+    interval_seconds: u64,
+    config: &Config,
+    prometheus_path: Option<PathBuf>,
+) -> Result<()> {
+    let mut monitor = build_monitor(storage_path, config)?.with_prometheus_export(prometheus_path);
     monitor.monitor_loop(interval_seconds)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_hook_line() {
+        let (severity, title, description) =
+            parse_hook_line("WARNING|Backup stale|Last backup 3 days ago").unwrap();
+        assert_eq!(severity, AlertSeverity::Warning);
+        assert_eq!(title, "Backup stale");
+        assert_eq!(description, "Last backup 3 days ago");
+
+        assert!(parse_hook_line("just some log output").is_none());
+        assert!(parse_hook_line("DEBUG|not a severity|x").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_creates_alert() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("check_backup.sh");
+        fs::write(
+            &script,
+            "#!/bin/sh\necho 'WARNING|Backup stale|Last backup 3 days ago'\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let storage_path = dir.path().join("alerts.json");
+        let alert_manager = AlertManager::new(storage_path.clone()).unwrap();
+        let mut monitor =
+            FsMonitor::new(alert_manager).with_hooks(vec![script], Duration::from_secs(5));
+        monitor.check_hooks().unwrap();
+
+        let manager = AlertManager::new(storage_path).unwrap();
+        let alerts = manager.get_alerts(None, None);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].title, "Backup stale");
+        assert_eq!(alerts[0].severity, AlertSeverity::Warning);
+        assert_eq!(alerts[0].source, "hook:check_backup.sh");
+        assert_eq!(alerts[0].metadata.get("exit_code").unwrap(), "0");
+    }
+
+    #[test]
+    fn test_disk_alert_resolves_when_usage_drops() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_path = dir.path().join("alerts.json");
+        let mut monitor = FsMonitor::new(AlertManager::new(storage_path.clone()).unwrap());
+
+        monitor
+            .record_disk_usage(HashMap::from([("/data".to_string(), 95)]))
+            .unwrap();
+        let firing = monitor
+            .alert_manager
+            .get_alerts(Some(AlertStatus::Firing), None);
+        assert_eq!(firing.len(), 1);
+        assert_eq!(firing[0].severity, AlertSeverity::Critical);
+
+        monitor
+            .record_disk_usage(HashMap::from([("/data".to_string(), 50)]))
+            .unwrap();
+
+        let manager = AlertManager::new(storage_path).unwrap();
+        let alerts = manager.get_alerts(None, None);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].status, AlertStatus::Resolved);
+        assert!(alerts[0].auto_resolved);
+        assert!(alerts[0].resolved_at.is_some());
+    }
+
+    #[test]
+    fn test_disk_alert_downgrade_resolves_critical() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut monitor =
+            FsMonitor::new(AlertManager::new(dir.path().join("alerts.json")).unwrap());
+
+        monitor
+            .record_disk_usage(HashMap::from([("/data".to_string(), 95)]))
+            .unwrap();
+        monitor
+            .record_disk_usage(HashMap::from([("/data".to_string(), 85)]))
+            .unwrap();
+
+        let firing = monitor
+            .alert_manager
+            .get_alerts(Some(AlertStatus::Firing), None);
+        assert_eq!(firing.len(), 1);
+        assert_eq!(firing[0].severity, AlertSeverity::Warning);
+        let resolved = monitor
+            .alert_manager
+            .get_alerts(Some(AlertStatus::Resolved), None);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].severity, AlertSeverity::Critical);
+
+        // And back up again: the warning resolves while critical fires anew
+        monitor
+            .record_disk_usage(HashMap::from([("/data".to_string(), 92)]))
+            .unwrap();
+        let firing = monitor
+            .alert_manager
+            .get_alerts(Some(AlertStatus::Firing), None);
+        assert_eq!(firing.len(), 1);
+        assert_eq!(firing[0].severity, AlertSeverity::Critical);
+    }
+
+    #[test]
+    fn test_smart_alerts() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_path = dir.path().join("alerts.json");
+        let mut monitor = FsMonitor::new(AlertManager::new(storage_path).unwrap());
+        let health = |passed, reallocated| SmartHealth {
+            device: "/dev/sda".to_string(),
+            passed: Some(passed),
+            reallocated_sectors: Some(reallocated),
+            pending_sectors: None,
+        };
+
+        // The first reading only sets the baseline
+        monitor.record_smart_health(vec![health(true, 4)]).unwrap();
+        assert!(monitor.alert_manager.get_alerts(None, None).is_empty());
+
+        monitor.record_smart_health(vec![health(true, 9)]).unwrap();
+        let alerts = monitor.alert_manager.get_alerts(None, None);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].source, "smart_sectors");
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+        assert_eq!(alerts[0].metadata.get("previous").unwrap(), "4");
+
+        monitor.record_smart_health(vec![health(false, 9)]).unwrap();
+        let firing = monitor
+            .alert_manager
+            .get_alerts(Some(AlertStatus::Firing), None);
+        assert!(firing.iter().any(|a| a.source == "smart_health"));
+        // The count held steady, so the rising alert resolves
+        assert!(!firing.iter().any(|a| a.source == "smart_sectors"));
+    }
+
+    #[test]
+    fn test_smart_baseline_survives_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline = dir.path().join(SMART_BASELINE_FILE);
+        let monitor = || {
+            FsMonitor::new(AlertManager::new(dir.path().join("alerts.json")).unwrap())
+                .with_smart_baseline(baseline.clone())
+        };
+        let health = |reallocated| SmartHealth {
+            device: "/dev/sda".to_string(),
+            passed: Some(true),
+            reallocated_sectors: Some(reallocated),
+            pending_sectors: None,
+        };
+
+        // Each run is a fresh monitor, as with `catdog check`
+        monitor().record_smart_health(vec![health(4)]).unwrap();
+        let mut second = monitor();
+        second.record_smart_health(vec![health(7)]).unwrap();
+
+        let alerts = second.alert_manager.get_alerts(None, None);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].source, "smart_sectors");
+        assert_eq!(alerts[0].metadata.get("previous").unwrap(), "4");
+        assert_eq!(load_smart_baseline(&baseline)["/dev/sda"], 7);
+    }
+
+    #[test]
+    fn test_critical_alerts_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_path = dir.path().join("alerts.json");
+        let mut monitor = FsMonitor::new(AlertManager::new(storage_path).unwrap());
+        let alert = |title: &str, severity| {
+            Alert::new(
+                title.to_string(),
+                "Fixture".to_string(),
+                severity,
+                "test".to_string(),
+            )
+        };
+
+        monitor
+            .alert_manager
+            .create_alert(alert("Earlier", AlertSeverity::Critical))
+            .unwrap();
+        let started = chrono::Utc::now();
+        monitor
+            .alert_manager
+            .create_alert(alert("Disk failing", AlertSeverity::Critical))
+            .unwrap();
+        monitor
+            .alert_manager
+            .create_alert(alert("Disk filling", AlertSeverity::Warning))
+            .unwrap();
+
+        assert_eq!(monitor.critical_alerts_since(started), 1);
+    }
+
+    #[test]
+    fn test_mount_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().to_string_lossy().to_string();
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        let entry = |mount_point: &str, options: &str| {
+            FstabEntry::from_fields(&["/dev/vdb1", mount_point, "ext4", options, "0", "2"]).unwrap()
+        };
+        let mounted =
+            mount::parse_mountinfo(&format!("31 22 253:17 / {} rw - ext4 /dev/vdb1 rw\n", data));
+
+        assert_eq!(
+            mount_failure(&entry(&data, "defaults"), Some(&mounted)),
+            None
+        );
+        assert_eq!(
+            mount_failure(&entry(&data, "defaults"), Some(&[])),
+            Some(MountFailure::NotMounted)
+        );
+        assert_eq!(
+            mount_failure(&entry(&missing, "defaults"), Some(&mounted)),
+            Some(MountFailure::DirectoryMissing)
+        );
+        // noauto entries aren't expected to be mounted
+        assert_eq!(mount_failure(&entry(&data, "noauto,user"), Some(&[])), None);
+        // Without a mount table only the directory can be checked
+        assert_eq!(mount_failure(&entry(&data, "defaults"), None), None);
+        assert_eq!(mount_failure(&entry("none", "defaults"), Some(&[])), None);
+
+        // nofail entries may be absent, so they alert one level lower
+        let nofail = entry(&data, "defaults,nofail");
+        assert_eq!(
+            MountFailure::NotMounted.severity(&entry(&data, "defaults")),
+            AlertSeverity::Critical
+        );
+        assert_eq!(
+            MountFailure::NotMounted.severity(&nofail),
+            AlertSeverity::Warning
+        );
+        assert_eq!(
+            MountFailure::DirectoryMissing.severity(&nofail),
+            AlertSeverity::Info
+        );
+    }
+
+    #[test]
+    fn test_sleep_stops_early() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let setter = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            setter.store(true, Ordering::SeqCst);
+        });
+
+        let started = Instant::now();
+        assert!(sleep_unless_stopped(Duration::from_secs(30), &stop));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        handle.join().unwrap();
+
+        let idle = AtomicBool::new(false);
+        assert!(!sleep_unless_stopped(Duration::from_millis(10), &idle));
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = MonitorMetrics {
+            disk_usage: BTreeMap::from([
+                ("/".to_string(), 73),
+                ("/mnt/my \"disk\"".to_string(), 12),
+            ]),
+            fstab_issues: 2,
+            firing_alerts: 1,
+        };
+
+        assert_eq!(
+            render_metrics(&metrics),
+            "# HELP catdog_disk_usage_percent Disk usage of a mounted filesystem in percent.\n\
+             # TYPE catdog_disk_usage_percent gauge\n\
+             catdog_disk_usage_percent{mount=\"/\"} 73\n\
+             catdog_disk_usage_percent{mount=\"/mnt/my \\\"disk\\\"\"} 12\n\
+             # HELP catdog_fstab_issues_total Problems found in /etc/fstab by the last check.\n\
+             # TYPE catdog_fstab_issues_total gauge\n\
+             catdog_fstab_issues_total 2\n\
+             # HELP catdog_firing_alerts_total Barks currently firing.\n\
+             # TYPE catdog_firing_alerts_total gauge\n\
+             catdog_firing_alerts_total 1\n"
+        );
+    }
+
+    #[test]
+    fn test_export_metrics_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("catdog.prom");
+        fs::write(&path, "stale").unwrap();
+
+        export_metrics(&path, &MonitorMetrics::default()).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("catdog_firing_alerts_total 0\n"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_timeout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("slow.sh");
+        fs::write(&script, "#!/bin/sh\nexec sleep 10\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let output = run_hook(&script, Duration::from_millis(200)).unwrap();
+        assert!(output.timed_out);
+        assert_eq!(output.exit_code, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_timeout_kills_forked_commands() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("forks.sh");
+        // No exec: sleep runs as a grandchild that holds the output pipes
+        fs::write(&script, "#!/bin/sh\necho started\nsleep 10\necho done\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let started = std::time::Instant::now();
+        let output = run_hook(&script, Duration::from_millis(300)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(output.timed_out);
+        assert_eq!(output.stdout, "started\n");
+    }
+}
@@ -0,0 +1,488 @@
+use anyhow::{Context, Result};
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::UserError;
+use crate::exec;
+use crate::fstab::{self, FstabEntry};
+
+/// A filesystem that is mounted right now
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveMount {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+}
+
+/// Where an fstab entry stands against the live mount table
+#[derive(Debug, Clone, PartialEq)]
+pub enum MountState {
+    /// The entry's device is mounted at the expected mount point
+    Mounted,
+    /// A different device is mounted at the expected mount point
+    OtherDevice(String),
+    /// The device is mounted, but at other mount points
+    MountedElsewhere(Vec<String>),
+    NotMounted,
+}
+
+/// Read the live mount table from `/proc/self/mountinfo` (falling back to
+/// `/proc/mounts`), or `mount` output on macOS
+pub fn read_active_mounts() -> Result<Vec<ActiveMount>> {
+    if cfg!(target_os = "macos") {
+        let output = exec::run_command("mount", &[])?;
+        if !output.status.success() {
+            anyhow::bail!("mount exited with {}", output.status);
+        }
+        return Ok(parse_mount_output(&String::from_utf8_lossy(&output.stdout)));
+    }
+
+    if let Ok(contents) = fs::read_to_string("/proc/self/mountinfo") {
+        return Ok(parse_mountinfo(&contents));
+    }
+
+    let contents = fs::read_to_string("/proc/mounts").context("Failed to read /proc/mounts")?;
+    Ok(parse_proc_mounts(&contents))
+}
+
+/// Parse `/proc/self/mountinfo`
+///
+/// Each line is `id parent major:minor root mount_point options [optional...] - fs_type source super_options`;
+/// the optional fields vary in number, so the fields after the `-` separator are located by position.
+pub fn parse_mountinfo(contents: &str) -> Vec<ActiveMount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let separator = fields.iter().position(|f| *f == "-")?;
+            Some(ActiveMount {
+                device: fstab::decode_field(fields.get(separator + 2)?),
+                mount_point: fstab::decode_field(fields.get(4)?),
+                fs_type: fields.get(separator + 1)?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `/proc/mounts`, decoding the octal escapes it shares with fstab
+pub fn parse_proc_mounts(contents: &str) -> Vec<ActiveMount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some(ActiveMount {
+                device: fstab::decode_field(parts.next()?),
+                mount_point: fstab::decode_field(parts.next()?),
+                fs_type: parts.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse BSD-style `mount` output, e.g. `/dev/disk3s1 on / (apfs, local, journaled)`
+pub fn parse_mount_output(output: &str) -> Vec<ActiveMount> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (device, rest) = line.split_once(" on ")?;
+            let (mount_point, details) = match rest.rsplit_once(" (") {
+                Some((mount_point, details)) => (mount_point, details),
+                None => (rest, ""),
+            };
+            let fs_type = details
+                .trim_end_matches(')')
+                .split(',')
+                .next()
+                .unwrap_or("")
+                .trim();
+
+            Some(ActiveMount {
+                device: device.to_string(),
+                mount_point: mount_point.to_string(),
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Resolve a device spec to a comparable path
+///
+/// `UUID=`, `LABEL=`, `PARTUUID=` and `PARTLABEL=` go through `/dev/disk/by-*`
+/// and symlinks are followed; anything that can't be resolved is returned as is.
+pub fn canonical_device(spec: &str) -> String {
+    let path = match spec.split_once('=') {
+        Some(("UUID", value)) => format!("/dev/disk/by-uuid/{}", value),
+        Some(("LABEL", value)) => format!("/dev/disk/by-label/{}", value),
+        Some(("PARTUUID", value)) => format!("/dev/disk/by-partuuid/{}", value),
+        Some(("PARTLABEL", value)) => format!("/dev/disk/by-partlabel/{}", value),
+        _ => spec.to_string(),
+    };
+
+    if path.starts_with("/dev/") {
+        if let Ok(resolved) = fs::canonicalize(Path::new(&path)) {
+            return resolved.to_string_lossy().to_string();
+        }
+    }
+
+    path
+}
+
+/// Check an fstab entry against the live mount table
+pub fn mount_state(entry: &FstabEntry, active: &[ActiveMount]) -> MountState {
+    let mount_point = entry.decoded_mount_point();
+    let device = canonical_device(&entry.decoded_device());
+    let is_device = |m: &ActiveMount| m.device == device || canonical_device(&m.device) == device;
+
+    // The mount table names the source of a bind mount by its underlying
+    // device, so only the mount point can be checked
+    let bind = entry
+        .options
+        .split(',')
+        .any(|opt| opt == "bind" || opt == "rbind");
+    // The last mount at a path is the one that's visible there
+    if let Some(mounted) = active.iter().rev().find(|m| m.mount_point == mount_point) {
+        return if bind || is_device(mounted) {
+            MountState::Mounted
+        } else {
+            MountState::OtherDevice(mounted.device.clone())
+        };
+    }
+
+    let elsewhere: Vec<String> = active
+        .iter()
+        .filter(|m| is_device(m))
+        .map(|m| m.mount_point.clone())
+        .collect();
+
+    if elsewhere.is_empty() {
+        MountState::NotMounted
+    } else {
+        MountState::MountedElsewhere(elsewhere)
+    }
+}
+
+/// Mount a filesystem through its fstab entry
+pub fn mount_path(mount_point: &str, use_sudo: bool, dry_run: bool, verbose: bool) -> Result<()> {
+    let cmd_parts = mount_command(mount_point, use_sudo);
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the mount command line; `mount` fills in the rest from fstab
+pub fn mount_command(mount_point: &str, use_sudo: bool) -> Vec<&str> {
+    let mut cmd_parts = Vec::new();
+    if use_sudo {
+        cmd_parts.push("sudo");
+    }
+    cmd_parts.push("mount");
+    cmd_parts.push(mount_point);
+    cmd_parts
+}
+
+/// Where mount point directories may be created without `--force`
+pub const MOUNT_DIR_ROOTS: &[&str] = &["/mnt", "/media", "/srv"];
+
+/// Refuse to create a mount point directory in a surprising place
+///
+/// The path must be absolute and free of `..`, and unless `force` is set it
+/// must sit beneath one of [`MOUNT_DIR_ROOTS`] or an `extra_roots` entry.
+/// Symlinks in the part of the path that already exists are followed first,
+/// so a link under a root can't lead the directory somewhere else.
+pub fn check_mount_dir(path: &str, extra_roots: &[PathBuf], force: bool) -> Result<()> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        anyhow::bail!("Mount point '{}' must be an absolute path", path.display());
+    }
+    if path
+        .components()
+        .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        anyhow::bail!("Mount point '{}' can't contain '..'", path.display());
+    }
+    if force {
+        return Ok(());
+    }
+
+    let resolved = resolve_existing_prefix(path);
+    let allowed = MOUNT_DIR_ROOTS
+        .iter()
+        .map(PathBuf::from)
+        .chain(extra_roots.iter().cloned())
+        .map(|root| resolve_existing_prefix(&root))
+        .any(|root| resolved != root && resolved.starts_with(&root));
+    if !allowed {
+        return Err(UserError::new(format!(
+            "Refusing to create {} outside {}",
+            path.display(),
+            MOUNT_DIR_ROOTS.join(", ")
+        ))
+        .with_suggestion(
+            "Pass --force, or add the parent to mount_dir_prefixes under [general] in ~/.catdog/config.toml",
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// `path` with its deepest existing ancestor canonicalized and the missing
+/// components appended as they are
+fn resolve_existing_prefix(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |resolved, name| resolved.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// Create a mount point directory (mode 755), returning false if it already exists
+pub fn create_mount_dir(path: &str, use_sudo: bool, dry_run: bool, verbose: bool) -> Result<bool> {
+    let target = Path::new(path);
+    if target.is_dir() {
+        return Ok(false);
+    }
+    if target.exists() {
+        anyhow::bail!("{} exists and is not a directory", path);
+    }
+
+    let cmd_parts = mkdir_command(path, use_sudo);
+    execute_command(&cmd_parts, dry_run, verbose)?;
+    Ok(true)
+}
+
+/// Build the mkdir command line for a new mount point
+pub fn mkdir_command(path: &str, use_sudo: bool) -> Vec<&str> {
+    let mut cmd_parts = Vec::new();
+    if use_sudo {
+        cmd_parts.push("sudo");
+    }
+    cmd_parts.extend(["mkdir", "-p", "-m", "755", path]);
+    cmd_parts
+}
+
+/// Unmount a filesystem
+pub fn unmount_path(mount_point: &str, use_sudo: bool, dry_run: bool, verbose: bool) -> Result<()> {
+    let cmd_parts = unmount_command(mount_point, use_sudo);
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the unmount command line
+pub fn unmount_command(mount_point: &str, use_sudo: bool) -> Vec<&str> {
+    let mut cmd_parts = Vec::new();
+    if use_sudo {
+        cmd_parts.push("sudo");
+    }
+    cmd_parts.push("umount");
+    cmd_parts.push(mount_point);
+    cmd_parts
+}
+
+/// Execute a command with proper error handling
+fn execute_command(cmd_parts: &[&str], dry_run: bool, verbose: bool) -> Result<()> {
+    if cmd_parts.is_empty() {
+        anyhow::bail!("No command to execute");
+    }
+
+    let cmd_str = cmd_parts.join(" ");
+
+    if dry_run {
+        println!(
+            "{} Would execute: {}",
+            "[DRY-RUN]".yellow().bold(),
+            cmd_str.bright_white()
+        );
+        return Ok(());
+    }
+
+    if verbose {
+        println!("{} {}", "Executing:".cyan(), cmd_str.bright_white());
+    }
+
+    let output = Command::new(cmd_parts[0])
+        .args(&cmd_parts[1..])
+        .output()
+        .context(format!("Failed to execute: {}", cmd_str))?;
+
+    if verbose || !output.stdout.is_empty() {
+        print!("{}", String::from_utf8_lossy(&output.stdout));
+    }
+
+    if !output.stderr.is_empty() {
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    if !output.status.success() {
+        anyhow::bail!("Command failed with exit code: {:?}", output.status.code());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(device: &str, mount_point: &str) -> FstabEntry {
+        FstabEntry::from_fields(&[device, mount_point, "ext4", "defaults", "0", "2"]).unwrap()
+    }
+
+    #[test]
+    fn test_mount_command() {
+        assert_eq!(mount_command("/data", false), vec!["mount", "/data"]);
+        assert_eq!(
+            mount_command("/mnt/My Disk", true),
+            vec!["sudo", "mount", "/mnt/My Disk"]
+        );
+    }
+
+    #[test]
+    fn test_unmount_command() {
+        assert_eq!(unmount_command("/data", false), vec!["umount", "/data"]);
+        assert_eq!(
+            unmount_command("/data", true),
+            vec!["sudo", "umount", "/data"]
+        );
+    }
+
+    #[test]
+    fn test_check_mount_dir() {
+        assert!(check_mount_dir("/mnt/data", &[], false).is_ok());
+        assert!(check_mount_dir("/media/usb/stick", &[], false).is_ok());
+        assert!(check_mount_dir("/srv/nfs", &[], false).is_ok());
+
+        // The roots themselves and anything outside them need --force
+        assert!(check_mount_dir("/mnt", &[], false).is_err());
+        assert!(check_mount_dir("/etc/data", &[], false).is_err());
+        assert!(check_mount_dir("/mntx/data", &[], false).is_err());
+        assert!(check_mount_dir("/etc/data", &[], true).is_ok());
+
+        let extra = [PathBuf::from("/data")];
+        assert!(check_mount_dir("/data/disk1", &extra, false).is_ok());
+
+        // Relative paths and .. are refused even with --force
+        assert!(check_mount_dir("mnt/data", &[], true).is_err());
+        assert!(check_mount_dir("/mnt/../etc", &[], true).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_mount_dir_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("mounts");
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(root.join("disks")).unwrap();
+        fs::create_dir(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("escape")).unwrap();
+        let extra = [root.clone()];
+        let under = |rest: &str| root.join(rest).to_string_lossy().to_string();
+
+        assert!(check_mount_dir(&under("disks/new"), &extra, false).is_ok());
+        assert!(check_mount_dir(&under("new/deeper"), &extra, false).is_ok());
+        assert!(check_mount_dir(&under("escape"), &extra, false).is_err());
+        assert!(check_mount_dir(&under("escape/new"), &extra, false).is_err());
+        assert!(check_mount_dir(&under("escape/new"), &extra, true).is_ok());
+    }
+
+    #[test]
+    fn test_mkdir_command() {
+        assert_eq!(
+            mkdir_command("/mnt/data", true),
+            vec!["sudo", "mkdir", "-p", "-m", "755", "/mnt/data"]
+        );
+        assert_eq!(mkdir_command("/mnt/data", false)[0], "mkdir");
+    }
+
+    #[test]
+    fn test_parse_proc_mounts() {
+        let contents = "/dev/vda1 / ext4 rw,relatime 0 0\n\
+                        proc /proc proc rw,nosuid 0 0\n\
+                        /dev/vdb1 /mnt/My\\040Disk xfs rw 0 0\n";
+        let mounts = parse_proc_mounts(contents);
+
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(mounts[0].device, "/dev/vda1");
+        assert_eq!(mounts[2].mount_point, "/mnt/My Disk");
+        assert_eq!(mounts[2].fs_type, "xfs");
+    }
+
+    #[test]
+    fn test_parse_mountinfo() {
+        let contents = "22 1 253:1 / / rw,relatime shared:1 - ext4 /dev/vda1 rw\n\
+                        25 22 0:21 / /proc rw,nosuid - proc proc rw\n\
+                        31 22 253:17 / /mnt/My\\040Disk rw master:2 shared:5 - xfs /dev/vdb1 rw\n\
+                        garbage line\n";
+        let mounts = parse_mountinfo(contents);
+
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(
+            mounts[0],
+            ActiveMount {
+                device: "/dev/vda1".to_string(),
+                mount_point: "/".to_string(),
+                fs_type: "ext4".to_string(),
+            }
+        );
+        assert_eq!(mounts[1].fs_type, "proc");
+        assert_eq!(mounts[2].mount_point, "/mnt/My Disk");
+        assert_eq!(mounts[2].device, "/dev/vdb1");
+        assert_eq!(mounts[2].fs_type, "xfs");
+    }
+
+    #[test]
+    fn test_parse_mount_output() {
+        let output = "/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)\n\
+                      map auto_home on /System/Volumes/Data/home (autofs, automounted)\n";
+        let mounts = parse_mount_output(output);
+
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts[0].device, "/dev/disk3s1s1");
+        assert_eq!(mounts[0].mount_point, "/");
+        assert_eq!(mounts[0].fs_type, "apfs");
+        assert_eq!(mounts[1].mount_point, "/System/Volumes/Data/home");
+    }
+
+    #[test]
+    fn test_mount_state() {
+        let active = parse_proc_mounts(
+            "/dev/catdog-test1 / ext4 rw 0 0\n/dev/catdog-test2 /srv/old xfs rw 0 0\n",
+        );
+
+        assert_eq!(
+            mount_state(&entry("/dev/catdog-test1", "/"), &active),
+            MountState::Mounted
+        );
+        assert_eq!(
+            mount_state(&entry("/dev/catdog-test2", "/data"), &active),
+            MountState::MountedElsewhere(vec!["/srv/old".to_string()])
+        );
+        assert_eq!(
+            mount_state(&entry("/dev/catdog-test3", "/backup"), &active),
+            MountState::NotMounted
+        );
+        assert_eq!(
+            mount_state(&entry("/dev/catdog-test3", "/srv/old"), &active),
+            MountState::OtherDevice("/dev/catdog-test2".to_string())
+        );
+        assert_eq!(
+            mount_state(&entry("UUID=catdog-test-missing", "/"), &active),
+            MountState::OtherDevice("/dev/catdog-test1".to_string())
+        );
+
+        let mut bind = entry("/srv/data", "/srv/old");
+        bind.options = "bind".to_string();
+        assert_eq!(mount_state(&bind, &active), MountState::Mounted);
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use std::fmt::Display;
+
+use crate::error::exit_codes;
+
+/// Tally of what a write command did, used for the summary line and exit code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CommandOutcome {
+    pub changed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl CommandOutcome {
+    pub fn changed(count: usize) -> Self {
+        CommandOutcome {
+            changed: count,
+            ..Default::default()
+        }
+    }
+
+    pub fn skipped(count: usize) -> Self {
+        CommandOutcome {
+            skipped: count,
+            ..Default::default()
+        }
+    }
+
+    /// Process exit code: non-zero whenever any item failed
+    pub fn exit_code(&self) -> i32 {
+        if self.failed > 0 {
+            exit_codes::PARTIAL_FAILURE
+        } else {
+            exit_codes::SUCCESS
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} changed, {} failed, {} skipped",
+            self.changed, self.failed, self.skipped
+        )
+    }
+
+    /// Print the one-line summary shared by all write commands
+    pub fn display(&self) {
+        let marker = if self.failed > 0 {
+            "✗".red().bold()
+        } else {
+            "✓".green().bold()
+        };
+        println!(
+            "\n{} {} {}",
+            marker,
+            "Summary:".cyan().bold(),
+            self.summary()
+        );
+    }
+}
+
+/// Apply an operation to each item, carrying on past failures
+///
+/// Failures are reported on stderr as they happen and counted in the outcome.
+pub fn run_batch<T, F>(items: &[T], mut op: F) -> CommandOutcome
+where
+    T: Display,
+    F: FnMut(&T) -> Result<()>,
+{
+    let mut outcome = CommandOutcome::default();
+
+    for item in items {
+        match op(item) {
+            Ok(()) => outcome.changed += 1,
+            Err(e) => {
+                eprintln!("{} {}: {:#}", "✗".red().bold(), item, e);
+                outcome.failed += 1;
+            }
+        }
+    }
+
+    outcome
+}
+
+/// Apply one operation to all items at once, returning the outcome and the items it changed
+///
+/// When the operation fails, items for which `took_effect` holds still count as
+/// changed; the rest are reported on stderr with the operation's error and counted
+/// as failed.
+pub fn run_together<T, F, C>(items: &[T], op: F, mut took_effect: C) -> (CommandOutcome, Vec<&T>)
+where
+    T: Display,
+    F: FnOnce(&[T]) -> Result<()>,
+    C: FnMut(&T) -> bool,
+{
+    if items.is_empty() {
+        return (CommandOutcome::default(), Vec::new());
+    }
+
+    let error = match op(items) {
+        Ok(()) => return (CommandOutcome::changed(items.len()), items.iter().collect()),
+        Err(e) => e,
+    };
+
+    let mut outcome = CommandOutcome::default();
+    let mut changed = Vec::new();
+    for item in items {
+        if took_effect(item) {
+            outcome.changed += 1;
+            changed.push(item);
+        } else {
+            eprintln!("{} {}: {:#}", "✗".red().bold(), item, error);
+            outcome.failed += 1;
+        }
+    }
+
+    (outcome, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_with_one_failure_exits_non_zero() {
+        let services = ["cron", "nginx", "sshd"];
+        let outcome = run_batch(&services, |name| {
+            if *name == "nginx" {
+                anyhow::bail!("Job for nginx.service failed");
+            }
+            Ok(())
+        });
+
+        assert_eq!(
+            outcome,
+            CommandOutcome {
+                changed: 2,
+                failed: 1,
+                skipped: 0
+            }
+        );
+        assert_eq!(outcome.exit_code(), exit_codes::PARTIAL_FAILURE);
+        assert_eq!(outcome.summary(), "2 changed, 1 failed, 0 skipped");
+    }
+
+    #[test]
+    fn test_together_runs_once_and_maps_failures_back() {
+        let packages = ["curl", "git", "nosuchpkg"];
+        let mut calls = Vec::new();
+        let (outcome, changed) = run_together(
+            &packages,
+            |batch| {
+                calls.push(batch.to_vec());
+                anyhow::bail!("Unable to locate package nosuchpkg")
+            },
+            |name| *name == "git",
+        );
+
+        assert_eq!(calls, vec![packages.to_vec()]);
+        assert_eq!(changed, vec![&"git"]);
+        assert_eq!(
+            outcome,
+            CommandOutcome {
+                changed: 1,
+                failed: 2,
+                skipped: 0
+            }
+        );
+
+        let (outcome, changed) = run_together(&packages, |_| Ok(()), |_| false);
+        assert_eq!(outcome, CommandOutcome::changed(3));
+        assert_eq!(changed.len(), 3);
+    }
+}
@@ -2,20 +2,33 @@ use anyhow::{Context, Result};
 use colored::*;
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::service::is_command_available;
+use crate::state;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PackageManager {
-    Apt,    // Debian/Ubuntu
-    Dnf,    // Fedora/RHEL 8+
-    Yum,    // CentOS/RHEL 7
-    Pacman, // Arch Linux
-    Zypper, // openSUSE
-    Brew,   // macOS
-    Apk,    // Alpine Linux
+    Apt,     // Debian/Ubuntu
+    Dnf,     // Fedora/RHEL 8+
+    Yum,     // CentOS/RHEL 7
+    Pacman,  // Arch Linux
+    Zypper,  // openSUSE
+    Brew,    // macOS
+    Apk,     // Alpine Linux
+    Flatpak, // Sandboxed desktop apps, alongside the system manager
+    Snap,    // Canonical snaps, alongside the system manager
     Unknown,
 }
 
+/// Names accepted by `--backend`, as offered by shell completion
+pub const BACKEND_NAMES: &[&str] = &[
+    "apt", "dnf", "yum", "pacman", "zypper", "brew", "apk", "flatpak", "snap",
+];
+
 impl PackageManager {
     pub fn name(&self) -> &str {
         match self {
@@ -26,20 +39,51 @@ impl PackageManager {
             PackageManager::Zypper => "zypper",
             PackageManager::Brew => "brew",
             PackageManager::Apk => "apk",
+            PackageManager::Flatpak => "flatpak",
+            PackageManager::Snap => "snap",
             PackageManager::Unknown => "unknown",
         }
     }
 
-    pub fn requires_sudo(&self) -> bool {
+    /// Look up a package manager by the name used for `--backend`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "apt" | "apt-get" => Some(PackageManager::Apt),
+            "dnf" => Some(PackageManager::Dnf),
+            "yum" => Some(PackageManager::Yum),
+            "pacman" => Some(PackageManager::Pacman),
+            "zypper" => Some(PackageManager::Zypper),
+            "brew" | "homebrew" => Some(PackageManager::Brew),
+            "apk" => Some(PackageManager::Apk),
+            "flatpak" => Some(PackageManager::Flatpak),
+            "snap" => Some(PackageManager::Snap),
+            _ => None,
+        }
+    }
+
+    /// The executable that has to be on PATH for this manager to work
+    pub fn command(&self) -> &str {
         match self {
-            PackageManager::Brew => false,
-            PackageManager::Unknown => false,
-            _ => true,
+            PackageManager::Apt => "apt-get",
+            _ => self.name(),
         }
     }
+
+    /// Flatpak asks for authorization through polkit itself, so it runs without sudo
+    pub fn requires_sudo(&self) -> bool {
+        !matches!(
+            self,
+            PackageManager::Brew | PackageManager::Flatpak | PackageManager::Unknown
+        )
+    }
+
+    /// App stores that coexist with a system package manager
+    pub fn is_supplementary(&self) -> bool {
+        matches!(self, PackageManager::Flatpak | PackageManager::Snap)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PackageInfo {
     pub name: String,
     pub version: Option<String>,
@@ -48,9 +92,47 @@ pub struct PackageInfo {
 }
 
 /// Detect the system's package manager
+///
+/// System managers win over Flatpak and Snap, which are only picked when
+/// nothing else is installed.
 pub fn detect_package_manager() -> Result<PackageManager> {
     debug!("Detecting package manager...");
 
+    let pm = detect_package_managers()
+        .into_iter()
+        .next()
+        .unwrap_or(PackageManager::Unknown);
+    if pm != PackageManager::Unknown {
+        info!("Detected package manager: {}", pm.name());
+    }
+
+    Ok(pm)
+}
+
+/// Use the package manager named by `--backend`, or detect one
+///
+/// A named backend must be known and installed; there is no fallback to detection.
+pub fn select_package_manager(backend: Option<&str>) -> Result<PackageManager> {
+    let name = match backend {
+        Some(name) => name,
+        None => return detect_package_manager(),
+    };
+
+    let pm = PackageManager::from_name(name).with_context(|| {
+        format!(
+            "Unknown package manager '{}': use apt, dnf, yum, pacman, zypper, brew, apk, flatpak or snap",
+            name
+        )
+    })?;
+    if !is_command_available(pm.command()) {
+        anyhow::bail!("{} is not installed or not on PATH", pm.command());
+    }
+
+    Ok(pm)
+}
+
+/// Every package manager available on this system, primary first
+pub fn detect_package_managers() -> Vec<PackageManager> {
     // Check for various package managers in order of specificity
     let managers = vec![
         ("brew", PackageManager::Brew),
@@ -60,34 +142,42 @@ pub fn detect_package_manager() -> Result<PackageManager> {
         ("pacman", PackageManager::Pacman),
         ("zypper", PackageManager::Zypper),
         ("apk", PackageManager::Apk),
+        ("flatpak", PackageManager::Flatpak),
+        ("snap", PackageManager::Snap),
     ];
 
+    let mut found: Vec<PackageManager> = Vec::new();
     for (cmd, pm) in managers {
+        // Only one system manager; yum is usually a dnf alias
+        if !pm.is_supplementary() && found.iter().any(|f| !f.is_supplementary()) {
+            continue;
+        }
         if is_command_available(cmd) {
-            info!("Detected package manager: {}", pm.name());
-            return Ok(pm);
+            found.push(pm);
         }
     }
 
-    Ok(PackageManager::Unknown)
-}
-
-/// Check if a command is available in PATH
-fn is_command_available(cmd: &str) -> bool {
-    Command::new("which")
-        .arg(cmd)
-        .output()
-        .map(|output| output.status.success())
-        .unwrap_or(false)
+    found
 }
 
 /// Install one or more packages
 pub fn install_packages(
     packages: &[String],
     pm: &PackageManager,
+    use_sudo: bool,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
+    let cmd_parts = install_command(packages, pm, use_sudo)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the install command line for a package manager
+pub fn install_command<'a>(
+    packages: &'a [String],
+    pm: &'a PackageManager,
+    use_sudo: bool,
+) -> Result<Vec<&'a str>> {
     if packages.is_empty() {
         anyhow::bail!("No packages specified");
     }
@@ -97,7 +187,7 @@ pub fn install_packages(
     // Build command based on package manager
     match pm {
         PackageManager::Apt => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apt-get");
@@ -106,7 +196,7 @@ pub fn install_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Dnf | PackageManager::Yum => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push(pm.name());
@@ -115,7 +205,7 @@ pub fn install_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Pacman => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("pacman");
@@ -124,7 +214,7 @@ pub fn install_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Zypper => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("zypper");
@@ -138,28 +228,53 @@ pub fn install_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Apk => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apk");
             cmd_parts.push("add");
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
+        PackageManager::Flatpak => {
+            cmd_parts.push("flatpak");
+            cmd_parts.push("install");
+            cmd_parts.push("-y");
+            cmd_parts.extend(packages.iter().map(|s| s.as_str()));
+        }
+        PackageManager::Snap => {
+            if use_sudo {
+                cmd_parts.push("sudo");
+            }
+            cmd_parts.push("snap");
+            cmd_parts.push("install");
+            cmd_parts.extend(packages.iter().map(|s| s.as_str()));
+        }
         PackageManager::Unknown => {
             anyhow::bail!("Unknown package manager - cannot install packages");
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
 }
 
 /// Remove one or more packages
 pub fn remove_packages(
     packages: &[String],
     pm: &PackageManager,
+    use_sudo: bool,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
+    let cmd_parts = remove_command(packages, pm, use_sudo)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the remove command line for a package manager
+pub fn remove_command<'a>(
+    packages: &'a [String],
+    pm: &'a PackageManager,
+    use_sudo: bool,
+) -> Result<Vec<&'a str>> {
     if packages.is_empty() {
         anyhow::bail!("No packages specified");
     }
@@ -168,7 +283,7 @@ pub fn remove_packages(
 
     match pm {
         PackageManager::Apt => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apt-get");
@@ -177,7 +292,7 @@ pub fn remove_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Dnf | PackageManager::Yum => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push(pm.name());
@@ -186,7 +301,7 @@ pub fn remove_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Pacman => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("pacman");
@@ -195,7 +310,7 @@ pub fn remove_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Zypper => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("zypper");
@@ -209,49 +324,74 @@ pub fn remove_packages(
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
         PackageManager::Apk => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apk");
             cmd_parts.push("del");
             cmd_parts.extend(packages.iter().map(|s| s.as_str()));
         }
+        PackageManager::Flatpak => {
+            cmd_parts.push("flatpak");
+            cmd_parts.push("uninstall");
+            cmd_parts.push("-y");
+            cmd_parts.extend(packages.iter().map(|s| s.as_str()));
+        }
+        PackageManager::Snap => {
+            if use_sudo {
+                cmd_parts.push("sudo");
+            }
+            cmd_parts.push("snap");
+            cmd_parts.push("remove");
+            cmd_parts.extend(packages.iter().map(|s| s.as_str()));
+        }
         PackageManager::Unknown => {
             anyhow::bail!("Unknown package manager - cannot remove packages");
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
 }
 
 /// Update package cache/repositories
-pub fn update_cache(pm: &PackageManager, dry_run: bool, verbose: bool) -> Result<()> {
+pub fn update_cache(
+    pm: &PackageManager,
+    use_sudo: bool,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<()> {
+    let cmd_parts = update_command(pm, use_sudo)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the cache refresh command line for a package manager
+pub fn update_command(pm: &PackageManager, use_sudo: bool) -> Result<Vec<&str>> {
     let mut cmd_parts = Vec::new();
 
     match pm {
         PackageManager::Apt => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apt-get");
             cmd_parts.push("update");
         }
         PackageManager::Dnf | PackageManager::Yum => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push(pm.name());
             cmd_parts.push("check-update");
         }
         PackageManager::Pacman => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("pacman");
             cmd_parts.push("-Sy");
         }
         PackageManager::Zypper => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("zypper");
@@ -262,27 +402,49 @@ pub fn update_cache(pm: &PackageManager, dry_run: bool, verbose: bool) -> Result
             cmd_parts.push("update");
         }
         PackageManager::Apk => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apk");
             cmd_parts.push("update");
         }
+        PackageManager::Flatpak => {
+            cmd_parts.push("flatpak");
+            cmd_parts.push("update");
+            cmd_parts.push("--appstream");
+        }
+        PackageManager::Snap => {
+            // snapd refreshes its metadata itself; list what a refresh would change
+            cmd_parts.push("snap");
+            cmd_parts.push("refresh");
+            cmd_parts.push("--list");
+        }
         PackageManager::Unknown => {
             anyhow::bail!("Unknown package manager - cannot update cache");
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
 }
 
 /// Upgrade all packages
-pub fn upgrade_packages(pm: &PackageManager, dry_run: bool, verbose: bool) -> Result<()> {
+pub fn upgrade_packages(
+    pm: &PackageManager,
+    use_sudo: bool,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<()> {
+    let cmd_parts = upgrade_command(pm, use_sudo)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the upgrade command line for a package manager
+pub fn upgrade_command(pm: &PackageManager, use_sudo: bool) -> Result<Vec<&str>> {
     let mut cmd_parts = Vec::new();
 
     match pm {
         PackageManager::Apt => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apt-get");
@@ -290,7 +452,7 @@ pub fn upgrade_packages(pm: &PackageManager, dry_run: bool, verbose: bool) -> Re
             cmd_parts.push("-y");
         }
         PackageManager::Dnf | PackageManager::Yum => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push(pm.name());
@@ -298,7 +460,7 @@ pub fn upgrade_packages(pm: &PackageManager, dry_run: bool, verbose: bool) -> Re
             cmd_parts.push("-y");
         }
         PackageManager::Pacman => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("pacman");
@@ -306,7 +468,7 @@ pub fn upgrade_packages(pm: &PackageManager, dry_run: bool, verbose: bool) -> Re
             cmd_parts.push("--noconfirm");
         }
         PackageManager::Zypper => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("zypper");
@@ -318,18 +480,502 @@ pub fn upgrade_packages(pm: &PackageManager, dry_run: bool, verbose: bool) -> Re
             cmd_parts.push("upgrade");
         }
         PackageManager::Apk => {
-            if pm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("apk");
             cmd_parts.push("upgrade");
         }
+        PackageManager::Flatpak => {
+            cmd_parts.push("flatpak");
+            cmd_parts.push("update");
+            cmd_parts.push("-y");
+        }
+        PackageManager::Snap => {
+            if use_sudo {
+                cmd_parts.push("sudo");
+            }
+            cmd_parts.push("snap");
+            cmd_parts.push("refresh");
+        }
         PackageManager::Unknown => {
             anyhow::bail!("Unknown package manager - cannot upgrade packages");
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
+}
+
+/// A single package change in an upgrade plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub name: String,
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+}
+
+/// What an upgrade would do, gathered without changing the system
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpgradePlan {
+    pub upgraded: Vec<PlannedChange>,
+    pub newly_installed: Vec<PlannedChange>,
+    pub removed: Vec<PlannedChange>,
+    pub held_back: Vec<String>,
+}
+
+/// Build an upgrade plan using each package manager's simulation or list-updates mode
+///
+/// Returns `None` when the package manager has no way to preview an upgrade.
+pub fn plan_upgrade(pm: &PackageManager) -> Result<Option<UpgradePlan>> {
+    let (program, args): (&str, &[&str]) = match pm {
+        PackageManager::Apt => ("apt-get", &["upgrade", "-s"]),
+        PackageManager::Dnf | PackageManager::Yum => (pm.name(), &["check-update", "-q"]),
+        PackageManager::Pacman => ("pacman", &["-Qu"]),
+        PackageManager::Brew => ("brew", &["outdated", "--verbose"]),
+        _ => return Ok(None),
+    };
+
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .context(format!("Failed to run {} to plan upgrade", program))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let plan = match pm {
+        PackageManager::Apt => {
+            if !output.status.success() {
+                anyhow::bail!(
+                    "apt-get simulation failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            parse_apt_simulation(&stdout)
+        }
+        PackageManager::Dnf | PackageManager::Yum => parse_dnf_check_update(&stdout),
+        PackageManager::Pacman => parse_pacman_updates(&stdout),
+        _ => parse_brew_outdated(&stdout),
+    };
+
+    Ok(Some(plan))
+}
+
+/// Parse the output of `apt-get upgrade -s`
+///
+/// Uses the `Inst`/`Remv` action lines for per-package versions and the
+/// "kept back" section for held packages.
+pub fn parse_apt_simulation(output: &str) -> UpgradePlan {
+    let mut plan = UpgradePlan::default();
+    let mut in_kept_back = false;
+
+    for line in output.lines() {
+        if line.starts_with("The following packages have been kept back") {
+            in_kept_back = true;
+            continue;
+        }
+        if in_kept_back {
+            if line.starts_with(' ') {
+                plan.held_back
+                    .extend(line.split_whitespace().map(|s| s.to_string()));
+                continue;
+            }
+            in_kept_back = false;
+        }
+
+        let mut parts = line.splitn(3, ' ');
+        let action = parts.next().unwrap_or("");
+        let name = match parts.next() {
+            Some(name) if action == "Inst" || action == "Remv" => name.to_string(),
+            _ => continue,
+        };
+        let rest = parts.next().unwrap_or("");
+
+        let from_version = rest
+            .strip_prefix('[')
+            .and_then(|r| r.split(']').next())
+            .map(|v| v.to_string());
+        let to_version = rest
+            .find('(')
+            .map(|start| &rest[start + 1..])
+            .and_then(|r| r.split_whitespace().next())
+            .map(|v| v.to_string());
+
+        let change = PlannedChange {
+            name,
+            from_version: from_version.clone(),
+            to_version,
+        };
+
+        match (action, from_version) {
+            ("Remv", _) => plan.removed.push(change),
+            ("Inst", Some(_)) => plan.upgraded.push(change),
+            _ => plan.newly_installed.push(change),
+        }
+    }
+
+    plan
+}
+
+fn parse_dnf_check_update(output: &str) -> UpgradePlan {
+    let mut plan = UpgradePlan::default();
+
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        // Format: "name.arch  version  repo"
+        if parts.len() == 3 && parts[0].contains('.') {
+            let name = parts[0]
+                .rsplit_once('.')
+                .map(|(n, _)| n)
+                .unwrap_or(parts[0]);
+            plan.upgraded.push(PlannedChange {
+                name: name.to_string(),
+                from_version: None,
+                to_version: Some(parts[1].to_string()),
+            });
+        }
+    }
+
+    plan
+}
+
+fn parse_pacman_updates(output: &str) -> UpgradePlan {
+    let mut plan = UpgradePlan::default();
+
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        // Format: "name old -> new"
+        if parts.len() >= 4 && parts[2] == "->" {
+            plan.upgraded.push(PlannedChange {
+                name: parts[0].to_string(),
+                from_version: Some(parts[1].to_string()),
+                to_version: Some(parts[3].to_string()),
+            });
+        }
+    }
+
+    plan
+}
+
+fn parse_brew_outdated(output: &str) -> UpgradePlan {
+    let mut plan = UpgradePlan::default();
+
+    for line in output.lines() {
+        // Format: "name (old) < new"
+        if let Some((name, rest)) = line.split_once(' ') {
+            let from_version = rest
+                .trim_start_matches('(')
+                .split(')')
+                .next()
+                .map(|v| v.to_string());
+            let to_version = rest.rsplit(' ').next().map(|v| v.to_string());
+            plan.upgraded.push(PlannedChange {
+                name: name.to_string(),
+                from_version,
+                to_version,
+            });
+        }
+    }
+
+    plan
+}
+
+/// A package with a newer version available
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpgradablePackage {
+    pub name: String,
+    pub current_version: Option<String>,
+    pub available_version: Option<String>,
+}
+
+impl From<PlannedChange> for UpgradablePackage {
+    fn from(change: PlannedChange) -> Self {
+        UpgradablePackage {
+            name: change.name,
+            current_version: change.from_version,
+            available_version: change.to_version,
+        }
+    }
+}
+
+/// List packages with pending updates, without changing anything
+pub fn list_upgradable(pm: &PackageManager) -> Result<Vec<UpgradablePackage>> {
+    let (program, args): (&str, &[&str]) = match pm {
+        PackageManager::Apt => ("apt", &["list", "--upgradable"]),
+        PackageManager::Dnf | PackageManager::Yum => (pm.name(), &["check-update", "-q"]),
+        PackageManager::Pacman => ("pacman", &["-Qu"]),
+        PackageManager::Zypper => ("zypper", &["--non-interactive", "list-updates"]),
+        PackageManager::Brew => ("brew", &["outdated", "--verbose"]),
+        PackageManager::Apk => ("apk", &["version", "-l", "<"]),
+        PackageManager::Flatpak => (
+            "flatpak",
+            &["remote-ls", "--updates", "--columns=application,version"],
+        ),
+        PackageManager::Snap => ("snap", &["refresh", "--list"]),
+        PackageManager::Unknown => {
+            anyhow::bail!("Unknown package manager - cannot list upgradable packages")
+        }
+    };
+
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .context(format!("Failed to run {} to list updates", program))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if !upgradable_exit_ok(pm, output.status.code(), &stdout) {
+        anyhow::bail!(
+            "{} failed to list updates: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(parse_upgradable(&stdout, pm))
+}
+
+/// Whether a list-updates exit code means success
+///
+/// `dnf check-update` exits 100 when updates are available, and `pacman -Qu`
+/// exits 1 when there are none.
+fn upgradable_exit_ok(pm: &PackageManager, code: Option<i32>, stdout: &str) -> bool {
+    match (pm, code) {
+        (_, Some(0)) => true,
+        (PackageManager::Dnf | PackageManager::Yum, Some(100)) => true,
+        (PackageManager::Pacman, Some(1)) => stdout.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Parse list-updates output into upgradable packages
+pub fn parse_upgradable(output: &str, pm: &PackageManager) -> Vec<UpgradablePackage> {
+    let changes = match pm {
+        PackageManager::Dnf | PackageManager::Yum => parse_dnf_check_update(output).upgraded,
+        PackageManager::Pacman => parse_pacman_updates(output).upgraded,
+        PackageManager::Brew => parse_brew_outdated(output).upgraded,
+        _ => output
+            .lines()
+            .filter_map(|line| parse_upgradable_line(line, pm))
+            .collect(),
+    };
+
+    changes.into_iter().map(UpgradablePackage::from).collect()
+}
+
+fn parse_upgradable_line(line: &str, pm: &PackageManager) -> Option<PlannedChange> {
+    let line = line.trim();
+    match pm {
+        PackageManager::Apt => {
+            // Format: "curl/jammy-updates 7.81.0-1ubuntu1.15 amd64 [upgradable from: 7.81.0-1ubuntu1.14]"
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 2 || !parts[0].contains('/') {
+                return None;
+            }
+            Some(PlannedChange {
+                name: parts[0].split('/').next()?.to_string(),
+                from_version: line
+                    .split("upgradable from: ")
+                    .nth(1)
+                    .map(|v| v.trim_end_matches(']').to_string()),
+                to_version: Some(parts[1].to_string()),
+            })
+        }
+        PackageManager::Zypper => {
+            // Format: "v | repo | name | current | available | arch"
+            let columns: Vec<&str> = line.split('|').map(str::trim).collect();
+            if columns.len() < 5 || columns[0] != "v" {
+                return None;
+            }
+            Some(PlannedChange {
+                name: columns[2].to_string(),
+                from_version: Some(columns[3].to_string()),
+                to_version: Some(columns[4].to_string()),
+            })
+        }
+        PackageManager::Apk => {
+            // Format: "curl-8.5.0-r0   < 8.9.0-r0"
+            let (installed, available) = line.split_once('<')?;
+            let mut pieces = installed.trim().rsplitn(3, '-');
+            let release = pieces.next()?;
+            let version = pieces.next()?;
+            let name = pieces.next()?;
+            Some(PlannedChange {
+                name: name.to_string(),
+                from_version: Some(format!("{}-{}", version, release)),
+                to_version: Some(available.trim().to_string()),
+            })
+        }
+        PackageManager::Flatpak => {
+            // Format: "application<TAB>version"
+            let mut columns = line.split('\t').map(str::trim);
+            let name = columns.next().filter(|n| n.contains('.'))?;
+            Some(PlannedChange {
+                name: name.to_string(),
+                from_version: None,
+                to_version: columns.next().filter(|v| !v.is_empty()).map(String::from),
+            })
+        }
+        PackageManager::Snap => {
+            // Format: "Name  Version  Rev  Size  Publisher  Notes"
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 2 || parts[0] == "Name" || line.starts_with("All snaps") {
+                return None;
+            }
+            Some(PlannedChange {
+                name: parts[0].to_string(),
+                from_version: None,
+                to_version: Some(parts[1].to_string()),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Snapshots kept in `~/.catdog/pkg-snapshots`; older ones are pruned
+const MAX_PACKAGE_SNAPSHOTS: usize = 10;
+
+/// The installed package list at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageSnapshot {
+    pub timestamp: String,
+    pub package_manager: String,
+    pub packages: Vec<PackageInfo>,
+}
+
+/// A package whose version differs between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionChange {
+    pub name: String,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+/// Packages added, removed or changed between two snapshots
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub added: Vec<PackageInfo>,
+    pub removed: Vec<PackageInfo>,
+    pub changed: Vec<VersionChange>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Where package snapshots are stored
+pub fn snapshot_dir() -> Result<PathBuf> {
+    Ok(state::state_dir()?.join("pkg-snapshots"))
+}
+
+/// Record the installed packages to `<dir>/<timestamp>.json`, pruning old snapshots
+pub fn save_snapshot(
+    pm: &PackageManager,
+    packages: Vec<PackageInfo>,
+    dir: &Path,
+) -> Result<PathBuf> {
+    state::ensure_private_dir(dir)?;
+
+    let now = chrono::Local::now();
+    let snapshot = PackageSnapshot {
+        timestamp: now.to_rfc3339(),
+        package_manager: pm.name().to_string(),
+        packages,
+    };
+
+    let path = dir.join(format!("{}.json", now.format("%Y%m%d_%H%M%S_%3f")));
+    state::write_atomic(&path, serde_json::to_string_pretty(&snapshot)?)
+        .with_context(|| format!("Failed to write snapshot {}", path.display()))?;
+
+    prune_snapshots(dir, MAX_PACKAGE_SNAPSHOTS)?;
+    Ok(path)
+}
+
+/// Snapshot files in `dir`, oldest first
+pub fn list_snapshots(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    // Names are timestamps, so name order is time order
+    snapshots.sort();
+
+    Ok(snapshots)
+}
+
+fn prune_snapshots(dir: &Path, keep: usize) -> Result<usize> {
+    let snapshots = list_snapshots(dir)?;
+    let excess = snapshots.len().saturating_sub(keep);
+
+    for path in &snapshots[..excess] {
+        debug!("Removing old package snapshot: {}", path.display());
+        fs::remove_file(path)
+            .with_context(|| format!("Failed to remove snapshot {}", path.display()))?;
+    }
+
+    Ok(excess)
+}
+
+/// Find a snapshot by path, file name or timestamp id (`20240101_120000_000`)
+pub fn resolve_snapshot(name: &str, dir: &Path) -> Result<PathBuf> {
+    let direct = PathBuf::from(name);
+    if direct.is_file() {
+        return Ok(direct);
+    }
+
+    for candidate in [dir.join(name), dir.join(format!("{}.json", name))] {
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+    }
+
+    anyhow::bail!(
+        "Snapshot '{}' not found (looked in {})",
+        name,
+        dir.display()
+    )
+}
+
+pub fn load_snapshot(path: &Path) -> Result<PackageSnapshot> {
+    let json = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read snapshot {}", path.display()))?;
+    serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse snapshot {}", path.display()))
+}
+
+/// Compare two snapshots by package name
+pub fn diff_snapshots(old: &PackageSnapshot, new: &PackageSnapshot) -> SnapshotDiff {
+    let old_by_name: BTreeMap<&str, &PackageInfo> =
+        old.packages.iter().map(|p| (p.name.as_str(), p)).collect();
+    let new_by_name: BTreeMap<&str, &PackageInfo> =
+        new.packages.iter().map(|p| (p.name.as_str(), p)).collect();
+
+    let mut diff = SnapshotDiff::default();
+
+    for (name, old_pkg) in &old_by_name {
+        match new_by_name.get(name) {
+            None => diff.removed.push((*old_pkg).clone()),
+            Some(new_pkg) if new_pkg.version != old_pkg.version => {
+                diff.changed.push(VersionChange {
+                    name: name.to_string(),
+                    old_version: old_pkg.version.clone(),
+                    new_version: new_pkg.version.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, new_pkg) in &new_by_name {
+        if !old_by_name.contains_key(name) {
+            diff.added.push((*new_pkg).clone());
+        }
+    }
+
+    diff
 }
 
 /// Search for packages
@@ -383,6 +1029,23 @@ pub fn search_packages(query: &str, pm: &PackageManager) -> Result<Vec<PackageIn
                 .context("Failed to search packages with apk")?;
             String::from_utf8_lossy(&cmd.stdout).to_string()
         }
+        PackageManager::Flatpak => {
+            let cmd = Command::new("flatpak")
+                .arg("search")
+                .arg(FLATPAK_COLUMNS)
+                .arg(query)
+                .output()
+                .context("Failed to search packages with flatpak")?;
+            String::from_utf8_lossy(&cmd.stdout).to_string()
+        }
+        PackageManager::Snap => {
+            let cmd = Command::new("snap")
+                .arg("find")
+                .arg(query)
+                .output()
+                .context("Failed to search packages with snap")?;
+            String::from_utf8_lossy(&cmd.stdout).to_string()
+        }
         PackageManager::Unknown => {
             anyhow::bail!("Unknown package manager - cannot search packages");
         }
@@ -439,6 +1102,22 @@ pub fn list_installed(pm: &PackageManager) -> Result<Vec<PackageInfo>> {
                 .context("Failed to list packages with apk")?;
             String::from_utf8_lossy(&cmd.stdout).to_string()
         }
+        PackageManager::Flatpak => {
+            let cmd = Command::new("flatpak")
+                .arg("list")
+                .arg("--app")
+                .arg(FLATPAK_COLUMNS)
+                .output()
+                .context("Failed to list packages with flatpak")?;
+            String::from_utf8_lossy(&cmd.stdout).to_string()
+        }
+        PackageManager::Snap => {
+            let cmd = Command::new("snap")
+                .arg("list")
+                .output()
+                .context("Failed to list packages with snap")?;
+            String::from_utf8_lossy(&cmd.stdout).to_string()
+        }
         PackageManager::Unknown => {
             anyhow::bail!("Unknown package manager - cannot list packages");
         }
@@ -489,12 +1168,27 @@ pub fn is_package_installed(package: &str, pm: &PackageManager) -> Result<bool>
             .output()
             .map(|o| o.status.success())
             .unwrap_or(false),
+        PackageManager::Flatpak => Command::new("flatpak")
+            .arg("info")
+            .arg(package)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false),
+        PackageManager::Snap => Command::new("snap")
+            .arg("list")
+            .arg(package)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false),
         PackageManager::Unknown => false,
     };
 
     Ok(result)
 }
 
+/// Tab-separated columns requested from `flatpak search` and `flatpak list`
+const FLATPAK_COLUMNS: &str = "--columns=application,version,description";
+
 /// Execute a command with proper output handling
 fn execute_command(cmd_parts: &[&str], dry_run: bool, verbose: bool) -> Result<()> {
     if cmd_parts.is_empty() {
@@ -574,11 +1268,25 @@ fn parse_search_results(output: &str, pm: &PackageManager) -> Result<Vec<Package
                     installed: false,
                 });
             }
+            PackageManager::Flatpak => {
+                if let Some(pkg) = parse_flatpak_line(line, false) {
+                    packages.push(pkg);
+                }
+            }
+            PackageManager::Snap => {
+                if let Some(pkg) = parse_snap_line(line, false) {
+                    packages.push(pkg);
+                }
+            }
             PackageManager::Pacman => {
                 // Format: "repo/package version"
                 let parts: Vec<&str> = line.split_whitespace().collect();
                 if parts.len() >= 2 {
-                    let name = parts[0].split('/').last().unwrap_or(parts[0]).to_string();
+                    let name = parts[0]
+                        .split('/')
+                        .next_back()
+                        .unwrap_or(parts[0])
+                        .to_string();
                     let version = parts[1].to_string();
                     packages.push(PackageInfo {
                         name,
@@ -652,6 +1360,16 @@ fn parse_installed_packages(output: &str, pm: &PackageManager) -> Result<Vec<Pac
                     });
                 }
             }
+            PackageManager::Flatpak => {
+                if let Some(pkg) = parse_flatpak_line(line, true) {
+                    packages.push(pkg);
+                }
+            }
+            PackageManager::Snap => {
+                if let Some(pkg) = parse_snap_line(line, true) {
+                    packages.push(pkg);
+                }
+            }
             _ => {
                 // Generic parsing
                 let parts: Vec<&str> = line.split_whitespace().collect();
@@ -669,3 +1387,366 @@ fn parse_installed_packages(output: &str, pm: &PackageManager) -> Result<Vec<Pac
 
     Ok(packages)
 }
+
+/// Parse a `flatpak search`/`flatpak list` line in `FLATPAK_COLUMNS` order
+///
+/// Columns are tab-separated; empty ones (apps without a version) are kept
+/// in place so the description doesn't shift.
+fn parse_flatpak_line(line: &str, installed: bool) -> Option<PackageInfo> {
+    let mut columns = line.split('\t').map(str::trim);
+    let name = columns
+        .next()
+        .filter(|n| !n.is_empty() && n.contains('.'))?;
+    let non_empty = |c: Option<&str>| c.filter(|c| !c.is_empty()).map(|c| c.to_string());
+
+    Some(PackageInfo {
+        name: name.to_string(),
+        version: non_empty(columns.next()),
+        description: non_empty(columns.next()),
+        installed,
+    })
+}
+
+/// Parse a `snap find` or `snap list` line, skipping the header
+///
+/// `snap find`: "Name  Version  Publisher  Notes  Summary..."
+/// `snap list`: "Name  Version  Rev  Tracking  Publisher  Notes"
+fn parse_snap_line(line: &str, installed: bool) -> Option<PackageInfo> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 2 || parts[0] == "Name" {
+        return None;
+    }
+
+    let description = if installed || parts.len() < 5 {
+        None
+    } else {
+        Some(parts[4..].join(" "))
+    };
+
+    Some(PackageInfo {
+        name: parts[0].to_string(),
+        version: Some(parts[1].to_string()),
+        description,
+        installed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_command_with_sudo() {
+        let packages = vec!["curl".to_string(), "git".to_string()];
+        let cmd = install_command(&packages, &PackageManager::Apt, true).unwrap();
+        assert_eq!(cmd, vec!["sudo", "apt-get", "install", "-y", "curl", "git"]);
+    }
+
+    #[test]
+    fn test_install_command_without_sudo() {
+        let packages = vec!["curl".to_string()];
+        let cmd = install_command(&packages, &PackageManager::Apt, false).unwrap();
+        assert_eq!(cmd, vec!["apt-get", "install", "-y", "curl"]);
+    }
+
+    #[test]
+    fn test_upgrade_command_sudo_prefix() {
+        let cmd = upgrade_command(&PackageManager::Dnf, true).unwrap();
+        assert_eq!(cmd, vec!["sudo", "dnf", "upgrade", "-y"]);
+
+        let cmd = upgrade_command(&PackageManager::Dnf, false).unwrap();
+        assert_eq!(cmd, vec!["dnf", "upgrade", "-y"]);
+    }
+
+    #[test]
+    fn test_brew_never_uses_sudo() {
+        let packages = vec!["wget".to_string()];
+        let cmd = remove_command(&packages, &PackageManager::Brew, true).unwrap();
+        assert_eq!(cmd, vec!["brew", "uninstall", "wget"]);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(PackageManager::from_name("apt"), Some(PackageManager::Apt));
+        assert_eq!(
+            PackageManager::from_name("apt-get"),
+            Some(PackageManager::Apt)
+        );
+        assert_eq!(
+            PackageManager::from_name("Flatpak"),
+            Some(PackageManager::Flatpak)
+        );
+        assert_eq!(
+            PackageManager::from_name("snap"),
+            Some(PackageManager::Snap)
+        );
+        assert_eq!(PackageManager::from_name("unknown"), None);
+        assert_eq!(PackageManager::from_name("npm"), None);
+
+        for pm in [
+            PackageManager::Apt,
+            PackageManager::Dnf,
+            PackageManager::Yum,
+            PackageManager::Pacman,
+            PackageManager::Zypper,
+            PackageManager::Brew,
+            PackageManager::Apk,
+            PackageManager::Flatpak,
+            PackageManager::Snap,
+        ] {
+            assert_eq!(PackageManager::from_name(pm.name()), Some(pm.clone()));
+        }
+        for name in BACKEND_NAMES {
+            assert!(PackageManager::from_name(name).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_select_unknown_backend() {
+        let err = select_package_manager(Some("npm")).unwrap_err();
+        assert!(err.to_string().contains("Unknown package manager 'npm'"));
+    }
+
+    #[test]
+    fn test_flatpak_and_snap_commands() {
+        let apps = vec!["org.gimp.GIMP".to_string()];
+        assert_eq!(
+            install_command(&apps, &PackageManager::Flatpak, true).unwrap(),
+            vec!["flatpak", "install", "-y", "org.gimp.GIMP"]
+        );
+        assert_eq!(
+            remove_command(&apps, &PackageManager::Flatpak, false).unwrap(),
+            vec!["flatpak", "uninstall", "-y", "org.gimp.GIMP"]
+        );
+
+        let snaps = vec!["firefox".to_string()];
+        assert_eq!(
+            install_command(&snaps, &PackageManager::Snap, true).unwrap(),
+            vec!["sudo", "snap", "install", "firefox"]
+        );
+        assert_eq!(
+            remove_command(&snaps, &PackageManager::Snap, true).unwrap(),
+            vec!["sudo", "snap", "remove", "firefox"]
+        );
+        assert_eq!(
+            upgrade_command(&PackageManager::Snap, true).unwrap(),
+            vec!["sudo", "snap", "refresh"]
+        );
+        assert_eq!(
+            upgrade_command(&PackageManager::Flatpak, true).unwrap(),
+            vec!["flatpak", "update", "-y"]
+        );
+    }
+
+    #[test]
+    fn test_parse_flatpak_output() {
+        let output = "org.gimp.GIMP\t2.10.38\tGNU Image Manipulation Program\n\
+                      com.example.NoVersion\t\tAn app without a version\n";
+        let packages = parse_search_results(output, &PackageManager::Flatpak).unwrap();
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "org.gimp.GIMP");
+        assert_eq!(packages[0].version.as_deref(), Some("2.10.38"));
+        assert_eq!(
+            packages[0].description.as_deref(),
+            Some("GNU Image Manipulation Program")
+        );
+        assert_eq!(packages[1].version, None);
+        assert_eq!(
+            packages[1].description.as_deref(),
+            Some("An app without a version")
+        );
+    }
+
+    #[test]
+    fn test_parse_snap_output() {
+        let find = "\
+Name     Version  Publisher     Notes  Summary
+firefox  131.0.3  mozilla**     -      Mozilla Firefox web browser
+";
+        let found = parse_search_results(find, &PackageManager::Snap).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "firefox");
+        assert_eq!(found[0].version.as_deref(), Some("131.0.3"));
+        assert_eq!(
+            found[0].description.as_deref(),
+            Some("Mozilla Firefox web browser")
+        );
+
+        let list = "\
+Name    Version        Rev    Tracking       Publisher   Notes
+core22  20240823       1621   latest/stable  canonical**  base
+";
+        let installed = parse_installed_packages(list, &PackageManager::Snap).unwrap();
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].name, "core22");
+        assert_eq!(installed[0].version.as_deref(), Some("20240823"));
+        assert!(installed[0].installed);
+    }
+
+    #[test]
+    fn test_parse_apt_upgradable() {
+        let output = "\
+Listing... Done
+curl/jammy-updates 7.81.0-1ubuntu1.15 amd64 [upgradable from: 7.81.0-1ubuntu1.14]
+tzdata/jammy-updates,jammy-security 2024a-0ubuntu0.22.04 all [upgradable from: 2023c-0ubuntu0.22.04]
+";
+        let packages = parse_upgradable(output, &PackageManager::Apt);
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(
+            packages[0],
+            UpgradablePackage {
+                name: "curl".to_string(),
+                current_version: Some("7.81.0-1ubuntu1.14".to_string()),
+                available_version: Some("7.81.0-1ubuntu1.15".to_string()),
+            }
+        );
+        assert_eq!(packages[1].name, "tzdata");
+    }
+
+    #[test]
+    fn test_parse_zypper_and_apk_upgradable() {
+        let zypper = "\
+S | Repository | Name | Current Version | Available Version | Arch
+--+------------+------+-----------------+-------------------+-------
+v | Main       | vim  | 9.0.2103-1.1    | 9.1.0330-1.1      | x86_64
+";
+        let packages = parse_upgradable(zypper, &PackageManager::Zypper);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "vim");
+        assert_eq!(packages[0].current_version.as_deref(), Some("9.0.2103-1.1"));
+
+        let apk = "\
+Installed:                                Available:
+busybox-1.36.1-r15                      < 1.36.1-r19
+";
+        let packages = parse_upgradable(apk, &PackageManager::Apk);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "busybox");
+        assert_eq!(packages[0].current_version.as_deref(), Some("1.36.1-r15"));
+        assert_eq!(packages[0].available_version.as_deref(), Some("1.36.1-r19"));
+    }
+
+    #[test]
+    fn test_upgradable_exit_codes() {
+        // dnf check-update exits 100 when updates are available
+        assert!(upgradable_exit_ok(
+            &PackageManager::Dnf,
+            Some(100),
+            "vim.x86_64 2:9.1 updates"
+        ));
+        assert!(upgradable_exit_ok(&PackageManager::Dnf, Some(0), ""));
+        assert!(!upgradable_exit_ok(&PackageManager::Dnf, Some(1), ""));
+        // pacman -Qu exits 1 when there is nothing to upgrade
+        assert!(upgradable_exit_ok(&PackageManager::Pacman, Some(1), ""));
+        assert!(!upgradable_exit_ok(&PackageManager::Apt, Some(100), ""));
+    }
+
+    fn pkg(name: &str, version: &str) -> PackageInfo {
+        PackageInfo {
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            description: None,
+            installed: true,
+        }
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let snapshot = |packages| PackageSnapshot {
+            timestamp: String::new(),
+            package_manager: "apt".to_string(),
+            packages,
+        };
+        let before = snapshot(vec![
+            pkg("curl", "7.81.0-1"),
+            pkg("libssl3", "3.0.2-0ubuntu1.15"),
+            pkg("oldlib", "1.0"),
+        ]);
+        let after = snapshot(vec![
+            pkg("curl", "7.81.0-1"),
+            pkg("libssl3", "3.0.2-0ubuntu1.18"),
+            pkg("newdep", "2.0"),
+        ]);
+
+        let diff = diff_snapshots(&before, &after);
+        assert_eq!(diff.added, vec![pkg("newdep", "2.0")]);
+        assert_eq!(diff.removed, vec![pkg("oldlib", "1.0")]);
+        assert_eq!(
+            diff.changed,
+            vec![VersionChange {
+                name: "libssl3".to_string(),
+                old_version: Some("3.0.2-0ubuntu1.15".to_string()),
+                new_version: Some("3.0.2-0ubuntu1.18".to_string()),
+            }]
+        );
+        assert!(diff_snapshots(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_save_resolve_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..3 {
+            fs::write(
+                dir.path().join(format!("20240101_00000{}_000.json", i)),
+                "{}",
+            )
+            .unwrap();
+        }
+
+        let path =
+            save_snapshot(&PackageManager::Apt, vec![pkg("curl", "8.0")], dir.path()).unwrap();
+        let id = path.file_stem().unwrap().to_string_lossy().to_string();
+        let resolved = resolve_snapshot(&id, dir.path()).unwrap();
+        assert_eq!(
+            load_snapshot(&resolved).unwrap().packages,
+            vec![pkg("curl", "8.0")]
+        );
+        assert!(resolve_snapshot("19990101_000000_000", dir.path()).is_err());
+
+        // Oldest go first
+        assert_eq!(prune_snapshots(dir.path(), 2).unwrap(), 2);
+        let remaining = list_snapshots(dir.path()).unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[1], path);
+    }
+
+    #[test]
+    fn test_parse_apt_simulation() {
+        let output = "\
+Reading package lists... Done
+Building dependency tree... Done
+Calculating upgrade... Done
+The following packages have been kept back:
+  linux-generic linux-headers-generic
+The following packages will be upgraded:
+  curl libcurl4
+2 upgraded, 1 newly installed, 1 to remove and 2 not upgraded.
+Remv oldlib [1.0-1]
+Inst curl [7.81.0-1ubuntu1.14] (7.81.0-1ubuntu1.15 Ubuntu:22.04/jammy-updates [amd64])
+Inst libcurl4 [7.81.0-1ubuntu1.14] (7.81.0-1ubuntu1.15 Ubuntu:22.04/jammy-updates [amd64]) []
+Inst newdep (2.0-1 Ubuntu:22.04/jammy [amd64])
+Conf curl (7.81.0-1ubuntu1.15 Ubuntu:22.04/jammy-updates [amd64])
+";
+        let plan = parse_apt_simulation(output);
+
+        assert_eq!(
+            plan.held_back,
+            vec!["linux-generic", "linux-headers-generic"]
+        );
+        assert_eq!(plan.upgraded.len(), 2);
+        assert_eq!(
+            plan.upgraded[0],
+            PlannedChange {
+                name: "curl".to_string(),
+                from_version: Some("7.81.0-1ubuntu1.14".to_string()),
+                to_version: Some("7.81.0-1ubuntu1.15".to_string()),
+            }
+        );
+        assert_eq!(plan.newly_installed.len(), 1);
+        assert_eq!(plan.newly_installed[0].name, "newdep");
+        assert_eq!(plan.removed.len(), 1);
+        assert_eq!(plan.removed[0].from_version.as_deref(), Some("1.0-1"));
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use colored::*;
+use log::debug;
+use std::process::Command;
+
+use crate::error::{exit_codes, UserError};
+
+/// Check whether the current process is running as root
+pub fn is_root() -> bool {
+    #[cfg(unix)]
+    {
+        // SAFETY: geteuid has no preconditions and cannot fail
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+/// The uid and gid of the user who ran catdog, looking through sudo
+///
+/// Under sudo the real ids are root's, so `$SUDO_UID`/`$SUDO_GID` win when set.
+pub fn current_uid_gid() -> (u32, u32) {
+    #[cfg(unix)]
+    // SAFETY: getuid and getgid have no preconditions and cannot fail
+    let real = unsafe { (libc::getuid(), libc::getgid()) };
+
+    #[cfg(not(unix))]
+    let real = (1000, 1000);
+
+    invoking_ids(
+        std::env::var("SUDO_UID").ok().as_deref(),
+        std::env::var("SUDO_GID").ok().as_deref(),
+        real,
+    )
+}
+
+/// Prefer ids passed down by sudo, falling back to the real ids of this process
+pub fn invoking_ids(
+    sudo_uid: Option<&str>,
+    sudo_gid: Option<&str>,
+    real: (u32, u32),
+) -> (u32, u32) {
+    let parse = |value: Option<&str>| value.and_then(|v| v.trim().parse().ok());
+    (
+        parse(sudo_uid).unwrap_or(real.0),
+        parse(sudo_gid).unwrap_or(real.1),
+    )
+}
+
+/// Check whether sudo can be used without prompting for a password
+pub fn sudo_available() -> bool {
+    Command::new("sudo")
+        .args(["-n", "true"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Decide whether a command line should be prefixed with sudo
+pub fn should_use_sudo(requires_sudo: bool, no_sudo: bool, is_root: bool) -> bool {
+    requires_sudo && !no_sudo && !is_root
+}
+
+/// Pre-flight check for privileged operations
+///
+/// Returns whether the command should be run through sudo. When sudo is
+/// needed, a notice is printed; if sudo would prompt or is missing, a
+/// `UserError` is returned instead of letting the command hang mid-operation.
+pub fn resolve_sudo(requires_sudo: bool, no_sudo: bool, dry_run: bool) -> Result<bool> {
+    let use_sudo = should_use_sudo(requires_sudo, no_sudo, is_root());
+    debug!(
+        "sudo pre-flight: requires={} no_sudo={} use_sudo={}",
+        requires_sudo, no_sudo, use_sudo
+    );
+
+    if !use_sudo {
+        return Ok(false);
+    }
+
+    println!(
+        "{} This operation needs elevated privileges, commands will run through {}",
+        "🔐".bold(),
+        "sudo".bright_white()
+    );
+
+    if !dry_run && !sudo_available() {
+        return Err(UserError::new("sudo is not available without a password prompt")
+            .with_suggestion(
+                "Re-run with privileges (e.g. sudo catdog <command>), or pass --no-sudo in a rootless setup",
+            )
+            .with_exit_code(exit_codes::PERMISSION_DENIED)
+            .into());
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_use_sudo() {
+        assert!(should_use_sudo(true, false, false));
+        assert!(!should_use_sudo(true, true, false));
+        assert!(!should_use_sudo(true, false, true));
+        assert!(!should_use_sudo(false, false, false));
+    }
+
+    #[test]
+    fn test_invoking_ids() {
+        assert_eq!(
+            invoking_ids(Some("1001"), Some("1002"), (0, 0)),
+            (1001, 1002)
+        );
+        assert_eq!(invoking_ids(None, None, (501, 20)), (501, 20));
+        assert_eq!(invoking_ids(Some("oops"), None, (501, 20)), (501, 20));
+    }
+
+    #[test]
+    fn test_resolve_sudo_not_required() {
+        assert!(!resolve_sudo(false, false, false).unwrap());
+        assert!(!resolve_sudo(true, true, false).unwrap());
+    }
+}
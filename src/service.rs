@@ -2,6 +2,8 @@ use anyhow::{Context, Result};
 use colored::*;
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -25,10 +27,7 @@ impl ServiceManager {
     }
 
     pub fn requires_sudo(&self) -> bool {
-        match self {
-            ServiceManager::Unknown => false,
-            _ => true,
-        }
+        !matches!(self, ServiceManager::Unknown)
     }
 }
 
@@ -95,7 +94,7 @@ pub fn detect_service_manager() -> Result<ServiceManager> {
 }
 
 /// Check if a command is available in PATH
-fn is_command_available(cmd: &str) -> bool {
+pub fn is_command_available(cmd: &str) -> bool {
     Command::new("which")
         .arg(cmd)
         .output()
@@ -103,26 +102,66 @@ fn is_command_available(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Reject `--user` for service managers without per-user units
+fn check_user_scope(sm: &ServiceManager, user: bool) -> Result<()> {
+    if user && *sm != ServiceManager::Systemd {
+        anyhow::bail!("--user is only supported with systemd, not {}", sm.name());
+    }
+    Ok(())
+}
+
+/// `systemctl` for system units (behind sudo if requested), or `systemctl --user`
+/// for the caller's own units, which never need root
+fn systemctl_prefix(use_sudo: bool, user: bool) -> Vec<&'static str> {
+    if user {
+        vec!["systemctl", "--user"]
+    } else if use_sudo {
+        vec!["sudo", "systemctl"]
+    } else {
+        vec!["systemctl"]
+    }
+}
+
+/// A `systemctl` query, scoped to user units when requested
+fn systemctl(user: bool) -> Command {
+    let mut command = Command::new("systemctl");
+    if user {
+        command.arg("--user");
+    }
+    command
+}
+
 /// Start a service
 pub fn start_service(
     service: &str,
     sm: &ServiceManager,
+    use_sudo: bool,
+    user: bool,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
+    let cmd_parts = start_command(service, sm, use_sudo, user)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the start command line for a service manager
+pub fn start_command<'a>(
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    check_user_scope(sm, user)?;
     let mut cmd_parts = Vec::new();
 
     match sm {
         ServiceManager::Systemd => {
-            if sm.requires_sudo() {
-                cmd_parts.push("sudo");
-            }
-            cmd_parts.push("systemctl");
+            cmd_parts.extend(systemctl_prefix(use_sudo, user));
             cmd_parts.push("start");
             cmd_parts.push(service);
         }
         ServiceManager::Launchd => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("launchctl");
@@ -130,7 +169,7 @@ pub fn start_service(
             cmd_parts.push(service);
         }
         ServiceManager::OpenRC => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("rc-service");
@@ -138,7 +177,7 @@ pub fn start_service(
             cmd_parts.push("start");
         }
         ServiceManager::InitD => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("/etc/init.d");
@@ -150,29 +189,40 @@ pub fn start_service(
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
 }
 
 /// Stop a service
 pub fn stop_service(
     service: &str,
     sm: &ServiceManager,
+    use_sudo: bool,
+    user: bool,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
+    let cmd_parts = stop_command(service, sm, use_sudo, user)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the stop command line for a service manager
+pub fn stop_command<'a>(
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    check_user_scope(sm, user)?;
     let mut cmd_parts = Vec::new();
 
     match sm {
         ServiceManager::Systemd => {
-            if sm.requires_sudo() {
-                cmd_parts.push("sudo");
-            }
-            cmd_parts.push("systemctl");
+            cmd_parts.extend(systemctl_prefix(use_sudo, user));
             cmd_parts.push("stop");
             cmd_parts.push(service);
         }
         ServiceManager::Launchd => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("launchctl");
@@ -180,7 +230,7 @@ pub fn stop_service(
             cmd_parts.push(service);
         }
         ServiceManager::OpenRC => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("rc-service");
@@ -188,7 +238,7 @@ pub fn stop_service(
             cmd_parts.push("stop");
         }
         ServiceManager::InitD => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("/etc/init.d");
@@ -200,29 +250,40 @@ pub fn stop_service(
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
 }
 
 /// Restart a service
 pub fn restart_service(
     service: &str,
     sm: &ServiceManager,
+    use_sudo: bool,
+    user: bool,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
+    let cmd_parts = restart_command(service, sm, use_sudo, user)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the restart command line for a service manager
+pub fn restart_command<'a>(
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    check_user_scope(sm, user)?;
     let mut cmd_parts = Vec::new();
 
     match sm {
         ServiceManager::Systemd => {
-            if sm.requires_sudo() {
-                cmd_parts.push("sudo");
-            }
-            cmd_parts.push("systemctl");
+            cmd_parts.extend(systemctl_prefix(use_sudo, user));
             cmd_parts.push("restart");
             cmd_parts.push(service);
         }
         ServiceManager::Launchd => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("launchctl");
@@ -231,7 +292,7 @@ pub fn restart_service(
             cmd_parts.push(service);
         }
         ServiceManager::OpenRC => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("rc-service");
@@ -239,7 +300,7 @@ pub fn restart_service(
             cmd_parts.push("restart");
         }
         ServiceManager::InitD => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("/etc/init.d");
@@ -251,29 +312,40 @@ pub fn restart_service(
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
 }
 
 /// Enable a service to start on boot
 pub fn enable_service(
     service: &str,
     sm: &ServiceManager,
+    use_sudo: bool,
+    user: bool,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
+    let cmd_parts = enable_command(service, sm, use_sudo, user)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the enable command line for a service manager
+pub fn enable_command<'a>(
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    check_user_scope(sm, user)?;
     let mut cmd_parts = Vec::new();
 
     match sm {
         ServiceManager::Systemd => {
-            if sm.requires_sudo() {
-                cmd_parts.push("sudo");
-            }
-            cmd_parts.push("systemctl");
+            cmd_parts.extend(systemctl_prefix(use_sudo, user));
             cmd_parts.push("enable");
             cmd_parts.push(service);
         }
         ServiceManager::Launchd => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("launchctl");
@@ -281,7 +353,7 @@ pub fn enable_service(
             cmd_parts.push(service);
         }
         ServiceManager::OpenRC => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("rc-update");
@@ -290,7 +362,7 @@ pub fn enable_service(
             cmd_parts.push("default");
         }
         ServiceManager::InitD => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("update-rc.d");
@@ -302,29 +374,40 @@ pub fn enable_service(
         }
     }
 
-    execute_command(&cmd_parts, dry_run, verbose)
+    Ok(cmd_parts)
 }
 
 /// Disable a service from starting on boot
 pub fn disable_service(
     service: &str,
     sm: &ServiceManager,
+    use_sudo: bool,
+    user: bool,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
+    let cmd_parts = disable_command(service, sm, use_sudo, user)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the disable command line for a service manager
+pub fn disable_command<'a>(
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    check_user_scope(sm, user)?;
     let mut cmd_parts = Vec::new();
 
     match sm {
         ServiceManager::Systemd => {
-            if sm.requires_sudo() {
-                cmd_parts.push("sudo");
-            }
-            cmd_parts.push("systemctl");
+            cmd_parts.extend(systemctl_prefix(use_sudo, user));
             cmd_parts.push("disable");
             cmd_parts.push(service);
         }
         ServiceManager::Launchd => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("launchctl");
@@ -332,7 +415,7 @@ pub fn disable_service(
             cmd_parts.push(service);
         }
         ServiceManager::OpenRC => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("rc-update");
@@ -340,7 +423,7 @@ pub fn disable_service(
             cmd_parts.push(service);
         }
         ServiceManager::InitD => {
-            if sm.requires_sudo() {
+            if use_sudo {
                 cmd_parts.push("sudo");
             }
             cmd_parts.push("update-rc.d");
@@ -352,13 +435,100 @@ pub fn disable_service(
         }
     }
 
+    Ok(cmd_parts)
+}
+
+/// Units whose masking leaves the machine unbootable or unreachable
+const CRITICAL_UNITS: &[&str] = &[
+    "dbus",
+    "dbus-broker",
+    "systemd-journald",
+    "systemd-logind",
+    "systemd-udevd",
+    "systemd-networkd",
+    "NetworkManager",
+    "ssh",
+    "sshd",
+];
+
+/// Whether masking this unit needs `--force`
+pub fn is_critical_unit(service: &str) -> bool {
+    CRITICAL_UNITS.contains(&service.trim_end_matches(".service"))
+}
+
+/// Whether the service manager can mask units (only systemd can)
+pub fn supports_masking(sm: &ServiceManager) -> bool {
+    *sm == ServiceManager::Systemd
+}
+
+/// Mask a service so it can't be started, even as a dependency
+pub fn mask_service(
+    service: &str,
+    sm: &ServiceManager,
+    use_sudo: bool,
+    user: bool,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<()> {
+    let cmd_parts = mask_command(service, sm, use_sudo, user)?;
+    execute_command(&cmd_parts, dry_run, verbose)
+}
+
+/// Build the mask command line; only systemd supports masking
+pub fn mask_command<'a>(
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    masking_command("mask", service, sm, use_sudo, user)
+}
+
+/// Undo `mask_service`
+pub fn unmask_service(
+    service: &str,
+    sm: &ServiceManager,
+    use_sudo: bool,
+    user: bool,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<()> {
+    let cmd_parts = unmask_command(service, sm, use_sudo, user)?;
     execute_command(&cmd_parts, dry_run, verbose)
 }
 
+/// Build the unmask command line; only systemd supports masking
+pub fn unmask_command<'a>(
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    masking_command("unmask", service, sm, use_sudo, user)
+}
+
+fn masking_command<'a>(
+    action: &'a str,
+    service: &'a str,
+    sm: &'a ServiceManager,
+    use_sudo: bool,
+    user: bool,
+) -> Result<Vec<&'a str>> {
+    if !supports_masking(sm) {
+        anyhow::bail!("{} does not support masking services", sm.name());
+    }
+
+    let mut cmd_parts = systemctl_prefix(use_sudo, user);
+    cmd_parts.push(action);
+    cmd_parts.push(service);
+    Ok(cmd_parts)
+}
+
 /// Get service status
-pub fn get_service_status(service: &str, sm: &ServiceManager) -> Result<ServiceInfo> {
+pub fn get_service_status(service: &str, sm: &ServiceManager, user: bool) -> Result<ServiceInfo> {
+    check_user_scope(sm, user)?;
     match sm {
-        ServiceManager::Systemd => get_systemd_status(service),
+        ServiceManager::Systemd => get_systemd_status(service, user),
         ServiceManager::Launchd => get_launchd_status(service),
         ServiceManager::OpenRC => get_openrc_status(service),
         ServiceManager::InitD => get_initd_status(service),
@@ -372,52 +542,64 @@ pub fn get_service_status(service: &str, sm: &ServiceManager) -> Result<ServiceI
     }
 }
 
-fn get_systemd_status(service: &str) -> Result<ServiceInfo> {
-    let output = Command::new("systemctl")
-        .arg("status")
+fn get_systemd_status(service: &str, user: bool) -> Result<ServiceInfo> {
+    let output = systemctl(user)
+        .arg("show")
         .arg(service)
+        .arg("--property=ActiveState,SubState,MainPID,UnitFileState,Description")
         .output()
         .context("Failed to get service status")?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        anyhow::bail!(
+            "systemctl show {} failed: {}",
+            service,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
 
-    // Parse status
-    let status = if stdout.contains("Active: active (running)") {
-        ServiceStatus::Running
-    } else if stdout.contains("Active: inactive") || stdout.contains("Active: dead") {
-        ServiceStatus::Stopped
-    } else if stdout.contains("Active: failed") {
-        ServiceStatus::Failed
-    } else {
-        ServiceStatus::Unknown
-    };
+    Ok(parse_systemd_show(
+        service,
+        &String::from_utf8_lossy(&output.stdout),
+    ))
+}
 
-    // Parse PID
-    let pid = stdout
+/// Parse the `key=value` lines of `systemctl show --property=...`
+///
+/// Units that are on their way up (`activating`, `reloading`) count as running, and
+/// so do oneshot services that finished successfully (`active` / `exited`).
+fn parse_systemd_show(service: &str, output: &str) -> ServiceInfo {
+    let properties: HashMap<&str, &str> = output
         .lines()
-        .find(|line| line.contains("Main PID:"))
-        .and_then(|line| {
-            line.split_whitespace()
-                .nth(2)
-                .and_then(|s| s.parse::<u32>().ok())
-        });
-
-    // Check if enabled
-    let enabled_output = Command::new("systemctl")
-        .arg("is-enabled")
-        .arg(service)
-        .output()
-        .ok();
-
-    let enabled = enabled_output.map(|o| String::from_utf8_lossy(&o.stdout).trim() == "enabled");
+        .filter_map(|line| line.split_once('='))
+        .collect();
+
+    let sub_state = properties.get("SubState").copied().unwrap_or("");
+    let status = match properties.get("ActiveState").copied() {
+        // Crash-looping units sit in activating/auto-restart between attempts
+        Some("activating") if sub_state == "auto-restart" => ServiceStatus::Failed,
+        Some("active") | Some("activating") | Some("reloading") => ServiceStatus::Running,
+        Some("inactive") | Some("deactivating") => ServiceStatus::Stopped,
+        Some("failed") => ServiceStatus::Failed,
+        _ => ServiceStatus::Unknown,
+    };
 
-    Ok(ServiceInfo {
+    ServiceInfo {
         name: service.to_string(),
         status,
-        enabled,
-        pid,
-        description: None,
-    })
+        enabled: properties
+            .get("UnitFileState")
+            .and_then(|state| unit_file_enabled(state)),
+        // systemd reports 0 when there is no main process
+        pid: properties
+            .get("MainPID")
+            .and_then(|pid| pid.parse::<u32>().ok())
+            .filter(|pid| *pid != 0),
+        description: properties
+            .get("Description")
+            .filter(|d| !d.is_empty())
+            .map(|d| d.to_string()),
+    }
 }
 
 fn get_launchd_status(service: &str) -> Result<ServiceInfo> {
@@ -493,9 +675,10 @@ fn get_initd_status(service: &str) -> Result<ServiceInfo> {
 }
 
 /// List all services
-pub fn list_services(sm: &ServiceManager) -> Result<Vec<ServiceInfo>> {
+pub fn list_services(sm: &ServiceManager, user: bool) -> Result<Vec<ServiceInfo>> {
+    check_user_scope(sm, user)?;
     match sm {
-        ServiceManager::Systemd => list_systemd_services(),
+        ServiceManager::Systemd => list_systemd_services(user),
         ServiceManager::Launchd => list_launchd_services(),
         ServiceManager::OpenRC => list_openrc_services(),
         ServiceManager::InitD => list_initd_services(),
@@ -503,41 +686,173 @@ pub fn list_services(sm: &ServiceManager) -> Result<Vec<ServiceInfo>> {
     }
 }
 
-fn list_systemd_services() -> Result<Vec<ServiceInfo>> {
-    let output = Command::new("systemctl")
+fn list_systemd_services(user: bool) -> Result<Vec<ServiceInfo>> {
+    let output = systemctl(user)
         .arg("list-units")
         .arg("--type=service")
         .arg("--all")
         .arg("--no-pager")
+        .arg("--plain")
+        .arg("--no-legend")
         .output()
         .context("Failed to list services")?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut services = parse_systemd_units(&stdout);
+
+    // Enablement comes from the unit files; without it the list is still useful
+    let unit_files = systemctl(user)
+        .arg("list-unit-files")
+        .arg("--type=service")
+        .arg("--no-pager")
+        .arg("--plain")
+        .arg("--no-legend")
+        .output();
+    match unit_files {
+        Ok(output) => {
+            let states = parse_unit_files(&String::from_utf8_lossy(&output.stdout));
+            for service in &mut services {
+                service.enabled = states.get(&service.name).and_then(|s| unit_file_enabled(s));
+            }
+        }
+        Err(e) => debug!("Could not list unit files: {}", e),
+    }
+
+    Ok(services)
+}
+
+/// Parse `systemctl list-units --plain --no-legend` output
+///
+/// Columns are UNIT LOAD ACTIVE SUB DESCRIPTION.
+fn parse_systemd_units(output: &str) -> Vec<ServiceInfo> {
     let mut services = Vec::new();
 
-    for line in stdout.lines().skip(1) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
+    for line in output.lines() {
+        let parts: Vec<&str> = line
+            .split_whitespace()
+            .skip_while(|p| *p == "●" || *p == "*")
+            .collect();
         if parts.len() >= 4 {
             let name = parts[0].trim_end_matches(".service").to_string();
-            let status = if parts[2] == "running" {
-                ServiceStatus::Running
-            } else if parts[2] == "failed" {
-                ServiceStatus::Failed
-            } else {
-                ServiceStatus::Stopped
+            let status = match parts[2] {
+                "active" => ServiceStatus::Running,
+                "failed" => ServiceStatus::Failed,
+                _ => ServiceStatus::Stopped,
             };
+            let description = parts[4..].join(" ");
 
             services.push(ServiceInfo {
                 name,
                 status,
                 enabled: None,
                 pid: None,
-                description: None,
+                description: (!description.is_empty()).then_some(description),
             });
         }
     }
 
-    Ok(services)
+    services
+}
+
+/// Parse `systemctl list-unit-files --plain --no-legend` output into unit states
+///
+/// Columns are UNIT FILE STATE [VENDOR PRESET]; names lose their `.service` suffix.
+fn parse_unit_files(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let unit = parts.next()?;
+            let state = parts.next()?;
+            Some((
+                unit.trim_end_matches(".service").to_string(),
+                state.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Whether a unit file state means the service starts at boot
+///
+/// `static`, `indirect` and similar states depend on other units, so they stay unknown.
+fn unit_file_enabled(state: &str) -> Option<bool> {
+    match state {
+        "enabled" | "enabled-runtime" => Some(true),
+        "disabled" | "masked" | "masked-runtime" => Some(false),
+        _ => None,
+    }
+}
+
+/// List services that are enabled to start at boot
+///
+/// Only systemd exposes this cheaply; other managers return an empty list.
+pub fn list_enabled_services(sm: &ServiceManager) -> Result<Vec<String>> {
+    if *sm != ServiceManager::Systemd {
+        return Ok(Vec::new());
+    }
+
+    let output = Command::new("systemctl")
+        .arg("list-unit-files")
+        .arg("--type=service")
+        .arg("--state=enabled")
+        .arg("--no-pager")
+        .arg("--plain")
+        .arg("--no-legend")
+        .output()
+        .context("Failed to list enabled services")?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        // Template units like getty@.service are never running themselves
+        .filter(|unit| !unit.contains('@'))
+        .map(|unit| unit.trim_end_matches(".service").to_string())
+        .collect())
+}
+
+/// Of the given systemd services, those that are expected to sit idle
+///
+/// Oneshot services exit once they've run, and services started by a timer
+/// or socket only run on demand, so neither should be running just because
+/// it's enabled.
+pub fn idle_by_design(services: &[String]) -> Result<HashSet<String>> {
+    if services.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let output = Command::new("systemctl")
+        .arg("show")
+        .arg("--property=Id,Type,TriggeredBy")
+        .args(services.iter().map(|name| format!("{}.service", name)))
+        .output()
+        .context("Failed to read service properties")?;
+
+    Ok(parse_idle_units(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `systemctl show --property=Id,Type,TriggeredBy` output, one blank-line
+/// separated block per unit, into the names of idle-by-design services
+fn parse_idle_units(output: &str) -> HashSet<String> {
+    output
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut id = None;
+            let mut idle = false;
+            for line in block.lines() {
+                match line.split_once('=') {
+                    Some(("Id", unit)) => id = Some(unit.trim_end_matches(".service")),
+                    Some(("Type", "oneshot")) => idle = true,
+                    Some(("TriggeredBy", units)) => {
+                        idle |= units
+                            .split_whitespace()
+                            .any(|unit| unit.ends_with(".timer") || unit.ends_with(".socket"))
+                    }
+                    _ => {}
+                }
+            }
+            id.filter(|_| idle).map(String::from)
+        })
+        .collect()
 }
 
 fn list_launchd_services() -> Result<Vec<ServiceInfo>> {
@@ -574,13 +889,22 @@ fn list_openrc_services() -> Result<Vec<ServiceInfo>> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let mut services = Vec::new();
 
+    // `rc-update show -v` lists every service with the runlevels it's added to
+    let runlevels = match Command::new("rc-update").arg("show").arg("-v").output() {
+        Ok(output) => parse_rc_update(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            debug!("Could not run rc-update: {}", e);
+            HashMap::new()
+        }
+    };
+
     for line in stdout.lines() {
         let name = line.trim().to_string();
         if !name.is_empty() {
             services.push(ServiceInfo {
+                enabled: runlevels.get(&name).copied(),
                 name,
                 status: ServiceStatus::Unknown,
-                enabled: None,
                 pid: None,
                 description: None,
             });
@@ -590,29 +914,164 @@ fn list_openrc_services() -> Result<Vec<ServiceInfo>> {
     Ok(services)
 }
 
+/// Parse `rc-update show -v` output, e.g. `sshd | boot default`
+///
+/// A service is enabled when it belongs to at least one runlevel.
+fn parse_rc_update(output: &str) -> HashMap<String, bool> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, runlevels) = line.split_once('|')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            Some((name.to_string(), !runlevels.trim().is_empty()))
+        })
+        .collect()
+}
+
 fn list_initd_services() -> Result<Vec<ServiceInfo>> {
     let mut services = Vec::new();
     let entries = std::fs::read_dir("/etc/init.d").context("Failed to read /etc/init.d")?;
 
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let name = entry.file_name().to_string_lossy().to_string();
-            // Skip common non-service files
-            if name != "README" && name != "." && name != ".." {
-                services.push(ServiceInfo {
-                    name,
-                    status: ServiceStatus::Unknown,
-                    enabled: None,
-                    pid: None,
-                    description: None,
-                });
-            }
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Skip common non-service files
+        if name != "README" && name != "." && name != ".." {
+            services.push(ServiceInfo {
+                enabled: Some(initd_enabled(&name)),
+                name,
+                status: ServiceStatus::Unknown,
+                pid: None,
+                description: None,
+            });
         }
     }
 
     Ok(services)
 }
 
+/// An init.d service is enabled when a multi-user runlevel has a start link for it
+fn initd_enabled(service: &str) -> bool {
+    ["/etc/rc2.d", "/etc/rc3.d", "/etc/rc5.d"]
+        .iter()
+        .any(|dir| {
+            std::fs::read_dir(dir)
+                .map(|entries| {
+                    entries.flatten().any(|entry| {
+                        let link = entry.file_name().to_string_lossy().to_string();
+                        link.starts_with('S') && link.get(3..) == Some(service)
+                    })
+                })
+                .unwrap_or(false)
+        })
+}
+
+/// Default number of log lines for `service logs`
+pub const DEFAULT_LOG_LINES: usize = 50;
+
+/// Where OpenRC and init.d services conventionally write their logs
+fn service_log_file(service: &str) -> PathBuf {
+    Path::new("/var/log").join(format!("{}.log", service))
+}
+
+/// Build the command that prints (or follows) a service's logs
+///
+/// launchd's `log show` has no line limit, so it covers the last hour instead.
+pub fn logs_command(
+    service: &str,
+    sm: &ServiceManager,
+    lines: usize,
+    follow: bool,
+    user: bool,
+) -> Result<Vec<String>> {
+    check_user_scope(sm, user)?;
+    let mut cmd_parts: Vec<String> = Vec::new();
+
+    match sm {
+        ServiceManager::Systemd => {
+            cmd_parts.push("journalctl".to_string());
+            if user {
+                cmd_parts.push("--user".to_string());
+            }
+            cmd_parts.extend([
+                "-u".to_string(),
+                service.to_string(),
+                "-n".to_string(),
+                lines.to_string(),
+                "--no-pager".to_string(),
+            ]);
+            if follow {
+                cmd_parts.push("--follow".to_string());
+            }
+        }
+        ServiceManager::Launchd => {
+            cmd_parts.push("log".to_string());
+            if follow {
+                cmd_parts.push("stream".to_string());
+            } else {
+                cmd_parts.extend(["show".to_string(), "--last".to_string(), "1h".to_string()]);
+            }
+            cmd_parts.extend([
+                "--style".to_string(),
+                "compact".to_string(),
+                "--predicate".to_string(),
+                format!("process == \"{}\"", service),
+            ]);
+        }
+        ServiceManager::OpenRC | ServiceManager::InitD => {
+            let log_file = service_log_file(service);
+            if !log_file.is_file() {
+                anyhow::bail!(
+                    "No log file for {} at {} - logs are not supported for {} services without one",
+                    service,
+                    log_file.display(),
+                    sm.name()
+                );
+            }
+            cmd_parts.extend(["tail".to_string(), "-n".to_string(), lines.to_string()]);
+            if follow {
+                cmd_parts.push("-f".to_string());
+            }
+            cmd_parts.push(log_file.to_string_lossy().to_string());
+        }
+        ServiceManager::Unknown => {
+            anyhow::bail!("Logs are not supported on this service manager");
+        }
+    }
+
+    Ok(cmd_parts)
+}
+
+/// Show a service's logs, streaming straight to the terminal
+pub fn show_logs(
+    service: &str,
+    sm: &ServiceManager,
+    lines: usize,
+    follow: bool,
+    user: bool,
+    verbose: bool,
+) -> Result<()> {
+    let cmd_parts = logs_command(service, sm, lines, follow, user)?;
+    let cmd_str = cmd_parts.join(" ");
+
+    if verbose {
+        println!("{} {}", "Executing:".cyan(), cmd_str.bright_white());
+    }
+
+    let status = Command::new(&cmd_parts[0])
+        .args(&cmd_parts[1..])
+        .status()
+        .with_context(|| format!("Failed to execute: {}", cmd_str))?;
+
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", cmd_parts[0], status);
+    }
+
+    Ok(())
+}
+
 /// Execute a command with proper output handling
 fn execute_command(cmd_parts: &[&str], dry_run: bool, verbose: bool) -> Result<()> {
     if cmd_parts.is_empty() {
@@ -657,3 +1116,243 @@ fn execute_command(cmd_parts: &[&str], dry_run: bool, verbose: bool) -> Result<(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_command_with_sudo() {
+        let cmd = start_command("nginx", &ServiceManager::Systemd, true, false).unwrap();
+        assert_eq!(cmd, vec!["sudo", "systemctl", "start", "nginx"]);
+    }
+
+    #[test]
+    fn test_start_command_without_sudo() {
+        let cmd = start_command("nginx", &ServiceManager::Systemd, false, false).unwrap();
+        assert_eq!(cmd, vec!["systemctl", "start", "nginx"]);
+    }
+
+    #[test]
+    fn test_user_commands_skip_sudo() {
+        let sm = ServiceManager::Systemd;
+        assert_eq!(
+            restart_command("my-user-daemon", &sm, true, true).unwrap(),
+            vec!["systemctl", "--user", "restart", "my-user-daemon"]
+        );
+        assert_eq!(
+            enable_command("my-user-daemon", &sm, false, true).unwrap(),
+            vec!["systemctl", "--user", "enable", "my-user-daemon"]
+        );
+        assert_eq!(
+            restart_command("nginx", &sm, true, false).unwrap(),
+            vec!["sudo", "systemctl", "restart", "nginx"]
+        );
+    }
+
+    #[test]
+    fn test_user_scope_requires_systemd() {
+        assert!(stop_command("sshd", &ServiceManager::OpenRC, false, true).is_err());
+        assert!(list_services(&ServiceManager::Launchd, true).is_err());
+    }
+
+    #[test]
+    fn test_enable_command_openrc() {
+        let cmd = enable_command("sshd", &ServiceManager::OpenRC, true, false).unwrap();
+        assert_eq!(cmd, vec!["sudo", "rc-update", "add", "sshd", "default"]);
+    }
+
+    #[test]
+    fn test_mask_commands() {
+        let sm = ServiceManager::Systemd;
+        assert_eq!(
+            mask_command("cups", &sm, true, false).unwrap(),
+            vec!["sudo", "systemctl", "mask", "cups"]
+        );
+        assert_eq!(
+            unmask_command("my-user-daemon", &sm, true, true).unwrap(),
+            vec!["systemctl", "--user", "unmask", "my-user-daemon"]
+        );
+        assert!(mask_command("sshd", &ServiceManager::OpenRC, true, false).is_err());
+    }
+
+    #[test]
+    fn test_critical_units() {
+        assert!(is_critical_unit("dbus"));
+        assert!(is_critical_unit("systemd-journald.service"));
+        assert!(!is_critical_unit("cups"));
+    }
+
+    #[test]
+    fn test_logs_command() {
+        assert_eq!(
+            logs_command("nginx", &ServiceManager::Systemd, 50, false, false).unwrap(),
+            vec!["journalctl", "-u", "nginx", "-n", "50", "--no-pager"]
+        );
+        assert_eq!(
+            logs_command("my-user-daemon", &ServiceManager::Systemd, 10, true, true).unwrap(),
+            vec![
+                "journalctl",
+                "--user",
+                "-u",
+                "my-user-daemon",
+                "-n",
+                "10",
+                "--no-pager",
+                "--follow"
+            ]
+        );
+        assert_eq!(
+            logs_command(
+                "com.example.agent",
+                &ServiceManager::Launchd,
+                50,
+                true,
+                false
+            )
+            .unwrap(),
+            vec![
+                "log",
+                "stream",
+                "--style",
+                "compact",
+                "--predicate",
+                "process == \"com.example.agent\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_logs_unsupported() {
+        let err = logs_command("nginx", &ServiceManager::Unknown, 50, false, false).unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+
+        let err = logs_command(
+            "catdog-no-such-service",
+            &ServiceManager::OpenRC,
+            50,
+            false,
+            false,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("/var/log/catdog-no-such-service.log"));
+    }
+
+    #[test]
+    fn test_parse_systemd_units() {
+        let output = "\
+cron.service      loaded active   running Regular background program processing daemon
+● nginx.service   loaded failed   failed  A high performance web server
+ssh.service       loaded inactive dead    OpenBSD Secure Shell server
+";
+        let services = parse_systemd_units(output);
+        assert_eq!(services.len(), 3);
+        assert_eq!(services[0].name, "cron");
+        assert_eq!(services[0].status, ServiceStatus::Running);
+        assert_eq!(services[1].name, "nginx");
+        assert_eq!(services[1].status, ServiceStatus::Failed);
+        assert_eq!(services[2].status, ServiceStatus::Stopped);
+        assert_eq!(
+            services[1].description.as_deref(),
+            Some("A high performance web server")
+        );
+    }
+
+    #[test]
+    fn test_parse_systemd_show() {
+        let running = parse_systemd_show(
+            "nginx",
+            "ActiveState=active\nSubState=running\nMainPID=1234\n\
+             UnitFileState=enabled\nDescription=A high performance web server\n",
+        );
+        assert_eq!(running.status, ServiceStatus::Running);
+        assert_eq!(running.pid, Some(1234));
+        assert_eq!(running.enabled, Some(true));
+        assert_eq!(
+            running.description.as_deref(),
+            Some("A high performance web server")
+        );
+
+        // A oneshot that ran to completion has no main process left
+        let oneshot = parse_systemd_show(
+            "systemd-tmpfiles-setup",
+            "ActiveState=active\nSubState=exited\nMainPID=0\nUnitFileState=static\n",
+        );
+        assert_eq!(oneshot.status, ServiceStatus::Running);
+        assert_eq!(oneshot.pid, None);
+        assert_eq!(oneshot.enabled, None);
+
+        let starting = parse_systemd_show("db", "ActiveState=activating\nSubState=start\n");
+        assert_eq!(starting.status, ServiceStatus::Running);
+        let looping = parse_systemd_show("db", "ActiveState=activating\nSubState=auto-restart\n");
+        assert_eq!(looping.status, ServiceStatus::Failed);
+        let stopping =
+            parse_systemd_show("db", "ActiveState=deactivating\nSubState=stop-sigterm\n");
+        assert_eq!(stopping.status, ServiceStatus::Stopped);
+        let failed = parse_systemd_show(
+            "db",
+            "ActiveState=failed\nSubState=failed\nUnitFileState=disabled\n",
+        );
+        assert_eq!(failed.status, ServiceStatus::Failed);
+        assert_eq!(failed.enabled, Some(false));
+    }
+
+    #[test]
+    fn test_parse_unit_files() {
+        let output = "\
+cron.service          enabled         enabled
+nginx.service         disabled        enabled
+dbus.service          static          -
+getty@.service        enabled         enabled
+rescue.service        masked          -
+";
+        let states = parse_unit_files(output);
+        assert_eq!(states.len(), 5);
+        assert_eq!(unit_file_enabled(&states["cron"]), Some(true));
+        assert_eq!(unit_file_enabled(&states["nginx"]), Some(false));
+        assert_eq!(unit_file_enabled(&states["dbus"]), None);
+        assert_eq!(unit_file_enabled(&states["rescue"]), Some(false));
+    }
+
+    #[test]
+    fn test_parse_idle_units() {
+        let output = "\
+Type=oneshot
+TriggeredBy=
+Id=apt-daily-upgrade.service
+
+Type=simple
+TriggeredBy=fstrim.timer
+Id=fstrim.service
+
+Type=notify
+TriggeredBy=cups.socket cups.path
+Id=cups.service
+
+Type=notify
+TriggeredBy=
+Id=nginx.service
+";
+        let idle = parse_idle_units(output);
+        assert_eq!(idle.len(), 3);
+        assert!(idle.contains("apt-daily-upgrade"));
+        assert!(idle.contains("fstrim"));
+        assert!(idle.contains("cups"));
+        assert!(!idle.contains("nginx"));
+    }
+
+    #[test]
+    fn test_parse_rc_update() {
+        let output = "\
+             sshd |      default
+            udev | sysinit
+           local |
+";
+        let enabled = parse_rc_update(output);
+        assert_eq!(enabled.get("sshd"), Some(&true));
+        assert_eq!(enabled.get("udev"), Some(&true));
+        assert_eq!(enabled.get("local"), Some(&false));
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::exec;
+
+/// SMART self-assessment and the sector counters that predict failure
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SmartHealth {
+    pub device: String,
+    /// Overall self-assessment; None when the drive doesn't report one
+    pub passed: Option<bool>,
+    pub reallocated_sectors: Option<u64>,
+    pub pending_sectors: Option<u64>,
+}
+
+impl SmartHealth {
+    pub fn failed(&self) -> bool {
+        self.passed == Some(false)
+    }
+}
+
+/// Why a device couldn't be checked
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum SmartUnavailable {
+    NotInstalled,
+    NeedsRoot,
+    Unsupported(String),
+}
+
+impl SmartUnavailable {
+    pub fn message(&self) -> String {
+        match self {
+            SmartUnavailable::NotInstalled => {
+                "smartctl is not installed (install smartmontools)".to_string()
+            }
+            SmartUnavailable::NeedsRoot => "smartctl needs root; re-run with sudo".to_string(),
+            SmartUnavailable::Unsupported(reason) => reason.clone(),
+        }
+    }
+}
+
+// smartctl exit status bits, see smartctl(8)
+const EXIT_PARSE_ERROR: i32 = 1 << 0;
+const EXIT_OPEN_FAILED: i32 = 1 << 1;
+
+pub fn smartctl_available() -> bool {
+    exec::run_command("smartctl", &["--version"])
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Whole disks to check: `lsblk` on Linux, `diskutil` on macOS
+pub fn list_disks() -> Result<Vec<String>> {
+    match std::env::consts::OS {
+        "linux" => {
+            let output = exec::run_command("lsblk", &["-d", "-n", "-p", "-o", "NAME,TYPE"])
+                .context("Failed to run lsblk")?;
+            Ok(parse_lsblk_disks(&String::from_utf8_lossy(&output.stdout)))
+        }
+        "macos" => {
+            let output = exec::run_command("diskutil", &["list", "-plist", "physical"])
+                .context("Failed to run diskutil list")?;
+            let root = plist::Value::from_reader(std::io::Cursor::new(output.stdout))
+                .context("Failed to parse diskutil plist output")?;
+            Ok(root
+                .as_dictionary()
+                .and_then(|d| d.get("WholeDisks"))
+                .and_then(|w| w.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|id| id.as_string().map(|id| format!("/dev/{}", id)))
+                .collect())
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Parse `lsblk -d -n -p -o NAME,TYPE`, keeping only disks (not loop or rom devices)
+fn parse_lsblk_disks(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            (parts.next()? == "disk").then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Run `smartctl -H -A` against a device
+pub fn check_device(device: &str) -> Result<std::result::Result<SmartHealth, SmartUnavailable>> {
+    let output = match exec::run_command("smartctl", &["-H", "-A", device]) {
+        Ok(output) => output,
+        Err(e)
+            if e.downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) =>
+        {
+            return Ok(Err(SmartUnavailable::NotInstalled))
+        }
+        Err(e) => return Err(e),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Other bits report disk problems, and the output is still worth parsing
+    let code = output.status.code().unwrap_or(0);
+    if code & (EXIT_PARSE_ERROR | EXIT_OPEN_FAILED) != 0 {
+        if stdout.contains("Permission denied") || stdout.contains("Operation not permitted") {
+            return Ok(Err(SmartUnavailable::NeedsRoot));
+        }
+        let reason = stdout
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("smartctl could not open the device")
+            .trim()
+            .to_string();
+        return Ok(Err(SmartUnavailable::Unsupported(reason)));
+    }
+
+    Ok(Ok(parse_smartctl(device, &stdout)))
+}
+
+/// Parse `smartctl -H -A` output for ATA, SCSI and NVMe drives
+pub fn parse_smartctl(device: &str, output: &str) -> SmartHealth {
+    let mut health = SmartHealth {
+        device: device.to_string(),
+        passed: None,
+        reallocated_sectors: None,
+        pending_sectors: None,
+    };
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        // ATA/NVMe: "SMART overall-health self-assessment test result: PASSED"
+        // SCSI: "SMART Health Status: OK"
+        if let Some(result) = line
+            .strip_prefix("SMART overall-health self-assessment test result:")
+            .or_else(|| line.strip_prefix("SMART Health Status:"))
+        {
+            let result = result.trim();
+            health.passed = Some(result == "PASSED" || result == "OK");
+            continue;
+        }
+
+        // ATA attribute rows: ID NAME FLAG VALUE WORST THRESH TYPE UPDATED WHEN_FAILED RAW_VALUE
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 10 {
+            let raw = parts[9].parse::<u64>().ok();
+            match parts[1] {
+                "Reallocated_Sector_Ct" => health.reallocated_sectors = raw,
+                "Current_Pending_Sector" => health.pending_sectors = raw,
+                _ => {}
+            }
+        }
+    }
+
+    health
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_smartctl_ata() {
+        let output = "\
+=== START OF READ SMART DATA SECTION ===
+SMART overall-health self-assessment test result: PASSED
+
+SMART Attributes Data Structure revision number: 16
+ID# ATTRIBUTE_NAME          FLAG     VALUE WORST THRESH TYPE      UPDATED  WHEN_FAILED RAW_VALUE
+  5 Reallocated_Sector_Ct   0x0033   100   100   010    Pre-fail  Always       -       8
+  9 Power_On_Hours          0x0032   095   095   000    Old_age   Always       -       21914
+197 Current_Pending_Sector  0x0012   100   100   000    Old_age   Always       -       2
+";
+        let health = parse_smartctl("/dev/sda", output);
+
+        assert_eq!(health.passed, Some(true));
+        assert_eq!(health.reallocated_sectors, Some(8));
+        assert_eq!(health.pending_sectors, Some(2));
+        assert!(!health.failed());
+    }
+
+    #[test]
+    fn test_parse_smartctl_failed_and_nvme() {
+        let failed = parse_smartctl(
+            "/dev/sdb",
+            "SMART overall-health self-assessment test result: FAILED!\n",
+        );
+        assert!(failed.failed());
+
+        let scsi = parse_smartctl("/dev/sdc", "SMART Health Status: OK\n");
+        assert_eq!(scsi.passed, Some(true));
+
+        // NVMe has no ATA attribute table
+        let nvme = parse_smartctl(
+            "/dev/nvme0",
+            "SMART overall-health self-assessment test result: PASSED\n\
+             Critical Warning:                   0x00\n\
+             Temperature:                        38 Celsius\n",
+        );
+        assert_eq!(nvme.reallocated_sectors, None);
+        assert_eq!(nvme.pending_sectors, None);
+    }
+
+    #[test]
+    fn test_parse_lsblk_disks() {
+        let output = "/dev/sda   disk\n/dev/sr0   rom\n/dev/loop0 loop\n/dev/nvme0n1 disk\n";
+        assert_eq!(parse_lsblk_disks(output), vec!["/dev/sda", "/dev/nvme0n1"]);
+    }
+}
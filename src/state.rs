@@ -0,0 +1,270 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const STATE_DIR_NAME: &str = ".catdog";
+
+/// Resolve the user's home directory, preferring `$HOME`
+///
+/// Fails instead of falling back to the current directory, which would
+/// scatter state wherever catdog happened to be run.
+pub fn home_dir() -> Result<PathBuf> {
+    env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(dirs::home_dir)
+        .context("Could not determine home directory; set $HOME")
+}
+
+/// The catdog state directory (`~/.catdog`), created with owner-only permissions
+pub fn state_dir() -> Result<PathBuf> {
+    let dir = home_dir()?.join(STATE_DIR_NAME);
+    ensure_private_dir(&dir)?;
+    Ok(dir)
+}
+
+/// Create a directory if needed and restrict it to the current user
+///
+/// Existing directories that are group- or world-accessible are tightened to `0700`.
+pub fn ensure_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = fs::metadata(dir)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o700)).with_context(|| {
+                format!("Failed to set permissions on directory: {}", dir.display())
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a file via a temporary sibling that is synced and renamed into place
+///
+/// Readers see either the old contents or the new ones, never a partial
+/// write. An existing file keeps its permissions and ownership, and a
+/// symlink is written through rather than replaced.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    write_atomic_like(path, contents, path)
+}
+
+/// [`write_atomic`], taking permissions and ownership from `template` if it exists
+///
+/// Used where the file being replaced may be missing but another file, such as
+/// a backup of it, has the right mode. Ownership is only changed where allowed.
+pub fn write_atomic_like(path: &Path, contents: impl AsRef<[u8]>, template: &Path) -> Result<()> {
+    let path = &resolve_symlink(path);
+    let mut tmp_name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?
+        .to_os_string();
+    tmp_name.push(".catdog.tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| -> std::io::Result<()> {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path).or_else(|_| fs::metadata(template)) {
+            fs::set_permissions(&tmp_path, metadata.permissions())?;
+            copy_ownership(&tmp_path, &metadata)?;
+        }
+        fs::rename(&tmp_path, path)?;
+
+        // Persist the rename too; not every platform can sync a directory
+        #[cfg(unix)]
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            let _ = fs::File::open(dir).and_then(|dir| dir.sync_all());
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Where writing to `path` should go: the final target when it is a symlink
+///
+/// Dangling links are followed too, so the link itself survives the write.
+fn resolve_symlink(path: &Path) -> PathBuf {
+    let mut path = path.to_path_buf();
+    // Bounded so a symlink loop can't spin forever
+    for _ in 0..40 {
+        let is_link = fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink());
+        if !is_link {
+            break;
+        }
+        match fs::read_link(&path) {
+            Ok(target) => {
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    path
+}
+
+/// Give `path` the owner and group in `metadata`, when the process may
+#[cfg(unix)]
+fn copy_ownership(path: &Path, metadata: &fs::Metadata) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let current = fs::metadata(path)?;
+    if (current.uid(), current.gid()) == (metadata.uid(), metadata.gid()) {
+        return Ok(());
+    }
+    match std::os::unix::fs::chown(path, Some(metadata.uid()), Some(metadata.gid())) {
+        // Only root can give files away; everyone else keeps their own
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(not(unix))]
+fn copy_ownership(_path: &Path, _metadata: &fs::Metadata) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.json");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+
+        // Nothing is left behind next to the file
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["alerts.json"]);
+    }
+
+    #[test]
+    fn test_write_atomic_never_exposes_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corpus.idx");
+        let old = "a".repeat(256 * 1024);
+        let new = "b".repeat(512 * 1024);
+        write_atomic(&path, &old).unwrap();
+
+        let reader = {
+            let (path, old, new) = (path.clone(), old.clone(), new.clone());
+            std::thread::spawn(move || {
+                for _ in 0..200 {
+                    let seen = fs::read_to_string(&path).unwrap();
+                    assert!(seen == old || seen == new, "saw {} bytes", seen.len());
+                }
+            })
+        };
+        for i in 0..50 {
+            write_atomic(&path, if i % 2 == 0 { &new } else { &old }).unwrap();
+        }
+        reader.join().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_through_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("resolv.conf.real");
+        let link = dir.path().join("resolv.conf");
+        fs::write(&target, "nameserver 1.1.1.1\n").unwrap();
+        std::os::unix::fs::symlink("resolv.conf.real", &link).unwrap();
+
+        write_atomic(&link, "nameserver 9.9.9.9\n").unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "nameserver 9.9.9.9\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_like_takes_template_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("secret.backup");
+        fs::write(&template, "x").unwrap();
+        fs::set_permissions(&template, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let path = dir.path().join("secret");
+        write_atomic_like(&path, "x", &template).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // An existing file keeps its own mode over the template's
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        write_atomic_like(&path, "y", &template).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[test]
+    fn test_write_atomic_failure_keeps_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_atomic(&path, "original").unwrap();
+
+        // A directory where the temporary file should go makes the write fail
+        fs::create_dir(dir.path().join("config.toml.catdog.tmp")).unwrap();
+        assert!(write_atomic(&path, "replacement").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fstab");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_atomic(&path, "new").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_dir_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("state").join(".catdog");
+
+        ensure_private_dir(&dir).unwrap();
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // Loose permissions on an existing directory are tightened
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        ensure_private_dir(&dir).unwrap();
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+}
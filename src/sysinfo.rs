@@ -1,7 +1,11 @@
 use anyhow::{Context, Result};
+use log::debug;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
 use std::process::Command;
+use std::thread;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
@@ -12,6 +16,12 @@ pub struct SystemInfo {
     pub network: NetworkInfo,
     pub hostname: String,
     pub uptime: Option<String>,
+    /// 1, 5 and 15 minute load averages
+    pub load_average: Option<[f64; 3]>,
+    #[serde(default)]
+    pub gpus: Vec<GpuInfo>,
+    #[serde(default)]
+    pub temperatures: Vec<SensorReading>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +39,10 @@ pub struct CpuInfo {
     pub cores: usize,
     pub threads: Option<usize>,
     pub frequency: Option<String>,
+    /// Busy share of all CPUs over a short sample
+    pub utilization_percent: Option<f64>,
+    #[serde(default)]
+    pub per_core: Vec<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +51,48 @@ pub struct MemoryInfo {
     pub available: String,
     pub used: String,
     pub percent_used: f64,
+    pub swap_total: String,
+    pub swap_used: String,
+    pub swap_percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub user: String,
+    pub cpu_percent: f64,
+    pub mem_percent: f64,
+    pub command: String,
+}
+
+/// Which resource `top_processes` ranks by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessSort {
+    Cpu,
+    Memory,
+}
+
+impl ProcessSort {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "cpu" => Some(ProcessSort::Cpu),
+            "mem" | "memory" => Some(ProcessSort::Memory),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub name: String,
+    pub vendor: Option<String>,
+    pub memory: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorReading {
+    pub label: String,
+    pub celsius: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,11 +112,39 @@ pub struct NetworkInfo {
     pub hostname: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct NetworkInterface {
     pub name: String,
+    /// First IPv4 address, kept for scripts that read the old field
     pub ip_address: Option<String>,
     pub mac_address: Option<String>,
+    #[serde(default)]
+    pub ipv4_addresses: Vec<String>,
+    #[serde(default)]
+    pub ipv6_addresses: Vec<String>,
+    /// Link state, "up" or "down"
+    pub state: Option<String>,
+    pub mtu: Option<u32>,
+}
+
+impl NetworkInterface {
+    fn new(name: &str) -> Self {
+        NetworkInterface {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn add_ipv4(&mut self, address: &str) {
+        if self.ip_address.is_none() {
+            self.ip_address = Some(address.to_string());
+        }
+        self.ipv4_addresses.push(address.to_string());
+    }
+
+    pub fn has_addresses(&self) -> bool {
+        !self.ipv4_addresses.is_empty() || !self.ipv6_addresses.is_empty()
+    }
 }
 
 /// Gather comprehensive system information
@@ -73,6 +157,9 @@ pub fn gather_system_info() -> Result<SystemInfo> {
         network: get_network_info()?,
         hostname: get_hostname()?,
         uptime: get_uptime().ok(),
+        load_average: get_load_average(),
+        gpus: get_gpu_info(),
+        temperatures: get_temperatures(),
     })
 }
 
@@ -83,6 +170,7 @@ fn get_os_info() -> Result<OsInfo> {
     match platform {
         "macos" => get_macos_info(),
         "linux" => get_linux_info(),
+        "windows" => get_windows_info(),
         _ => Ok(OsInfo {
             name: platform.to_string(),
             version: "Unknown".to_string(),
@@ -151,7 +239,7 @@ fn get_linux_info() -> Result<OsInfo> {
     })
 }
 
-fn get_kernel_version() -> Result<String> {
+pub fn get_kernel_version() -> Result<String> {
     let output = Command::new("uname")
         .arg("-r")
         .output()
@@ -160,20 +248,130 @@ fn get_kernel_version() -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
+/// How long to wait between the two CPU usage samples
+const CPU_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
 /// Get CPU information
 fn get_cpu_info() -> Result<CpuInfo> {
     let platform = std::env::consts::OS;
 
-    match platform {
-        "macos" => get_macos_cpu_info(),
-        "linux" => get_linux_cpu_info(),
-        _ => Ok(CpuInfo {
+    let mut cpu = match platform {
+        "macos" => get_macos_cpu_info()?,
+        "linux" => get_linux_cpu_info()?,
+        "windows" => get_windows_cpu_info()?,
+        _ => CpuInfo {
             model: "Unknown".to_string(),
             cores: num_cpus::get_physical(),
             threads: Some(num_cpus::get()),
             frequency: None,
-        }),
+            utilization_percent: None,
+            per_core: Vec::new(),
+        },
+    };
+
+    if let Some((total, per_core)) = sample_cpu_utilization() {
+        cpu.utilization_percent = Some(total);
+        cpu.per_core = per_core;
+    }
+
+    Ok(cpu)
+}
+
+/// Measure CPU utilization; per-core figures are only available on Linux
+fn sample_cpu_utilization() -> Option<(f64, Vec<f64>)> {
+    match std::env::consts::OS {
+        "linux" => {
+            let before = fs::read_to_string("/proc/stat").ok()?;
+            thread::sleep(CPU_SAMPLE_INTERVAL);
+            let after = fs::read_to_string("/proc/stat").ok()?;
+            cpu_utilization(&before, &after)
+        }
+        "macos" => {
+            // The first top sample covers time since boot, so take the second
+            let output = Command::new("top")
+                .args(["-l", "2", "-n", "0", "-s", "0"])
+                .output()
+                .ok()?;
+            parse_top_cpu_usage(&String::from_utf8_lossy(&output.stdout)).map(|u| (u, Vec::new()))
+        }
+        _ => None,
+    }
+}
+
+/// Busy and total jiffies from a `/proc/stat` cpu line
+///
+/// guest time is already counted in user, so only the first eight columns are summed;
+/// iowait counts as idle.
+fn cpu_jiffies(line: &str) -> Option<(u64, u64)> {
+    let values: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(|v| v.parse().ok())
+        .collect::<Option<_>>()?;
+    if values.len() < 4 {
+        return None;
+    }
+
+    let total: u64 = values.iter().sum();
+    let idle = values[3] + values.get(4).copied().unwrap_or(0);
+    Some((total - idle, total))
+}
+
+/// Overall and per-core utilization between two `/proc/stat` snapshots
+pub fn cpu_utilization(before: &str, after: &str) -> Option<(f64, Vec<f64>)> {
+    let cpu_lines = |stat: &str| -> Vec<(String, (u64, u64))> {
+        stat.lines()
+            .filter(|line| line.starts_with("cpu"))
+            .filter_map(|line| {
+                let name = line.split_whitespace().next()?.to_string();
+                Some((name, cpu_jiffies(line)?))
+            })
+            .collect()
+    };
+    let usage = |(busy0, total0): (u64, u64), (busy1, total1): (u64, u64)| {
+        let total = total1.saturating_sub(total0);
+        if total == 0 {
+            0.0
+        } else {
+            busy1.saturating_sub(busy0) as f64 / total as f64 * 100.0
+        }
+    };
+
+    let before = cpu_lines(before);
+    let after = cpu_lines(after);
+
+    let mut overall = None;
+    let mut per_core = Vec::new();
+    for (name, end) in &after {
+        let Some((_, start)) = before.iter().find(|(n, _)| n == name) else {
+            continue;
+        };
+        if name == "cpu" {
+            overall = Some(usage(*start, *end));
+        } else {
+            per_core.push(usage(*start, *end));
+        }
     }
+
+    overall.map(|total| (total, per_core))
+}
+
+/// Busy percentage from the last `CPU usage: 5.12% user, 10.25% sys, 84.61% idle` line of `top -l`
+fn parse_top_cpu_usage(output: &str) -> Option<f64> {
+    let line = output
+        .lines()
+        .rev()
+        .find(|line| line.starts_with("CPU usage:"))?;
+    let idle = line
+        .split(',')
+        .find(|part| part.contains("idle"))?
+        .split_whitespace()
+        .next()?
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .ok()?;
+    Some(100.0 - idle)
 }
 
 fn get_macos_cpu_info() -> Result<CpuInfo> {
@@ -223,6 +421,8 @@ fn get_macos_cpu_info() -> Result<CpuInfo> {
         cores,
         threads,
         frequency,
+        utilization_percent: None,
+        per_core: Vec::new(),
     })
 }
 
@@ -251,6 +451,8 @@ fn get_linux_cpu_info() -> Result<CpuInfo> {
         cores: num_cpus::get_physical(),
         threads: Some(num_cpus::get()),
         frequency,
+        utilization_percent: None,
+        per_core: Vec::new(),
     })
 }
 
@@ -261,11 +463,15 @@ fn get_memory_info() -> Result<MemoryInfo> {
     match platform {
         "macos" => get_macos_memory_info(),
         "linux" => get_linux_memory_info(),
+        "windows" => get_windows_memory_info(),
         _ => Ok(MemoryInfo {
             total: "Unknown".to_string(),
             available: "Unknown".to_string(),
             used: "Unknown".to_string(),
             percent_used: 0.0,
+            swap_total: "Unknown".to_string(),
+            swap_used: "Unknown".to_string(),
+            swap_percent: 0.0,
         }),
     }
 }
@@ -307,35 +513,70 @@ fn get_macos_memory_info() -> Result<MemoryInfo> {
 
     let available_bytes = (free_pages + inactive_pages) * page_size;
     let used_bytes = total_bytes.saturating_sub(available_bytes);
-    let percent_used = if total_bytes > 0 {
-        (used_bytes as f64 / total_bytes as f64) * 100.0
-    } else {
-        0.0
-    };
+
+    let (swap_total_bytes, swap_used_bytes) = Command::new("sysctl")
+        .arg("vm.swapusage")
+        .output()
+        .ok()
+        .and_then(|o| parse_swapusage(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or((0, 0));
 
     Ok(MemoryInfo {
         total: format_bytes(total_bytes),
         available: format_bytes(available_bytes),
         used: format_bytes(used_bytes),
-        percent_used,
+        percent_used: percent(used_bytes, total_bytes),
+        swap_total: format_bytes(swap_total_bytes),
+        swap_used: format_bytes(swap_used_bytes),
+        swap_percent: percent(swap_used_bytes, swap_total_bytes),
     })
 }
 
+/// Parse `sysctl vm.swapusage`, e.g.
+/// `vm.swapusage: total = 2048.00M  used = 1024.50M  free = 1023.50M  (encrypted)`
+///
+/// Returns (total, used) in bytes.
+fn parse_swapusage(output: &str) -> Option<(u64, u64)> {
+    let value_of = |key: &str| {
+        let rest = output.split(&format!("{} = ", key)).nth(1)?;
+        let value = rest.split_whitespace().next()?;
+        let (number, unit) = value.split_at(value.len() - 1);
+        let multiplier = match unit {
+            "K" => 1024.0,
+            "M" => 1024.0 * 1024.0,
+            "G" => 1024.0 * 1024.0 * 1024.0,
+            _ => return None,
+        };
+        Some((number.parse::<f64>().ok()? * multiplier) as u64)
+    };
+
+    Some((value_of("total")?, value_of("used")?))
+}
+
 fn get_linux_memory_info() -> Result<MemoryInfo> {
     let meminfo = fs::read_to_string("/proc/meminfo").context("Failed to read /proc/meminfo")?;
+    Ok(parse_meminfo(&meminfo))
+}
 
+/// Build memory and swap usage from `/proc/meminfo`
+fn parse_meminfo(meminfo: &str) -> MemoryInfo {
     let mut total = 0u64;
     let mut available = 0u64;
+    let mut swap_total = 0u64;
+    let mut swap_free = 0u64;
 
     for line in meminfo.lines() {
-        if line.starts_with("MemTotal:") {
-            if let Some(value) = line.split_whitespace().nth(1) {
-                total = value.parse().unwrap_or(0);
-            }
-        } else if line.starts_with("MemAvailable:") {
-            if let Some(value) = line.split_whitespace().nth(1) {
-                available = value.parse().unwrap_or(0);
-            }
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let value = value.parse().unwrap_or(0);
+        match key {
+            "MemTotal:" => total = value,
+            "MemAvailable:" => available = value,
+            "SwapTotal:" => swap_total = value,
+            "SwapFree:" => swap_free = value,
+            _ => {}
         }
     }
 
@@ -343,18 +584,46 @@ fn get_linux_memory_info() -> Result<MemoryInfo> {
     let total_bytes = total * 1024;
     let available_bytes = available * 1024;
     let used_bytes = total_bytes.saturating_sub(available_bytes);
-    let percent_used = if total_bytes > 0 {
-        (used_bytes as f64 / total_bytes as f64) * 100.0
-    } else {
-        0.0
-    };
+    let swap_total_bytes = swap_total * 1024;
+    let swap_used_bytes = swap_total_bytes.saturating_sub(swap_free * 1024);
 
-    Ok(MemoryInfo {
+    MemoryInfo {
         total: format_bytes(total_bytes),
         available: format_bytes(available_bytes),
         used: format_bytes(used_bytes),
-        percent_used,
-    })
+        percent_used: percent(used_bytes, total_bytes),
+        swap_total: format_bytes(swap_total_bytes),
+        swap_used: format_bytes(swap_used_bytes),
+        swap_percent: percent(swap_used_bytes, swap_total_bytes),
+    }
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total > 0 {
+        (used as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Get the 1, 5 and 15 minute load averages
+fn get_load_average() -> Option<[f64; 3]> {
+    if let Ok(loadavg) = fs::read_to_string("/proc/loadavg") {
+        return parse_load_average(&loadavg);
+    }
+
+    // macOS prints "{ 1.23 1.45 1.67 }"
+    let output = Command::new("sysctl")
+        .arg("-n")
+        .arg("vm.loadavg")
+        .output()
+        .ok()?;
+    parse_load_average(
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .trim_start_matches('{')
+            .trim_end_matches('}'),
+    )
 }
 
 /// Get disk information
@@ -363,6 +632,10 @@ fn get_disk_info() -> Result<Vec<DiskInfo>> {
 
     match platform {
         "macos" | "linux" => get_df_disk_info(),
+        "windows" => Ok(windows_disks(&query_cim(
+            "Win32_LogicalDisk",
+            &["DeviceID", "FileSystem", "Size", "FreeSpace"],
+        )?)),
         _ => Ok(Vec::new()),
     }
 }
@@ -420,70 +693,633 @@ fn get_network_interfaces() -> Result<Vec<NetworkInterface>> {
     let platform = std::env::consts::OS;
 
     match platform {
-        "macos" | "linux" => get_ifconfig_interfaces(),
+        "linux" => get_linux_interfaces(),
+        "macos" => get_ifconfig_interfaces(),
         _ => Ok(Vec::new()),
     }
 }
 
+/// Prefer `ip -j addr`, then `ip addr` text, then net-tools `ifconfig`
+fn get_linux_interfaces() -> Result<Vec<NetworkInterface>> {
+    if let Some(output) = command_stdout("ip", &["-j", "addr"]) {
+        match parse_ip_json(&output) {
+            Ok(interfaces) => return Ok(interfaces),
+            Err(e) => debug!("Could not parse ip -j addr output: {}", e),
+        }
+    }
+
+    if let Some(output) = command_stdout("ip", &["addr"]) {
+        return Ok(parse_ip_addr(&output));
+    }
+
+    get_ifconfig_interfaces()
+}
+
+/// Parse `ip -j addr` JSON
+fn parse_ip_json(output: &str) -> Result<Vec<NetworkInterface>> {
+    let links: Vec<serde_json::Value> = serde_json::from_str(output)?;
+
+    Ok(links
+        .iter()
+        .filter_map(|link| {
+            let mut iface = NetworkInterface::new(link["ifname"].as_str()?);
+            iface.mac_address = link["address"]
+                .as_str()
+                .filter(|_| link["link_type"].as_str() == Some("ether"))
+                .map(String::from);
+            iface.mtu = link["mtu"].as_u64().map(|mtu| mtu as u32);
+
+            let flags: Vec<&str> = link["flags"]
+                .as_array()
+                .map(|f| f.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            iface.state = Some(link_state(link["operstate"].as_str(), &flags));
+
+            for addr in link["addr_info"].as_array().into_iter().flatten() {
+                let Some(local) = addr["local"].as_str() else {
+                    continue;
+                };
+                match addr["family"].as_str() {
+                    Some("inet") => iface.add_ipv4(local),
+                    Some("inet6") => iface.ipv6_addresses.push(local.to_string()),
+                    _ => {}
+                }
+            }
+
+            Some(iface)
+        })
+        .collect())
+}
+
+/// operstate is UNKNOWN for loopback and some virtual links, so fall back to the UP flag
+fn link_state(operstate: Option<&str>, flags: &[&str]) -> String {
+    match operstate.map(|s| s.to_lowercase()) {
+        Some(state) if state == "up" || state == "down" => state,
+        _ if flags.contains(&"UP") => "up".to_string(),
+        _ => "down".to_string(),
+    }
+}
+
+/// Parse `ip addr` text output
+fn parse_ip_addr(output: &str) -> Vec<NetworkInterface> {
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+
+        if !line.starts_with(' ') {
+            // "4: eth0@if5: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1400 ... state UP ..."
+            let Some(name) = parts.get(1) else {
+                continue;
+            };
+            let name = name.trim_end_matches(':');
+            let mut iface = NetworkInterface::new(name.split('@').next().unwrap_or(name));
+
+            let value_after = |key: &str| {
+                parts
+                    .iter()
+                    .position(|p| *p == key)
+                    .and_then(|i| parts.get(i + 1).copied())
+            };
+            iface.mtu = value_after("mtu").and_then(|m| m.parse().ok());
+            let flags: Vec<&str> = parts
+                .get(2)
+                .map(|f| {
+                    f.trim_matches(|c| c == '<' || c == '>')
+                        .split(',')
+                        .collect()
+                })
+                .unwrap_or_default();
+            iface.state = Some(link_state(value_after("state"), &flags));
+
+            interfaces.push(iface);
+            continue;
+        }
+
+        let Some(iface) = interfaces.last_mut() else {
+            continue;
+        };
+        let address = parts.get(1).map(|a| a.split('/').next().unwrap_or(a));
+        match (parts.first().copied(), address) {
+            (Some("inet"), Some(address)) => iface.add_ipv4(address),
+            (Some("inet6"), Some(address)) => iface.ipv6_addresses.push(address.to_string()),
+            (Some("link/ether"), Some(address)) => iface.mac_address = Some(address.to_string()),
+            _ => {}
+        }
+    }
+
+    interfaces
+}
+
 fn get_ifconfig_interfaces() -> Result<Vec<NetworkInterface>> {
     let output = Command::new("ifconfig")
         .output()
-        .or_else(|_| Command::new("ip").arg("addr").output())
         .context("Failed to get network interfaces")?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut interfaces = Vec::new();
-    let mut current_name = String::new();
-    let mut current_ip = None;
-    let mut current_mac = None;
+    Ok(parse_ifconfig(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `ifconfig` output from macOS or Linux net-tools
+fn parse_ifconfig(output: &str) -> Vec<NetworkInterface> {
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let value_after = |key: &str| {
+            parts
+                .iter()
+                .position(|p| *p == key)
+                .and_then(|i| parts.get(i + 1).copied())
+        };
 
-    for line in stdout.lines() {
         if !line.starts_with(' ') && !line.starts_with('\t') && line.contains(':') {
-            // New interface - save previous one
-            if !current_name.is_empty() {
-                interfaces.push(NetworkInterface {
-                    name: current_name.clone(),
-                    ip_address: current_ip.clone(),
-                    mac_address: current_mac.clone(),
-                });
+            // "en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500"
+            let name = line.split(':').next().unwrap_or("").trim();
+            let mut iface = NetworkInterface::new(name);
+            iface.mtu = value_after("mtu").and_then(|m| m.parse().ok());
+            let up = line
+                .split_once('<')
+                .and_then(|(_, rest)| rest.split_once('>'))
+                .is_some_and(|(flags, _)| flags.split(',').any(|f| f == "UP"));
+            iface.state = Some(if up { "up" } else { "down" }.to_string());
+            interfaces.push(iface);
+            continue;
+        }
+
+        let Some(iface) = interfaces.last_mut() else {
+            continue;
+        };
+        match parts.first().copied() {
+            Some("inet") => {
+                if let Some(address) = parts.get(1) {
+                    // net-tools may print "inet addr:10.0.0.2"
+                    iface.add_ipv4(address.trim_start_matches("addr:"));
+                }
+            }
+            Some("inet6") => {
+                if let Some(address) = parts.get(1) {
+                    // Drop the "%en0" zone suffix on link-local addresses
+                    let address = address.split('%').next().unwrap_or(address);
+                    iface.ipv6_addresses.push(address.to_string());
+                }
             }
+            Some("ether") => iface.mac_address = parts.get(1).map(|m| m.to_string()),
+            Some("status:") => {
+                if let Some(status) = parts.get(1) {
+                    let state = if *status == "active" { "up" } else { "down" };
+                    iface.state = Some(state.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
 
-            // Parse interface name
-            current_name = line.split(':').next().unwrap_or("").trim().to_string();
-            current_ip = None;
-            current_mac = None;
-        } else if line.contains("inet ") && !line.contains("inet6") {
-            // Parse IP address
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if let Some(pos) = parts.iter().position(|&s| s == "inet") {
-                if parts.len() > pos + 1 {
-                    current_ip = Some(parts[pos + 1].to_string());
+    interfaces
+}
+
+/// Get GPU information
+///
+/// Best-effort: an empty list means no GPU was found or the tools are missing.
+fn get_gpu_info() -> Vec<GpuInfo> {
+    match std::env::consts::OS {
+        "macos" => command_stdout("system_profiler", &["SPDisplaysDataType"])
+            .map(|output| parse_system_profiler_displays(&output))
+            .unwrap_or_default(),
+        "linux" => get_linux_gpu_info(),
+        _ => Vec::new(),
+    }
+}
+
+fn get_linux_gpu_info() -> Vec<GpuInfo> {
+    let mut gpus = command_stdout("lspci", &[])
+        .map(|output| parse_lspci_gpus(&output))
+        .unwrap_or_default();
+
+    // nvidia-smi knows the memory size, so it replaces lspci's NVIDIA entries
+    let nvidia = command_stdout(
+        "nvidia-smi",
+        &["--query-gpu=name,memory.total", "--format=csv,noheader"],
+    )
+    .map(|output| parse_nvidia_smi(&output))
+    .unwrap_or_default();
+    if !nvidia.is_empty() {
+        gpus.retain(|gpu| gpu.vendor.as_deref() != Some("NVIDIA"));
+        gpus.extend(nvidia);
+    }
+
+    gpus
+}
+
+/// Parse GPUs out of `lspci` output, e.g.
+/// `01:00.0 VGA compatible controller: NVIDIA Corporation GA104 [GeForce RTX 3070] (rev a1)`
+fn parse_lspci_gpus(output: &str) -> Vec<GpuInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (class, name) = line.split_once(": ")?;
+            if ![
+                "VGA compatible controller",
+                "3D controller",
+                "Display controller",
+            ]
+            .iter()
+            .any(|c| class.ends_with(c))
+            {
+                return None;
+            }
+
+            let name = match name.rfind(" (rev ") {
+                Some(pos) => &name[..pos],
+                None => name,
+            };
+            Some(GpuInfo {
+                name: name.trim().to_string(),
+                vendor: gpu_vendor(name),
+                memory: None,
+            })
+        })
+        .collect()
+}
+
+/// Parse `nvidia-smi --query-gpu=name,memory.total --format=csv,noheader`
+fn parse_nvidia_smi(output: &str) -> Vec<GpuInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, memory) = line.split_once(',')?;
+            Some(GpuInfo {
+                name: name.trim().to_string(),
+                vendor: Some("NVIDIA".to_string()),
+                memory: Some(memory.trim().to_string()).filter(|m| !m.is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// Parse `system_profiler SPDisplaysDataType`; each GPU starts at `Chipset Model:`
+fn parse_system_profiler_displays(output: &str) -> Vec<GpuInfo> {
+    let mut gpus: Vec<GpuInfo> = Vec::new();
+
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match key {
+            "Chipset Model" => gpus.push(GpuInfo {
+                name: value.to_string(),
+                vendor: gpu_vendor(value),
+                memory: None,
+            }),
+            "Vendor" => {
+                if let Some(gpu) = gpus.last_mut() {
+                    // "Apple (0x106b)" -> "Apple"
+                    let vendor = value.split(" (").next().unwrap_or(value);
+                    gpu.vendor = Some(vendor.trim_start_matches("sppci_vendor_").to_string());
                 }
             }
-        } else if line.contains("ether ") {
-            // Parse MAC address
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if let Some(pos) = parts.iter().position(|&s| s == "ether") {
-                if parts.len() > pos + 1 {
-                    current_mac = Some(parts[pos + 1].to_string());
+            "VRAM (Total)" | "VRAM (Dynamic, Max)" => {
+                if let Some(gpu) = gpus.last_mut() {
+                    gpu.memory = Some(value.to_string());
                 }
             }
+            _ => {}
         }
     }
 
-    // Add last interface
-    if !current_name.is_empty() {
-        interfaces.push(NetworkInterface {
-            name: current_name,
-            ip_address: current_ip,
-            mac_address: current_mac,
-        });
+    gpus
+}
+
+fn gpu_vendor(name: &str) -> Option<String> {
+    let lower = name.to_lowercase();
+    [
+        ("nvidia", "NVIDIA"),
+        ("advanced micro devices", "AMD"),
+        ("amd", "AMD"),
+        ("intel", "Intel"),
+        ("apple", "Apple"),
+    ]
+    .iter()
+    .find(|(needle, _)| lower.contains(needle))
+    .map(|(_, vendor)| vendor.to_string())
+}
+
+/// Get temperature sensor readings
+///
+/// Linux reads `/sys/class/thermal`, falling back to lm-sensors; macOS asks
+/// `powermetrics`, which only works as root. Missing sources give an empty list.
+fn get_temperatures() -> Vec<SensorReading> {
+    match std::env::consts::OS {
+        "linux" => {
+            let readings = read_thermal_zones(Path::new("/sys/class/thermal"));
+            if !readings.is_empty() {
+                return readings;
+            }
+            command_stdout("sensors", &[])
+                .map(|output| parse_sensors_output(&output))
+                .unwrap_or_default()
+        }
+        "macos" => command_stdout("powermetrics", &["--samplers", "smc", "-n", "1"])
+            .map(|output| parse_powermetrics_temperatures(&output))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Read `thermal_zone*/{type,temp}` under `root`; temps are in millidegrees
+fn read_thermal_zones(root: &Path) -> Vec<SensorReading> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut zones: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("thermal_zone"))
+        })
+        .collect();
+    zones.sort();
+
+    zones
+        .iter()
+        .filter_map(|zone| {
+            let millidegrees: f64 = fs::read_to_string(zone.join("temp"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+            let label = fs::read_to_string(zone.join("type"))
+                .map(|t| t.trim().to_string())
+                .unwrap_or_else(|_| zone.file_name().unwrap().to_string_lossy().to_string());
+            Some(SensorReading {
+                label,
+                celsius: millidegrees / 1000.0,
+            })
+        })
+        .collect()
+}
+
+/// Parse `sensors` output lines like `Core 0:        +45.0°C  (high = +80.0°C, crit = +100.0°C)`
+fn parse_sensors_output(output: &str) -> Vec<SensorReading> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (label, rest) = line.split_once(':')?;
+            let value = rest.split_whitespace().next()?;
+            let celsius = value
+                .strip_suffix("°C")?
+                .trim_start_matches('+')
+                .parse()
+                .ok()?;
+            Some(SensorReading {
+                label: label.trim().to_string(),
+                celsius,
+            })
+        })
+        .collect()
+}
+
+/// Parse `powermetrics --samplers smc` lines like `CPU die temperature: 45.67 C`
+fn parse_powermetrics_temperatures(output: &str) -> Vec<SensorReading> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (label, value) = line.split_once(" temperature:")?;
+            let celsius = value.trim().strip_suffix(" C")?.trim().parse().ok()?;
+            Some(SensorReading {
+                label: label.trim().to_string(),
+                celsius,
+            })
+        })
+        .collect()
+}
+
+/// The `n` processes using the most CPU or memory, from `ps aux`
+pub fn top_processes(n: usize, sort: ProcessSort) -> Result<Vec<ProcessInfo>> {
+    let output = Command::new("ps")
+        .arg("aux")
+        .output()
+        .context("Failed to run ps")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ps exited with {}", output.status);
+    }
+
+    let mut processes = parse_ps_aux(&String::from_utf8_lossy(&output.stdout));
+    sort_processes(&mut processes, sort);
+    processes.truncate(n);
+    Ok(processes)
+}
+
+/// Parse `ps aux`: USER PID %CPU %MEM VSZ RSS TTY STAT START TIME COMMAND
+fn parse_ps_aux(output: &str) -> Vec<ProcessInfo> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 11 {
+                return None;
+            }
+            Some(ProcessInfo {
+                pid: parts[1].parse().ok()?,
+                user: parts[0].to_string(),
+                cpu_percent: parts[2].parse().ok()?,
+                mem_percent: parts[3].parse().ok()?,
+                // The command keeps its arguments, which may contain spaces
+                command: parts[10..].join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Sort busiest first, breaking ties on the other resource
+fn sort_processes(processes: &mut [ProcessInfo], sort: ProcessSort) {
+    let key = |p: &ProcessInfo| match sort {
+        ProcessSort::Cpu => (p.cpu_percent, p.mem_percent),
+        ProcessSort::Memory => (p.mem_percent, p.cpu_percent),
+    };
+    processes.sort_by(|a, b| {
+        key(b)
+            .partial_cmp(&key(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Run a command and return its stdout, or None if it is missing or fails
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Query a WMI/CIM class through PowerShell, returning one JSON object per instance
+pub fn query_cim(class: &str, properties: &[&str]) -> Result<Vec<serde_json::Value>> {
+    let script = format!(
+        "Get-CimInstance {} | Select-Object {} | ConvertTo-Json -Compress",
+        class,
+        properties.join(",")
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .output()
+        .with_context(|| format!("Failed to query {}", class))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Get-CimInstance {} failed: {}",
+            class,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    parse_cim_json(&String::from_utf8_lossy(&output.stdout))
+        .with_context(|| format!("Failed to parse {} output", class))
+}
+
+/// ConvertTo-Json emits a bare object for one instance and an array for several
+fn parse_cim_json(output: &str) -> Result<Vec<serde_json::Value>> {
+    if output.trim().is_empty() {
+        return Ok(Vec::new());
     }
 
-    Ok(interfaces)
+    Ok(match serde_json::from_str(output.trim())? {
+        serde_json::Value::Array(items) => items,
+        value => vec![value],
+    })
+}
+
+/// Read a numeric CIM property; uint64 values sometimes arrive as strings
+pub fn cim_u64(value: &serde_json::Value, key: &str) -> Option<u64> {
+    match &value[key] {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn cim_str(value: &serde_json::Value, key: &str) -> Option<String> {
+    value[key]
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn get_windows_info() -> Result<OsInfo> {
+    let os = query_cim("Win32_OperatingSystem", &["Caption", "Version"])?;
+    let os = os
+        .first()
+        .context("Win32_OperatingSystem returned nothing")?;
+    let version = cim_str(os, "Version").unwrap_or_else(|| "Unknown".to_string());
+
+    Ok(OsInfo {
+        name: cim_str(os, "Caption").unwrap_or_else(|| "Windows".to_string()),
+        kernel: format!("NT {}", version),
+        version,
+        architecture: std::env::consts::ARCH.to_string(),
+        platform: "windows".to_string(),
+    })
 }
 
-fn get_hostname() -> Result<String> {
+fn get_windows_cpu_info() -> Result<CpuInfo> {
+    let processors = query_cim(
+        "Win32_Processor",
+        &[
+            "Name",
+            "NumberOfCores",
+            "NumberOfLogicalProcessors",
+            "MaxClockSpeed",
+        ],
+    )?;
+    Ok(windows_cpu_info(&processors))
+}
+
+/// Combine Win32_Processor instances (one per socket) into a CpuInfo
+fn windows_cpu_info(processors: &[serde_json::Value]) -> CpuInfo {
+    let sum = |key: &str| -> u64 { processors.iter().filter_map(|p| cim_u64(p, key)).sum() };
+
+    CpuInfo {
+        model: processors
+            .first()
+            .and_then(|p| cim_str(p, "Name"))
+            .unwrap_or_else(|| "Unknown".to_string()),
+        cores: (sum("NumberOfCores") as usize).max(1),
+        threads: Some(sum("NumberOfLogicalProcessors") as usize).filter(|t| *t > 0),
+        frequency: processors
+            .first()
+            .and_then(|p| cim_u64(p, "MaxClockSpeed"))
+            .map(|mhz| format!("{:.2} GHz", mhz as f64 / 1000.0)),
+        utilization_percent: None,
+        per_core: Vec::new(),
+    }
+}
+
+fn get_windows_memory_info() -> Result<MemoryInfo> {
+    let os = query_cim(
+        "Win32_OperatingSystem",
+        &[
+            "TotalVisibleMemorySize",
+            "FreePhysicalMemory",
+            "SizeStoredInPagingFiles",
+            "FreeSpaceInPagingFiles",
+        ],
+    )?;
+    Ok(windows_memory_info(
+        os.first()
+            .context("Win32_OperatingSystem returned nothing")?,
+    ))
+}
+
+/// Memory and page file usage from Win32_OperatingSystem, which reports KB
+fn windows_memory_info(os: &serde_json::Value) -> MemoryInfo {
+    let kb = |key: &str| cim_u64(os, key).unwrap_or(0) * 1024;
+
+    let total_bytes = kb("TotalVisibleMemorySize");
+    let available_bytes = kb("FreePhysicalMemory");
+    let used_bytes = total_bytes.saturating_sub(available_bytes);
+    let swap_total_bytes = kb("SizeStoredInPagingFiles");
+    let swap_used_bytes = swap_total_bytes.saturating_sub(kb("FreeSpaceInPagingFiles"));
+
+    MemoryInfo {
+        total: format_bytes(total_bytes),
+        available: format_bytes(available_bytes),
+        used: format_bytes(used_bytes),
+        percent_used: percent(used_bytes, total_bytes),
+        swap_total: format_bytes(swap_total_bytes),
+        swap_used: format_bytes(swap_used_bytes),
+        swap_percent: percent(swap_used_bytes, swap_total_bytes),
+    }
+}
+
+/// Drives from Win32_LogicalDisk; empty card readers and optical drives have no size
+fn windows_disks(disks: &[serde_json::Value]) -> Vec<DiskInfo> {
+    disks
+        .iter()
+        .filter_map(|disk| {
+            let device = cim_str(disk, "DeviceID")?;
+            let total = cim_u64(disk, "Size")?;
+            let free = cim_u64(disk, "FreeSpace").unwrap_or(0);
+            let used = total.saturating_sub(free);
+
+            Some(DiskInfo {
+                mount_point: format!("{}\\", device),
+                device,
+                filesystem: cim_str(disk, "FileSystem").unwrap_or_else(|| "Unknown".to_string()),
+                total: format_bytes(total),
+                used: format_bytes(used),
+                available: format_bytes(free),
+                percent_used: (percent(used, total) * 10.0).round() / 10.0,
+            })
+        })
+        .collect()
+}
+
+pub fn get_hostname() -> Result<String> {
     let output = Command::new("hostname")
         .output()
         .context("Failed to get hostname")?;
@@ -499,8 +1335,102 @@ fn get_uptime() -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UptimeInfo {
+    pub uptime_seconds: Option<u64>,
+    pub uptime: String,
+    pub load_average: Option<[f64; 3]>,
+}
+
+/// Get parsed uptime and load averages
+///
+/// Reads `/proc/uptime` and `/proc/loadavg` where available, falling back to
+/// parsing the output of the `uptime` command.
+pub fn get_uptime_info() -> Result<UptimeInfo> {
+    let proc_uptime = fs::read_to_string("/proc/uptime").ok().and_then(|s| {
+        s.split_whitespace()
+            .next()
+            .and_then(|secs| secs.parse::<f64>().ok())
+    });
+    let proc_loadavg = fs::read_to_string("/proc/loadavg")
+        .ok()
+        .and_then(|s| parse_load_average(&s));
+
+    if let Some(secs) = proc_uptime {
+        let secs = secs as u64;
+        return Ok(UptimeInfo {
+            uptime_seconds: Some(secs),
+            uptime: format_duration(secs),
+            load_average: proc_loadavg,
+        });
+    }
+
+    let (uptime, load_average) = parse_uptime_output(&get_uptime()?);
+    Ok(UptimeInfo {
+        uptime_seconds: None,
+        uptime: uptime.unwrap_or_else(|| "Unknown".to_string()),
+        load_average,
+    })
+}
+
+/// Parse the human uptime and load averages from `uptime` command output
+///
+/// Handles both the Linux (`load average: a, b, c`) and macOS
+/// (`load averages: a b c`) formats.
+fn parse_uptime_output(output: &str) -> (Option<String>, Option<[f64; 3]>) {
+    let (before_load, load) = match output.find("load average") {
+        Some(pos) => (&output[..pos], Some(&output[pos..])),
+        None => (output, None),
+    };
+
+    let uptime = before_load.find(" up ").map(|pos| {
+        let rest = &before_load[pos + 4..];
+        // Drop the trailing "N users," segment
+        let segments: Vec<&str> = rest
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty() && !s.contains("user"))
+            .collect();
+        segments.join(", ")
+    });
+
+    let load_average = load
+        .and_then(|l| l.split_once(':'))
+        .and_then(|(_, values)| parse_load_average(&values.replace(',', " ")));
+
+    (uptime, load_average)
+}
+
+fn parse_load_average(text: &str) -> Option<[f64; 3]> {
+    let values: Vec<f64> = text
+        .split_whitespace()
+        .take(3)
+        .filter_map(|v| v.parse().ok())
+        .collect();
+
+    match values.as_slice() {
+        [one, five, fifteen] => Some([*one, *five, *fifteen]),
+        _ => None,
+    }
+}
+
+/// Format a number of seconds as e.g. "3d 4h 5m"
+pub fn format_duration(total_seconds: u64) -> String {
+    let days = total_seconds / 86400;
+    let hours = (total_seconds % 86400) / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
 /// Format bytes into human-readable format
-fn format_bytes(bytes: u64) -> String {
+pub fn format_bytes(bytes: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
 
     if bytes == 0 {
@@ -517,3 +1447,348 @@ fn format_bytes(bytes: u64) -> String {
 
     format!("{:.2} {}", size, UNITS[unit_idx])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uptime_output_linux() {
+        let output = " 10:14:03 up 3 days,  4:05,  2 users,  load average: 0.12, 0.34, 0.56";
+        let (uptime, load) = parse_uptime_output(output);
+        assert_eq!(uptime.as_deref(), Some("3 days, 4:05"));
+        assert_eq!(load, Some([0.12, 0.34, 0.56]));
+    }
+
+    #[test]
+    fn test_parse_uptime_output_macos() {
+        let output = "10:14  up 12 mins, 3 users, load averages: 1.23 1.45 1.67";
+        let (uptime, load) = parse_uptime_output(output);
+        assert_eq!(uptime.as_deref(), Some("12 mins"));
+        assert_eq!(load, Some([1.23, 1.45, 1.67]));
+    }
+
+    #[test]
+    fn test_parse_lspci_gpus() {
+        let output = "00:02.0 VGA compatible controller: Intel Corporation UHD Graphics 630 (rev 02)\n\
+                      00:1f.3 Audio device: Intel Corporation Cannon Lake PCH cAVS (rev 10)\n\
+                      01:00.0 3D controller: NVIDIA Corporation GA104 [GeForce RTX 3070] (rev a1)\n";
+        let gpus = parse_lspci_gpus(output);
+
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].name, "Intel Corporation UHD Graphics 630");
+        assert_eq!(gpus[0].vendor.as_deref(), Some("Intel"));
+        assert_eq!(gpus[1].vendor.as_deref(), Some("NVIDIA"));
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let gpus = parse_nvidia_smi("NVIDIA GeForce RTX 3070, 8192 MiB\n");
+        assert_eq!(
+            gpus,
+            vec![GpuInfo {
+                name: "NVIDIA GeForce RTX 3070".to_string(),
+                vendor: Some("NVIDIA".to_string()),
+                memory: Some("8192 MiB".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_system_profiler_displays() {
+        let output = "Graphics/Displays:
+
+    Apple M1 Pro:
+
+      Chipset Model: Apple M1 Pro
+      Type: GPU
+      Total Number of Cores: 16
+      Vendor: Apple (0x106b)
+
+    Radeon Pro 560X:
+
+      Chipset Model: Radeon Pro 560X
+      VRAM (Total): 4 GB
+      Vendor: AMD (0x1002)
+";
+        let gpus = parse_system_profiler_displays(output);
+
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].vendor.as_deref(), Some("Apple"));
+        assert_eq!(gpus[0].memory, None);
+        assert_eq!(gpus[1].name, "Radeon Pro 560X");
+        assert_eq!(gpus[1].memory.as_deref(), Some("4 GB"));
+        assert_eq!(gpus[1].vendor.as_deref(), Some("AMD"));
+    }
+
+    #[test]
+    fn test_read_thermal_zones() {
+        let dir = tempfile::tempdir().unwrap();
+        let zone = dir.path().join("thermal_zone0");
+        fs::create_dir(&zone).unwrap();
+        fs::write(zone.join("type"), "x86_pkg_temp\n").unwrap();
+        fs::write(zone.join("temp"), "47000\n").unwrap();
+        fs::create_dir(dir.path().join("cooling_device0")).unwrap();
+
+        assert_eq!(
+            read_thermal_zones(dir.path()),
+            vec![SensorReading {
+                label: "x86_pkg_temp".to_string(),
+                celsius: 47.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_temperature_tools() {
+        let sensors = "coretemp-isa-0000\nAdapter: ISA adapter\n\
+                       Package id 0:  +52.0°C  (high = +80.0°C, crit = +100.0°C)\n\
+                       Core 0:        +45.5°C  (high = +80.0°C, crit = +100.0°C)\n";
+        let readings = parse_sensors_output(sensors);
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[1].label, "Core 0");
+        assert_eq!(readings[1].celsius, 45.5);
+
+        let powermetrics =
+            "**** SMC sensors ****\n\nCPU die temperature: 45.67 C\nGPU die temperature: 40.12 C\n";
+        let readings = parse_powermetrics_temperatures(powermetrics);
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[0].label, "CPU die");
+        assert_eq!(readings[0].celsius, 45.67);
+    }
+
+    #[test]
+    fn test_parse_meminfo_with_swap() {
+        let meminfo = "MemTotal:        8000000 kB\n\
+                       MemFree:          500000 kB\n\
+                       MemAvailable:    2000000 kB\n\
+                       SwapTotal:       2097148 kB\n\
+                       SwapFree:        1572861 kB\n";
+        let memory = parse_meminfo(meminfo);
+
+        assert_eq!(memory.percent_used, 75.0);
+        assert_eq!(memory.swap_total, "2.00 GB");
+        assert_eq!(memory.swap_used, "512.00 MB");
+        assert!((memory.swap_percent - 25.0).abs() < 0.01);
+
+        let no_swap = parse_meminfo("MemTotal: 1000 kB\nSwapTotal: 0 kB\nSwapFree: 0 kB\n");
+        assert_eq!(no_swap.swap_percent, 0.0);
+    }
+
+    #[test]
+    fn test_parse_swapusage() {
+        let output =
+            "vm.swapusage: total = 2048.00M  used = 1024.50M  free = 1023.50M  (encrypted)";
+        assert_eq!(
+            parse_swapusage(output),
+            Some((2048 * 1024 * 1024, (1024.5 * 1024.0 * 1024.0) as u64))
+        );
+        assert_eq!(parse_swapusage("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_load_average() {
+        assert_eq!(
+            parse_load_average("0.53 0.42 0.44 2/345 6789\n"),
+            Some([0.53, 0.42, 0.44])
+        );
+        assert_eq!(
+            parse_load_average(" 1.23 1.45 1.67 "),
+            Some([1.23, 1.45, 1.67])
+        );
+        assert_eq!(parse_load_average("0.53"), None);
+    }
+
+    #[test]
+    fn test_cpu_utilization() {
+        let before = "cpu  100 0 100 800 0 0 0 0 0 0\n\
+                      cpu0 50 0 50 400 0 0 0 0 0 0\n\
+                      cpu1 50 0 50 400 0 0 0 0 0 0\n\
+                      intr 12345\n";
+        // cpu0 fully busy, cpu1 idle apart from iowait
+        let after = "cpu  200 0 200 900 100 0 0 0 0 0\n\
+                     cpu0 150 0 150 400 0 0 0 0 0 0\n\
+                     cpu1 50 0 50 500 100 0 0 0 0 0\n\
+                     intr 23456\n";
+
+        let (total, per_core) = cpu_utilization(before, after).unwrap();
+        assert_eq!(total, 50.0);
+        assert_eq!(per_core, vec![100.0, 0.0]);
+
+        // No time passed
+        let (total, _) = cpu_utilization(before, before).unwrap();
+        assert_eq!(total, 0.0);
+        assert!(cpu_utilization("intr 1\n", "intr 2\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_top_cpu_usage() {
+        let output = "Processes: 512 total\n\
+                      CPU usage: 12.50% user, 25.00% sys, 62.50% idle\n\
+                      Processes: 512 total\n\
+                      CPU usage: 5.12% user, 10.25% sys, 84.63% idle\n";
+        let usage = parse_top_cpu_usage(output).unwrap();
+        assert!((usage - 15.37).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_cim_json() {
+        assert_eq!(parse_cim_json("").unwrap().len(), 0);
+        assert_eq!(parse_cim_json(r#"{"Name":"a"}"#).unwrap().len(), 1);
+        assert_eq!(
+            parse_cim_json(r#"[{"Name":"a"},{"Name":"b"}]"#)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(parse_cim_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_windows_info_from_cim() {
+        let processors = parse_cim_json(
+            r#"{"Name":"AMD Ryzen 7 5800X 8-Core Processor","NumberOfCores":8,"NumberOfLogicalProcessors":16,"MaxClockSpeed":3801}"#,
+        )
+        .unwrap();
+        let cpu = windows_cpu_info(&processors);
+        assert_eq!(cpu.cores, 8);
+        assert_eq!(cpu.threads, Some(16));
+        assert_eq!(cpu.frequency.as_deref(), Some("3.80 GHz"));
+
+        let os = serde_json::json!({
+            "TotalVisibleMemorySize": 16777216,
+            "FreePhysicalMemory": 4194304,
+            "SizeStoredInPagingFiles": "2097152",
+            "FreeSpaceInPagingFiles": 2097152
+        });
+        let memory = windows_memory_info(&os);
+        assert_eq!(memory.total, "16.00 GB");
+        assert_eq!(memory.percent_used, 75.0);
+        assert_eq!(memory.swap_used, "0 B");
+
+        let disks = parse_cim_json(
+            r#"[{"DeviceID":"C:","FileSystem":"NTFS","Size":"500000000000","FreeSpace":"125000000000"},
+                {"DeviceID":"D:","FileSystem":null,"Size":null,"FreeSpace":null}]"#,
+        )
+        .unwrap();
+        let disks = windows_disks(&disks);
+        assert_eq!(disks.len(), 1);
+        assert_eq!(disks[0].mount_point, "C:\\");
+        assert_eq!(disks[0].percent_used, 75.0);
+    }
+
+    #[test]
+    fn test_parse_and_sort_ps_aux() {
+        let output = "\
+USER         PID %CPU %MEM    VSZ   RSS TTY      STAT START   TIME COMMAND
+root           1  0.0  0.1 167744 11520 ?        Ss   Oct15   0:03 /sbin/init splash
+postgres    4242 35.5  2.0 215000 98000 ?        Ssl  09:00   1:10 postgres: writer
+alice       5150  1.2 12.4 4200000 990000 ?      Sl   08:12  12:01 /usr/lib/firefox/firefox -contentproc
+";
+        let mut processes = parse_ps_aux(output);
+        assert_eq!(processes.len(), 3);
+        assert_eq!(processes[0].command, "/sbin/init splash");
+
+        sort_processes(&mut processes, ProcessSort::Memory);
+        assert_eq!(processes[0].pid, 5150);
+        assert_eq!(processes[0].user, "alice");
+        assert_eq!(processes[0].mem_percent, 12.4);
+
+        sort_processes(&mut processes, ProcessSort::Cpu);
+        assert_eq!(processes[0].pid, 4242);
+        assert_eq!(processes[0].command, "postgres: writer");
+        assert_eq!(processes[2].pid, 1);
+    }
+
+    #[test]
+    fn test_parse_ip_json() {
+        let output = r#"[
+            {"ifindex":1,"ifname":"lo","flags":["LOOPBACK","UP","LOWER_UP"],"mtu":65536,
+             "operstate":"UNKNOWN","link_type":"loopback","address":"00:00:00:00:00:00",
+             "addr_info":[{"family":"inet","local":"127.0.0.1","prefixlen":8},
+                          {"family":"inet6","local":"::1","prefixlen":128}]},
+            {"ifindex":2,"ifname":"eth0","flags":["BROADCAST","MULTICAST","UP","LOWER_UP"],"mtu":1500,
+             "operstate":"UP","link_type":"ether","address":"52:54:00:12:34:56",
+             "addr_info":[{"family":"inet","local":"10.0.2.15","prefixlen":24},
+                          {"family":"inet","local":"10.0.2.99","prefixlen":24},
+                          {"family":"inet6","local":"fe80::5054:ff:fe12:3456","prefixlen":64}]},
+            {"ifindex":3,"ifname":"wlan0","flags":["BROADCAST","MULTICAST"],"mtu":1500,
+             "operstate":"DOWN","link_type":"ether","address":"a4:83:e7:00:00:01","addr_info":[]}
+        ]"#;
+        let interfaces = parse_ip_json(output).unwrap();
+
+        assert_eq!(interfaces.len(), 3);
+        assert_eq!(interfaces[0].state.as_deref(), Some("up"));
+        assert_eq!(interfaces[0].mac_address, None);
+        assert_eq!(
+            interfaces[1],
+            NetworkInterface {
+                name: "eth0".to_string(),
+                ip_address: Some("10.0.2.15".to_string()),
+                mac_address: Some("52:54:00:12:34:56".to_string()),
+                ipv4_addresses: vec!["10.0.2.15".to_string(), "10.0.2.99".to_string()],
+                ipv6_addresses: vec!["fe80::5054:ff:fe12:3456".to_string()],
+                state: Some("up".to_string()),
+                mtu: Some(1500),
+            }
+        );
+        assert_eq!(interfaces[2].state.as_deref(), Some("down"));
+        assert!(!interfaces[2].has_addresses());
+        assert!(parse_ip_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_ip_addr_text() {
+        let output = "\
+4: eth0@if5: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1400 qdisc pfifo_fast state UP group default qlen 1000
+    link/ether 02:fc:00:00:00:01 brd ff:ff:ff:ff:ff:ff
+    inet 172.16.0.2/30 brd 172.16.0.3 scope global eth0
+       valid_lft forever preferred_lft forever
+    inet6 fe80::fc:ff:fe00:1/64 scope link
+       valid_lft forever preferred_lft forever
+";
+        let interfaces = parse_ip_addr(output);
+
+        assert_eq!(interfaces.len(), 1);
+        assert_eq!(interfaces[0].name, "eth0");
+        assert_eq!(interfaces[0].mtu, Some(1400));
+        assert_eq!(interfaces[0].ip_address.as_deref(), Some("172.16.0.2"));
+        assert_eq!(interfaces[0].ipv6_addresses, vec!["fe80::fc:ff:fe00:1"]);
+        assert_eq!(
+            interfaces[0].mac_address.as_deref(),
+            Some("02:fc:00:00:00:01")
+        );
+    }
+
+    #[test]
+    fn test_parse_ifconfig_macos() {
+        let output = "\
+lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384
+\tinet 127.0.0.1 netmask 0xff000000
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\tether a4:83:e7:12:34:56
+\tinet6 fe80::1c2b:3a4d:5e6f:7a8b%en0 prefixlen 64 secured scopeid 0x6
+\tinet 192.168.1.23 netmask 0xffffff00 broadcast 192.168.1.255
+\tinet 192.168.1.24 netmask 0xffffff00 broadcast 192.168.1.255
+\tstatus: active
+en1: flags=8822<BROADCAST,SMART,SIMPLEX,MULTICAST> mtu 1500
+\tstatus: inactive
+";
+        let interfaces = parse_ifconfig(output);
+
+        assert_eq!(interfaces.len(), 3);
+        let en0 = &interfaces[1];
+        assert_eq!(en0.ipv4_addresses, vec!["192.168.1.23", "192.168.1.24"]);
+        assert_eq!(en0.ipv6_addresses, vec!["fe80::1c2b:3a4d:5e6f:7a8b"]);
+        assert_eq!(en0.mac_address.as_deref(), Some("a4:83:e7:12:34:56"));
+        assert_eq!(en0.state.as_deref(), Some("up"));
+        assert_eq!(interfaces[2].state.as_deref(), Some("down"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(59), "0m");
+        assert_eq!(format_duration(3 * 3600 + 120), "3h 2m");
+        assert_eq!(format_duration(2 * 86400 + 3600 + 60), "2d 1h 1m");
+    }
+}